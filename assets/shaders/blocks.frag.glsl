@@ -0,0 +1,16 @@
+#version 330
+out vec4 FragColor;
+in vec2 outTexCoord;
+in vec4 col;
+
+uniform sampler2D atlas;
+
+void main() {
+    vec4 tex_col = texture(atlas, outTexCoord);
+#ifdef ALPHA_TEST
+    if (tex_col.a == 0.0) {
+        discard;
+    }
+#endif
+    FragColor = col * tex_col;
+}
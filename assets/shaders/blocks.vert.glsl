@@ -0,0 +1,19 @@
+#version 330
+
+#include "light.glsl"
+
+in vec3 pos;
+in vec2 tex;
+in vec4 light;
+
+out vec4 col;
+out vec2 outTexCoord;
+
+uniform mat4 viewMat;
+uniform mat4 modelMat;
+
+void main() {
+    gl_Position = viewMat * modelMat * vec4(pos, 1.0);
+    col = get_light_color(light);
+    outTexCoord = tex;
+}
@@ -0,0 +1,11 @@
+#version 330
+
+in vec2 pos;
+in vec4 col;
+
+out vec4 out_color;
+
+void main() {
+    gl_Position = vec4(pos, 0.0, 1.0);
+    out_color = col;
+}
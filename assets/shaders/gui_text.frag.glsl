@@ -0,0 +1,11 @@
+#version 330
+
+in vec2 out_tex;
+in vec4 out_color;
+out vec4 f_color;
+
+uniform sampler2D atlas;
+
+void main() {
+    f_color = out_color * texture(atlas, out_tex);
+}
@@ -0,0 +1,14 @@
+#version 330
+
+in vec2 pos;
+in vec2 tex;
+in vec4 col;
+
+out vec2 out_tex;
+out vec4 out_color;
+
+void main() {
+    gl_Position = vec4(pos, 0.0, 1.0);
+    out_tex = tex;
+    out_color = col;
+}
@@ -0,0 +1,16 @@
+#version 330
+
+in vec2 out_tex;
+in vec4 out_color;
+out vec4 f_color;
+
+uniform sampler2D atlas;
+
+//Negative tex coords mark plain colored quads
+void main() {
+    if (out_tex.x < 0.0) {
+        f_color = out_color;
+    } else {
+        f_color = out_color * texture(atlas, out_tex);
+    }
+}
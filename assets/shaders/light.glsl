@@ -0,0 +1,5 @@
+vec4 get_light_color(vec4 light) {
+    vec4 color = vec4(light.r, light.g, light.b, 1.0f);
+    color.rgb += light.a;
+    return color;
+}
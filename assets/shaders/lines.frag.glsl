@@ -0,0 +1,9 @@
+#version 330
+
+out vec4 FragColor;
+
+in vec4 out_col;
+
+void main() {
+    FragColor = out_col;
+}
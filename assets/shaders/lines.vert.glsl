@@ -0,0 +1,13 @@
+#version 330
+
+in vec3 pos;
+in vec4 col;
+
+out vec4 out_col;
+
+uniform mat4 viewMat;
+
+void main() {
+    gl_Position = viewMat * vec4(pos, 1.0);
+    out_col = col;
+}
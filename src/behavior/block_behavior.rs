@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use thiserror::Error;
+use crate::entity::entity::EntityId;
+use crate::physics::hitbox::HitBox;
+use crate::render::blocks_loader::{is_namespaced_name, Block, BlocksLoader};
+use crate::render::types::Vec3ub;
+use crate::world::World;
+
+#[derive(Error, Debug)]
+pub enum BehaviorRegistrationError {
+    #[error("Behavior name {0} is not namespaced like namespace:name")]
+    InvalidNameError(String),
+    #[error("Behavior {0} is registered twice")]
+    RedefinitionError(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TickKind {
+    Scheduled,
+    Random,
+}
+
+//Hooks of the blocks bound to the behavior, changed positions are collected for lighting
+pub trait BlockBehavior {
+    //After the block is set at pos
+    fn on_place(&self, _world: &World, _blocks_loader: &BlocksLoader, _pos: &Vec3ub, _changed: &mut Vec<Vec3ub>) {}
+
+    //Before the block at pos is removed
+    fn on_break(&self, _world: &World, _blocks_loader: &BlocksLoader, _pos: &Vec3ub, _changed: &mut Vec<Vec3ub>) {}
+
+    //True if the use is handled, so nothing is placed against the block
+    fn on_use(&self, _world: &World, _blocks_loader: &BlocksLoader, _pos: &Vec3ub, _changed: &mut Vec<Vec3ub>) -> bool {
+        return false;
+    }
+
+    fn on_neighbor_changed(&self, _world: &World, _blocks_loader: &BlocksLoader, _pos: &Vec3ub, _neighbor_pos: &Vec3ub, _changed: &mut Vec<Vec3ub>) {}
+
+    //Every tick the hitbox overlaps the block, entity is None for the player
+    fn on_entity_collide(&self, _world: &World, _blocks_loader: &BlocksLoader, _pos: &Vec3ub, _entity: Option<EntityId>, _hitbox: &mut HitBox, _changed: &mut Vec<Vec3ub>) {}
+
+    fn on_tick(&self, _world: &World, _blocks_loader: &BlocksLoader, _pos: &Vec3ub, _kind: TickKind, _changed: &mut Vec<Vec3ub>) {}
+}
+
+//Behaviors by names used in blocks definitions
+pub struct BlockBehaviors {
+    behaviors: HashMap<String, Rc<dyn BlockBehavior>>,
+}
+
+impl BlockBehaviors {
+    pub fn new() -> Self {
+        return Self {
+            behaviors: HashMap::new(),
+        };
+    }
+
+    pub fn register(&mut self, name: &str, behavior: Rc<dyn BlockBehavior>) -> Result<(), BehaviorRegistrationError> {
+        if !is_namespaced_name(name) {
+            return Err(BehaviorRegistrationError::InvalidNameError(String::from(name)));
+        }
+        if self.behaviors.contains_key(name) {
+            return Err(BehaviorRegistrationError::RedefinitionError(String::from(name)));
+        }
+        self.behaviors.insert(String::from(name), behavior);
+        return Ok(());
+    }
+
+    pub fn get(&self, name: &str) -> Option<Rc<dyn BlockBehavior>> {
+        return self.behaviors.get(name).cloned();
+    }
+
+    pub fn get_block_behavior(&self, block: &Block) -> Option<Rc<dyn BlockBehavior>> {
+        return block.behavior.as_ref().and_then(|name| self.get(name));
+    }
+
+    //Blocks bound to behaviors which are not registered, as (block name, behavior name)
+    pub fn get_unknown(&self, blocks_loader: &BlocksLoader) -> Vec<(String, String)> {
+        let mut unknown: Vec<(String, String)> = Vec::new();
+        for block in &blocks_loader.loaded_blocks {
+            if let Some(behavior) = &block.behavior {
+                if !self.behaviors.contains_key(behavior) {
+                    unknown.push((block.name.clone(), behavior.clone()));
+                }
+            }
+        }
+        return unknown;
+    }
+}
\ No newline at end of file
@@ -0,0 +1,91 @@
+use std::rc::Rc;
+use rand::Rng;
+use crate::behavior::block_behavior::{BehaviorRegistrationError, BlockBehavior, BlockBehaviors, TickKind};
+use crate::physics::fluid_solver::FluidSolver;
+use crate::render::blocks_loader::BlocksLoader;
+use crate::render::light::light_map::S_CHANNEL;
+use crate::render::light::light_solver::LightSolver;
+use crate::render::types::{Vec3b, Vec3ub};
+use crate::world::World;
+
+pub const FLUID_BEHAVIOR_NAME: &str = "core:fluid";
+pub const GRASS_BEHAVIOR_NAME: &str = "core:grass";
+
+const NEIGHBOR_TOP: Vec3b = [0, 1, 0];
+const GRASS_DIRT_BLOCK_NAME: &str = "core:dirt";
+const GRASS_SPREAD_LIGHT: u8 = 9; //minimal sky light over dirt for grass to spread on it
+
+pub fn register_core_behaviors(behaviors: &mut BlockBehaviors) -> Result<(), BehaviorRegistrationError> {
+    behaviors.register(FLUID_BEHAVIOR_NAME, Rc::new(FluidBehavior {}))?;
+    behaviors.register(GRASS_BEHAVIOR_NAME, Rc::new(GrassBehavior {}))?;
+    return Ok(());
+}
+
+//Flows by the fluid data of the block
+pub struct FluidBehavior {}
+
+impl FluidBehavior {
+    fn schedule(world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub) {
+        if let Some(fluid) = &blocks_loader.get_block(world.get_block(pos)).fluid {
+            world.ticks.schedule(pos, fluid.tick_delay);
+        }
+    }
+}
+
+impl BlockBehavior for FluidBehavior {
+    fn on_place(&self, world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub, _changed: &mut Vec<Vec3ub>) {
+        Self::schedule(world, blocks_loader, pos);
+    }
+
+    fn on_neighbor_changed(&self, world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub, _neighbor_pos: &Vec3ub, _changed: &mut Vec<Vec3ub>) {
+        Self::schedule(world, blocks_loader, pos);
+    }
+
+    fn on_tick(&self, world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub, kind: TickKind, changed: &mut Vec<Vec3ub>) {
+        if kind == TickKind::Scheduled {
+            FluidSolver::update(world, blocks_loader, pos, changed);
+        }
+    }
+}
+
+//Turns into dirt under cubes and spreads over lit dirt nearby
+pub struct GrassBehavior {}
+
+impl GrassBehavior {
+    //Light passes through the block above and nothing covers it
+    fn is_uncovered(world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub) -> bool {
+        return if let Some(top_pos) = LightSolver::get_neighbor_pos(pos, &NEIGHBOR_TOP) {
+            !blocks_loader.get_block(world.get_block(&top_pos)).mesh.is_cube()
+        } else {
+            true
+        }
+    }
+}
+
+impl BlockBehavior for GrassBehavior {
+    fn on_tick(&self, world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub, kind: TickKind, changed: &mut Vec<Vec3ub>) {
+        if kind != TickKind::Random {
+            return;
+        }
+        if let Ok(dirt_lid) = blocks_loader.get_lid(GRASS_DIRT_BLOCK_NAME) {
+            if !Self::is_uncovered(world, blocks_loader, pos) {
+                world.set_block(pos, dirt_lid);
+                changed.push(*pos);
+                return;
+            }
+            let mut rng = rand::thread_rng();
+            let offset: Vec3b = [rng.gen_range(-1..=1), rng.gen_range(-3..=1), rng.gen_range(-1..=1)];
+            if let Some(spread_pos) = LightSolver::get_neighbor_pos(pos, &offset) {
+                if world.get_block(&spread_pos) == dirt_lid && Self::is_uncovered(world, blocks_loader, &spread_pos) {
+                    if let Some(top_pos) = LightSolver::get_neighbor_pos(&spread_pos, &NEIGHBOR_TOP) {
+                        if world.get_light_level(&top_pos, S_CHANNEL) < GRASS_SPREAD_LIGHT {
+                            return;
+                        }
+                    }
+                    world.set_block(&spread_pos, world.get_block(pos));
+                    changed.push(spread_pos);
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
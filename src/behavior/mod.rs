@@ -0,0 +1,2 @@
+pub mod block_behavior;
+pub mod core_behaviors;
\ No newline at end of file
@@ -0,0 +1,45 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use cubecode_a000::packs::pack_stack::PackStack;
+use cubecode_a000::render::assets_validator::{AssetsIssue, AssetsValidator};
+
+const DEFAULT_PACKS_LIST_PATH: &str = "packs.json";
+
+fn print_usage() {
+    println!("Usage: cubecode-assets check [packs list path, {} by default]", DEFAULT_PACKS_LIST_PATH);
+}
+
+fn check(packs_list_path: &Path) -> Result<Vec<AssetsIssue>, Box<dyn std::error::Error>> {
+    let packs_paths: Vec<PathBuf> = PackStack::read_packs_list(packs_list_path)?;
+    let packs: PackStack = PackStack::open(&packs_paths)?;
+    return AssetsValidator::check(&packs);
+}
+
+//Exits with 1 if any issue is found, so it can run in pre-commit hooks
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 || args.len() > 3 || args[1] != "check" {
+        print_usage();
+        return ExitCode::from(2);
+    }
+    let packs_list_path: &Path = Path::new(args.get(2).map_or(DEFAULT_PACKS_LIST_PATH, |path| path.as_str()));
+    return match check(packs_list_path) {
+        Ok(issues) => {
+            for issue in &issues {
+                println!("{}", issue);
+            }
+            if issues.len() == 0 {
+                println!("No issues found");
+                ExitCode::SUCCESS
+            } else {
+                println!("{} issues found", issues.len());
+                ExitCode::from(1)
+            }
+        }
+        Err(error) => {
+            println!("Failed to check assets: {}", error);
+            ExitCode::from(1)
+        }
+    }
+}
\ No newline at end of file
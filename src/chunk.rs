@@ -1,9 +1,10 @@
 use std::cell::{Cell, RefCell};
 use std::ops::Deref;
 use std::ptr;
+use std::rc::Rc;
 use thiserror::Error;
 use crate::render::block_renderer;
-use crate::render::blocks_loader::{AIR_BLOCK_ID, BlocksLoader, BlockUsingError, UNKNOWN_BLOCK_ID};
+use crate::render::blocks_loader::{BlocksLoader, BlockUsingError};
 use crate::render::buffer::Buffer;
 use crate::render::light::light_map::LightMap;
 use crate::render::shader_program::ShaderProgram;
@@ -16,30 +17,38 @@ use crate::world::World;
 pub enum ChunkLoadingError {
     #[error("Id (b or l) byte of u16 not found")]
     IdConstructError(),
+    #[error("Invalid block states size")]
+    InvalidStatesSizeError(),
 }
 
+pub const CHUNK_STATES_SIZE: usize = 16 * 16 * 16 * 16;
+
 //TODO REPLACE CHUNKS WITH SUBCHUNKS
 pub struct SubChunk {
     //TODO MB ANOTHER ARRAYS FOR CUSTOM BLOCKS
     pub data: RefCell<[[[u16; 16]; 16]; 16]>,
+    pub states: RefCell<[[[u8; 16]; 16]; 16]>, //per block state like fluid levels, same layout as data
     pub light_map: RefCell<LightMap>,
     pub is_changed: Cell<bool>,
-    pub vert_buf: RefCell<Buffer>,
-    pub ind_buf: RefCell<Buffer>,
+    //GL objects are made by the first render, so worlds can be used without a GL context
+    pub vert_buf: RefCell<Option<Buffer>>,
+    pub ind_buf: RefCell<Option<Buffer>>,
     pub ind_cnt: Cell<i32>,
-    pub vert_array: RefCell<VertexArray>,
+    pub vert_array: RefCell<Option<VertexArray>>,
 }
 
 impl SubChunk {
     pub fn new(data: [[[u16; 16]; 16]; 16]) -> SubChunk {
-        unsafe {
-            return SubChunk {data: RefCell::new(data), light_map: RefCell::from(LightMap::new()), is_changed: Cell::new(true), vert_buf: RefCell::new(Buffer::new(gl::ARRAY_BUFFER)), ind_buf: RefCell::new(Buffer::new(gl::ELEMENT_ARRAY_BUFFER)), ind_cnt: Cell::new(0), vert_array: RefCell::new(VertexArray::new()), };
-        }
+        return SubChunk {data: RefCell::new(data), states: RefCell::new([[[0; 16]; 16]; 16]), light_map: RefCell::from(LightMap::new()), is_changed: Cell::new(true), vert_buf: RefCell::new(None), ind_buf: RefCell::new(None), ind_cnt: Cell::new(0), vert_array: RefCell::new(None), };
     }
 
     pub fn render(&self, world: &World, blocks_loader: &BlocksLoader, subchunk_pos: &Vec3ub) -> Result<(), Vec<Box<dyn std::error::Error>>> {
         let mut errors: Vec<Box<dyn std::error::Error>> = Vec::new();
         if self.is_changed.get() {
+            let shader_program: &Rc<ShaderProgram> = match blocks_loader.meshes_loader.faces_loader.get_shader_program() {
+                Ok(shader_program) => shader_program,
+                Err(error) => return Err(vec![Box::new(error)]),
+            };
             let mut vertices: Vec<LightedTexVertex> = Vec::new();
             let mut indices: Vec<i32> = Vec::new();
             for block_pos in 0..0x1000 {
@@ -57,29 +66,30 @@ impl SubChunk {
             }
 
             unsafe {
-                self.vert_array.replace(VertexArray::new());
-                self.vert_array.borrow().bind();
+                let vert_array: VertexArray = VertexArray::new();
+                vert_array.bind();
 
-                self.vert_buf.replace(Buffer::new(gl::ARRAY_BUFFER));
-                self.vert_buf.borrow().set_data(vertices.as_slice(), gl::STATIC_DRAW);
+                let vert_buf: Buffer = Buffer::new(gl::ARRAY_BUFFER);
+                vert_buf.set_data(vertices.as_slice(), gl::STATIC_DRAW);
 
-                match blocks_loader.meshes_loader.faces_loader.shader_program.get_attrib_location("pos") {
+                match shader_program.get_attrib_location("pos") {
                     Ok(pos_attrib) => {
 
-                        set_attribute!(self.vert_array.borrow(), pos_attrib, LightedTexVertex::0);
+                        set_attribute!(vert_array, pos_attrib, LightedTexVertex::0);
 
-                        match blocks_loader.meshes_loader.faces_loader.shader_program.get_attrib_location("tex") {
+                        match shader_program.get_attrib_location("tex") {
                             Ok(tex_attrib) => {
 
-                                set_attribute!(self.vert_array.borrow(), tex_attrib, LightedTexVertex::1);
+                                set_attribute!(vert_array, tex_attrib, LightedTexVertex::1);
 
-                                match blocks_loader.meshes_loader.faces_loader.shader_program.get_attrib_location("light") {
+                                match shader_program.get_attrib_location("light") {
                                     Ok(light_attrib) => {
 
-                                        set_attribute!(self.vert_array.borrow(), light_attrib, LightedTexVertex::2);
+                                        set_attribute!(vert_array, light_attrib, LightedTexVertex::2);
 
-                                        self.ind_buf.replace(Buffer::new(gl::ELEMENT_ARRAY_BUFFER));
-                                        self.ind_buf.borrow().set_data(indices.as_slice(), gl::STATIC_DRAW);
+                                        let ind_buf: Buffer = Buffer::new(gl::ELEMENT_ARRAY_BUFFER);
+                                        ind_buf.set_data(indices.as_slice(), gl::STATIC_DRAW);
+                                        self.ind_buf.replace(Some(ind_buf));
                                     }
                                     Err(error) => {
                                         errors.push(Box::new(error));
@@ -95,6 +105,8 @@ impl SubChunk {
                         errors.push(Box::new(error));
                     }
                 }
+                self.vert_buf.replace(Some(vert_buf));
+                self.vert_array.replace(Some(vert_array));
             }
 
             self.ind_cnt.set(indices.len() as i32);
@@ -108,16 +120,21 @@ impl SubChunk {
         }
     }
 
-    pub fn draw(&self, shader_program: &ShaderProgram, pos: &Vec3ub) {
+    //Returns whether anything was drawn
+    pub fn draw(&self, shader_program: &ShaderProgram, pos: &Vec3ub) -> bool {
         if self.ind_cnt.get() != 0 {
-            unsafe {
-                if let Ok(_) = shader_program.set_uniform_mat4f("modelMat", &Mat4f::get_subchunk_model_mat(pos)) {
-                    shader_program.apply();
-                    self.vert_array.borrow().bind();
-                    gl::DrawElements(gl::TRIANGLES, self.ind_cnt.get(), gl::UNSIGNED_INT, ptr::null());
+            if let Some(vert_array) = self.vert_array.borrow().as_ref() {
+                unsafe {
+                    if let Ok(_) = shader_program.set_uniform_mat4f("modelMat", &Mat4f::get_subchunk_model_mat(pos)) {
+                        shader_program.apply();
+                        vert_array.bind();
+                        gl::DrawElements(gl::TRIANGLES, self.ind_cnt.get(), gl::UNSIGNED_INT, ptr::null());
+                        return true;
+                    }
                 }
             }
         }
+        return false;
     }
 }
 
@@ -137,8 +154,8 @@ impl Chunk {
                 for mut line_data in plane_data {
                     for block_data in line_data {
                         if let Some(block) = blocks_loader.loaded_blocks.get(*block_data as usize) {
-                            store_data.push((block.id >> 8) as u8);
-                            store_data.push(block.id as u8);
+                            store_data.push((block.lid >> 8) as u8);
+                            store_data.push(block.lid as u8);
                         } else {
                             return Err(Box::new(BlockUsingError::BlockNotFoundError()));
                         }
@@ -149,60 +166,83 @@ impl Chunk {
         return Ok( store_data );
     }
 
-    pub fn load(blocks_loader: &BlocksLoader, data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
-        return if let Some(unknown_block) = blocks_loader.blocks_ids.get(&UNKNOWN_BLOCK_ID) {
-            let mut chunk_data: [[[[u16; 16]; 16]; 16]; 16] = [[[[unknown_block.lid; 16]; 16]; 16]; 16];
-            for subchunk_pos in 0..16u8 {
-                for plane_pos in 0..16u8 {
-                    for line_pos in 0..16u8 {
-                        for block_pos in 0..16u8 {
-                            let block_data_pos = ((((subchunk_pos as u16) << 12) | ((plane_pos as u16) << 8) | ((line_pos as u16) << 4) | (block_pos as u16)) as usize) << 1;
-                            if let Some(b_block_data) = data.get(block_data_pos) {
-                                if let Some(l_block_data) = data.get(block_data_pos + 1) {
-                                    let block_id = (((*b_block_data as u16) << 8) | (*l_block_data as u16));
-                                    if let Some(block) = blocks_loader.blocks_ids.get(&block_id) {
-                                        chunk_data[subchunk_pos as usize][plane_pos as usize][line_pos as usize][block_pos as usize] = block.lid;
-                                    } else {
-                                        return Err(Box::new(BlockUsingError::BlockNotFoundError()));
-                                    }
+    //lids_map[saved_id] = lid, saved ids come from the registry of the save file
+    pub fn load(blocks_loader: &BlocksLoader, data: &[u8], lids_map: &[u16]) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut chunk_data: [[[[u16; 16]; 16]; 16]; 16] = [[[[blocks_loader.unknown_block.lid; 16]; 16]; 16]; 16];
+        for subchunk_pos in 0..16u8 {
+            for plane_pos in 0..16u8 {
+                for line_pos in 0..16u8 {
+                    for block_pos in 0..16u8 {
+                        let block_data_pos = ((((subchunk_pos as u16) << 12) | ((plane_pos as u16) << 8) | ((line_pos as u16) << 4) | (block_pos as u16)) as usize) << 1;
+                        if let Some(b_block_data) = data.get(block_data_pos) {
+                            if let Some(l_block_data) = data.get(block_data_pos + 1) {
+                                let block_id = (((*b_block_data as u16) << 8) | (*l_block_data as u16));
+                                if let Some(block_lid) = lids_map.get(block_id as usize) {
+                                    chunk_data[subchunk_pos as usize][plane_pos as usize][line_pos as usize][block_pos as usize] = *block_lid;
                                 } else {
-                                    return Err(Box::new(ChunkLoadingError::IdConstructError()));
+                                    return Err(Box::new(BlockUsingError::BlockNotFoundError()));
                                 }
                             } else {
                                 return Err(Box::new(ChunkLoadingError::IdConstructError()));
                             }
+                        } else {
+                            return Err(Box::new(ChunkLoadingError::IdConstructError()));
                         }
                     }
                 }
             }
-            Ok(Chunk {
-                subchunks: [
-                    SubChunk::new(chunk_data[0 ]),
-                    SubChunk::new(chunk_data[1 ]),
-                    SubChunk::new(chunk_data[2 ]),
-                    SubChunk::new(chunk_data[3 ]),
-                    SubChunk::new(chunk_data[4 ]),
-                    SubChunk::new(chunk_data[5 ]),
-                    SubChunk::new(chunk_data[6 ]),
-                    SubChunk::new(chunk_data[7 ]),
-                    SubChunk::new(chunk_data[8 ]),
-                    SubChunk::new(chunk_data[9 ]),
-                    SubChunk::new(chunk_data[10]),
-                    SubChunk::new(chunk_data[11]),
-                    SubChunk::new(chunk_data[12]),
-                    SubChunk::new(chunk_data[13]),
-                    SubChunk::new(chunk_data[14]),
-                    SubChunk::new(chunk_data[15]),
-                ],
-            })
-        } else {
-            Err(Box::new(BlockUsingError::BlockNotFoundError()))
         }
+        return Ok(Chunk {
+            subchunks: [
+                SubChunk::new(chunk_data[0 ]),
+                SubChunk::new(chunk_data[1 ]),
+                SubChunk::new(chunk_data[2 ]),
+                SubChunk::new(chunk_data[3 ]),
+                SubChunk::new(chunk_data[4 ]),
+                SubChunk::new(chunk_data[5 ]),
+                SubChunk::new(chunk_data[6 ]),
+                SubChunk::new(chunk_data[7 ]),
+                SubChunk::new(chunk_data[8 ]),
+                SubChunk::new(chunk_data[9 ]),
+                SubChunk::new(chunk_data[10]),
+                SubChunk::new(chunk_data[11]),
+                SubChunk::new(chunk_data[12]),
+                SubChunk::new(chunk_data[13]),
+                SubChunk::new(chunk_data[14]),
+                SubChunk::new(chunk_data[15]),
+            ],
+        });
+    }
+
+    pub fn store_states(&self) -> Vec<u8> {
+        let mut store_data: Vec<u8> = Vec::new();
+        for subchunk in &self.subchunks {
+            for plane_data in subchunk.states.borrow().deref() {
+                for line_data in plane_data {
+                    store_data.extend_from_slice(line_data);
+                }
+            }
+        }
+        return store_data;
+    }
+
+    pub fn load_states(&self, data: &[u8]) -> Result<(), ChunkLoadingError> {
+        if data.len() != CHUNK_STATES_SIZE {
+            return Err(ChunkLoadingError::InvalidStatesSizeError());
+        }
+        for (subchunk_pos, subchunk_data) in data.chunks(16 * 16 * 16).enumerate() {
+            let mut states = self.subchunks[subchunk_pos].states.borrow_mut();
+            for (line_ind, line_data) in subchunk_data.chunks(16).enumerate() {
+                states[line_ind >> 4][line_ind & 0x0F].copy_from_slice(line_data);
+            }
+        }
+        return Ok(());
     }
 
     pub fn set_data(&self, chunk: Chunk) {
         for subchunk_pos in 0..16u8 {
             self.subchunks[subchunk_pos as usize].data.replace_with(|_| *chunk.subchunks[subchunk_pos as usize].data.borrow());
+            self.subchunks[subchunk_pos as usize].states.replace_with(|_| *chunk.subchunks[subchunk_pos as usize].states.borrow());
             self.subchunks[subchunk_pos as usize].is_changed.set(true);
         }
     }
@@ -226,10 +266,19 @@ impl Chunk {
         }
     }
 
-    pub fn draw(&self, shader_program: &ShaderProgram, pos: &Vec3ub) {
+    //Returns the count of draw calls
+    pub fn draw(&self, shader_program: &ShaderProgram, pos: &Vec3ub) -> usize {
+        let mut draw_calls: usize = 0;
         for chunk_pos in 0..16u8 {
-            self.subchunks[chunk_pos as usize].draw(shader_program, &[pos[0], pos[1] + (chunk_pos << 4), pos[2]]);
+            if self.subchunks[chunk_pos as usize].draw(shader_program, &[pos[0], pos[1] + (chunk_pos << 4), pos[2]]) {
+                draw_calls += 1;
+            }
         }
+        return draw_calls;
+    }
+
+    pub fn get_changed_subchunks_cnt(&self) -> usize {
+        return self.subchunks.iter().filter(|subchunk| subchunk.is_changed.get()).count();
     }
 }
 
@@ -239,18 +288,19 @@ pub trait ChunkGenerator {
 
 
 
+//Layers are lids, so the generator must be built from the blocks_loader it is used with
 pub struct LayerChunkGenerator {
     pub layers: [[u16; 16]; 16],
 }
 
 impl LayerChunkGenerator {
 
-    pub fn new() -> Self {
-        Self { layers: [[AIR_BLOCK_ID; 16]; 16] }
+    pub fn new(fill_lid: u16) -> Self {
+        Self { layers: [[fill_lid; 16]; 16] }
     }
 
-    pub fn from_bottom_layers(bottom_layers: &[u16]) -> Self {
-        let mut layers: [[u16; 16]; 16] = [[AIR_BLOCK_ID; 16]; 16];
+    pub fn from_bottom_layers(bottom_layers: &[u16], fill_lid: u16) -> Self {
+        let mut layers: [[u16; 16]; 16] = [[fill_lid; 16]; 16];
         let top_pos = {
             if bottom_layers.len() < 256 {
                 bottom_layers.len()
@@ -265,8 +315,8 @@ impl LayerChunkGenerator {
         Self { layers }
     }
 
-    pub fn from_top_layers(top_layers: &[u16]) -> Self {
-        let mut layers: [[u16; 16]; 16] = [[AIR_BLOCK_ID; 16]; 16];
+    pub fn from_top_layers(top_layers: &[u16], fill_lid: u16) -> Self {
+        let mut layers: [[u16; 16]; 16] = [[fill_lid; 16]; 16];
         let bottom_pos = {
             if top_layers.len() < 256 {
                 256 - top_layers.len()
@@ -284,39 +334,35 @@ impl LayerChunkGenerator {
 
 impl ChunkGenerator for LayerChunkGenerator {
     fn get_chunk(&self, blocks_loader: &BlocksLoader) -> Result<Chunk, Box<dyn std::error::Error>> {
-        return if let Some(unknown_block) = blocks_loader.blocks_ids.get(&UNKNOWN_BLOCK_ID) {
-            let mut data: [[[[u16; 16]; 16]; 16]; 16] = [[[[unknown_block.lid; 16]; 16]; 16]; 16];
-            for subchunk_pos in 0..16u8 {
-                for layer_pos in 0..16u8 {
-                    if let Some(block) = blocks_loader.blocks_ids.get(&self.layers[subchunk_pos as usize][layer_pos as usize]) {
-                        data[subchunk_pos as usize][layer_pos as usize] = [[block.lid; 16]; 16];
-                    } else {
-                        return Err(Box::new(BlockUsingError::BlockNotFoundError()));
-                    }
+        let mut data: [[[[u16; 16]; 16]; 16]; 16] = [[[[blocks_loader.unknown_block.lid; 16]; 16]; 16]; 16];
+        for subchunk_pos in 0..16u8 {
+            for layer_pos in 0..16u8 {
+                if let Some(block) = blocks_loader.loaded_blocks.get(self.layers[subchunk_pos as usize][layer_pos as usize] as usize) {
+                    data[subchunk_pos as usize][layer_pos as usize] = [[block.lid; 16]; 16];
+                } else {
+                    return Err(Box::new(BlockUsingError::BlockNotFoundError()));
                 }
             }
-            Ok(Chunk {
-                subchunks: [
-                    SubChunk::new(data[0 ]),
-                    SubChunk::new(data[1 ]),
-                    SubChunk::new(data[2 ]),
-                    SubChunk::new(data[3 ]),
-                    SubChunk::new(data[4 ]),
-                    SubChunk::new(data[5 ]),
-                    SubChunk::new(data[6 ]),
-                    SubChunk::new(data[7 ]),
-                    SubChunk::new(data[8 ]),
-                    SubChunk::new(data[9 ]),
-                    SubChunk::new(data[10]),
-                    SubChunk::new(data[11]),
-                    SubChunk::new(data[12]),
-                    SubChunk::new(data[13]),
-                    SubChunk::new(data[14]),
-                    SubChunk::new(data[15]),
-                ],
-            })
-        } else {
-            Err(Box::new(BlockUsingError::BlockNotFoundError()))
         }
+        return Ok(Chunk {
+            subchunks: [
+                SubChunk::new(data[0 ]),
+                SubChunk::new(data[1 ]),
+                SubChunk::new(data[2 ]),
+                SubChunk::new(data[3 ]),
+                SubChunk::new(data[4 ]),
+                SubChunk::new(data[5 ]),
+                SubChunk::new(data[6 ]),
+                SubChunk::new(data[7 ]),
+                SubChunk::new(data[8 ]),
+                SubChunk::new(data[9 ]),
+                SubChunk::new(data[10]),
+                SubChunk::new(data[11]),
+                SubChunk::new(data[12]),
+                SubChunk::new(data[13]),
+                SubChunk::new(data[14]),
+                SubChunk::new(data[15]),
+            ],
+        });
     }
 }
\ No newline at end of file
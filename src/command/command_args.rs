@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+use thiserror::Error;
+use crate::render::blocks_loader::BlocksLoader;
+use crate::render::types::{Vec3f, Vec3ub};
+
+#[derive(Error, Debug)]
+pub enum CommandParsingError {
+    #[error("Unclosed quote")]
+    UnclosedQuoteError(),
+    #[error("Missing argument {0}, usage: {1}")]
+    MissingArgumentError(String, String),
+    #[error("Unexpected argument {0}, usage: {1}")]
+    UnexpectedArgumentError(String, String),
+    #[error("Invalid number {0}")]
+    InvalidNumberError(String),
+    #[error("Number {0} is not in {1}..={2}")]
+    NumberRangeError(i64, i64, i64),
+    #[error("Invalid coordinate {0}")]
+    InvalidCoordError(String),
+    #[error("Position {0} {1} {2} is outside of the world")]
+    OutOfWorldError(f32, f32, f32),
+    #[error("Unknown block {0}")]
+    UnknownBlockError(String),
+    #[error("Unknown value {0}, expected one of {1}")]
+    UnknownChoiceError(String, String),
+    #[error("Argument {0} is absent")]
+    AbsentArgumentError(String),
+    #[error("Argument {0} is of another kind")]
+    ArgumentKindError(String),
+}
+
+pub const COMMAND_PREFIX: char = '/';
+pub const RELATIVE_COORD_PREFIX: char = '~';
+
+const WORLD_SIZE: f32 = 256.0;
+
+#[derive(Clone, Copy, Debug)]
+pub enum ArgKind {
+    Pos, //x y z, each absolute or relative to the player like ~ or ~-2
+    Block, //namespaced name, or just the part after ':' if only one block has it
+    Int(i64, i64), //min and max
+    Choice(&'static [&'static str]),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ArgSpec {
+    pub name: &'static str,
+    pub kind: ArgKind,
+    pub optional: bool, //only the last arguments may be optional
+}
+
+impl ArgSpec {
+    pub const fn new(name: &'static str, kind: ArgKind) -> Self {
+        return Self { name, kind, optional: false };
+    }
+
+    pub const fn optional(name: &'static str, kind: ArgKind) -> Self {
+        return Self { name, kind, optional: true };
+    }
+
+    pub fn get_tokens_cnt(&self) -> usize {
+        return match self.kind {
+            ArgKind::Pos => 3,
+            _ => 1,
+        }
+    }
+
+    pub fn get_usage(&self) -> String {
+        let usage: String = match self.kind {
+            ArgKind::Pos => format!("{}: x y z", self.name),
+            ArgKind::Choice(choices) => choices.join("|"),
+            _ => String::from(self.name),
+        };
+        return if self.optional {
+            format!("[{}]", usage)
+        } else {
+            format!("<{}>", usage)
+        }
+    }
+
+    //Values the argument can start with, for completion
+    pub fn get_candidates(&self, blocks_loader: &BlocksLoader) -> Vec<String> {
+        return match self.kind {
+            ArgKind::Pos => vec![String::from(RELATIVE_COORD_PREFIX)],
+            ArgKind::Block => blocks_loader.loaded_blocks.iter().map(|block| block.name.clone()).collect(),
+            ArgKind::Int(_, _) => Vec::new(),
+            ArgKind::Choice(choices) => choices.iter().map(|choice| String::from(*choice)).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArgValue {
+    Pos(Vec3f),
+    Block(u16),
+    Int(i64),
+    Choice(String),
+}
+
+//Parsed arguments by names, omitted optional arguments are absent
+pub struct CommandArgs {
+    values: HashMap<&'static str, ArgValue>,
+}
+
+impl CommandArgs {
+    fn get(&self, name: &str) -> Result<&ArgValue, CommandParsingError> {
+        return self.values.get(name).ok_or_else(|| CommandParsingError::AbsentArgumentError(String::from(name)));
+    }
+
+    pub fn get_pos(&self, name: &str) -> Result<Vec3f, CommandParsingError> {
+        return match self.get(name)? {
+            ArgValue::Pos(pos) => Ok(*pos),
+            _ => Err(CommandParsingError::ArgumentKindError(String::from(name))),
+        }
+    }
+
+    //Block containing the position, positions are checked to be inside of the world by the parsing
+    pub fn get_block_pos(&self, name: &str) -> Result<Vec3ub, CommandParsingError> {
+        let pos: Vec3f = self.get_pos(name)?;
+        return Ok([pos[0].floor() as u8, pos[1].floor() as u8, pos[2].floor() as u8]);
+    }
+
+    pub fn get_block(&self, name: &str) -> Result<u16, CommandParsingError> {
+        return match self.get(name)? {
+            ArgValue::Block(block_lid) => Ok(*block_lid),
+            _ => Err(CommandParsingError::ArgumentKindError(String::from(name))),
+        }
+    }
+
+    pub fn get_int(&self, name: &str) -> Result<i64, CommandParsingError> {
+        return match self.get(name)? {
+            ArgValue::Int(value) => Ok(*value),
+            _ => Err(CommandParsingError::ArgumentKindError(String::from(name))),
+        }
+    }
+
+    pub fn get_choice(&self, name: &str) -> Result<&str, CommandParsingError> {
+        return match self.get(name)? {
+            ArgValue::Choice(choice) => Ok(choice.as_str()),
+            _ => Err(CommandParsingError::ArgumentKindError(String::from(name))),
+        }
+    }
+}
+
+//Splits at spaces, double quotes keep spaces inside of a token
+pub fn tokenize(line: &str) -> Result<Vec<String>, CommandParsingError> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut token: Option<String> = None;
+    let mut is_quoted: bool = false;
+    for char in line.chars() {
+        if char == '"' {
+            is_quoted = !is_quoted;
+            token.get_or_insert_with(String::new);
+        } else if char.is_whitespace() && !is_quoted {
+            if let Some(token) = token.take() {
+                tokens.push(token);
+            }
+        } else {
+            token.get_or_insert_with(String::new).push(char);
+        }
+    }
+    if is_quoted {
+        return Err(CommandParsingError::UnclosedQuoteError());
+    }
+    if let Some(token) = token {
+        tokens.push(token);
+    }
+    return Ok(tokens);
+}
+
+//Name of the command without the prefix and its arguments
+pub fn split_command(line: &str) -> Result<Option<(String, Vec<String>)>, CommandParsingError> {
+    let mut tokens: Vec<String> = tokenize(line.trim_start().strip_prefix(COMMAND_PREFIX).unwrap_or(line))?;
+    return if tokens.len() != 0 {
+        let name: String = tokens.remove(0);
+        Ok(Some((name, tokens)))
+    } else {
+        Ok(None)
+    }
+}
+
+//Exact name, or the part after ':' when it is unique
+pub fn find_block(blocks_loader: &BlocksLoader, name: &str) -> Option<u16> {
+    if let Ok(block_lid) = blocks_loader.get_lid(name) {
+        return Some(block_lid);
+    }
+    let mut found: Option<u16> = None;
+    for block in &blocks_loader.loaded_blocks {
+        if block.name.split_once(':').map_or(false, |(_, path)| path == name) {
+            if found.is_some() {
+                return None;
+            }
+            found = Some(block.lid);
+        }
+    }
+    return found;
+}
+
+fn parse_coord(token: &str, base: f32) -> Result<f32, CommandParsingError> {
+    let (offset, base): (&str, f32) = match token.strip_prefix(RELATIVE_COORD_PREFIX) {
+        Some(offset) => (offset, base),
+        None => (token, 0.0),
+    };
+    if offset.is_empty() && token.starts_with(RELATIVE_COORD_PREFIX) {
+        return Ok(base);
+    }
+    return match offset.parse::<f32>() {
+        Ok(value) if value.is_finite() => Ok(base + value),
+        _ => Err(CommandParsingError::InvalidCoordError(String::from(token))),
+    }
+}
+
+fn parse_arg(spec: &ArgSpec, tokens: &[String], player_pos: &Vec3f, blocks_loader: &BlocksLoader) -> Result<ArgValue, CommandParsingError> {
+    return match spec.kind {
+        ArgKind::Pos => {
+            let pos: Vec3f = [parse_coord(&tokens[0], player_pos[0])?, parse_coord(&tokens[1], player_pos[1])?, parse_coord(&tokens[2], player_pos[2])?];
+            if pos.iter().any(|coord| *coord < 0.0 || *coord >= WORLD_SIZE) {
+                return Err(CommandParsingError::OutOfWorldError(pos[0], pos[1], pos[2]));
+            }
+            Ok(ArgValue::Pos(pos))
+        }
+        ArgKind::Block => {
+            let block_lid: u16 = find_block(blocks_loader, &tokens[0]).ok_or_else(|| CommandParsingError::UnknownBlockError(tokens[0].clone()))?;
+            Ok(ArgValue::Block(block_lid))
+        }
+        ArgKind::Int(min, max) => {
+            let value: i64 = tokens[0].parse::<i64>().map_err(|_| CommandParsingError::InvalidNumberError(tokens[0].clone()))?;
+            if value < min || value > max {
+                return Err(CommandParsingError::NumberRangeError(value, min, max));
+            }
+            Ok(ArgValue::Int(value))
+        }
+        ArgKind::Choice(choices) => {
+            if !choices.contains(&tokens[0].as_str()) {
+                return Err(CommandParsingError::UnknownChoiceError(tokens[0].clone(), choices.join(", ")));
+            }
+            Ok(ArgValue::Choice(tokens[0].clone()))
+        }
+    }
+}
+
+//Relative coordinates are resolved against the player position, usage is shown in the errors
+pub fn parse_args(specs: &[ArgSpec], tokens: &[String], player_pos: &Vec3f, blocks_loader: &BlocksLoader, usage: &str) -> Result<CommandArgs, CommandParsingError> {
+    let mut values: HashMap<&'static str, ArgValue> = HashMap::new();
+    let mut pos: usize = 0;
+    for spec in specs {
+        let tokens_cnt: usize = spec.get_tokens_cnt();
+        if pos >= tokens.len() && spec.optional {
+            break;
+        }
+        if pos + tokens_cnt > tokens.len() {
+            return Err(CommandParsingError::MissingArgumentError(String::from(spec.name), String::from(usage)));
+        }
+        values.insert(spec.name, parse_arg(spec, &tokens[pos..(pos + tokens_cnt)], player_pos, blocks_loader)?);
+        pos += tokens_cnt;
+    }
+    if let Some(token) = tokens.get(pos) {
+        return Err(CommandParsingError::UnexpectedArgumentError(token.clone(), String::from(usage)));
+    }
+    return Ok(CommandArgs { values });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use crate::packs::pack_stack::PackStack;
+    use super::*;
+
+    const POS_ARGS: &[ArgSpec] = &[ArgSpec::new("pos", ArgKind::Pos)];
+    const INT_ARGS: &[ArgSpec] = &[ArgSpec::new("count", ArgKind::Int(1, 64)), ArgSpec::optional("extra", ArgKind::Int(-5, 5))];
+    const BLOCK_ARGS: &[ArgSpec] = &[ArgSpec::new("block", ArgKind::Block), ArgSpec::optional("mode", ArgKind::Choice(&["keep", "replace"]))];
+
+    fn get_blocks_loader() -> BlocksLoader {
+        let packs: PackStack = PackStack::open(&[PathBuf::from("assets")]).unwrap();
+        return BlocksLoader::load_headless(&packs).unwrap();
+    }
+
+    fn get_tokens(line: &str) -> Vec<String> {
+        return tokenize(line).unwrap();
+    }
+
+    #[test]
+    fn tokenize_splits_at_spaces() {
+        assert_eq!(get_tokens("  setblock ~ 5\t~-2  dirt "), vec!["setblock", "~", "5", "~-2", "dirt"]);
+        assert_eq!(get_tokens(""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn tokenize_keeps_quoted_spaces() {
+        assert_eq!(get_tokens("say \"hello  world\" a\"b c\""), vec!["say", "hello  world", "ab c"]);
+        assert_eq!(get_tokens("say \"\""), vec!["say", ""]);
+        assert!(matches!(tokenize("say \"hello"), Err(CommandParsingError::UnclosedQuoteError())));
+    }
+
+    #[test]
+    fn split_command_strips_the_prefix() {
+        assert_eq!(split_command(" /tp 1 2 3").unwrap(), Some((String::from("tp"), vec![String::from("1"), String::from("2"), String::from("3")])));
+        assert_eq!(split_command("/").unwrap(), None);
+    }
+
+    #[test]
+    fn parse_args_resolves_relative_coords() {
+        let blocks_loader: BlocksLoader = get_blocks_loader();
+        let player_pos: Vec3f = [10.5, 20.0, 30.25];
+        let args: CommandArgs = parse_args(POS_ARGS, &get_tokens("~ ~-2.5 ~4"), &player_pos, &blocks_loader, "").unwrap();
+        assert_eq!(args.get_pos("pos").unwrap(), [10.5, 17.5, 34.25]);
+        assert_eq!(args.get_block_pos("pos").unwrap(), [10, 17, 34]);
+        let args: CommandArgs = parse_args(POS_ARGS, &get_tokens("1 ~ 255.5"), &player_pos, &blocks_loader, "").unwrap();
+        assert_eq!(args.get_pos("pos").unwrap(), [1.0, 20.0, 255.5]);
+        assert!(matches!(parse_args(POS_ARGS, &get_tokens("~x 0 0"), &player_pos, &blocks_loader, ""), Err(CommandParsingError::InvalidCoordError(_))));
+        assert!(matches!(parse_args(POS_ARGS, &get_tokens("~-11 0 0"), &player_pos, &blocks_loader, ""), Err(CommandParsingError::OutOfWorldError(_, _, _))));
+        assert!(matches!(parse_args(POS_ARGS, &get_tokens("0 256 0"), &player_pos, &blocks_loader, ""), Err(CommandParsingError::OutOfWorldError(_, _, _))));
+        assert!(matches!(parse_args(POS_ARGS, &get_tokens("0 0"), &player_pos, &blocks_loader, ""), Err(CommandParsingError::MissingArgumentError(_, _))));
+    }
+
+    #[test]
+    fn parse_args_checks_int_ranges() {
+        let blocks_loader: BlocksLoader = get_blocks_loader();
+        let player_pos: Vec3f = [0.0, 0.0, 0.0];
+        let args: CommandArgs = parse_args(INT_ARGS, &get_tokens("64 -5"), &player_pos, &blocks_loader, "").unwrap();
+        assert_eq!(args.get_int("count").unwrap(), 64);
+        assert_eq!(args.get_int("extra").unwrap(), -5);
+        let args: CommandArgs = parse_args(INT_ARGS, &get_tokens("1"), &player_pos, &blocks_loader, "").unwrap();
+        assert!(matches!(args.get_int("extra"), Err(CommandParsingError::AbsentArgumentError(_))));
+        assert!(matches!(parse_args(INT_ARGS, &get_tokens("0"), &player_pos, &blocks_loader, ""), Err(CommandParsingError::NumberRangeError(0, 1, 64))));
+        assert!(matches!(parse_args(INT_ARGS, &get_tokens("1 6"), &player_pos, &blocks_loader, ""), Err(CommandParsingError::NumberRangeError(6, -5, 5))));
+        assert!(matches!(parse_args(INT_ARGS, &get_tokens("one"), &player_pos, &blocks_loader, ""), Err(CommandParsingError::InvalidNumberError(_))));
+        assert!(matches!(parse_args(INT_ARGS, &get_tokens("1 2 3"), &player_pos, &blocks_loader, ""), Err(CommandParsingError::UnexpectedArgumentError(_, _))));
+        assert!(matches!(parse_args(INT_ARGS, &[], &player_pos, &blocks_loader, ""), Err(CommandParsingError::MissingArgumentError(_, _))));
+    }
+
+    #[test]
+    fn parse_args_finds_blocks_and_choices() {
+        let blocks_loader: BlocksLoader = get_blocks_loader();
+        let player_pos: Vec3f = [0.0, 0.0, 0.0];
+        let dirt: u16 = blocks_loader.get_lid("core:dirt").unwrap();
+        let args: CommandArgs = parse_args(BLOCK_ARGS, &get_tokens("dirt keep"), &player_pos, &blocks_loader, "").unwrap();
+        assert_eq!(args.get_block("block").unwrap(), dirt);
+        assert_eq!(args.get_choice("mode").unwrap(), "keep");
+        assert!(matches!(args.get_int("block"), Err(CommandParsingError::ArgumentKindError(_))));
+        let args: CommandArgs = parse_args(BLOCK_ARGS, &get_tokens("core:dirt"), &player_pos, &blocks_loader, "").unwrap();
+        assert_eq!(args.get_block("block").unwrap(), dirt);
+        assert!(matches!(parse_args(BLOCK_ARGS, &get_tokens("core:nothing"), &player_pos, &blocks_loader, ""), Err(CommandParsingError::UnknownBlockError(_))));
+        assert!(matches!(parse_args(BLOCK_ARGS, &get_tokens("dirt drop"), &player_pos, &blocks_loader, ""), Err(CommandParsingError::UnknownChoiceError(_, _))));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use thiserror::Error;
+use crate::behavior::block_behavior::BlockBehaviors;
+use crate::command::command_args::{ArgSpec, COMMAND_PREFIX, CommandArgs, parse_args, tokenize};
+use crate::player::player_data::PlayerData;
+use crate::render::blocks_loader::BlocksLoader;
+use crate::render::light::light_solvers::LightSolvers;
+use crate::world::World;
+
+#[derive(Error, Debug)]
+pub enum CommandRegistrationError {
+    #[error("Command name {0} is not a single lowercase word")]
+    InvalidNameError(String),
+    #[error("Command {0} is registered twice")]
+    RedefinitionError(String),
+}
+
+#[derive(Error, Debug)]
+pub enum CommandRunningError {
+    #[error("Unknown command {0}")]
+    UnknownCommandError(String),
+}
+
+//Everything commands may touch, nothing here needs a window or a GL context
+pub struct CommandContext<'a> {
+    pub world: &'a World,
+    pub blocks_loader: &'a BlocksLoader,
+    pub behaviors: &'a BlockBehaviors,
+    pub light_solvers: &'a LightSolvers,
+    pub player: &'a mut PlayerData,
+    pub world_path: &'a str,
+    pub player_data_path: &'a str,
+}
+
+//Returns the output shown to the player
+pub type CommandHandler = fn(&mut CommandContext, &CommandArgs) -> Result<String, Box<dyn std::error::Error>>;
+
+pub struct Command {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub args: &'static [ArgSpec],
+    pub handler: CommandHandler,
+}
+
+impl Command {
+    pub fn get_usage(&self) -> String {
+        let mut usage: String = format!("{}{}", COMMAND_PREFIX, self.name);
+        for arg in self.args {
+            usage.push(' ');
+            usage.push_str(&arg.get_usage());
+        }
+        return usage;
+    }
+}
+
+pub struct CommandRegistry {
+    commands: HashMap<String, Command>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        return Self {
+            commands: HashMap::new(),
+        };
+    }
+
+    pub fn register(&mut self, command: Command) -> Result<(), CommandRegistrationError> {
+        if command.name.is_empty() || !command.name.chars().all(|char| char.is_ascii_lowercase() || char.is_ascii_digit() || char == '_') {
+            return Err(CommandRegistrationError::InvalidNameError(String::from(command.name)));
+        }
+        if self.commands.contains_key(command.name) {
+            return Err(CommandRegistrationError::RedefinitionError(String::from(command.name)));
+        }
+        self.commands.insert(String::from(command.name), command);
+        return Ok(());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Command> {
+        return self.commands.get(name);
+    }
+
+    pub fn get_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.commands.keys().cloned().collect();
+        names.sort();
+        return names;
+    }
+
+    //Arguments are parsed against the player position before the handler is called
+    pub fn run(&self, context: &mut CommandContext, name: &str, args: &[String]) -> Result<String, Box<dyn std::error::Error>> {
+        let command: &Command = self.get(name).ok_or_else(|| CommandRunningError::UnknownCommandError(String::from(name)))?;
+        let command_args: CommandArgs = parse_args(command.args, args, &context.player.pos, context.blocks_loader, &command.get_usage())?;
+        return (command.handler)(context, &command_args);
+    }
+
+    //Whole lines the last word of line can be completed to, extra names are commands handled elsewhere
+    pub fn complete(&self, line: &str, blocks_loader: &BlocksLoader, extra_names: &[String]) -> Vec<String> {
+        let (head, word): (&str, &str) = match line.rfind(char::is_whitespace) {
+            Some(split_pos) => line.split_at(split_pos + 1),
+            None => ("", line),
+        };
+        let tokens: Vec<String> = match tokenize(head) {
+            Ok(tokens) => tokens,
+            Err(_) => return Vec::new(),
+        };
+        let mut candidates: Vec<String> = Vec::new();
+        if tokens.len() == 0 {
+            let name_start: &str = word.strip_prefix(COMMAND_PREFIX).unwrap_or(word);
+            for name in self.get_names().iter().chain(extra_names.iter()) {
+                if name.starts_with(name_start) {
+                    candidates.push(format!("{}{}", COMMAND_PREFIX, name));
+                }
+            }
+        } else if let Some(command) = self.get(tokens[0].strip_prefix(COMMAND_PREFIX).unwrap_or(&tokens[0])) {
+            //Positions take three words, so the argument is found by counting words
+            let mut word_ind: usize = tokens.len() - 1;
+            for arg in command.args {
+                if word_ind < arg.get_tokens_cnt() {
+                    for candidate in arg.get_candidates(blocks_loader) {
+                        if candidate.starts_with(word) || candidate.split_once(':').map_or(false, |(_, path)| path.starts_with(word)) {
+                            candidates.push(format!("{}{}", head, candidate));
+                        }
+                    }
+                    break;
+                }
+                word_ind -= arg.get_tokens_cnt();
+            }
+        }
+        candidates.sort();
+        candidates.dedup();
+        return candidates;
+    }
+}
\ No newline at end of file
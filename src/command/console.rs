@@ -0,0 +1,136 @@
+const MAX_LINES: usize = 256;
+const MAX_HISTORY: usize = 64;
+
+//Output and input history of the console, drawing and keys are handled by the game
+pub struct Console {
+    lines: Vec<String>,
+    history: Vec<String>,
+    history_pos: Option<usize>, //browsed history entry, None while editing a new line
+    draft: String, //new line kept while browsing the history
+}
+
+impl Console {
+    pub fn new() -> Self {
+        return Self {
+            lines: Vec::new(),
+            history: Vec::new(),
+            history_pos: None,
+            draft: String::new(),
+        };
+    }
+
+    pub fn print(&mut self, text: &str) {
+        for line in text.lines() {
+            self.lines.push(String::from(line));
+        }
+        if self.lines.len() > MAX_LINES {
+            self.lines.drain(0..(self.lines.len() - MAX_LINES));
+        }
+    }
+
+    //Last lines_cnt lines joined for a label
+    pub fn get_output(&self, lines_cnt: usize) -> String {
+        return self.lines[self.lines.len().saturating_sub(lines_cnt)..].join("\n");
+    }
+
+    //Echoes the line and puts it into the history, repeated lines are kept once
+    pub fn submit(&mut self, line: &str) {
+        self.print(&format!("> {}", line));
+        self.history_pos = None;
+        self.draft.clear();
+        if line.trim().is_empty() || self.history.last().map_or(false, |last| last == line) {
+            return;
+        }
+        self.history.push(String::from(line));
+        if self.history.len() > MAX_HISTORY {
+            self.history.remove(0);
+        }
+    }
+
+    //Older history entry, line is the current input kept as the draft
+    pub fn get_prev(&mut self, line: &str) -> Option<String> {
+        let history_pos: usize = match self.history_pos {
+            Some(0) => return None,
+            Some(history_pos) => history_pos - 1,
+            None if self.history.len() != 0 => {
+                self.draft = String::from(line);
+                self.history.len() - 1
+            }
+            None => return None,
+        };
+        self.history_pos = Some(history_pos);
+        return Some(self.history[history_pos].clone());
+    }
+
+    //Newer history entry, the draft after the newest one
+    pub fn get_next(&mut self) -> Option<String> {
+        let history_pos: usize = self.history_pos?;
+        return if history_pos + 1 < self.history.len() {
+            self.history_pos = Some(history_pos + 1);
+            Some(self.history[history_pos + 1].clone())
+        } else {
+            self.history_pos = None;
+            Some(self.draft.clone())
+        }
+    }
+
+    //A single candidate is taken whole, several are completed to their common start or printed
+    pub fn complete(&mut self, line: &str, candidates: &[String]) -> Option<String> {
+        if candidates.len() == 1 {
+            return Some(format!("{} ", candidates[0]));
+        }
+        let first: &String = candidates.first()?;
+        let mut common: &str = first;
+        for candidate in &candidates[1..] {
+            while !candidate.starts_with(common) {
+                common = &common[..common.char_indices().last().map_or(0, |(pos, _)| pos)];
+            }
+        }
+        if common.len() > line.len() {
+            return Some(String::from(common));
+        }
+        //Candidates are whole lines, only their last words are shown
+        let words: Vec<&str> = candidates.iter().map(|candidate| candidate.rsplit(char::is_whitespace).next().unwrap_or(candidate)).collect();
+        self.print(&words.join("  "));
+        return None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_candidates(candidates: &[&str]) -> Vec<String> {
+        return candidates.iter().map(|candidate| String::from(*candidate)).collect();
+    }
+
+    #[test]
+    fn complete_takes_a_single_candidate_whole() {
+        let mut console: Console = Console::new();
+        assert_eq!(console.complete("/se", &get_candidates(&["/setblock"])), Some(String::from("/setblock ")));
+        assert_eq!(console.get_output(1), "");
+    }
+
+    #[test]
+    fn complete_extends_to_the_common_start() {
+        let mut console: Console = Console::new();
+        let candidates: Vec<String> = get_candidates(&["/give core:dirt", "/give core:dirt_slab", "/give core:dirt_stairs"]);
+        assert_eq!(console.complete("/give d", &candidates), Some(String::from("/give core:dirt")));
+        assert_eq!(console.get_output(1), "");
+    }
+
+    #[test]
+    fn complete_prints_last_words_without_common_start() {
+        let mut console: Console = Console::new();
+        let candidates: Vec<String> = get_candidates(&["/give core:dirt", "/give core:dirt_slab", "/give core:dirt_stairs"]);
+        assert_eq!(console.complete("/give core:dirt", &candidates), None);
+        assert_eq!(console.get_output(1), "core:dirt  core:dirt_slab  core:dirt_stairs");
+        assert_eq!(console.complete("/nothing", &[]), None);
+    }
+
+    #[test]
+    fn complete_keeps_multibyte_chars_whole() {
+        let mut console: Console = Console::new();
+        assert_eq!(console.complete("/say ", &get_candidates(&["/say héllo", "/say hé!"])), Some(String::from("/say hé")));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,274 @@
+use thiserror::Error;
+use crate::command::command_args::{ArgKind, ArgSpec, CommandArgs};
+use crate::command::command_registry::{Command, CommandContext, CommandRegistrationError, CommandRegistry};
+use crate::player::inventory::{INVENTORY_SIZE, ItemStack, MAX_STACK_SIZE};
+use crate::render::types::{Vec3f, Vec3ub};
+
+const MAX_FILL_VOLUME: u64 = 32 * 32 * 32;
+const MAX_TICKS: i64 = 1 << 40;
+const MAX_GIVE_COUNT: i64 = (INVENTORY_SIZE as i64) * (MAX_STACK_SIZE as i64);
+
+const TP_ARGS: &[ArgSpec] = &[ArgSpec::new("pos", ArgKind::Pos)];
+const SETBLOCK_ARGS: &[ArgSpec] = &[ArgSpec::new("pos", ArgKind::Pos), ArgSpec::new("block", ArgKind::Block)];
+const FILL_ARGS: &[ArgSpec] = &[ArgSpec::new("from", ArgKind::Pos), ArgSpec::new("to", ArgKind::Pos), ArgSpec::new("block", ArgKind::Block)];
+const TIME_ARGS: &[ArgSpec] = &[ArgSpec::optional("action", ArgKind::Choice(&["query", "set", "add"])), ArgSpec::optional("ticks", ArgKind::Int(0, MAX_TICKS))];
+const GIVE_ARGS: &[ArgSpec] = &[ArgSpec::new("block", ArgKind::Block), ArgSpec::optional("count", ArgKind::Int(1, MAX_GIVE_COUNT))];
+
+#[derive(Error, Debug)]
+pub enum CoreCommandError {
+    #[error("Filled volume {0} is bigger than {1} blocks")]
+    FillVolumeError(u64, u64),
+    #[error("Ticks are needed to {0} the time")]
+    MissingTicksError(String),
+}
+
+pub fn register_core_commands(registry: &mut CommandRegistry) -> Result<(), CommandRegistrationError> {
+    registry.register(Command {
+        name: "tp",
+        description: "Moves the player",
+        args: TP_ARGS,
+        handler: run_tp,
+    })?;
+    registry.register(Command {
+        name: "setblock",
+        description: "Places a block, air breaks the block",
+        args: SETBLOCK_ARGS,
+        handler: run_setblock,
+    })?;
+    registry.register(Command {
+        name: "fill",
+        description: "Places a block in every position of a box",
+        args: FILL_ARGS,
+        handler: run_fill,
+    })?;
+    registry.register(Command {
+        name: "time",
+        description: "Shows or changes the world tick",
+        args: TIME_ARGS,
+        handler: run_time,
+    })?;
+    registry.register(Command {
+        name: "give",
+        description: "Adds blocks to the inventory",
+        args: GIVE_ARGS,
+        handler: run_give,
+    })?;
+    registry.register(Command {
+        name: "save",
+        description: "Saves the world and the player",
+        args: &[],
+        handler: run_save,
+    })?;
+    registry.register(Command {
+        name: "relight",
+        description: "Lights the whole world from scratch",
+        args: &[],
+        handler: run_relight,
+    })?;
+    return Ok(());
+}
+
+//Block hooks may change more blocks than the edited ones
+fn edit_block(context: &CommandContext, pos: &Vec3ub, block_lid: u16) {
+    let changed: Vec<Vec3ub> = if block_lid == context.blocks_loader.air_block.lid {
+        context.world.break_block(context.blocks_loader, context.behaviors, pos)
+    } else {
+        context.world.place_block(context.blocks_loader, context.behaviors, pos, block_lid)
+    };
+    for changed_pos in &changed {
+        context.light_solvers.on_block_edited(context.world, context.blocks_loader, changed_pos);
+    }
+}
+
+fn run_tp(context: &mut CommandContext, args: &CommandArgs) -> Result<String, Box<dyn std::error::Error>> {
+    let pos: Vec3f = args.get_pos("pos")?;
+    context.player.pos = pos;
+    return Ok(format!("Teleported to {:.2} {:.2} {:.2}", pos[0], pos[1], pos[2]));
+}
+
+fn run_setblock(context: &mut CommandContext, args: &CommandArgs) -> Result<String, Box<dyn std::error::Error>> {
+    let pos: Vec3ub = args.get_block_pos("pos")?;
+    let block_lid: u16 = args.get_block("block")?;
+    edit_block(context, &pos, block_lid);
+    return Ok(format!("Set {} at {} {} {}", context.blocks_loader.get_block(block_lid).name, pos[0], pos[1], pos[2]));
+}
+
+fn run_fill(context: &mut CommandContext, args: &CommandArgs) -> Result<String, Box<dyn std::error::Error>> {
+    let from: Vec3ub = args.get_block_pos("from")?;
+    let to: Vec3ub = args.get_block_pos("to")?;
+    let block_lid: u16 = args.get_block("block")?;
+    let min: Vec3ub = [from[0].min(to[0]), from[1].min(to[1]), from[2].min(to[2])];
+    let max: Vec3ub = [from[0].max(to[0]), from[1].max(to[1]), from[2].max(to[2])];
+    let volume: u64 = (0..3).map(|axis| (max[axis] - min[axis]) as u64 + 1).product();
+    if volume > MAX_FILL_VOLUME {
+        return Err(Box::new(CoreCommandError::FillVolumeError(volume, MAX_FILL_VOLUME)));
+    }
+    for x_pos in min[0]..=max[0] {
+        for y_pos in min[1]..=max[1] {
+            for z_pos in min[2]..=max[2] {
+                edit_block(context, &[x_pos, y_pos, z_pos], block_lid);
+            }
+        }
+    }
+    return Ok(format!("Filled {} blocks with {}", volume, context.blocks_loader.get_block(block_lid).name));
+}
+
+fn run_time(context: &mut CommandContext, args: &CommandArgs) -> Result<String, Box<dyn std::error::Error>> {
+    let action: &str = args.get_choice("action").unwrap_or("query");
+    if action == "query" {
+        return Ok(format!("Tick {}", context.world.ticks.get_tick()));
+    }
+    let ticks: u64 = args.get_int("ticks").map_err(|_| CoreCommandError::MissingTicksError(String::from(action)))? as u64;
+    let tick: u64 = if action == "set" { ticks } else { context.world.ticks.get_tick() + ticks };
+    context.world.ticks.set_tick(tick);
+    return Ok(format!("Tick set to {}", tick));
+}
+
+fn run_give(context: &mut CommandContext, args: &CommandArgs) -> Result<String, Box<dyn std::error::Error>> {
+    let name: String = context.blocks_loader.get_block(args.get_block("block")?).name.clone();
+    let count: i64 = args.get_int("count").unwrap_or(1);
+    let mut left: i64 = count;
+    while left > 0 {
+        let stack_count: u8 = left.min(MAX_STACK_SIZE as i64) as u8;
+        let stack_left: u8 = context.player.inventory.add(&ItemStack::new(&name, stack_count));
+        left -= (stack_count - stack_left) as i64;
+        if stack_left != 0 {
+            break;
+        }
+    }
+    return Ok(if left == 0 {
+        format!("Gave {} {}", count, name)
+    } else {
+        format!("Gave {} {}, {} did not fit into the inventory", count - left, name, left)
+    });
+}
+
+fn run_save(context: &mut CommandContext, _args: &CommandArgs) -> Result<String, Box<dyn std::error::Error>> {
+    context.world.store(context.blocks_loader, String::from(context.world_path))?;
+    context.player.store(String::from(context.player_data_path))?;
+    return Ok(String::from("World and player saved"));
+}
+
+fn run_relight(context: &mut CommandContext, _args: &CommandArgs) -> Result<String, Box<dyn std::error::Error>> {
+    context.world.clear_light();
+    context.light_solvers.init(context.world, context.blocks_loader);
+    return Ok(String::from("World relit"));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::thread::{Builder, JoinHandle};
+    use crate::behavior::block_behavior::BlockBehaviors;
+    use crate::chunk::LayerChunkGenerator;
+    use crate::command::command_args::split_command;
+    use crate::packs::pack_stack::PackStack;
+    use crate::player::inventory::Inventory;
+    use crate::player::player_controller::PlayerMode;
+    use crate::player::player_data::PlayerData;
+    use crate::render::blocks_loader::BlocksLoader;
+    use crate::render::light::light_map::R_CHANNEL;
+    use crate::render::light::light_solvers::LightSolvers;
+    use crate::world::World;
+    use super::*;
+
+    const TEST_STACK_SIZE: usize = 64 * 1024 * 1024;
+
+    struct TestGame {
+        blocks_loader: BlocksLoader,
+        behaviors: BlockBehaviors,
+        light_solvers: LightSolvers,
+        world: World,
+        player: PlayerData,
+        registry: CommandRegistry,
+    }
+
+    //Flat world of 4 dirt layers over bedrock, the player stands on it
+    fn get_test_game() -> TestGame {
+        let packs: PackStack = PackStack::open(&[PathBuf::from("assets")]).unwrap();
+        let blocks_loader: BlocksLoader = BlocksLoader::load_headless(&packs).unwrap();
+        let bedrock: u16 = blocks_loader.get_lid("core:bedrock").unwrap();
+        let dirt: u16 = blocks_loader.get_lid("core:dirt").unwrap();
+        let world: World = World::new(&LayerChunkGenerator::from_bottom_layers(&[bedrock, dirt, dirt, dirt, dirt], blocks_loader.air_block.lid), &blocks_loader).unwrap();
+        let mut registry: CommandRegistry = CommandRegistry::new();
+        register_core_commands(&mut registry).unwrap();
+        return TestGame {
+            blocks_loader,
+            behaviors: BlockBehaviors::new(),
+            light_solvers: LightSolvers::new(),
+            world,
+            player: PlayerData { pos: [8.5, 5.0, 8.5], rot: [0.0, 0.0, 0.0], mode: PlayerMode::Walk, inventory: Inventory::new() },
+            registry,
+        };
+    }
+
+    //Chunks are generated on the stack, which is too big for the default test threads
+    fn run_test(test: fn(&mut TestGame)) {
+        let handle: JoinHandle<()> = Builder::new().stack_size(TEST_STACK_SIZE).spawn(move || test(&mut get_test_game())).unwrap();
+        handle.join().unwrap();
+    }
+
+    fn run(game: &mut TestGame, line: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let mut context: CommandContext = CommandContext {
+            world: &game.world,
+            blocks_loader: &game.blocks_loader,
+            behaviors: &game.behaviors,
+            light_solvers: &game.light_solvers,
+            player: &mut game.player,
+            world_path: "",
+            player_data_path: "",
+        };
+        let (name, args) = split_command(line)?.unwrap();
+        return game.registry.run(&mut context, &name, &args);
+    }
+
+    #[test]
+    fn setblock_places_and_breaks() {
+        run_test(|game: &mut TestGame| {
+            let dirt: u16 = game.blocks_loader.get_lid("core:dirt").unwrap();
+            run(game, "/setblock ~ ~ ~2 dirt").unwrap();
+            assert_eq!(game.world.get_block(&[8, 5, 10]), dirt);
+            run(game, "/setblock 8 4 8 core:air").unwrap();
+            assert_eq!(game.world.get_block(&[8, 4, 8]), game.blocks_loader.air_block.lid);
+            assert!(run(game, "/setblock 8 4 8 core:nothing").is_err());
+            assert!(run(game, "/setblock 8 300 8 dirt").is_err());
+        });
+    }
+
+    #[test]
+    fn setblock_lights_emitting_blocks() {
+        run_test(|game: &mut TestGame| {
+            run(game, "/setblock 8 6 8 core:lava").unwrap();
+            assert_eq!(game.world.get_light_level(&[8, 6, 8], R_CHANNEL), 15);
+            assert_eq!(game.world.get_light_level(&[8, 8, 8], R_CHANNEL), 13);
+            run(game, "/setblock 8 6 8 core:air").unwrap();
+            assert_eq!(game.world.get_light_level(&[8, 6, 8], R_CHANNEL), 0);
+            assert_eq!(game.world.get_light_level(&[8, 8, 8], R_CHANNEL), 0);
+        });
+    }
+
+    #[test]
+    fn fill_sets_every_block_of_the_box() {
+        run_test(|game: &mut TestGame| {
+            let dirt: u16 = game.blocks_loader.get_lid("core:dirt").unwrap();
+            let output: String = run(game, "/fill 12 7 12 10 5 10 dirt").unwrap();
+            assert_eq!(output, "Filled 27 blocks with core:dirt");
+            for x_pos in 9..=13u8 {
+                for y_pos in 5..=8u8 {
+                    for z_pos in 9..=13u8 {
+                        let is_inside: bool = (10..=12).contains(&x_pos) && (5..=7).contains(&y_pos) && (10..=12).contains(&z_pos);
+                        assert_eq!(game.world.get_block(&[x_pos, y_pos, z_pos]) == dirt, is_inside);
+                    }
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn fill_rejects_too_big_boxes() {
+        run_test(|game: &mut TestGame| {
+            assert!(run(game, "/fill 0 5 0 40 45 40 dirt").is_err());
+            assert_eq!(game.world.get_block(&[0, 5, 0]), game.blocks_loader.air_block.lid);
+        });
+    }
+}
\ No newline at end of file
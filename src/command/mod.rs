@@ -0,0 +1,4 @@
+pub mod command_args;
+pub mod command_registry;
+pub mod core_commands;
+pub mod console;
\ No newline at end of file
@@ -0,0 +1,204 @@
+use crate::render::light::light_solvers::LightSolvers;
+use crate::render::types::{Vec3f, Vec3i};
+use crate::tick_timer::TickTimer;
+use crate::world::World;
+
+const FPS_UPDATE_PERIOD: f32 = 0.5; //seconds the fps and frame time are averaged over
+const LIGHT_CHANNELS_NAMES: [&str; 4] = ["R", "G", "B", "S"];
+
+pub struct TargetStats {
+    pub pos: Vec3i,
+    pub name: String,
+    pub light: [u8; 4], //light levels of the R, G, B and S channels
+}
+
+//Collected from the game every frame, the overlay only shows the lines
+pub struct DebugStats {
+    frames_cnt: u32,
+    frames_time: f32,
+    pub fps: f32,
+    pub frame_time: f32,
+    pub ticks_count: u64,
+    pub ticks_duration: f32,
+    pub player_pos: Vec3f,
+    pub player_dir: Vec3f,
+    pub target: Option<TargetStats>,
+    pub changed_subchunks: usize, //collected before rendering
+    pub draw_calls: usize,
+    pub loaded_chunks: usize,
+    pub light_queues: [(usize, usize); 4], //largest add and remove queues of every channel solved since the last frame
+}
+
+impl DebugStats {
+    pub fn new() -> Self {
+        return Self {
+            frames_cnt: 0,
+            frames_time: 0.0,
+            fps: 0.0,
+            frame_time: 0.0,
+            ticks_count: 0,
+            ticks_duration: 0.0,
+            player_pos: [0.0, 0.0, 0.0],
+            player_dir: [0.0, 0.0, -1.0],
+            target: None,
+            changed_subchunks: 0,
+            draw_calls: 0,
+            loaded_chunks: 0,
+            light_queues: [(0, 0); 4],
+        };
+    }
+
+    pub fn add_frame(&mut self, frame_time: f32) {
+        self.frames_cnt += 1;
+        self.frames_time += frame_time.max(0.0);
+        if self.frames_time >= FPS_UPDATE_PERIOD {
+            self.fps = (self.frames_cnt as f32) / self.frames_time;
+            self.frame_time = self.frames_time / (self.frames_cnt as f32);
+            self.frames_cnt = 0;
+            self.frames_time = 0.0;
+        }
+    }
+
+    pub fn set_ticks(&mut self, tick_timer: &TickTimer) {
+        self.ticks_count = tick_timer.get_ticks_count();
+        self.ticks_duration = tick_timer.get_ticks_duration();
+    }
+
+    pub fn set_player(&mut self, pos: &Vec3f, dir: &Vec3f) {
+        self.player_pos = *pos;
+        self.player_dir = *dir;
+    }
+
+    pub fn set_world(&mut self, world: &World, light_solvers: &LightSolvers) {
+        self.changed_subchunks = world.get_changed_subchunks_cnt();
+        self.loaded_chunks = world.get_loaded_chunks_cnt();
+        self.light_queues = [
+            light_solvers.r.take_peak_queues_len(),
+            light_solvers.g.take_peak_queues_len(),
+            light_solvers.b.take_peak_queues_len(),
+            light_solvers.s.take_peak_queues_len(),
+        ];
+    }
+
+    pub fn get_block_pos(&self) -> Vec3i {
+        return [self.player_pos[0].floor() as i32, self.player_pos[1].floor() as i32, self.player_pos[2].floor() as i32];
+    }
+
+    pub fn get_chunk_pos(&self) -> [i32; 2] {
+        let block_pos: Vec3i = self.get_block_pos();
+        return [block_pos[0] >> 4, block_pos[2] >> 4];
+    }
+
+    pub fn get_subchunk_pos(&self) -> Vec3i {
+        let block_pos: Vec3i = self.get_block_pos();
+        return [block_pos[0] >> 4, block_pos[1] >> 4, block_pos[2] >> 4];
+    }
+
+    //Horizontal axis the player looks along the most
+    pub fn get_facing(&self) -> &'static str {
+        return if self.player_dir[0].abs() > self.player_dir[2].abs() {
+            if self.player_dir[0] > 0.0 { "east (+x)" } else { "west (-x)" }
+        } else {
+            if self.player_dir[2] > 0.0 { "south (+z)" } else { "north (-z)" }
+        }
+    }
+
+    pub fn get_lines(&self) -> Vec<String> {
+        let block_pos: Vec3i = self.get_block_pos();
+        let chunk_pos: [i32; 2] = self.get_chunk_pos();
+        let subchunk_pos: Vec3i = self.get_subchunk_pos();
+        let mut lines: Vec<String> = vec![
+            format!("{:.0} fps, {:.2} ms", self.fps, self.frame_time * 1000.0),
+            format!("Tick {}, ticks took {:.2} ms", self.ticks_count, self.ticks_duration * 1000.0),
+            format!("XYZ: {:.3} / {:.3} / {:.3}", self.player_pos[0], self.player_pos[1], self.player_pos[2]),
+            format!("Block: {} {} {}", block_pos[0], block_pos[1], block_pos[2]),
+            format!("Chunk: {} {}, subchunk: {} {} {}", chunk_pos[0], chunk_pos[1], subchunk_pos[0], subchunk_pos[1], subchunk_pos[2]),
+            format!("Facing: {} ({:.2} / {:.2} / {:.2})", self.get_facing(), self.player_dir[0], self.player_dir[1], self.player_dir[2]),
+        ];
+        if let Some(target) = &self.target {
+            lines.push(format!("Target: {} at {} {} {}", target.name, target.pos[0], target.pos[1], target.pos[2]));
+            lines.push(format!("Target light: R {} G {} B {} S {}", target.light[0], target.light[1], target.light[2], target.light[3]));
+        } else {
+            lines.push(String::from("Target: none"));
+        }
+        lines.push(format!("Chunks: {} loaded, {} subchunks changed, {} draw calls", self.loaded_chunks, self.changed_subchunks, self.draw_calls));
+        let queues: Vec<String> = self.light_queues.iter().zip(LIGHT_CHANNELS_NAMES).map(|((add_len, rem_len), channel)| format!("{} {}/{}", channel, add_len, rem_len)).collect();
+        lines.push(format!("Light queues peak (add/remove): {}", queues.join(", ")));
+        return lines;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const E: f32 = 0.0001;
+
+    fn get_stats(pos: &Vec3f, dir: &Vec3f) -> DebugStats {
+        let mut stats: DebugStats = DebugStats::new();
+        stats.set_player(pos, dir);
+        return stats;
+    }
+
+    #[test]
+    fn add_frame_averages_over_the_period() {
+        let mut stats: DebugStats = DebugStats::new();
+        stats.add_frame(0.1);
+        stats.add_frame(0.2);
+        assert_eq!(stats.fps, 0.0);
+        stats.add_frame(0.3);
+        assert!((stats.fps - 5.0).abs() < E);
+        assert!((stats.frame_time - 0.2).abs() < E);
+        //A new period starts from scratch
+        stats.add_frame(0.25);
+        stats.add_frame(-1.0);
+        assert!((stats.fps - 5.0).abs() < E);
+        stats.add_frame(0.25);
+        assert!((stats.fps - 6.0).abs() < E);
+        assert!((stats.frame_time - 0.5 / 3.0).abs() < E);
+    }
+
+    #[test]
+    fn positions_round_down() {
+        let stats: DebugStats = get_stats(&[17.9, 33.0, 255.5], &[0.0, 0.0, -1.0]);
+        assert_eq!(stats.get_block_pos(), [17, 33, 255]);
+        assert_eq!(stats.get_chunk_pos(), [1, 15]);
+        assert_eq!(stats.get_subchunk_pos(), [1, 2, 15]);
+        let stats: DebugStats = get_stats(&[-0.5, -16.0, -17.0], &[0.0, 0.0, -1.0]);
+        assert_eq!(stats.get_block_pos(), [-1, -16, -17]);
+        assert_eq!(stats.get_chunk_pos(), [-1, -2]);
+        assert_eq!(stats.get_subchunk_pos(), [-1, -1, -2]);
+    }
+
+    #[test]
+    fn facing_follows_the_largest_horizontal_axis() {
+        assert_eq!(get_stats(&[0.0, 0.0, 0.0], &[0.8, 0.0, 0.6]).get_facing(), "east (+x)");
+        assert_eq!(get_stats(&[0.0, 0.0, 0.0], &[-0.8, 0.0, 0.6]).get_facing(), "west (-x)");
+        assert_eq!(get_stats(&[0.0, 0.0, 0.0], &[0.6, 0.0, 0.8]).get_facing(), "south (+z)");
+        assert_eq!(get_stats(&[0.0, 0.0, 0.0], &[0.6, -0.9, -0.8]).get_facing(), "north (-z)");
+    }
+
+    #[test]
+    fn lines_show_the_collected_stats() {
+        let mut stats: DebugStats = get_stats(&[17.25, 5.0, 3.5], &[1.0, 0.0, 0.0]);
+        stats.fps = 60.0;
+        stats.frame_time = 0.0125;
+        stats.draw_calls = 12;
+        stats.loaded_chunks = 256;
+        stats.light_queues = [(1, 2), (0, 0), (0, 0), (30, 4)];
+        let lines: Vec<String> = stats.get_lines();
+        assert_eq!(lines[0], "60 fps, 12.50 ms");
+        assert_eq!(lines[2], "XYZ: 17.250 / 5.000 / 3.500");
+        assert_eq!(lines[3], "Block: 17 5 3");
+        assert_eq!(lines[4], "Chunk: 1 0, subchunk: 1 0 0");
+        assert_eq!(lines[5], "Facing: east (+x) (1.00 / 0.00 / 0.00)");
+        assert_eq!(lines[6], "Target: none");
+        assert_eq!(lines[7], "Chunks: 256 loaded, 0 subchunks changed, 12 draw calls");
+        assert_eq!(lines[8], "Light queues peak (add/remove): R 1/2, G 0/0, B 0/0, S 30/4");
+        stats.target = Some(TargetStats { pos: [1, 2, 3], name: String::from("core:dirt"), light: [0, 1, 2, 15] });
+        let lines: Vec<String> = stats.get_lines();
+        assert_eq!(lines[6], "Target: core:dirt at 1 2 3");
+        assert_eq!(lines[7], "Target light: R 0 G 1 B 2 S 15");
+        assert_eq!(lines.len(), 10);
+    }
+}
\ No newline at end of file
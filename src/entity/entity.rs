@@ -0,0 +1,9 @@
+use crate::physics::hitbox::HitBox;
+
+pub type EntityId = u32;
+
+pub struct Entity {
+    pub id: EntityId,
+    pub type_name: String, //namespaced like blocks names
+    pub hitbox: HitBox, //position, velocity and size
+}
\ No newline at end of file
@@ -0,0 +1,334 @@
+use std::cell::{Cell, Ref, RefCell, RefMut};
+use std::collections::BTreeMap;
+use thiserror::Error;
+use crate::entity::entity::{Entity, EntityId};
+use crate::physics::aabb::Aabb;
+use crate::physics::collider::Collider;
+use crate::physics::hitbox::HitBox;
+use crate::physics::physics_solver::PhysicsSolver;
+use crate::render::blocks_loader::is_namespaced_name;
+use crate::render::types::Vec3f;
+
+const CHUNKS_CNT: usize = 16 * 16;
+
+#[derive(Error, Debug)]
+pub enum EntitySpawningError {
+    #[error("Invalid entity type name {0}")]
+    InvalidTypeNameError(String),
+}
+
+#[derive(Error, Debug)]
+pub enum EntityLoadingError {
+    #[error("Invalid entity data")]
+    InvalidEntityDataError(),
+}
+
+#[derive(Error, Debug)]
+pub enum EntityStoringError {
+    #[error("Too many entities in chunk")]
+    EntitiesOverflowError(),
+}
+
+//Entities ordered by id, so stepping is deterministic, and ids of every chunk for spatial queries
+pub struct EntityStore {
+    entities: RefCell<BTreeMap<EntityId, Entity>>,
+    buckets: RefCell<Vec<Vec<EntityId>>>,
+    next_id: Cell<EntityId>,
+}
+
+impl EntityStore {
+    pub fn new() -> Self {
+        return Self {
+            entities: RefCell::new(BTreeMap::new()),
+            buckets: RefCell::new(vec![Vec::new(); CHUNKS_CNT]),
+            next_id: Cell::new(0),
+        };
+    }
+
+    //Index of the chunk as in World::chunks[ind >> 4][ind & 0x0F], entities outside of the world belong to the border chunks
+    pub fn get_chunk_ind(pos: &Vec3f) -> usize {
+        let line_pos: usize = ((pos[0].floor() as i32) >> 4).clamp(0, 15) as usize;
+        let chunk_pos: usize = ((pos[2].floor() as i32) >> 4).clamp(0, 15) as usize;
+        return (line_pos << 4) | chunk_pos;
+    }
+
+    pub fn spawn(&self, type_name: &str, hitbox: HitBox) -> Result<EntityId, EntitySpawningError> {
+        if !is_namespaced_name(type_name) {
+            return Err(EntitySpawningError::InvalidTypeNameError(String::from(type_name)));
+        }
+        let id: EntityId = self.next_id.get();
+        self.next_id.set(id.wrapping_add(1));
+        self.buckets.borrow_mut()[Self::get_chunk_ind(&hitbox.pos)].push(id);
+        self.entities.borrow_mut().insert(id, Entity { id, type_name: String::from(type_name), hitbox });
+        return Ok(id);
+    }
+
+    pub fn remove(&self, id: EntityId) -> Option<Entity> {
+        let entity: Entity = self.entities.borrow_mut().remove(&id)?;
+        let mut buckets = self.buckets.borrow_mut();
+        let bucket: &mut Vec<EntityId> = &mut buckets[Self::get_chunk_ind(&entity.hitbox.pos)];
+        if let Some(bucket_pos) = bucket.iter().position(|bucket_id| *bucket_id == id) {
+            bucket.remove(bucket_pos);
+        } else {
+            //Moved via get_entity_mut and not re-bucketed by a step yet
+            for bucket in buckets.iter_mut() {
+                bucket.retain(|bucket_id| *bucket_id != id);
+            }
+        }
+        return Some(entity);
+    }
+
+    pub fn clear(&self) {
+        self.entities.borrow_mut().clear();
+        for bucket in self.buckets.borrow_mut().iter_mut() {
+            bucket.clear();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        return self.entities.borrow().len();
+    }
+
+    pub fn get_entity(&self, id: EntityId) -> Option<Ref<'_, Entity>> {
+        return Ref::filter_map(self.entities.borrow(), |entities| entities.get(&id)).ok();
+    }
+
+    //Moved entities change their buckets on the next step
+    pub fn get_entity_mut(&self, id: EntityId) -> Option<RefMut<'_, Entity>> {
+        return RefMut::filter_map(self.entities.borrow_mut(), |entities| entities.get_mut(&id)).ok();
+    }
+
+    pub fn get_chunk_entities(&self, chunk_ind: usize) -> Vec<EntityId> {
+        return self.buckets.borrow().get(chunk_ind).cloned().unwrap_or_default();
+    }
+
+    //Entities with hitboxes intersecting the area, only the buckets of the overlapped chunks are checked
+    pub fn get_entities_in(&self, area: &Aabb) -> Vec<EntityId> {
+        let mut ids: Vec<EntityId> = Vec::new();
+        let min_ind: usize = Self::get_chunk_ind(&area.min);
+        let max_ind: usize = Self::get_chunk_ind(&area.max);
+        let entities = self.entities.borrow();
+        let buckets = self.buckets.borrow();
+        for line_pos in (min_ind >> 4)..=(max_ind >> 4) {
+            for chunk_pos in (min_ind & 0x0F)..=(max_ind & 0x0F) {
+                for id in &buckets[(line_pos << 4) | chunk_pos] {
+                    if let Some(entity) = entities.get(id) {
+                        if Aabb::from_center(&entity.hitbox.pos, &entity.hitbox.half_size).intersects(area) {
+                            ids.push(*id);
+                        }
+                    }
+                }
+            }
+        }
+        ids.sort();
+        return ids;
+    }
+
+    //Nearest entity hit along dir within max_dist with the distance and the axis of the entered side
+    pub fn ray_intersect(&self, pos: &Vec3f, dir: &Vec3f, max_dist: f32) -> Option<(EntityId, f32, i8)> {
+        let end: Vec3f = [pos[0] + dir[0] * max_dist, pos[1] + dir[1] * max_dist, pos[2] + dir[2] * max_dist];
+        let area: Aabb = Aabb::new([pos[0].min(end[0]), pos[1].min(end[1]), pos[2].min(end[2])], [pos[0].max(end[0]), pos[1].max(end[1]), pos[2].max(end[2])]);
+        let entities = self.entities.borrow();
+        let buckets = self.buckets.borrow();
+        let min_ind: usize = Self::get_chunk_ind(&area.min);
+        let max_ind: usize = Self::get_chunk_ind(&area.max);
+        let mut hit: Option<(EntityId, f32, i8)> = None;
+        for line_pos in (min_ind >> 4)..=(max_ind >> 4) {
+            for chunk_pos in (min_ind & 0x0F)..=(max_ind & 0x0F) {
+                for id in &buckets[(line_pos << 4) | chunk_pos] {
+                    if let Some(entity) = entities.get(id) {
+                        if let Some((dist, axis)) = Aabb::from_center(&entity.hitbox.pos, &entity.hitbox.half_size).ray_intersect(pos, dir) {
+                            if dist <= max_dist && hit.map_or(true, |(hit_id, hit_dist, _)| dist < hit_dist || (dist == hit_dist && *id < hit_id)) {
+                                hit = Some((*id, dist, axis));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        return hit;
+    }
+
+    pub fn step(&self, physics_solver: &PhysicsSolver, collider: &dyn Collider, delta: f32) {
+        let mut entities = self.entities.borrow_mut();
+        let mut buckets = self.buckets.borrow_mut();
+        for (id, entity) in entities.iter_mut() {
+            let last_chunk_ind: usize = Self::get_chunk_ind(&entity.hitbox.pos);
+            physics_solver.step(collider, &mut entity.hitbox, delta);
+            let chunk_ind: usize = Self::get_chunk_ind(&entity.hitbox.pos);
+            if chunk_ind != last_chunk_ind {
+                buckets[last_chunk_ind].retain(|bucket_id| bucket_id != id);
+                buckets[chunk_ind].push(*id);
+            }
+        }
+        //Entities moved via get_entity_mut
+        for chunk_ind in 0..CHUNKS_CNT {
+            let (kept, moved): (Vec<EntityId>, Vec<EntityId>) = buckets[chunk_ind].iter().partition(|id|
+                entities.get(id).map_or(false, |entity| Self::get_chunk_ind(&entity.hitbox.pos) == chunk_ind));
+            if moved.len() != 0 {
+                buckets[chunk_ind] = kept;
+                for id in moved {
+                    if let Some(entity) = entities.get(&id) {
+                        buckets[Self::get_chunk_ind(&entity.hitbox.pos)].push(id);
+                    }
+                }
+            }
+        }
+    }
+
+    //Count of entities, then for every entity: type name length and bytes, position, velocity and half size
+    pub fn store_chunk(&self, chunk_ind: usize, data: &mut Vec<u8>) -> Result<(), Box<dyn std::error::Error>> {
+        let entities = self.entities.borrow();
+        let buckets = self.buckets.borrow();
+        let stored: Vec<&Entity> = buckets[chunk_ind].iter().filter_map(|id| entities.get(id)).collect();
+        if stored.len() > (u16::MAX as usize) {
+            return Err(Box::new(EntityStoringError::EntitiesOverflowError()));
+        }
+        data.extend_from_slice(&(stored.len() as u16).to_be_bytes());
+        for entity in stored {
+            let name_data: &[u8] = entity.type_name.as_bytes();
+            data.extend_from_slice(&(name_data.len() as u16).to_be_bytes());
+            data.extend_from_slice(name_data);
+            for vec in [&entity.hitbox.pos, &entity.hitbox.vel, &entity.hitbox.half_size] {
+                for value in vec {
+                    data.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    fn read_data<'a>(data: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], EntityLoadingError> {
+        return if let Some(read_data) = data.get(*pos..(*pos + len)) {
+            *pos += len;
+            Ok(read_data)
+        } else {
+            Err(EntityLoadingError::InvalidEntityDataError())
+        }
+    }
+
+    fn read_vec3f(data: &[u8], pos: &mut usize) -> Result<Vec3f, EntityLoadingError> {
+        let mut vec: Vec3f = [0.0, 0.0, 0.0];
+        for value in vec.iter_mut() {
+            let value_data: &[u8] = Self::read_data(data, pos, 4)?;
+            *value = f32::from_be_bytes([value_data[0], value_data[1], value_data[2], value_data[3]]);
+        }
+        return Ok(vec);
+    }
+
+    //Returns type names and hitboxes, so the whole world can be parsed before spawning
+    pub fn load_chunk(data: &[u8], pos: &mut usize) -> Result<Vec<(String, HitBox)>, Box<dyn std::error::Error>> {
+        let mut loaded: Vec<(String, HitBox)> = Vec::new();
+        let cnt_data: &[u8] = Self::read_data(data, pos, 2)?;
+        let entities_cnt: u16 = ((cnt_data[0] as u16) << 8) | (cnt_data[1] as u16);
+        for _entity_pos in 0..entities_cnt {
+            let len_data: &[u8] = Self::read_data(data, pos, 2)?;
+            let name_len: usize = (((len_data[0] as u16) << 8) | (len_data[1] as u16)) as usize;
+            let type_name: String = String::from_utf8(Self::read_data(data, pos, name_len)?.to_vec())?;
+            let position: Vec3f = Self::read_vec3f(data, pos)?;
+            let vel: Vec3f = Self::read_vec3f(data, pos)?;
+            let half_size: Vec3f = Self::read_vec3f(data, pos)?;
+            let mut hitbox: HitBox = HitBox::new(position, half_size);
+            hitbox.vel = vel;
+            loaded.push((type_name, hitbox));
+        }
+        return Ok(loaded);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::types::Vec3i;
+
+    struct EmptyCollider {}
+
+    impl Collider for EmptyCollider {
+        fn get_collision_boxes(&self, _pos: &Vec3i, _boxes: &mut Vec<Aabb>) {}
+    }
+
+    fn spawn_at(store: &EntityStore, pos: Vec3f) -> EntityId {
+        return store.spawn("test:box", HitBox::new(pos, [0.25, 0.25, 0.25])).unwrap();
+    }
+
+    #[test]
+    fn spawn_rejects_invalid_type_names() {
+        let store: EntityStore = EntityStore::new();
+        assert!(store.spawn("box", HitBox::new([0.0, 0.0, 0.0], [0.25, 0.25, 0.25])).is_err());
+        assert_eq!(store.len(), 0);
+    }
+
+    #[test]
+    fn moved_entities_are_rebucketed_on_step() {
+        let store: EntityStore = EntityStore::new();
+        let id: EntityId = spawn_at(&store, [1.5, 10.0, 1.5]);
+        assert_eq!(store.get_chunk_entities(0), vec![id]);
+        store.get_entity_mut(id).unwrap().hitbox.pos = [40.5, 10.0, 20.5];
+        store.step(&PhysicsSolver::new([0.0, 0.0, 0.0]), &EmptyCollider {}, 0.05);
+        assert_eq!(store.get_chunk_entities(0), Vec::<EntityId>::new());
+        assert_eq!(store.get_chunk_entities((2 << 4) | 1), vec![id]);
+        assert_eq!(store.remove(id).unwrap().id, id);
+        assert_eq!(store.get_chunk_entities((2 << 4) | 1), Vec::<EntityId>::new());
+        assert_eq!(store.len(), 0);
+    }
+
+    #[test]
+    fn remove_finds_moved_entities_before_step() {
+        let store: EntityStore = EntityStore::new();
+        let id: EntityId = spawn_at(&store, [1.5, 10.0, 1.5]);
+        store.get_entity_mut(id).unwrap().hitbox.pos = [40.5, 10.0, 20.5];
+        assert!(store.remove(id).is_some());
+        assert!(store.remove(id).is_none());
+        for chunk_ind in 0..CHUNKS_CNT {
+            assert_eq!(store.get_chunk_entities(chunk_ind).len(), 0);
+        }
+        let mut data: Vec<u8> = Vec::new();
+        store.store_chunk(0, &mut data).unwrap();
+        assert_eq!(data, vec![0, 0]);
+    }
+
+    #[test]
+    fn get_entities_in_checks_hitboxes_across_chunks() {
+        let store: EntityStore = EntityStore::new();
+        let first_id: EntityId = spawn_at(&store, [15.5, 10.0, 1.5]);
+        let second_id: EntityId = spawn_at(&store, [16.5, 10.0, 1.5]);
+        spawn_at(&store, [20.5, 10.0, 1.5]);
+        spawn_at(&store, [16.5, 20.0, 1.5]);
+        let area: Aabb = Aabb::new([15.0, 9.0, 1.0], [17.0, 11.0, 2.0]);
+        assert_eq!(store.get_entities_in(&area), vec![first_id, second_id]);
+        //Touching hitboxes do not count
+        let touching_area: Aabb = Aabb::new([16.75, 9.0, 1.0], [17.0, 11.0, 2.0]);
+        assert_eq!(store.get_entities_in(&touching_area), Vec::<EntityId>::new());
+    }
+
+    #[test]
+    fn store_chunk_round_trips_through_load_chunk() {
+        let store: EntityStore = EntityStore::new();
+        let id: EntityId = spawn_at(&store, [1.5, 10.0, 1.5]);
+        store.get_entity_mut(id).unwrap().hitbox.vel = [1.0, -2.0, 0.5];
+        spawn_at(&store, [2.5, 12.0, 3.5]);
+        spawn_at(&store, [40.5, 10.0, 1.5]);
+        let mut data: Vec<u8> = Vec::new();
+        store.store_chunk(0, &mut data).unwrap();
+        let mut pos: usize = 0;
+        let loaded: Vec<(String, HitBox)> = EntityStore::load_chunk(&data, &mut pos).unwrap();
+        assert_eq!(pos, data.len());
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].0, "test:box");
+        assert_eq!(loaded[0].1.pos, [1.5, 10.0, 1.5]);
+        assert_eq!(loaded[0].1.vel, [1.0, -2.0, 0.5]);
+        assert_eq!(loaded[0].1.half_size, [0.25, 0.25, 0.25]);
+        assert_eq!(loaded[1].1.pos, [2.5, 12.0, 3.5]);
+    }
+
+    #[test]
+    fn load_chunk_rejects_truncated_data() {
+        let store: EntityStore = EntityStore::new();
+        spawn_at(&store, [1.5, 10.0, 1.5]);
+        let mut data: Vec<u8> = Vec::new();
+        store.store_chunk(0, &mut data).unwrap();
+        data.pop();
+        let mut pos: usize = 0;
+        assert!(EntityStore::load_chunk(&data, &mut pos).is_err());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,2 @@
+pub mod entity;
+pub mod entity_store;
\ No newline at end of file
@@ -0,0 +1,54 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+//Polls modification times, no OS notifications
+pub struct FilesWatcher {
+    files: RefCell<HashMap<PathBuf, Option<SystemTime>>>,
+}
+
+impl FilesWatcher {
+    pub fn new() -> Self {
+        return Self {
+            files: RefCell::new(HashMap::new()),
+        };
+    }
+
+    fn get_modified_time(path: &Path) -> Option<SystemTime> {
+        return match fs::metadata(path) {
+            Ok(metadata) => {
+                metadata.modified().ok()
+            }
+            Err(_) => {
+                None
+            }
+        }
+    }
+
+    pub fn watch(&self, path: &Path) {
+        self.files.borrow_mut().insert(path.to_path_buf(), Self::get_modified_time(path));
+    }
+
+    pub fn clear(&self) {
+        self.files.borrow_mut().clear();
+    }
+
+    pub fn get_files(&self) -> Vec<PathBuf> {
+        return self.files.borrow().keys().cloned().collect();
+    }
+
+    //Reports every change once, stored times are updated while checking
+    pub fn is_changed(&self) -> bool {
+        let mut changed: bool = false;
+        for (path, modified_time) in self.files.borrow_mut().iter_mut() {
+            let cur_modified_time: Option<SystemTime> = Self::get_modified_time(path);
+            if cur_modified_time != *modified_time {
+                *modified_time = cur_modified_time;
+                changed = true;
+            }
+        }
+        return changed;
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::gui::widget::{Anchor, Widget, WidgetId, WidgetKind, WidgetLayout};
+use crate::gui::widget_tree::{ROOT_WIDGET, WidgetTree};
+use crate::packs::pack_stack::PackStack;
+use crate::render::blocks_loader::is_namespaced_name;
+use crate::render::icons_baker::BlockIcons;
+use crate::render::types::{Vec2f, Vec4f};
+
+pub const LAYOUTS_FILE: &str = "layouts.json";
+pub const GUI_ATLAS_FILE: &str = "gui_atlas.png";
+
+const DEFAULT_ITEM_HEIGHT: f32 = 0.06;
+const DEFAULT_MAX_LEN: usize = 256;
+
+#[derive(Error, Debug)]
+pub enum LayoutsLoadingError {
+    #[error("Layout {0} is defined twice")]
+    RedefinitionError(String),
+    #[error("Invalid layout: {0}")]
+    InvalidLayoutError(String),
+    #[error("Layout name {0} is not namespaced like namespace:name")]
+    InvalidNameError(String),
+    #[error("Layout {0} element {1}: {2}")]
+    ElementError(String, String, String),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LayoutData {
+    pub name: String,
+    pub elements: Vec<serde_json::Value>,
+}
+
+//Sizes and offsets are in GUI units, texture is x, y, width and height in pixels of the GUI atlas
+#[derive(Serialize, Deserialize)]
+pub struct ElementData {
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default = "get_default_anchor")]
+    pub anchor: String,
+    #[serde(default)]
+    pub offset: Vec2f,
+    #[serde(default)]
+    pub size: Vec2f,
+    #[serde(default)]
+    pub stretch: [bool; 2],
+    #[serde(default)]
+    pub color: Option<Vec4f>,
+    #[serde(default)]
+    pub texture: Option<[u32; 4]>,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub icon: String, //block name
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default = "get_default_text_scale")]
+    pub text_scale: f32,
+    #[serde(default = "get_default_visible")]
+    pub visible: bool,
+    #[serde(default)]
+    pub value: f32,
+    #[serde(default)]
+    pub min: f32,
+    #[serde(default = "get_default_max")]
+    pub max: f32,
+    #[serde(default)]
+    pub step: f32,
+    #[serde(default)]
+    pub max_len: Option<usize>,
+    #[serde(default)]
+    pub items: Vec<String>,
+    #[serde(default)]
+    pub item_height: Option<f32>,
+    #[serde(default)]
+    pub children: Vec<serde_json::Value>,
+}
+
+fn get_default_anchor() -> String {
+    return String::from("center");
+}
+
+fn get_default_visible() -> bool {
+    return true;
+}
+
+fn get_default_max() -> f32 {
+    return 1.0;
+}
+
+fn get_default_text_scale() -> f32 {
+    return 1.0;
+}
+
+//Widget with the templates of its children
+pub struct LayoutElement {
+    pub widget: Widget,
+    pub children: Vec<LayoutElement>,
+}
+
+pub struct Layout {
+    pub name: String,
+    pub pack: String, //name of the pack the layout came from
+    pub elements: Vec<LayoutElement>,
+}
+
+//Element names joined with '/', unnamed elements are shown by their index
+fn get_element_path(parent_path: &str, ind: usize, value: &serde_json::Value) -> String {
+    let name: String = match value.get("name").and_then(|name| name.as_str()) {
+        Some(name) if !name.is_empty() => String::from(name),
+        _ => format!("#{}", ind),
+    };
+    return if parent_path.is_empty() {
+        name
+    } else {
+        format!("{}/{}", parent_path, name)
+    }
+}
+
+fn get_kind(element_data: &ElementData) -> Result<WidgetKind, String> {
+    return match element_data.kind.as_str() {
+        "panel" => Ok(WidgetKind::Panel),
+        "image" => {
+            if element_data.texture.is_none() {
+                return Err(String::from("image has no texture"));
+            }
+            Ok(WidgetKind::Image)
+        }
+        "icon" => Ok(WidgetKind::Icon { name: element_data.icon.clone() }),
+        "label" => Ok(WidgetKind::Label { text: element_data.text.clone() }),
+        "button" => Ok(WidgetKind::Button { text: element_data.text.clone() }),
+        "slider" => {
+            if !(element_data.min < element_data.max) {
+                return Err(format!("slider min {} is not less than max {}", element_data.min, element_data.max));
+            }
+            if element_data.step < 0.0 {
+                return Err(format!("slider step {} is negative", element_data.step));
+            }
+            Ok(WidgetKind::Slider { value: element_data.value.clamp(element_data.min, element_data.max), min: element_data.min, max: element_data.max, step: element_data.step })
+        }
+        "text_field" => {
+            let max_len: usize = element_data.max_len.unwrap_or(DEFAULT_MAX_LEN);
+            let text: String = element_data.text.chars().take(max_len).collect();
+            Ok(WidgetKind::TextField { cursor: text.chars().count(), text, max_len })
+        }
+        "scroll_list" => {
+            let item_height: f32 = element_data.item_height.unwrap_or(DEFAULT_ITEM_HEIGHT);
+            if !(item_height > 0.0) {
+                return Err(format!("item height {} is not positive", item_height));
+            }
+            Ok(WidgetKind::ScrollList { items: element_data.items.clone(), item_height, scroll: 0, selected: None })
+        }
+        kind => Err(format!("unknown element type {}", kind)),
+    }
+}
+
+pub struct GuiLayouts {
+    pub layouts: HashMap<String, Rc<Layout>>,
+    pub atlas: RgbaImage, //GUI atlas of the packs with the block icons below it
+    pub icons: HashMap<String, [Vec2f; 2]>, //tex coords of block icons by block names
+}
+
+impl GuiLayouts {
+    fn load_widget(element_data: ElementData, atlas_size: &[u32; 2]) -> Result<Widget, String> {
+        let anchor: Anchor = Anchor::from_name(&element_data.anchor).ok_or_else(|| format!("unknown anchor {}", element_data.anchor))?;
+        if element_data.size[0] < 0.0 || element_data.size[1] < 0.0 {
+            return Err(format!("size {:?} is negative", element_data.size));
+        }
+        if !(element_data.text_scale > 0.0) {
+            return Err(format!("text scale {} is not positive", element_data.text_scale));
+        }
+        let kind: WidgetKind = get_kind(&element_data)?;
+        let mut texture: Option<[Vec2f; 2]> = None;
+        if let Some([x, y, width, height]) = element_data.texture {
+            if width == 0 || height == 0 || x.checked_add(width).map_or(true, |end| end > atlas_size[0]) || y.checked_add(height).map_or(true, |end| end > atlas_size[1]) {
+                return Err(format!("texture {:?} is outside of the {}x{} atlas", [x, y, width, height], atlas_size[0], atlas_size[1]));
+            }
+            let min: Vec2f = [(x as f32) / (atlas_size[0] as f32), (y as f32) / (atlas_size[1] as f32)];
+            let max: Vec2f = [((x + width) as f32) / (atlas_size[0] as f32), ((y + height) as f32) / (atlas_size[1] as f32)];
+            texture = Some([min, max]);
+        }
+        //Textures and icons are shown as they are unless tinted, plain elements are transparent unless colored
+        let is_textured: bool = texture.is_some() || matches!(kind, WidgetKind::Icon { .. });
+        let color: Vec4f = element_data.color.unwrap_or(if is_textured { [1.0, 1.0, 1.0, 1.0] } else { [0.0, 0.0, 0.0, 0.0] });
+        let mut layout: WidgetLayout = WidgetLayout::new(anchor, element_data.offset, element_data.size);
+        layout.stretch = element_data.stretch;
+        let mut widget: Widget = Widget::new(&element_data.name, kind, layout, color);
+        widget.texture = texture;
+        widget.visible = element_data.visible;
+        widget.text_scale = element_data.text_scale;
+        if let Some(source) = element_data.source {
+            widget.format = element_data.text;
+            widget.source = Some(source);
+        }
+        return Ok(widget);
+    }
+
+    //Errors come with the path of the offending element
+    fn load_element(value: &serde_json::Value, path: &str, atlas_size: &[u32; 2]) -> Result<LayoutElement, (String, String)> {
+        let mut element_data: ElementData = ElementData::deserialize(value).map_err(|error| (String::from(path), format!("invalid element: {}", error)))?;
+        let children_values: Vec<serde_json::Value> = std::mem::take(&mut element_data.children);
+        let widget: Widget = Self::load_widget(element_data, atlas_size).map_err(|error| (String::from(path), error))?;
+        let mut children: Vec<LayoutElement> = Vec::new();
+        for (child_ind, child_value) in children_values.iter().enumerate() {
+            children.push(Self::load_element(child_value, &get_element_path(path, child_ind, child_value), atlas_size)?);
+        }
+        return Ok(LayoutElement { widget, children });
+    }
+
+    //No GL here
+    pub fn load_layout(value: &serde_json::Value, pack: &str, atlas_size: &[u32; 2]) -> Result<Layout, LayoutsLoadingError> {
+        let layout_data: LayoutData = LayoutData::deserialize(value).map_err(|error| LayoutsLoadingError::InvalidLayoutError(error.to_string()))?;
+        if !is_namespaced_name(&layout_data.name) {
+            return Err(LayoutsLoadingError::InvalidNameError(layout_data.name));
+        }
+        let mut elements: Vec<LayoutElement> = Vec::new();
+        for (element_ind, element_value) in layout_data.elements.iter().enumerate() {
+            match Self::load_element(element_value, &get_element_path("", element_ind, element_value), atlas_size) {
+                Ok(element) => elements.push(element),
+                Err((path, message)) => {
+                    return Err(LayoutsLoadingError::ElementError(layout_data.name, path, message));
+                }
+            }
+        }
+        return Ok(Layout { name: layout_data.name, pack: String::from(pack), elements });
+    }
+
+    pub fn load(packs: &PackStack, block_icons: &BlockIcons) -> Result<Self, Box<dyn std::error::Error>> {
+        let (atlas, icons) = block_icons.pack_into(&packs.load_atlas(GUI_ATLAS_FILE)?);
+        let atlas_size: [u32; 2] = [atlas.width(), atlas.height()];
+        let mut layouts: HashMap<String, Rc<Layout>> = HashMap::new();
+        for pack_definitions in packs.read_definitions(LAYOUTS_FILE)? {
+            let mut pack_layouts: HashSet<String> = HashSet::new();
+            for layout_value in &pack_definitions.values {
+                let layout: Layout = Self::load_layout(layout_value, &pack_definitions.pack, &atlas_size).map_err(|error| pack_definitions.get_error(Box::new(error)))?;
+                if !pack_layouts.insert(layout.name.clone()) {
+                    return Err(pack_definitions.get_error(Box::new(LayoutsLoadingError::RedefinitionError(layout.name))));
+                }
+                layouts.insert(layout.name.clone(), Rc::new(layout));
+            }
+        }
+        return Ok(Self { layouts, atlas, icons });
+    }
+
+    fn add_element(widget_tree: &mut WidgetTree, parent: WidgetId, element: &LayoutElement) -> Option<WidgetId> {
+        let id: WidgetId = widget_tree.add(parent, element.widget.clone())?;
+        for child in &element.children {
+            Self::add_element(widget_tree, id, child)?;
+        }
+        return Some(id);
+    }
+
+    //Adds the layout under a transparent panel covering the window, named like the layout
+    pub fn build(&self, name: &str, widget_tree: &mut WidgetTree) -> Option<WidgetId> {
+        let layout: &Rc<Layout> = self.layouts.get(name)?;
+        let root: WidgetId = widget_tree.add(ROOT_WIDGET, Widget::new(name, WidgetKind::Panel, WidgetLayout::fill(0.0), [0.0, 0.0, 0.0, 0.0]))?;
+        for element in &layout.elements {
+            Self::add_element(widget_tree, root, element)?;
+        }
+        return Some(root);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,3 @@
+pub mod widget;
+pub mod widget_tree;
+pub mod layouts_loader;
\ No newline at end of file
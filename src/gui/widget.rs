@@ -0,0 +1,254 @@
+use crate::render::types::{Vec2f, Vec4f};
+
+pub type WidgetId = usize;
+
+//GUI units, x to the right and y up like the GUI renderer before the aspect ratio scaling
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub min: Vec2f,
+    pub max: Vec2f,
+}
+
+impl Rect {
+    pub fn new(min: Vec2f, max: Vec2f) -> Self {
+        return Self { min, max };
+    }
+
+    //Whole window, the top edge is at 1 / asp_rat because the renderer scales y by the aspect ratio
+    pub fn from_asp_rat(asp_rat: f32) -> Self {
+        return Self::new([-1.0, -1.0 / asp_rat], [1.0, 1.0 / asp_rat]);
+    }
+
+    pub fn get_size(&self) -> Vec2f {
+        return [self.max[0] - self.min[0], self.max[1] - self.min[1]];
+    }
+
+    pub fn get_center(&self) -> Vec2f {
+        return [(self.min[0] + self.max[0]) * 0.5, (self.min[1] + self.max[1]) * 0.5];
+    }
+
+    pub fn contains(&self, pos: &Vec2f) -> bool {
+        return pos[0] >= self.min[0] && pos[0] < self.max[0] && pos[1] >= self.min[1] && pos[1] < self.max[1];
+    }
+
+    pub fn shrink(&self, padding: f32) -> Self {
+        return Self::new([self.min[0] + padding, self.min[1] + padding], [self.max[0] - padding, self.max[1] - padding]);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    //-1, 0 or 1 per axis, the side of the parent and of the widget which are aligned
+    pub fn get_factors(&self) -> Vec2f {
+        return match self {
+            Anchor::TopLeft => [-1.0, 1.0],
+            Anchor::Top => [0.0, 1.0],
+            Anchor::TopRight => [1.0, 1.0],
+            Anchor::Left => [-1.0, 0.0],
+            Anchor::Center => [0.0, 0.0],
+            Anchor::Right => [1.0, 0.0],
+            Anchor::BottomLeft => [-1.0, -1.0],
+            Anchor::Bottom => [0.0, -1.0],
+            Anchor::BottomRight => [1.0, -1.0],
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name {
+            "top_left" => Some(Anchor::TopLeft),
+            "top" => Some(Anchor::Top),
+            "top_right" => Some(Anchor::TopRight),
+            "left" => Some(Anchor::Left),
+            "center" => Some(Anchor::Center),
+            "right" => Some(Anchor::Right),
+            "bottom_left" => Some(Anchor::BottomLeft),
+            "bottom" => Some(Anchor::Bottom),
+            "bottom_right" => Some(Anchor::BottomRight),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WidgetLayout {
+    pub anchor: Anchor,
+    pub offset: Vec2f, //from the anchor point of the parent, inwards from sides and along the axes from the center
+    pub size: Vec2f,
+    pub stretch: [bool; 2], //fill the parent on the axis, the size is subtracted from the parent size
+}
+
+impl WidgetLayout {
+    pub fn new(anchor: Anchor, offset: Vec2f, size: Vec2f) -> Self {
+        return Self { anchor, offset, size, stretch: [false, false] };
+    }
+
+    pub fn fill(margin: f32) -> Self {
+        return Self { anchor: Anchor::Center, offset: [0.0, 0.0], size: [margin * 2.0, margin * 2.0], stretch: [true, true] };
+    }
+
+    pub fn get_rect(&self, parent_rect: &Rect) -> Rect {
+        let factors: Vec2f = self.anchor.get_factors();
+        let parent_center: Vec2f = parent_rect.get_center();
+        let parent_size: Vec2f = parent_rect.get_size();
+        let mut min: Vec2f = [0.0, 0.0];
+        let mut max: Vec2f = [0.0, 0.0];
+        for axis in 0..2 {
+            let size: f32 = if self.stretch[axis] {
+                (parent_size[axis] - self.size[axis]).max(0.0)
+            } else {
+                self.size[axis]
+            };
+            //Offsets from sides move widgets inwards, so the same offset works for every corner
+            let anchor_pos: f32 = if factors[axis] == 0.0 {
+                parent_center[axis] + self.offset[axis]
+            } else {
+                parent_center[axis] + factors[axis] * (parent_size[axis] * 0.5 - self.offset[axis])
+            };
+            let center: f32 = anchor_pos - factors[axis] * size * 0.5;
+            min[axis] = center - size * 0.5;
+            max[axis] = center + size * 0.5;
+        }
+        return Rect::new(min, max);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum WidgetKind {
+    Panel,
+    Image,
+    Icon { name: String }, //icon of the block with the name, from the icons of the tree
+    Label { text: String },
+    Button { text: String },
+    Slider { value: f32, min: f32, max: f32, step: f32 },
+    TextField { text: String, max_len: usize, cursor: usize }, //cursor is in chars
+    ScrollList { items: Vec<String>, item_height: f32, scroll: usize, selected: Option<usize> }, //scroll is the first shown item
+}
+
+impl WidgetKind {
+    pub fn is_focusable(&self) -> bool {
+        return match self {
+            WidgetKind::Panel | WidgetKind::Image | WidgetKind::Icon { .. } | WidgetKind::Label { .. } => false,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Widget {
+    pub name: String, //for looking widgets up, may be empty
+    pub kind: WidgetKind,
+    pub layout: WidgetLayout,
+    pub color: Vec4f,
+    pub texture: Option<[Vec2f; 2]>, //min and max tex coords in the GUI atlas, tinted by the color
+    pub source: Option<String>, //name of the data source the widget shows
+    pub format: String, //text with "{}" replaced by the value of the source
+    pub text_scale: f32, //multiplies the text scale of the renderer
+    pub visible: bool,
+    pub enabled: bool,
+    pub(crate) parent: Option<WidgetId>,
+    pub(crate) children: Vec<WidgetId>,
+    pub(crate) rect: Rect,
+}
+
+impl Widget {
+    pub fn new(name: &str, kind: WidgetKind, layout: WidgetLayout, color: Vec4f) -> Self {
+        return Self {
+            name: String::from(name),
+            kind,
+            layout,
+            color,
+            texture: None,
+            source: None,
+            format: String::new(),
+            text_scale: 1.0,
+            visible: true,
+            enabled: true,
+            parent: None,
+            children: Vec::new(),
+            rect: Rect::new([0.0, 0.0], [0.0, 0.0]),
+        };
+    }
+
+    pub fn get_parent(&self) -> Option<WidgetId> {
+        return self.parent;
+    }
+
+    pub fn get_children(&self) -> &[WidgetId] {
+        return &self.children;
+    }
+
+    //Valid after the layout of the tree
+    pub fn get_rect(&self) -> &Rect {
+        return &self.rect;
+    }
+
+    //Text widgets show the formatted value, sliders parse it, scroll lists take its lines as items, icons take it as the block name and the rest is shown while the value isn't empty
+    pub fn set_source_value(&mut self, value: &str) {
+        let text: String = if self.format.is_empty() { String::from(value) } else { self.format.replace("{}", value) };
+        match &mut self.kind {
+            WidgetKind::Label { .. } | WidgetKind::Button { .. } | WidgetKind::TextField { .. } => {
+                self.set_text(&text);
+            }
+            WidgetKind::Slider { value: cur_value, .. } => {
+                if let Ok(new_value) = value.trim().parse::<f32>() {
+                    *cur_value = new_value;
+                }
+            }
+            WidgetKind::ScrollList { items, scroll, selected, .. } => {
+                *items = value.lines().map(String::from).collect();
+                *scroll = (*scroll).min(items.len().saturating_sub(1));
+                *selected = selected.filter(|selected| *selected < items.len());
+            }
+            WidgetKind::Icon { name } => {
+                *name = String::from(value);
+            }
+            WidgetKind::Panel | WidgetKind::Image => {
+                self.visible = !value.is_empty();
+            }
+        }
+    }
+
+    //Text fields get the cursor at the end, other kinds have no text
+    pub fn set_text(&mut self, text: &str) {
+        match &mut self.kind {
+            WidgetKind::Label { text: cur_text } | WidgetKind::Button { text: cur_text } => {
+                *cur_text = String::from(text);
+            }
+            WidgetKind::TextField { text: cur_text, cursor, .. } => {
+                *cursor = text.chars().count();
+                *cur_text = String::from(text);
+            }
+            _ => {}
+        }
+    }
+
+    //Value of a slider mapped to 0..1
+    pub fn get_slider_fraction(&self) -> f32 {
+        return if let WidgetKind::Slider { value, min, max, .. } = &self.kind {
+            if max > min { ((value - min) / (max - min)).clamp(0.0, 1.0) } else { 0.0 }
+        } else {
+            0.0
+        }
+    }
+
+    //Count of scroll list items that fit into the widget
+    pub fn get_visible_items_cnt(&self) -> usize {
+        return if let WidgetKind::ScrollList { item_height, .. } = &self.kind {
+            if *item_height > 0.0 { (self.rect.get_size()[1] / item_height).floor().max(0.0) as usize } else { 0 }
+        } else {
+            0
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,795 @@
+use std::collections::HashMap;
+use glfw::Key;
+use crate::gui::widget::{Rect, Widget, WidgetId, WidgetKind, WidgetLayout};
+use crate::render::font::Font;
+use crate::render::text_layout::{layout_text, TextLayout};
+use crate::render::text_renderer::TextElement;
+use crate::render::types::{Vec2d, Vec2f, Vec4f};
+
+pub const ROOT_WIDGET: WidgetId = 0; //transparent panel covering the whole window
+
+const TEXT_COLOR: Vec4f = [1.0, 1.0, 1.0, 1.0];
+const DISABLED_TEXT_COLOR: Vec4f = [0.5, 0.5, 0.5, 1.0];
+const FOCUS_COLOR: Vec4f = [1.0, 1.0, 1.0, 0.8];
+const HANDLE_COLOR: Vec4f = [0.85, 0.85, 0.85, 1.0];
+const SELECTED_ITEM_COLOR: Vec4f = [1.0, 1.0, 1.0, 0.25];
+const HOVERED_ITEM_COLOR: Vec4f = [1.0, 1.0, 1.0, 0.1];
+const HOVER_FACTOR: f32 = 1.25;
+const PRESS_FACTOR: f32 = 0.75;
+const FOCUS_BORDER: f32 = 0.005;
+const SLIDER_HANDLE_WIDTH: f32 = 0.02;
+const TEXT_PADDING: f32 = 0.01;
+const TEXT_CURSOR: char = '_';
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GuiEvent {
+    Clicked(WidgetId),
+    ValueChanged(WidgetId, f32),
+    TextChanged(WidgetId, String),
+    TextSubmitted(WidgetId, String),
+    ItemSelected(WidgetId, usize),
+    FocusChanged(Option<WidgetId>),
+}
+
+//Input of one frame
+pub struct GuiInput {
+    pub cursor_pos: Vec2f, //GUI units
+    pub pressed: bool, //state of the left mouse button
+    pub scroll: f32, //up is positive
+    pub keys: Vec<Key>, //presses and repeats
+    pub chars: Vec<char>,
+}
+
+impl GuiInput {
+    //Window pixels with y down to GUI units
+    pub fn get_gui_pos(cursor_pos: &Vec2d, width: u32, height: u32) -> Vec2f {
+        if width == 0 || height == 0 {
+            return [0.0, 0.0];
+        }
+        let asp_rat: f32 = (width as f32) / (height as f32);
+        let x: f32 = ((cursor_pos[0] as f32) / (width as f32)) * 2.0 - 1.0;
+        let y: f32 = (1.0 - ((cursor_pos[1] as f32) / (height as f32)) * 2.0) / asp_rat;
+        return [x, y];
+    }
+}
+
+pub struct GuiQuad {
+    pub rect: Rect,
+    pub color: Vec4f,
+    pub tex: Option<[Vec2f; 2]>, //plain color without a texture
+}
+
+//Quads are drawn first, in order, then texts
+pub struct GuiDrawList {
+    pub quads: Vec<GuiQuad>,
+    pub texts: Vec<TextElement>,
+}
+
+//Widgets are stored by ids, children are drawn over their parents and later siblings over earlier ones
+pub struct WidgetTree {
+    widgets: Vec<Option<Widget>>,
+    asp_rat: f32,
+    focused: Option<WidgetId>,
+    hovered: Option<WidgetId>,
+    pressed: Option<WidgetId>, //widget the left button was pressed on, until the release
+    hovered_item: Option<usize>,
+    last_pressed: bool,
+    is_changed: bool,
+    sources: HashMap<String, String>, //values are kept when widgets are rebuilt
+    icons: HashMap<String, [Vec2f; 2]>, //tex coords of block icons in the GUI atlas by block names
+}
+
+impl WidgetTree {
+    pub fn new(asp_rat: f32) -> Self {
+        let root: Widget = Widget::new("", WidgetKind::Panel, WidgetLayout::fill(0.0), [0.0, 0.0, 0.0, 0.0]);
+        let mut tree: Self = Self {
+            widgets: vec![Some(root)],
+            asp_rat,
+            focused: None,
+            hovered: None,
+            pressed: None,
+            hovered_item: None,
+            last_pressed: false,
+            is_changed: true,
+            sources: HashMap::new(),
+            icons: HashMap::new(),
+        };
+        tree.layout();
+        return tree;
+    }
+
+    pub fn add(&mut self, parent: WidgetId, mut widget: Widget) -> Option<WidgetId> {
+        if self.get(parent).is_none() {
+            return None;
+        }
+        let id: WidgetId = self.widgets.len();
+        widget.parent = Some(parent);
+        widget.children.clear();
+        if let Some(value) = widget.source.as_ref().and_then(|source| self.sources.get(source)) {
+            widget.set_source_value(value);
+        }
+        self.widgets.push(Some(widget));
+        if let Some(Some(parent_widget)) = self.widgets.get_mut(parent) {
+            parent_widget.children.push(id);
+        }
+        self.is_changed = true;
+        self.layout();
+        return Some(id);
+    }
+
+    //Removes the widget with all of its children, the root can't be removed
+    pub fn remove(&mut self, id: WidgetId) {
+        if id == ROOT_WIDGET {
+            return;
+        }
+        if let Some(Some(widget)) = self.widgets.get_mut(id).map(|widget| widget.take()) {
+            if let Some(parent) = widget.parent {
+                if let Some(Some(parent_widget)) = self.widgets.get_mut(parent) {
+                    parent_widget.children.retain(|child| *child != id);
+                }
+            }
+            for child in widget.children {
+                self.remove(child);
+            }
+            for state in [&mut self.focused, &mut self.hovered, &mut self.pressed] {
+                if *state == Some(id) {
+                    *state = None;
+                }
+            }
+            self.is_changed = true;
+        }
+    }
+
+    //Removes everything but the root
+    pub fn clear(&mut self) {
+        self.widgets.truncate(1);
+        if let Some(Some(root)) = self.widgets.get_mut(ROOT_WIDGET) {
+            root.children.clear();
+        }
+        self.focused = None;
+        self.hovered = None;
+        self.pressed = None;
+        self.is_changed = true;
+    }
+
+    pub fn get(&self, id: WidgetId) -> Option<&Widget> {
+        return self.widgets.get(id).and_then(|widget| widget.as_ref());
+    }
+
+    //Layout is recomputed on the next input or draw list
+    pub fn get_mut(&mut self, id: WidgetId) -> Option<&mut Widget> {
+        self.is_changed = true;
+        return self.widgets.get_mut(id).and_then(|widget| widget.as_mut());
+    }
+
+    pub fn find(&self, name: &str) -> Option<WidgetId> {
+        return self.widgets.iter().position(|widget| widget.as_ref().map_or(false, |widget| widget.name == name));
+    }
+
+    //Updates every widget bound to the source
+    pub fn set_source(&mut self, source: &str, value: &str) {
+        if self.sources.get(source).map_or(false, |cur_value| cur_value == value) {
+            return;
+        }
+        self.sources.insert(String::from(source), String::from(value));
+        for widget in self.widgets.iter_mut().flatten() {
+            if widget.source.as_deref() == Some(source) {
+                widget.set_source_value(value);
+            }
+        }
+        self.is_changed = true;
+    }
+
+    pub fn get_source(&self, source: &str) -> Option<&str> {
+        return self.sources.get(source).map(|value| value.as_str());
+    }
+
+    pub fn set_icons(&mut self, icons: HashMap<String, [Vec2f; 2]>) {
+        self.icons = icons;
+        self.is_changed = true;
+    }
+
+    pub fn set_asp_rat(&mut self, asp_rat: f32) {
+        if self.asp_rat != asp_rat {
+            self.asp_rat = asp_rat;
+            self.is_changed = true;
+            self.layout();
+        }
+    }
+
+    pub fn get_focused(&self) -> Option<WidgetId> {
+        return self.focused;
+    }
+
+    pub fn get_hovered(&self) -> Option<WidgetId> {
+        return self.hovered;
+    }
+
+    //Changed since the last call, for rebuilding the buffers
+    pub fn take_changed(&mut self) -> bool {
+        let is_changed: bool = self.is_changed;
+        self.is_changed = false;
+        return is_changed;
+    }
+
+    pub fn layout(&mut self) {
+        let root_rect: Rect = Rect::from_asp_rat(self.asp_rat);
+        self.layout_widget(ROOT_WIDGET, &root_rect);
+    }
+
+    fn layout_widget(&mut self, id: WidgetId, parent_rect: &Rect) {
+        let mut children: Vec<WidgetId> = Vec::new();
+        let mut rect: Rect = *parent_rect;
+        if let Some(widget) = self.widgets.get_mut(id).and_then(|widget| widget.as_mut()) {
+            rect = widget.layout.get_rect(parent_rect);
+            widget.rect = rect;
+            children = widget.children.clone();
+        }
+        for child in children {
+            self.layout_widget(child, &rect);
+        }
+    }
+
+    //Visible and enabled widgets in drawing order
+    fn get_drawn_widgets(&self, id: WidgetId, only_enabled: bool, drawn: &mut Vec<WidgetId>) {
+        if let Some(widget) = self.get(id) {
+            if widget.visible && (widget.enabled || !only_enabled) {
+                drawn.push(id);
+                for child in &widget.children {
+                    self.get_drawn_widgets(*child, only_enabled, drawn);
+                }
+            }
+        }
+    }
+
+    //Topmost enabled widget under the position, transparent widgets which can't be focused let the mouse through
+    pub fn get_widget_at(&self, pos: &Vec2f) -> Option<WidgetId> {
+        let mut drawn: Vec<WidgetId> = Vec::new();
+        self.get_drawn_widgets(ROOT_WIDGET, true, &mut drawn);
+        return drawn.into_iter().rev().find(|id| self.get(*id).map_or(false, |widget|
+            (widget.kind.is_focusable() || widget.color[3] > 0.0) && widget.rect.contains(pos)));
+    }
+
+    //Nearest focusable widget from the one under the position up to the root, so labels on buttons don't block them
+    pub fn get_target_at(&self, pos: &Vec2f) -> Option<WidgetId> {
+        let mut cur: Option<WidgetId> = self.get_widget_at(pos);
+        while let Some(id) = cur {
+            if let Some(widget) = self.get(id) {
+                if widget.kind.is_focusable() {
+                    return Some(id);
+                }
+                cur = widget.parent;
+            } else {
+                cur = None;
+            }
+        }
+        return None;
+    }
+
+    pub fn set_focus(&mut self, focused: Option<WidgetId>, events: &mut Vec<GuiEvent>) {
+        if self.focused != focused {
+            self.focused = focused;
+            self.is_changed = true;
+            events.push(GuiEvent::FocusChanged(focused));
+        }
+    }
+
+    //Next focusable widget in drawing order, wraps around
+    pub fn focus_next(&mut self, events: &mut Vec<GuiEvent>) {
+        let mut drawn: Vec<WidgetId> = Vec::new();
+        self.get_drawn_widgets(ROOT_WIDGET, true, &mut drawn);
+        let focusable: Vec<WidgetId> = drawn.into_iter().filter(|id| self.get(*id).map_or(false, |widget| widget.kind.is_focusable())).collect();
+        if focusable.len() != 0 {
+            let next_ind: usize = match self.focused.and_then(|focused| focusable.iter().position(|id| *id == focused)) {
+                Some(ind) => (ind + 1) % focusable.len(),
+                None => 0,
+            };
+            self.set_focus(Some(focusable[next_ind]), events);
+        }
+    }
+
+    fn set_slider_value(&mut self, id: WidgetId, new_value: f32, events: &mut Vec<GuiEvent>) {
+        if let Some(Some(widget)) = self.widgets.get_mut(id) {
+            if let WidgetKind::Slider { value, min, max, step } = &mut widget.kind {
+                let mut clamped: f32 = new_value.clamp(min.min(*max), max.max(*min));
+                if *step > 0.0 {
+                    clamped = (*min + ((clamped - *min) / *step).round() * *step).clamp(min.min(*max), max.max(*min));
+                }
+                if clamped != *value {
+                    *value = clamped;
+                    self.is_changed = true;
+                    events.push(GuiEvent::ValueChanged(id, clamped));
+                }
+            }
+        }
+    }
+
+    fn set_slider_from_pos(&mut self, id: WidgetId, pos: &Vec2f, events: &mut Vec<GuiEvent>) {
+        if let Some(widget) = self.get(id) {
+            if let WidgetKind::Slider { min, max, .. } = &widget.kind {
+                let width: f32 = widget.rect.get_size()[0] - SLIDER_HANDLE_WIDTH;
+                let fraction: f32 = if width > 0.0 { ((pos[0] - widget.rect.min[0] - SLIDER_HANDLE_WIDTH * 0.5) / width).clamp(0.0, 1.0) } else { 0.0 };
+                let value: f32 = min + (max - min) * fraction;
+                self.set_slider_value(id, value, events);
+            }
+        }
+    }
+
+    fn step_slider(&mut self, id: WidgetId, steps: f32, events: &mut Vec<GuiEvent>) {
+        if let Some(widget) = self.get(id) {
+            if let WidgetKind::Slider { value, min, max, step } = &widget.kind {
+                let step_size: f32 = if *step > 0.0 { *step } else { (max - min) / 10.0 };
+                let new_value: f32 = value + step_size * steps;
+                self.set_slider_value(id, new_value, events);
+            }
+        }
+    }
+
+    //Item of a scroll list under the position
+    fn get_item_at(&self, id: WidgetId, pos: &Vec2f) -> Option<usize> {
+        let widget: &Widget = self.get(id)?;
+        if let WidgetKind::ScrollList { items, item_height, scroll, .. } = &widget.kind {
+            if widget.rect.contains(pos) && *item_height > 0.0 {
+                let ind: usize = scroll + ((widget.rect.max[1] - pos[1]) / item_height).floor() as usize;
+                if ind < items.len() && ind < scroll + widget.get_visible_items_cnt() {
+                    return Some(ind);
+                }
+            }
+        }
+        return None;
+    }
+
+    fn select_item(&mut self, id: WidgetId, item: usize, events: &mut Vec<GuiEvent>) {
+        let visible_cnt: usize = self.get(id).map_or(0, |widget| widget.get_visible_items_cnt()).max(1);
+        if let Some(Some(widget)) = self.widgets.get_mut(id) {
+            if let WidgetKind::ScrollList { items, scroll, selected, .. } = &mut widget.kind {
+                if item < items.len() {
+                    if *selected != Some(item) {
+                        *selected = Some(item);
+                        events.push(GuiEvent::ItemSelected(id, item));
+                    }
+                    //Keeps the selected item shown
+                    if item < *scroll {
+                        *scroll = item;
+                    } else if item >= *scroll + visible_cnt {
+                        *scroll = item + 1 - visible_cnt;
+                    }
+                    self.is_changed = true;
+                }
+            }
+        }
+    }
+
+    fn scroll_list(&mut self, id: WidgetId, lines: i32) {
+        let visible_cnt: usize = self.get(id).map_or(0, |widget| widget.get_visible_items_cnt());
+        if let Some(Some(widget)) = self.widgets.get_mut(id) {
+            if let WidgetKind::ScrollList { items, scroll, .. } = &mut widget.kind {
+                let max_scroll: i32 = (items.len() as i32 - visible_cnt as i32).max(0);
+                let new_scroll: usize = (*scroll as i32 + lines).clamp(0, max_scroll) as usize;
+                if new_scroll != *scroll {
+                    *scroll = new_scroll;
+                    self.is_changed = true;
+                }
+            }
+        }
+    }
+
+    fn process_text_key(&mut self, id: WidgetId, key: Key, events: &mut Vec<GuiEvent>) {
+        if let Some(Some(widget)) = self.widgets.get_mut(id) {
+            if let WidgetKind::TextField { text, cursor, .. } = &mut widget.kind {
+                let len: usize = text.chars().count();
+                *cursor = (*cursor).min(len);
+                let mut is_edited: bool = false;
+                match key {
+                    Key::Backspace => {
+                        if *cursor > 0 {
+                            text.remove(get_byte_ind(text, *cursor - 1));
+                            *cursor -= 1;
+                            is_edited = true;
+                        }
+                    }
+                    Key::Delete => {
+                        if *cursor < len {
+                            text.remove(get_byte_ind(text, *cursor));
+                            is_edited = true;
+                        }
+                    }
+                    Key::Left => { *cursor = cursor.saturating_sub(1); }
+                    Key::Right => { *cursor = (*cursor + 1).min(len); }
+                    Key::Home => { *cursor = 0; }
+                    Key::End => { *cursor = len; }
+                    Key::Enter | Key::KpEnter => {
+                        events.push(GuiEvent::TextSubmitted(id, text.clone()));
+                    }
+                    _ => {}
+                }
+                if is_edited {
+                    events.push(GuiEvent::TextChanged(id, text.clone()));
+                }
+                self.is_changed = true;
+            }
+        }
+    }
+
+    fn insert_chars(&mut self, id: WidgetId, chars: &[char], events: &mut Vec<GuiEvent>) {
+        if let Some(Some(widget)) = self.widgets.get_mut(id) {
+            if let WidgetKind::TextField { text, max_len, cursor } = &mut widget.kind {
+                let mut is_edited: bool = false;
+                for char in chars {
+                    let len: usize = text.chars().count();
+                    if !char.is_control() && len < *max_len {
+                        *cursor = (*cursor).min(len);
+                        text.insert(get_byte_ind(text, *cursor), *char);
+                        *cursor += 1;
+                        is_edited = true;
+                    }
+                }
+                if is_edited {
+                    events.push(GuiEvent::TextChanged(id, text.clone()));
+                    self.is_changed = true;
+                }
+            }
+        }
+    }
+
+    fn process_key(&mut self, key: Key, events: &mut Vec<GuiEvent>) {
+        match key {
+            Key::Tab => {
+                self.focus_next(events);
+                return;
+            }
+            Key::Escape => {
+                self.set_focus(None, events);
+                return;
+            }
+            _ => {}
+        }
+        if let Some(focused) = self.focused {
+            let kind: Option<WidgetKind> = self.get(focused).map(|widget| widget.kind.clone());
+            match kind {
+                Some(WidgetKind::Button { .. }) => {
+                    if key == Key::Enter || key == Key::KpEnter {
+                        events.push(GuiEvent::Clicked(focused));
+                    }
+                }
+                Some(WidgetKind::Slider { .. }) => {
+                    match key {
+                        Key::Left | Key::Down => self.step_slider(focused, -1.0, events),
+                        Key::Right | Key::Up => self.step_slider(focused, 1.0, events),
+                        _ => {}
+                    }
+                }
+                Some(WidgetKind::TextField { .. }) => {
+                    self.process_text_key(focused, key, events);
+                }
+                Some(WidgetKind::ScrollList { items, selected, .. }) => {
+                    if items.len() != 0 {
+                        match key {
+                            Key::Up => self.select_item(focused, selected.map_or(0, |selected| selected.saturating_sub(1)), events),
+                            Key::Down => self.select_item(focused, selected.map_or(0, |selected| (selected + 1).min(items.len() - 1)), events),
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    //Updates hovering, pressing and focus, returns the events in order of happening
+    pub fn process_input(&mut self, input: &GuiInput) -> Vec<GuiEvent> {
+        let mut events: Vec<GuiEvent> = Vec::new();
+        self.layout();
+        let hovered: Option<WidgetId> = self.get_target_at(&input.cursor_pos);
+        let hovered_item: Option<usize> = hovered.and_then(|id| self.get_item_at(id, &input.cursor_pos));
+        if hovered != self.hovered || hovered_item != self.hovered_item {
+            self.hovered = hovered;
+            self.hovered_item = hovered_item;
+            self.is_changed = true;
+        }
+        if input.pressed && !self.last_pressed {
+            self.pressed = hovered;
+            self.set_focus(hovered, &mut events);
+            if let Some(id) = hovered {
+                match self.get(id).map(|widget| widget.kind.clone()) {
+                    Some(WidgetKind::Slider { .. }) => self.set_slider_from_pos(id, &input.cursor_pos, &mut events),
+                    Some(WidgetKind::ScrollList { .. }) => {
+                        if let Some(item) = hovered_item {
+                            self.select_item(id, item, &mut events);
+                        }
+                    }
+                    Some(WidgetKind::TextField { .. }) => self.process_text_key(id, Key::End, &mut events),
+                    _ => {}
+                }
+            }
+            self.is_changed = true;
+        } else if input.pressed {
+            if let Some(id) = self.pressed {
+                if let Some(WidgetKind::Slider { .. }) = self.get(id).map(|widget| &widget.kind) {
+                    self.set_slider_from_pos(id, &input.cursor_pos, &mut events);
+                }
+            }
+        } else if self.last_pressed {
+            if let Some(id) = self.pressed.take() {
+                if hovered == Some(id) {
+                    if let Some(WidgetKind::Button { .. }) = self.get(id).map(|widget| &widget.kind) {
+                        events.push(GuiEvent::Clicked(id));
+                    }
+                }
+            }
+            self.is_changed = true;
+        }
+        self.last_pressed = input.pressed;
+        if input.scroll != 0.0 {
+            if let Some(id) = hovered {
+                self.scroll_list(id, -input.scroll.round() as i32);
+            }
+        }
+        //Text is typed before the keys of the frame, so typing and submitting in one frame works
+        if let Some(focused) = self.focused {
+            self.insert_chars(focused, &input.chars, &mut events);
+        }
+        for key in &input.keys {
+            self.process_key(*key, &mut events);
+        }
+        return events;
+    }
+
+    fn get_state_color(&self, id: WidgetId, widget: &Widget) -> Vec4f {
+        let mut color: Vec4f = widget.color;
+        let factor: f32 = if self.pressed == Some(id) && self.hovered == Some(id) {
+            PRESS_FACTOR
+        } else if self.hovered == Some(id) && widget.kind.is_focusable() {
+            HOVER_FACTOR
+        } else {
+            1.0
+        };
+        for channel in 0..3 {
+            color[channel] = (color[channel] * factor).min(1.0);
+        }
+        if !widget.enabled {
+            color[3] *= 0.5;
+        }
+        return color;
+    }
+
+    //Text centered vertically in the rect and optionally horizontally
+    fn add_text(font: &Font, text_scale: f32, text: &str, rect: &Rect, color: &Vec4f, centered: bool, texts: &mut Vec<TextElement>) {
+        let text_layout: TextLayout = layout_text(font, text, color, None);
+        let size: Vec2f = [text_layout.size[0] * text_scale, text_layout.size[1] * text_scale];
+        let x: f32 = if centered {
+            rect.get_center()[0] - size[0] * 0.5
+        } else {
+            rect.min[0] + TEXT_PADDING
+        };
+        texts.push(TextElement {
+            pos: [x, rect.get_center()[1] + size[1] * 0.5],
+            text: String::from(text),
+            color: *color,
+            scale: text_scale,
+            max_width: None,
+        });
+    }
+
+    //Labels start at the top left corner and wrap at the width of the rect, if it has one
+    fn add_label_text(text_scale: f32, text: &str, rect: &Rect, color: &Vec4f, texts: &mut Vec<TextElement>) {
+        let width: f32 = rect.get_size()[0] - TEXT_PADDING * 2.0;
+        texts.push(TextElement {
+            pos: [rect.min[0] + TEXT_PADDING, rect.max[1] - TEXT_PADDING],
+            text: String::from(text),
+            color: *color,
+            scale: text_scale,
+            max_width: if width > 0.0 { Some(width) } else { None },
+        });
+    }
+
+    pub fn get_draw_list(&mut self, font: &Font, text_scale: f32) -> GuiDrawList {
+        self.layout();
+        let mut quads: Vec<GuiQuad> = Vec::new();
+        let mut texts: Vec<TextElement> = Vec::new();
+        let mut drawn: Vec<WidgetId> = Vec::new();
+        self.get_drawn_widgets(ROOT_WIDGET, false, &mut drawn);
+        for id in drawn {
+            if let Some(widget) = self.get(id) {
+                let rect: Rect = widget.rect;
+                let text_scale: f32 = text_scale * widget.text_scale;
+                let text_color: Vec4f = if widget.enabled { TEXT_COLOR } else { DISABLED_TEXT_COLOR };
+                if self.focused == Some(id) {
+                    quads.push(GuiQuad { rect: rect.shrink(-FOCUS_BORDER), color: FOCUS_COLOR, tex: None });
+                }
+                if widget.color[3] > 0.0 && !matches!(widget.kind, WidgetKind::Icon { .. }) {
+                    quads.push(GuiQuad { rect, color: self.get_state_color(id, widget), tex: widget.texture });
+                }
+                match &widget.kind {
+                    WidgetKind::Panel | WidgetKind::Image => {}
+                    WidgetKind::Icon { name } => {
+                        if let Some(tex) = self.icons.get(name) {
+                            quads.push(GuiQuad { rect, color: widget.color, tex: Some(*tex) });
+                        }
+                    }
+                    WidgetKind::Label { text } => Self::add_label_text(text_scale, text, &rect, &text_color, &mut texts),
+                    WidgetKind::Button { text } => Self::add_text(font, text_scale, text, &rect, &text_color, true, &mut texts),
+                    WidgetKind::Slider { value, .. } => {
+                        let handle_x: f32 = rect.min[0] + (rect.get_size()[0] - SLIDER_HANDLE_WIDTH) * widget.get_slider_fraction();
+                        quads.push(GuiQuad { rect: Rect::new([handle_x, rect.min[1]], [handle_x + SLIDER_HANDLE_WIDTH, rect.max[1]]), color: HANDLE_COLOR, tex: None });
+                        Self::add_text(font, text_scale, &format!("{}", value), &rect, &text_color, true, &mut texts);
+                    }
+                    WidgetKind::TextField { text, cursor, .. } => {
+                        let mut shown: String = text.clone();
+                        if self.focused == Some(id) {
+                            shown.insert(get_byte_ind(&shown, *cursor), TEXT_CURSOR);
+                        }
+                        Self::add_text(font, text_scale, &shown, &rect, &text_color, false, &mut texts);
+                    }
+                    WidgetKind::ScrollList { items, item_height, scroll, selected } => {
+                        for (item_pos, item) in items.iter().enumerate().skip(*scroll).take(widget.get_visible_items_cnt()) {
+                            let item_max_y: f32 = rect.max[1] - ((item_pos - scroll) as f32) * item_height;
+                            let item_rect: Rect = Rect::new([rect.min[0], item_max_y - item_height], [rect.max[0], item_max_y]);
+                            if *selected == Some(item_pos) {
+                                quads.push(GuiQuad { rect: item_rect, color: SELECTED_ITEM_COLOR, tex: None });
+                            } else if self.hovered == Some(id) && self.hovered_item == Some(item_pos) {
+                                quads.push(GuiQuad { rect: item_rect, color: HOVERED_ITEM_COLOR, tex: None });
+                            }
+                            Self::add_text(font, text_scale, item, &item_rect, &text_color, false, &mut texts);
+                        }
+                    }
+                }
+            }
+        }
+        return GuiDrawList { quads, texts };
+    }
+}
+
+//Byte index of the char with the position, or the length of the text
+fn get_byte_ind(text: &str, char_pos: usize) -> usize {
+    return text.char_indices().nth(char_pos).map_or(text.len(), |(ind, _)| ind);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gui::widget::Anchor;
+    use super::*;
+
+    const E: f32 = 0.0001;
+    const COLOR: Vec4f = [0.5, 0.5, 0.5, 1.0];
+
+    fn assert_rect(actual: &Rect, expected: &Rect) {
+        for axis in 0..2usize {
+            assert!((actual.min[axis] - expected.min[axis]).abs() < E && (actual.max[axis] - expected.max[axis]).abs() < E, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    fn get_input(cursor_pos: Vec2f, pressed: bool) -> GuiInput {
+        return GuiInput { cursor_pos, pressed, scroll: 0.0, keys: Vec::new(), chars: Vec::new() };
+    }
+
+    fn get_key_input(keys: &[Key]) -> GuiInput {
+        return GuiInput { cursor_pos: [10.0, 10.0], pressed: false, scroll: 0.0, keys: keys.to_vec(), chars: Vec::new() };
+    }
+
+    fn add_button(tree: &mut WidgetTree, parent: WidgetId, name: &str, layout: WidgetLayout) -> WidgetId {
+        return tree.add(parent, Widget::new(name, WidgetKind::Button { text: String::from(name) }, layout, COLOR)).unwrap();
+    }
+
+    fn get_text_field(tree: &WidgetTree, id: WidgetId) -> (String, usize) {
+        return match &tree.get(id).unwrap().kind {
+            WidgetKind::TextField { text, cursor, .. } => (text.clone(), *cursor),
+            _ => panic!("Not a text field"),
+        }
+    }
+
+    #[test]
+    fn layout_anchors_move_inwards() {
+        let parent: Rect = Rect::new([-1.0, -0.5], [1.0, 0.5]);
+        assert_rect(&WidgetLayout::new(Anchor::TopLeft, [0.1, 0.1], [0.4, 0.2]).get_rect(&parent), &Rect::new([-0.9, 0.2], [-0.5, 0.4]));
+        assert_rect(&WidgetLayout::new(Anchor::BottomRight, [0.1, 0.1], [0.4, 0.2]).get_rect(&parent), &Rect::new([0.5, -0.4], [0.9, -0.2]));
+        assert_rect(&WidgetLayout::new(Anchor::Top, [0.1, 0.0], [0.4, 0.2]).get_rect(&parent), &Rect::new([-0.1, 0.3], [0.3, 0.5]));
+        assert_rect(&WidgetLayout::new(Anchor::Center, [0.0, -0.1], [0.4, 0.2]).get_rect(&parent), &Rect::new([-0.2, -0.2], [0.2, 0.0]));
+        assert_rect(&WidgetLayout::fill(0.1).get_rect(&parent), &Rect::new([-0.9, -0.4], [0.9, 0.4]));
+        //Stretching never makes widgets inside out
+        assert_rect(&WidgetLayout::fill(2.0).get_rect(&parent), &Rect::new([0.0, 0.0], [0.0, 0.0]));
+    }
+
+    #[test]
+    fn layout_follows_the_aspect_ratio() {
+        let mut tree: WidgetTree = WidgetTree::new(2.0);
+        let id: WidgetId = add_button(&mut tree, ROOT_WIDGET, "button", WidgetLayout::new(Anchor::TopRight, [0.0, 0.0], [0.5, 0.1]));
+        assert_rect(tree.get(ROOT_WIDGET).unwrap().get_rect(), &Rect::new([-1.0, -0.5], [1.0, 0.5]));
+        assert_rect(tree.get(id).unwrap().get_rect(), &Rect::new([0.5, 0.4], [1.0, 0.5]));
+        tree.set_asp_rat(1.0);
+        assert_rect(tree.get(id).unwrap().get_rect(), &Rect::new([0.5, 0.9], [1.0, 1.0]));
+    }
+
+    #[test]
+    fn target_is_the_nearest_focusable_widget() {
+        let mut tree: WidgetTree = WidgetTree::new(1.0);
+        let panel: WidgetId = tree.add(ROOT_WIDGET, Widget::new("panel", WidgetKind::Panel, WidgetLayout::new(Anchor::Center, [0.0, 0.0], [1.0, 1.0]), COLOR)).unwrap();
+        let button: WidgetId = add_button(&mut tree, panel, "button", WidgetLayout::new(Anchor::Center, [0.0, 0.0], [0.5, 0.5]));
+        let label: WidgetId = tree.add(button, Widget::new("label", WidgetKind::Label { text: String::from("label") }, WidgetLayout::fill(0.0), COLOR)).unwrap();
+        assert_eq!(tree.get_widget_at(&[0.0, 0.0]), Some(label));
+        assert_eq!(tree.get_target_at(&[0.0, 0.0]), Some(button));
+        assert_eq!(tree.get_widget_at(&[0.4, 0.4]), Some(panel));
+        assert_eq!(tree.get_target_at(&[0.4, 0.4]), None);
+        assert_eq!(tree.get_target_at(&[0.9, 0.9]), None);
+        //Hidden and disabled widgets let the mouse through
+        tree.get_mut(button).unwrap().enabled = false;
+        assert_eq!(tree.get_widget_at(&[0.0, 0.0]), Some(panel));
+        tree.get_mut(button).unwrap().enabled = true;
+        tree.get_mut(panel).unwrap().visible = false;
+        assert_eq!(tree.get_target_at(&[0.0, 0.0]), None);
+    }
+
+    #[test]
+    fn press_and_release_over_a_button_clicks() {
+        let mut tree: WidgetTree = WidgetTree::new(1.0);
+        let button: WidgetId = add_button(&mut tree, ROOT_WIDGET, "button", WidgetLayout::new(Anchor::Center, [0.0, 0.0], [0.5, 0.5]));
+        assert_eq!(tree.process_input(&get_input([0.0, 0.0], false)), vec![]);
+        assert_eq!(tree.get_hovered(), Some(button));
+        assert_eq!(tree.process_input(&get_input([0.0, 0.0], true)), vec![GuiEvent::FocusChanged(Some(button))]);
+        assert_eq!(tree.process_input(&get_input([0.1, 0.0], true)), vec![]);
+        assert_eq!(tree.process_input(&get_input([0.1, 0.0], false)), vec![GuiEvent::Clicked(button)]);
+        //Releasing somewhere else cancels the click
+        tree.process_input(&get_input([0.0, 0.0], true));
+        assert_eq!(tree.process_input(&get_input([0.9, 0.9], false)), vec![]);
+        //Pressing outside and releasing over the button doesn't click either
+        assert_eq!(tree.process_input(&get_input([0.9, 0.9], true)), vec![GuiEvent::FocusChanged(None)]);
+        assert_eq!(tree.process_input(&get_input([0.0, 0.0], false)), vec![]);
+        //Enter clicks the focused button
+        tree.set_focus(Some(button), &mut Vec::new());
+        assert_eq!(tree.process_input(&get_key_input(&[Key::Enter])), vec![GuiEvent::Clicked(button)]);
+    }
+
+    #[test]
+    fn focus_next_wraps_around_focusable_widgets() {
+        let mut tree: WidgetTree = WidgetTree::new(1.0);
+        let first: WidgetId = add_button(&mut tree, ROOT_WIDGET, "first", WidgetLayout::new(Anchor::Top, [0.0, 0.0], [0.5, 0.1]));
+        tree.add(ROOT_WIDGET, Widget::new("label", WidgetKind::Label { text: String::new() }, WidgetLayout::new(Anchor::Center, [0.0, 0.0], [0.5, 0.1]), COLOR));
+        let hidden: WidgetId = add_button(&mut tree, ROOT_WIDGET, "hidden", WidgetLayout::new(Anchor::Center, [0.0, 0.0], [0.5, 0.1]));
+        tree.get_mut(hidden).unwrap().visible = false;
+        let last: WidgetId = add_button(&mut tree, ROOT_WIDGET, "last", WidgetLayout::new(Anchor::Bottom, [0.0, 0.0], [0.5, 0.1]));
+        let mut events: Vec<GuiEvent> = Vec::new();
+        tree.focus_next(&mut events);
+        assert_eq!(tree.get_focused(), Some(first));
+        tree.focus_next(&mut events);
+        assert_eq!(tree.get_focused(), Some(last));
+        tree.focus_next(&mut events);
+        assert_eq!(tree.get_focused(), Some(first));
+        assert_eq!(events, vec![GuiEvent::FocusChanged(Some(first)), GuiEvent::FocusChanged(Some(last)), GuiEvent::FocusChanged(Some(first))]);
+        assert_eq!(tree.process_input(&get_key_input(&[Key::Tab, Key::Escape])), vec![GuiEvent::FocusChanged(Some(last)), GuiEvent::FocusChanged(None)]);
+    }
+
+    #[test]
+    fn dragging_moves_the_slider_in_steps() {
+        let mut tree: WidgetTree = WidgetTree::new(1.0);
+        let layout: WidgetLayout = WidgetLayout::new(Anchor::Center, [0.0, 0.0], [1.0 + SLIDER_HANDLE_WIDTH, 0.1]);
+        let slider: WidgetId = tree.add(ROOT_WIDGET, Widget::new("slider", WidgetKind::Slider { value: 0.0, min: 0.0, max: 10.0, step: 1.0 }, layout, COLOR)).unwrap();
+        //The handle center covers -0.5..0.5
+        assert_eq!(tree.process_input(&get_input([0.0, 0.0], true)), vec![GuiEvent::FocusChanged(Some(slider)), GuiEvent::ValueChanged(slider, 5.0)]);
+        assert_eq!(tree.process_input(&get_input([0.23, 0.0], true)), vec![GuiEvent::ValueChanged(slider, 7.0)]);
+        assert_eq!(tree.process_input(&get_input([0.24, 0.0], true)), vec![]);
+        //Dragging keeps working outside of the slider
+        assert_eq!(tree.process_input(&get_input([0.9, 0.5], true)), vec![GuiEvent::ValueChanged(slider, 10.0)]);
+        assert_eq!(tree.process_input(&get_input([-0.9, 0.5], true)), vec![GuiEvent::ValueChanged(slider, 0.0)]);
+        assert_eq!(tree.process_input(&get_input([-0.9, 0.5], false)), vec![]);
+        assert_eq!(tree.process_input(&get_input([0.0, 0.5], false)), vec![]);
+        assert!((tree.get(slider).unwrap().get_slider_fraction() - 0.0).abs() < E);
+        assert_eq!(tree.process_input(&get_key_input(&[Key::Right, Key::Right])), vec![GuiEvent::ValueChanged(slider, 1.0), GuiEvent::ValueChanged(slider, 2.0)]);
+    }
+
+    #[test]
+    fn text_field_edits_multibyte_text() {
+        let mut tree: WidgetTree = WidgetTree::new(1.0);
+        let field: WidgetId = tree.add(ROOT_WIDGET, Widget::new("field", WidgetKind::TextField { text: String::new(), max_len: 6, cursor: 0 }, WidgetLayout::new(Anchor::Center, [0.0, 0.0], [0.5, 0.1]), COLOR)).unwrap();
+        tree.get_mut(field).unwrap().set_text("añ€😀");
+        tree.set_focus(Some(field), &mut Vec::new());
+        assert_eq!(get_text_field(&tree, field), (String::from("añ€😀"), 4));
+        assert_eq!(tree.process_input(&get_key_input(&[Key::Backspace])), vec![GuiEvent::TextChanged(field, String::from("añ€"))]);
+        assert_eq!(tree.process_input(&get_key_input(&[Key::Left, Key::Left, Key::Delete])), vec![GuiEvent::TextChanged(field, String::from("a€"))]);
+        assert_eq!(get_text_field(&tree, field), (String::from("a€"), 1));
+        assert_eq!(tree.process_input(&get_key_input(&[Key::Home, Key::Backspace, Key::End, Key::Delete])), vec![]);
+        let mut input: GuiInput = get_key_input(&[Key::Left, Key::Backspace]);
+        input.chars = vec!['ü', '\n', 'ß', 'x', 'y', 'z'];
+        assert_eq!(tree.process_input(&input), vec![GuiEvent::TextChanged(field, String::from("a€üßxy")), GuiEvent::TextChanged(field, String::from("a€üßy"))]);
+        assert_eq!(get_text_field(&tree, field), (String::from("a€üßy"), 4));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,152 @@
+use glfw::{Key, MouseButtonLeft, MouseButtonRight};
+use crate::input::input_source::InputSource;
+use crate::player::inventory::HOTBAR_SIZE;
+
+//Things the player can do, each one is bound to any number of inputs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputAction {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Sneak,
+    Sprint,
+    BreakBlock,
+    UseBlock,
+    RepeatClick,
+    LookUp,
+    LookDown,
+    LookLeft,
+    LookRight,
+    RollLeft,
+    RollRight,
+    Hotbar1,
+    Hotbar2,
+    Hotbar3,
+    Hotbar4,
+    Hotbar5,
+    Hotbar6,
+    Hotbar7,
+    Hotbar8,
+    Hotbar9,
+    Inventory,
+    Chat,
+    Command,
+    ToggleCursor,
+    DebugInfo,
+    SwitchMode,
+    Pause,
+    SaveWorld,
+    LoadWorld,
+    RandomBlock,
+    PrintCamera,
+    PrintBlock,
+    AddLight,
+}
+
+//In the order of the variants, names are used in the bindings file
+pub const INPUT_ACTIONS: [(InputAction, &str, &str); 38] = [
+    (InputAction::MoveForward, "move_forward", "Move forward"),
+    (InputAction::MoveBack, "move_back", "Move back"),
+    (InputAction::MoveLeft, "move_left", "Move left"),
+    (InputAction::MoveRight, "move_right", "Move right"),
+    (InputAction::Jump, "jump", "Jump, fly up"),
+    (InputAction::Sneak, "sneak", "Sneak, fly down"),
+    (InputAction::Sprint, "sprint", "Sprint"),
+    (InputAction::BreakBlock, "break_block", "Break block"),
+    (InputAction::UseBlock, "use_block", "Use or place block"),
+    (InputAction::RepeatClick, "repeat_click", "Repeat breaking and placing"),
+    (InputAction::LookUp, "look_up", "Look up with free cursor"),
+    (InputAction::LookDown, "look_down", "Look down with free cursor"),
+    (InputAction::LookLeft, "look_left", "Look left with free cursor"),
+    (InputAction::LookRight, "look_right", "Look right with free cursor"),
+    (InputAction::RollLeft, "roll_left", "Roll left with free cursor"),
+    (InputAction::RollRight, "roll_right", "Roll right with free cursor"),
+    (InputAction::Hotbar1, "hotbar_1", "Hotbar slot 1"),
+    (InputAction::Hotbar2, "hotbar_2", "Hotbar slot 2"),
+    (InputAction::Hotbar3, "hotbar_3", "Hotbar slot 3"),
+    (InputAction::Hotbar4, "hotbar_4", "Hotbar slot 4"),
+    (InputAction::Hotbar5, "hotbar_5", "Hotbar slot 5"),
+    (InputAction::Hotbar6, "hotbar_6", "Hotbar slot 6"),
+    (InputAction::Hotbar7, "hotbar_7", "Hotbar slot 7"),
+    (InputAction::Hotbar8, "hotbar_8", "Hotbar slot 8"),
+    (InputAction::Hotbar9, "hotbar_9", "Hotbar slot 9"),
+    (InputAction::Inventory, "inventory", "Inventory"),
+    (InputAction::Chat, "chat", "Open console"),
+    (InputAction::Command, "command", "Open console with a command"),
+    (InputAction::ToggleCursor, "toggle_cursor", "Free the cursor"),
+    (InputAction::DebugInfo, "debug_info", "Debug info"),
+    (InputAction::SwitchMode, "switch_mode", "Switch player mode"),
+    (InputAction::Pause, "pause", "Pause, close screen"),
+    (InputAction::SaveWorld, "save_world", "Save world"),
+    (InputAction::LoadWorld, "load_world", "Load world"),
+    (InputAction::RandomBlock, "random_block", "Place random block"),
+    (InputAction::PrintCamera, "print_camera", "Print camera"),
+    (InputAction::PrintBlock, "print_block", "Print target block"),
+    (InputAction::AddLight, "add_light", "Light target block"),
+];
+
+pub const HOTBAR_ACTIONS: [InputAction; HOTBAR_SIZE] = [
+    InputAction::Hotbar1, InputAction::Hotbar2, InputAction::Hotbar3,
+    InputAction::Hotbar4, InputAction::Hotbar5, InputAction::Hotbar6,
+    InputAction::Hotbar7, InputAction::Hotbar8, InputAction::Hotbar9,
+];
+
+impl InputAction {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return INPUT_ACTIONS.iter().find(|(_, action_name, _)| *action_name == name).map(|(action, _, _)| *action);
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        return INPUT_ACTIONS[*self as usize].1;
+    }
+
+    pub fn get_description(&self) -> &'static str {
+        return INPUT_ACTIONS[*self as usize].2;
+    }
+
+    pub fn get_default_sources(&self) -> Vec<InputSource> {
+        let keys: Vec<Key> = match self {
+            Self::MoveForward => vec![Key::W],
+            Self::MoveBack => vec![Key::S],
+            Self::MoveLeft => vec![Key::A],
+            Self::MoveRight => vec![Key::D],
+            Self::Jump => vec![Key::Space],
+            Self::Sneak => vec![Key::LeftShift, Key::RightShift],
+            Self::Sprint => vec![Key::LeftControl],
+            Self::BreakBlock => return vec![InputSource::MouseButton(MouseButtonRight)],
+            Self::UseBlock => return vec![InputSource::MouseButton(MouseButtonLeft)],
+            Self::RepeatClick => vec![Key::R],
+            Self::LookUp => vec![Key::Up],
+            Self::LookDown => vec![Key::Down],
+            Self::LookLeft => vec![Key::Left],
+            Self::LookRight => vec![Key::Right],
+            Self::RollLeft => vec![Key::Y],
+            Self::RollRight => vec![Key::H],
+            Self::Hotbar1 => vec![Key::Num1],
+            Self::Hotbar2 => vec![Key::Num2],
+            Self::Hotbar3 => vec![Key::Num3],
+            Self::Hotbar4 => vec![Key::Num4],
+            Self::Hotbar5 => vec![Key::Num5],
+            Self::Hotbar6 => vec![Key::Num6],
+            Self::Hotbar7 => vec![Key::Num7],
+            Self::Hotbar8 => vec![Key::Num8],
+            Self::Hotbar9 => vec![Key::Num9],
+            Self::Inventory => vec![Key::E],
+            Self::Chat => vec![Key::T],
+            Self::Command => vec![Key::Slash],
+            Self::ToggleCursor => vec![Key::Tab],
+            Self::DebugInfo => vec![Key::F3],
+            Self::SwitchMode => vec![Key::V],
+            Self::Pause => vec![Key::Escape],
+            Self::SaveWorld => vec![Key::F],
+            Self::LoadWorld => vec![Key::G],
+            Self::RandomBlock => vec![Key::Q],
+            Self::PrintCamera => vec![Key::M],
+            Self::PrintBlock => vec![Key::B],
+            Self::AddLight => vec![Key::L],
+        };
+        return keys.into_iter().map(InputSource::Key).collect();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use thiserror::Error;
+use crate::input::input_action::{INPUT_ACTIONS, InputAction};
+use crate::input::input_source::InputSource;
+use crate::input::keyboard::Keyboard;
+use crate::input::mouse::Mouse;
+
+#[derive(Error, Debug)]
+pub enum InputMapError {
+    #[error("Input action {0} does not exist")]
+    UnknownActionError(String),
+    #[error("Input {0} does not exist")]
+    UnknownInputError(String),
+    #[error("{0} is already bound to {1}")]
+    ConflictError(String, String),
+}
+
+#[derive(Clone, Copy, Default)]
+struct ActionState {
+    pressed: bool,
+    just_pressed: bool,
+    just_released: bool,
+    repeated: bool, //just pressed or repeated by the keyboard
+}
+
+//Binds actions to keys and mouse buttons and keeps their states between frames
+pub struct InputMap {
+    bindings: Vec<Vec<InputSource>>, //in the order of INPUT_ACTIONS
+    states: Vec<ActionState>,
+    down_sources: Vec<InputSource>,
+    just_pressed_sources: Vec<InputSource>,
+}
+
+impl InputMap {
+    pub fn new() -> Self {
+        return Self {
+            bindings: INPUT_ACTIONS.iter().map(|(action, _, _)| action.get_default_sources()).collect(),
+            states: vec![ActionState::default(); INPUT_ACTIONS.len()],
+            down_sources: Vec::new(),
+            just_pressed_sources: Vec::new(),
+        };
+    }
+
+    //Maps action names to input names, missing actions keep the defaults, inputs bound to several actions are rejected
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut data: Vec<u8> = Vec::new();
+        File::open(path)?.read_to_end(&mut data)?;
+        let names: BTreeMap<String, Vec<String>> = serde_json::from_slice(&data)?;
+        let mut input_map: Self = Self::new();
+        for (action_name, source_names) in &names {
+            let action: InputAction = InputAction::from_name(action_name).ok_or_else(|| InputMapError::UnknownActionError(action_name.clone()))?;
+            let mut sources: Vec<InputSource> = Vec::new();
+            for source_name in source_names {
+                sources.push(InputSource::from_name(source_name).ok_or_else(|| InputMapError::UnknownInputError(source_name.clone()))?);
+            }
+            input_map.bindings[action as usize] = sources;
+        }
+        if let Some((source, action, _other)) = input_map.get_conflicts().first() {
+            return Err(Box::new(InputMapError::ConflictError(source.get_name(), String::from(action.get_description()))));
+        }
+        return Ok(input_map);
+    }
+
+    pub fn store(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let mut names: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for (action, action_name, _) in &INPUT_ACTIONS {
+            names.insert(action_name, self.get_sources(*action).iter().map(|source| source.get_name()).collect());
+        }
+        let mut file: File = File::create(path)?;
+        file.write_all(serde_json::to_string_pretty(&names)?.as_bytes())?;
+        return Ok(());
+    }
+
+    pub fn get_sources(&self, action: InputAction) -> &[InputSource] {
+        return &self.bindings[action as usize];
+    }
+
+    //Action other than the given one that uses the input
+    fn find_owner(&self, source: &InputSource, action: InputAction) -> Option<InputAction> {
+        return INPUT_ACTIONS.iter().map(|(other, _, _)| *other).find(|other| *other != action && self.get_sources(*other).contains(source));
+    }
+
+    //Inputs bound to several actions, with the first two of their actions
+    pub fn get_conflicts(&self) -> Vec<(InputSource, InputAction, InputAction)> {
+        let mut conflicts: Vec<(InputSource, InputAction, InputAction)> = Vec::new();
+        for (action, _, _) in &INPUT_ACTIONS {
+            for source in self.get_sources(*action) {
+                if let Some(other) = self.find_owner(source, *action).filter(|other| (*other as usize) > (*action as usize)) {
+                    conflicts.push((*source, *action, other));
+                }
+            }
+        }
+        return conflicts;
+    }
+
+    //Replaces the bindings of the action, inputs of other actions are not taken
+    pub fn bind(&mut self, action: InputAction, source: InputSource) -> Result<(), InputMapError> {
+        if let Some(other) = self.find_owner(&source, action) {
+            return Err(InputMapError::ConflictError(source.get_name(), String::from(other.get_description())));
+        }
+        self.bindings[action as usize] = vec![source];
+        return Ok(());
+    }
+
+    pub fn reset(&mut self, action: InputAction) -> Result<(), InputMapError> {
+        let sources: Vec<InputSource> = action.get_default_sources();
+        for source in &sources {
+            if let Some(other) = self.find_owner(source, action) {
+                return Err(InputMapError::ConflictError(source.get_name(), String::from(other.get_description())));
+            }
+        }
+        self.bindings[action as usize] = sources;
+        return Ok(());
+    }
+
+    //Called once per frame after the window events
+    pub fn update(&mut self, keyboard: &Keyboard, mouse: &Mouse) {
+        let down_sources: Vec<InputSource> = InputSource::get_all().into_iter().filter(|source| source.is_down(keyboard, mouse)).collect();
+        self.just_pressed_sources = down_sources.iter().filter(|source| !self.down_sources.contains(source)).copied().collect();
+        self.down_sources = down_sources;
+        let pressed_keys: Vec<InputSource> = keyboard.get_pressed_keys().into_iter().map(InputSource::Key).collect();
+        for (action_ind, sources) in self.bindings.iter().enumerate() {
+            let state: &mut ActionState = &mut self.states[action_ind];
+            let pressed: bool = sources.iter().any(|source| self.down_sources.contains(source));
+            state.just_pressed = pressed && !state.pressed;
+            state.just_released = !pressed && state.pressed;
+            state.repeated = state.just_pressed || (pressed && sources.iter().any(|source| pressed_keys.contains(source)));
+            state.pressed = pressed;
+        }
+    }
+
+    pub fn is_pressed(&self, action: InputAction) -> bool {
+        return self.states[action as usize].pressed;
+    }
+
+    pub fn is_just_pressed(&self, action: InputAction) -> bool {
+        return self.states[action as usize].just_pressed;
+    }
+
+    pub fn is_just_released(&self, action: InputAction) -> bool {
+        return self.states[action as usize].just_released;
+    }
+
+    pub fn is_repeated(&self, action: InputAction) -> bool {
+        return self.states[action as usize].repeated;
+    }
+
+    //Used to pick a new binding, repeats are ignored
+    pub fn get_just_pressed_source(&self) -> Option<InputSource> {
+        return self.just_pressed_sources.first().copied();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use glfw::{Action, Key, Modifiers};
+    use crate::packs::test_packs::get_test_dir;
+    use super::*;
+
+    fn load_test_controls(test_name: &str, data: &str) -> Result<InputMap, Box<dyn std::error::Error>> {
+        let path: PathBuf = get_test_dir(test_name).join("controls.json");
+        fs::write(&path, data).unwrap();
+        return InputMap::load(&path);
+    }
+
+    #[test]
+    fn bind_and_reset_keep_inputs_of_other_actions() {
+        let mut input_map: InputMap = InputMap::new();
+        assert_eq!(input_map.bind(InputAction::MoveForward, InputSource::Key(Key::S)).unwrap_err().to_string(), "S is already bound to Move back");
+        assert_eq!(input_map.get_sources(InputAction::MoveForward), &[InputSource::Key(Key::W)]);
+        input_map.bind(InputAction::MoveForward, InputSource::Key(Key::K)).unwrap();
+        input_map.bind(InputAction::Jump, InputSource::Key(Key::W)).unwrap();
+        assert_eq!(input_map.reset(InputAction::MoveForward).unwrap_err().to_string(), format!("W is already bound to {}", InputAction::Jump.get_description()));
+        assert_eq!(input_map.get_sources(InputAction::MoveForward), &[InputSource::Key(Key::K)]);
+        input_map.reset(InputAction::Jump).unwrap();
+        input_map.reset(InputAction::MoveForward).unwrap();
+        assert_eq!(input_map.get_sources(InputAction::MoveForward), &[InputSource::Key(Key::W)]);
+        assert_eq!(input_map.get_conflicts(), Vec::new());
+    }
+
+    #[test]
+    fn get_conflicts_lists_inputs_of_several_actions() {
+        let mut input_map: InputMap = InputMap::new();
+        input_map.bindings[InputAction::Jump as usize] = vec![InputSource::Key(Key::Space), InputSource::Key(Key::W)];
+        assert_eq!(input_map.get_conflicts(), vec![(InputSource::Key(Key::W), InputAction::MoveForward, InputAction::Jump)]);
+    }
+
+    #[test]
+    fn load_rejects_conflicting_controls() {
+        let input_map: InputMap = load_test_controls("controls_valid", r#"{"jump": ["K"], "use_block": ["Space"]}"#).unwrap();
+        assert_eq!(input_map.get_sources(InputAction::Jump), &[InputSource::Key(Key::K)]);
+        assert_eq!(input_map.get_sources(InputAction::MoveForward), &[InputSource::Key(Key::W)]);
+        let error: Box<dyn std::error::Error> = load_test_controls("controls_conflict", r#"{"jump": ["W"]}"#).err().unwrap();
+        assert_eq!(error.to_string(), "W is already bound to Move forward");
+        let error: Box<dyn std::error::Error> = load_test_controls("controls_unknown", r#"{"fly": ["K"]}"#).err().unwrap();
+        assert_eq!(error.to_string(), "Input action fly does not exist");
+    }
+
+    #[test]
+    fn update_tracks_edge_states() {
+        let mut input_map: InputMap = InputMap::new();
+        let keyboard: Keyboard = Keyboard::new();
+        let mouse: Mouse = Mouse::new();
+        keyboard.key_callback(Key::W, 0, Action::Press, Modifiers::empty());
+        input_map.update(&keyboard, &mouse);
+        assert!(input_map.is_pressed(InputAction::MoveForward) && input_map.is_just_pressed(InputAction::MoveForward) && input_map.is_repeated(InputAction::MoveForward));
+        assert_eq!(input_map.get_just_pressed_source(), Some(InputSource::Key(Key::W)));
+        keyboard.poll_events();
+        input_map.update(&keyboard, &mouse);
+        assert!(input_map.is_pressed(InputAction::MoveForward));
+        assert!(!input_map.is_just_pressed(InputAction::MoveForward) && !input_map.is_repeated(InputAction::MoveForward));
+        assert_eq!(input_map.get_just_pressed_source(), None);
+        keyboard.key_callback(Key::W, 0, Action::Repeat, Modifiers::empty());
+        input_map.update(&keyboard, &mouse);
+        assert!(input_map.is_repeated(InputAction::MoveForward) && !input_map.is_just_pressed(InputAction::MoveForward));
+        keyboard.poll_events();
+        keyboard.key_callback(Key::W, 0, Action::Release, Modifiers::empty());
+        input_map.update(&keyboard, &mouse);
+        assert!(!input_map.is_pressed(InputAction::MoveForward) && input_map.is_just_released(InputAction::MoveForward));
+        input_map.update(&keyboard, &mouse);
+        assert!(!input_map.is_just_released(InputAction::MoveForward));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,67 @@
+use glfw::{Key, MouseButton};
+use crate::input::keyboard::Keyboard;
+use crate::input::mouse::Mouse;
+
+//Names of the keys are the glfw ones
+const KEYS: [Key; 100] = [
+    Key::Space, Key::Apostrophe, Key::Comma, Key::Minus, Key::Period, Key::Slash,
+    Key::Num0, Key::Num1, Key::Num2, Key::Num3, Key::Num4, Key::Num5, Key::Num6, Key::Num7, Key::Num8, Key::Num9,
+    Key::Semicolon, Key::Equal,
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+    Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    Key::LeftBracket, Key::Backslash, Key::RightBracket, Key::GraveAccent,
+    Key::Escape, Key::Enter, Key::Tab, Key::Backspace, Key::Insert, Key::Delete,
+    Key::Right, Key::Left, Key::Down, Key::Up, Key::PageUp, Key::PageDown, Key::Home, Key::End,
+    Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8, Key::F9, Key::F10, Key::F11, Key::F12,
+    Key::Kp0, Key::Kp1, Key::Kp2, Key::Kp3, Key::Kp4, Key::Kp5, Key::Kp6, Key::Kp7, Key::Kp8, Key::Kp9,
+    Key::KpDecimal, Key::KpDivide, Key::KpMultiply, Key::KpSubtract, Key::KpAdd, Key::KpEnter, Key::KpEqual,
+    Key::LeftShift, Key::LeftControl, Key::LeftAlt, Key::LeftSuper, Key::RightShift, Key::RightControl, Key::RightAlt, Key::RightSuper, Key::Menu,
+];
+
+const MOUSE_BUTTONS: [(MouseButton, &str); 8] = [
+    (MouseButton::Button1, "MouseLeft"),
+    (MouseButton::Button2, "MouseRight"),
+    (MouseButton::Button3, "MouseMiddle"),
+    (MouseButton::Button4, "Mouse4"),
+    (MouseButton::Button5, "Mouse5"),
+    (MouseButton::Button6, "Mouse6"),
+    (MouseButton::Button7, "Mouse7"),
+    (MouseButton::Button8, "Mouse8"),
+];
+
+//Physical input an action can be bound to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputSource {
+    Key(Key),
+    MouseButton(MouseButton),
+}
+
+impl InputSource {
+    //Every bindable input, keys first
+    pub fn get_all() -> Vec<Self> {
+        let mut sources: Vec<Self> = KEYS.iter().map(|key| Self::Key(*key)).collect();
+        sources.extend(MOUSE_BUTTONS.iter().map(|(button, _)| Self::MouseButton(*button)));
+        return sources;
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some((button, _)) = MOUSE_BUTTONS.iter().find(|(_, button_name)| *button_name == name) {
+            return Some(Self::MouseButton(*button));
+        }
+        return KEYS.iter().find(|key| format!("{:?}", key) == name).map(|key| Self::Key(*key));
+    }
+
+    pub fn get_name(&self) -> String {
+        return match self {
+            Self::Key(key) => format!("{:?}", key),
+            Self::MouseButton(button) => MOUSE_BUTTONS.iter().find(|(mouse_button, _)| mouse_button == button).map_or(format!("{:?}", button), |(_, name)| String::from(*name)),
+        }
+    }
+
+    pub fn is_down(&self, keyboard: &Keyboard, mouse: &Mouse) -> bool {
+        return match self {
+            Self::Key(key) => keyboard.get_key_state(*key),
+            Self::MouseButton(button) => mouse.get_button_state(*button),
+        }
+    }
+}
\ No newline at end of file
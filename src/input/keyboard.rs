@@ -1,8 +1,10 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use glfw::{Action, Key, Modifiers, Scancode};
 
 pub struct Keyboard {
     keys: Vec<Cell<bool>>,
+    pressed_keys: RefCell<Vec<Key>>, //presses and repeats since the last poll, in order
+    chars: RefCell<Vec<char>>, //typed text since the last poll
 }
 
 impl Keyboard {
@@ -13,7 +15,7 @@ impl Keyboard {
             keys.push(Cell::from(false));
         }
 
-        Keyboard { keys }
+        Keyboard { keys, pressed_keys: RefCell::new(Vec::new()), chars: RefCell::new(Vec::new()) }
     }
 
     pub fn key_callback(&self, key: Key, scancode: Scancode, action: Action, mode: Modifiers) {
@@ -22,6 +24,9 @@ impl Keyboard {
                 state.set(true);
             }
         }
+        if action == Action::Press || action == Action::Repeat {
+            self.pressed_keys.borrow_mut().push(key);
+        }
         if action == Action::Release {
             if let Some(state) = self.keys.get(key as usize) {
                 state.set(false);
@@ -36,4 +41,21 @@ impl Keyboard {
             false
         }
     }
+
+    pub fn char_callback(&self, char: char) {
+        self.chars.borrow_mut().push(char);
+    }
+
+    pub fn get_pressed_keys(&self) -> Vec<Key> {
+        return self.pressed_keys.borrow().clone();
+    }
+
+    pub fn get_chars(&self) -> Vec<char> {
+        return self.chars.borrow().clone();
+    }
+
+    pub fn poll_events(&self) {
+        self.pressed_keys.borrow_mut().clear();
+        self.chars.borrow_mut().clear();
+    }
 }
\ No newline at end of file
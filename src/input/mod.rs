@@ -1,2 +1,5 @@
+pub mod input_action;
+pub mod input_map;
+pub mod input_source;
 pub mod keyboard;
 pub mod mouse;
\ No newline at end of file
@@ -6,6 +6,7 @@ use crate::window::Window;
 pub struct Mouse {
     pos: Vec2d,
     delta_pos: Vec2d,
+    scroll_delta: Vec2d,
     cursor_state: bool,
     is_started: bool,
     buttons: Vec<Cell<bool>>,
@@ -21,6 +22,7 @@ impl Mouse {
         return Self {
             pos: [0.0f64, 0.0f64],
             delta_pos: [0.0f64, 0.0f64],
+            scroll_delta: [0.0f64, 0.0f64],
             cursor_state: true,
             is_started: false,
             buttons,
@@ -33,6 +35,10 @@ impl Mouse {
         self.pos = [xpos, ypos];
     }
 
+    pub fn scroll_callback(&mut self, xoffset: f64, yoffset: f64) {
+        self.scroll_delta = [(self.scroll_delta[0] + xoffset), (self.scroll_delta[1] + yoffset)];
+    }
+
     pub fn button_callback(&self, button: MouseButton, action: Action, modifiers: Modifiers) {
         if action == Action::Press {
             if let Some(state) = self.buttons.get(button as usize) {
@@ -75,11 +81,16 @@ impl Mouse {
         return &self.delta_pos;
     }
 
+    pub fn get_scroll_delta(&self) -> &Vec2d {
+        return &self.scroll_delta;
+    }
+
     pub fn toggle_cursor(&mut self, window: &Window) {
         self.set_cursor_state(window, (!self.cursor_state));
     }
 
     pub fn poll_events(&mut self) {
         self.delta_pos = [0.0, 0.0];
+        self.scroll_delta = [0.0, 0.0];
     }
 }
\ No newline at end of file
@@ -1,6 +1,19 @@
 pub mod chunk;
+pub mod command;
 pub mod window;
 pub mod world;
 pub mod render;
 pub mod input;
-pub mod physics;
\ No newline at end of file
+pub mod physics;
+pub mod entity;
+pub mod behavior;
+pub mod scripting;
+pub mod gui;
+pub mod player;
+pub mod files_watcher;
+pub mod packs;
+pub mod tick_timer;
+pub mod tick_scheduler;
+pub mod debug_stats;
+pub mod settings;
+pub mod state;
\ No newline at end of file
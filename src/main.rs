@@ -2,533 +2,1043 @@ use std::path::Path;
 use std::ptr;
 use std::rc::Rc;
 use glfw::ffi::KEY_ESCAPE;
-use glfw::{MouseButtonLeft, MouseButtonRight};
+use glfw::MouseButtonLeft;
 use rand::distributions::uniform::SampleBorrow;
 use rand::Rng;
+use cubecode_a000::behavior::block_behavior::BlockBehaviors;
+use cubecode_a000::behavior::core_behaviors::register_core_behaviors;
 use cubecode_a000::chunk::{Chunk, LayerChunkGenerator, SubChunk};
+use cubecode_a000::command::command_args::{COMMAND_PREFIX, split_command};
+use cubecode_a000::command::command_registry::{CommandContext, CommandRegistry};
+use cubecode_a000::command::console::Console;
+use cubecode_a000::command::core_commands::register_core_commands;
+use cubecode_a000::debug_stats::{DebugStats, TargetStats};
+use cubecode_a000::gui::layouts_loader::GuiLayouts;
+use cubecode_a000::gui::widget::{WidgetId, WidgetKind};
+use cubecode_a000::gui::widget_tree::{GuiEvent, GuiInput, WidgetTree};
+use cubecode_a000::input::input_action::{HOTBAR_ACTIONS, INPUT_ACTIONS, InputAction};
+use cubecode_a000::input::input_map::InputMap;
+use cubecode_a000::input::input_source::InputSource;
 use cubecode_a000::input::keyboard::Keyboard;
 use cubecode_a000::input::mouse::Mouse;
+use cubecode_a000::physics::collider::WorldCollider;
 use cubecode_a000::physics::hitbox::HitBox;
 use cubecode_a000::physics::physics_solver::PhysicsSolver;
-use cubecode_a000::render::blocks_loader::{AIR_BLOCK_ID, BEDROCK_BLOCK_ID, BlocksLoader, DIRT_BLOCK_ID, GRASS_BLOCK_ID, UNKNOWN_BLOCK_ID};
+use cubecode_a000::physics::raycast::RayFilter;
+use cubecode_a000::player::inventory::{HOTBAR_SIZE, Inventory, ItemStack};
+use cubecode_a000::player::player_controller::{PlayerController, PlayerInput};
+use cubecode_a000::player::player_data::PlayerData;
+use cubecode_a000::render::blocks_loader::{AIR_BLOCK_NAME, Block, BlocksLoader, BlockUsingError, UNKNOWN_BLOCK_NAME};
+use cubecode_a000::render::assets_reloader::AssetsReloader;
 use cubecode_a000::render::buffer::Buffer;
 use cubecode_a000::render::camera::Camera;
-use cubecode_a000::render::faces_loader::FacesLoader;
+use cubecode_a000::render::font::FontsLoader;
+use cubecode_a000::render::icons_baker::BlockIcons;
 use cubecode_a000::render::gui_renderer::{GuiRenderer, GuiRenderer2f};
 use cubecode_a000::render::light::light_map::{B_CHANNEL, G_CHANNEL, LightMap, R_CHANNEL, S_CHANNEL};
-use cubecode_a000::render::light::light_solver::LightSolver;
+use cubecode_a000::render::light::light_solvers::LightSolvers;
 use cubecode_a000::render::lines_renderer::LinesRenderer;
-use cubecode_a000::render::meshes_loader::MeshesLoader;
-use cubecode_a000::render::shader::Shader;
 use cubecode_a000::render::shader_program::ShaderProgram;
-use cubecode_a000::render::types::{Mat4f, Vec3f, Vec3ub, LightedTexVertex, Vec3s, Vec3b, add_vec3f, sub_vec3f, norm_vec3f, Vec2d};
+use cubecode_a000::render::shader_registry::ShaderRegistry;
+use cubecode_a000::render::types::{Mat4f, Vec3f, Vec3i, Vec3ub, LightedTexVertex, add_vec3f, sub_vec3f, norm_vec3f, Vec2d};
 use cubecode_a000::render::vertex_array::VertexArray;
+use cubecode_a000::render::widget_renderer::WidgetRenderer;
+use cubecode_a000::scripting::script_engine::ScriptEngine;
+use cubecode_a000::settings::Settings;
+use cubecode_a000::state::game_state::{GameState, StateStack};
+use cubecode_a000::state::world_loading::WorldLoading;
+use cubecode_a000::state::worlds_list::WorldsList;
 use cubecode_a000::set_attribute;
+use cubecode_a000::tick_timer::TickTimer;
 use cubecode_a000::window::Window;
 use cubecode_a000::world::World;
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
 const TITLE: &str = "CubeCode >_";
-const BLOCKS_ATLAS_PATH: &str = "assets/blocks_atlas.png";
-const FACES_PATH: &str = "assets/faces.json";
-const MESHES_PATH: &str = "assets/meshes.json";
-const BLOCKS_PATH: &str = "assets/blocks.json";
+const PACKS_LIST_PATH: &str = "packs.json";
+const SETTINGS_PATH: &str = "settings.json";
+const CONTROLS_PATH: &str = "controls.json";
+const WORLDS_PATH: &str = "worlds";
+const SHADERS_PATH: &str = "assets/shaders";
+const BLOCKS_SHADER_PROGRAM: &str = "blocks";
+const RELOAD_PERIOD: f32 = 1.0;
+const TICK_RATE: f32 = 60.0;
+const PHYSICS_TIME_SCALE: f32 = 16.0; //physics units per second
+const TEXT_SCALE: f32 = 0.004; //GUI units per font pixel
+const HUD_LAYOUT: &str = "core:hud";
+const INVENTORY_LAYOUT: &str = "core:inventory";
+const CONSOLE_LAYOUT: &str = "core:console";
+const MAIN_MENU_LAYOUT: &str = "core:main_menu";
+const WORLD_LIST_LAYOUT: &str = "core:world_list";
+const LOADING_LAYOUT: &str = "core:loading";
+const PAUSE_MENU_LAYOUT: &str = "core:pause_menu";
+const SETTINGS_LAYOUT: &str = "core:settings";
+const CONTROLS_LAYOUT: &str = "core:controls";
+const LAYOUTS: [&str; 9] = [HUD_LAYOUT, INVENTORY_LAYOUT, CONSOLE_LAYOUT, MAIN_MENU_LAYOUT, WORLD_LIST_LAYOUT, LOADING_LAYOUT, PAUSE_MENU_LAYOUT, SETTINGS_LAYOUT, CONTROLS_LAYOUT];
+const WORLD_LIST_NAME: &str = "world_list";
+const WORLD_NAME_FIELD_NAME: &str = "world_name_field";
+const SETTINGS_DONE_BUTTON_NAME: &str = "settings_done_button";
+const FOV_SLIDER_NAME: &str = "fov_slider";
+const SENSITIVITY_SLIDER_NAME: &str = "sensitivity_slider";
+const CONTROLS_LIST_NAME: &str = "controls_list";
+const CONTROLS_DONE_BUTTON_NAME: &str = "controls_done_button";
+const CONSOLE_INPUT_NAME: &str = "console_input";
+const CONSOLE_OUTPUT_SOURCE: &str = "console.output";
+const CONSOLE_LINES: usize = 9;
+const BLOCK_LIST_NAME: &str = "block_list";
+const BLOCK_NAME_SOURCE: &str = "block.name";
+const BLOCKS_NAMES_SOURCE: &str = "blocks.names";
+const DEBUG_LINES_SOURCE: &str = "debug.lines";
+const WORLDS_NAMES_SOURCE: &str = "worlds.names";
+const WORLDS_STATUS_SOURCE: &str = "worlds.status";
+const LOADING_TEXT_SOURCE: &str = "loading.text";
+const FOV_SOURCE: &str = "settings.fov";
+const MOUSE_SENSITIVITY_SOURCE: &str = "settings.mouse_sensitivity";
+const CONTROLS_BINDINGS_SOURCE: &str = "controls.bindings";
+const CONTROLS_STATUS_SOURCE: &str = "controls.status";
+const SPAWN_POS: Vec3f = [1.0, 7.0, 1.0];
+const PLAYER_HALF_SIZE: Vec3f = [0.25, 0.875, 0.25];
 
-fn get_blocks_loader(shader_program: Rc<ShaderProgram>) -> Result<BlocksLoader, Box<dyn std::error::Error>> {
-    Ok(BlocksLoader::load(Path::new(BLOCKS_PATH), MeshesLoader::load(Path::new(MESHES_PATH), FacesLoader::load(Path::new(BLOCKS_ATLAS_PATH), Path::new(FACES_PATH), shader_program)?)?)?)
-}
-
-fn get_shader_program(vertex_src: &str, fragment_src: &str) -> Result<ShaderProgram, Box<dyn std::error::Error>> {
+fn get_blocks_shader_program(shader_registry: &ShaderRegistry) -> Result<Rc<ShaderProgram>, Box<dyn std::error::Error>> {
+    let shader_program: Rc<ShaderProgram> = shader_registry.load(BLOCKS_SHADER_PROGRAM, &[("ALPHA_TEST", "1")])?;
     unsafe {
-        let vert_shader = Shader::new(vertex_src, gl::VERTEX_SHADER)?;
-        let frag_shader = Shader::new(fragment_src, gl::FRAGMENT_SHADER)?;
-        let shader_program = ShaderProgram::new(&[vert_shader, frag_shader])?;
         shader_program.set_uniform_i32("atlas", 0)?;
-        return Ok(shader_program);
     }
+    return Ok(shader_program);
 }
 
-pub const VERTEX_SHADER_SOURCE: &str = r#"
-#version 330
+//Lids change between loads, so blocks used by the game are resolved by name
+struct GameBlocks {
+    air: u16,
+    unknown: u16,
+    dirt: u16,
+    grass: u16,
+    bedrock: u16,
+    dirt_slab: u16,
+    dirt_stairs: u16,
+    water: u16,
+    lava: u16,
+}
 
-in vec3 pos;
-in vec2 tex;
-in vec4 light;
+impl GameBlocks {
+    fn resolve(blocks_loader: &BlocksLoader) -> Result<Self, BlockUsingError> {
+        return Ok(Self {
+            air: blocks_loader.get_lid(AIR_BLOCK_NAME)?,
+            unknown: blocks_loader.get_lid(UNKNOWN_BLOCK_NAME)?,
+            dirt: blocks_loader.get_lid("core:dirt")?,
+            grass: blocks_loader.get_lid("core:grass")?,
+            bedrock: blocks_loader.get_lid("core:bedrock")?,
+            dirt_slab: blocks_loader.get_lid("core:dirt_slab")?,
+            dirt_stairs: blocks_loader.get_lid("core:dirt_stairs")?,
+            water: blocks_loader.get_lid("core:water")?,
+            lava: blocks_loader.get_lid("core:lava")?,
+        });
+    }
+}
 
-out vec4 col;
-out vec2 outTexCoord;
+//Names in lid order, so the index of a selected item is the lid
+fn get_blocks_names(blocks_loader: &BlocksLoader) -> String {
+    return blocks_loader.loaded_blocks.iter().map(|block| block.name.as_str()).collect::<Vec<&str>>().join("\n");
+}
 
-uniform mat4 viewMat;
-uniform mat4 modelMat;
+//Layouts are rebuilt from scratch, values of data sources are kept by the tree
+fn build_layouts(gui_layouts: &GuiLayouts, widget_tree: &mut WidgetTree) {
+    widget_tree.clear();
+    widget_tree.set_icons(gui_layouts.icons.clone());
+    for layout_name in LAYOUTS {
+        if gui_layouts.build(layout_name, widget_tree).is_none() {
+            println!("GUI layout {} not found", layout_name);
+        }
+    }
+}
 
-void main() {
-    gl_Position = viewMat * modelMat * vec4(pos, 1.0);
-    col = vec4(light.r, light.g, light.b, 1.0f);
-    col.rgb += light.a;
-    outTexCoord = tex;
+//Hotbar of a new player
+fn get_default_inventory(blocks_loader: &BlocksLoader, game_blocks: &GameBlocks) -> Inventory {
+    let mut inventory: Inventory = Inventory::new();
+    let hotbar_lids: [u16; 8] = [game_blocks.unknown, game_blocks.dirt, game_blocks.grass, game_blocks.bedrock, game_blocks.dirt_slab, game_blocks.dirt_stairs, game_blocks.water, game_blocks.lava];
+    for (slot_ind, lid) in hotbar_lids.iter().enumerate() {
+        inventory.set_slot(slot_ind, Some(ItemStack::new(&blocks_loader.get_block(*lid).name, 1)));
+    }
+    return inventory;
 }
-"#;
 
-pub const FRAGMENT_SHADER_SOURCE: &str = r#"
-#version 330
-out vec4 FragColor;
-in vec2 outTexCoord;
-in vec4 col;
+//Hotbar slots show icons of their blocks, the selected one has a frame
+fn set_hotbar_sources(widget_tree: &mut WidgetTree, inventory: &Inventory) {
+    for slot_ind in 0..HOTBAR_SIZE {
+        widget_tree.set_source(&format!("hotbar.{}", slot_ind), inventory.get_slot(slot_ind).map_or("", |stack| stack.name.as_str()));
+        widget_tree.set_source(&format!("hotbar.{}.selected", slot_ind), if slot_ind == inventory.get_selected_ind() { "1" } else { "" });
+    }
+}
+
+//Layouts are built visible, screens are shown by the game state
+fn set_layout_visible(widget_tree: &mut WidgetTree, layout_name: &str, visible: bool) {
+    if let Some(layout) = widget_tree.find(layout_name) {
+        if widget_tree.get(layout).map_or(false, |widget| widget.visible != visible) {
+            if let Some(widget) = widget_tree.get_mut(layout) {
+                widget.visible = visible;
+            }
+        }
+    }
+}
 
-uniform sampler2D atlas;
+//Only the layout of the top state gets input, the HUD stays under the pause menu
+fn set_state_layouts(widget_tree: &mut WidgetTree, state_stack: &StateStack, inventory_open: bool, console_open: bool) {
+    let top: GameState = state_stack.get_top();
+    set_layout_visible(widget_tree, HUD_LAYOUT, state_stack.contains(GameState::Playing));
+    set_layout_visible(widget_tree, INVENTORY_LAYOUT, top == GameState::Playing && inventory_open);
+    set_layout_visible(widget_tree, CONSOLE_LAYOUT, top == GameState::Playing && console_open);
+    set_layout_visible(widget_tree, MAIN_MENU_LAYOUT, top == GameState::MainMenu);
+    set_layout_visible(widget_tree, WORLD_LIST_LAYOUT, top == GameState::WorldList);
+    set_layout_visible(widget_tree, LOADING_LAYOUT, top == GameState::Loading);
+    set_layout_visible(widget_tree, PAUSE_MENU_LAYOUT, top == GameState::Paused);
+    set_layout_visible(widget_tree, SETTINGS_LAYOUT, top == GameState::Settings);
+    set_layout_visible(widget_tree, CONTROLS_LAYOUT, top == GameState::Controls);
+}
 
-void main() {
-    vec4 tex_col = texture(atlas, outTexCoord);
-    if (tex_col.a == 0.0) {
-        discard;
+//One line per action in the order of INPUT_ACTIONS
+fn get_bindings_lines(input_map: &InputMap) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for (action, _, description) in &INPUT_ACTIONS {
+        let names: Vec<String> = input_map.get_sources(*action).iter().map(|source| source.get_name()).collect();
+        lines.push(format!("{}: {}", description, if names.is_empty() { String::from("none") } else { names.join(", ") }));
     }
-    FragColor = col * tex_col;
+    return lines.join("\n");
 }
-"#;
 
-const NEIGHBORHOOD: [Vec3b; 6] = [[0, 0, -1], [0, 0, 1], [0, -1, 0], [0, 1, 0], [-1, 0, 0], [1, 0, 0]];
+fn set_text_field_text(widget_tree: &mut WidgetTree, name: &str, text: &str) {
+    if let Some(id) = widget_tree.find(name) {
+        if let Some(widget) = widget_tree.get_mut(id) {
+            widget.set_text(text);
+        }
+    }
+}
 
+fn get_text_field_text(widget_tree: &WidgetTree, id: WidgetId) -> String {
+    return match widget_tree.get(id).map(|widget| &widget.kind) {
+        Some(WidgetKind::TextField { text, .. }) => text.clone(),
+        _ => String::new(),
+    }
+}
+
+//Registered commands go first, other names are tried as script commands
+fn run_console_line(console: &mut Console, command_registry: &CommandRegistry, script_engine: &ScriptEngine, context: &mut CommandContext, line: &str) {
+    console.submit(line);
+    match split_command(line) {
+        Ok(Some((name, args))) => {
+            if command_registry.get(&name).is_some() {
+                match command_registry.run(context, &name, &args) {
+                    Ok(output) => console.print(&output),
+                    Err(error) => console.print(&format!("§c{}", error)),
+                }
+            } else {
+                match script_engine.run_command(context.world, context.blocks_loader, &name, &args) {
+                    Ok((output, changed)) => {
+                        for pos in &changed {
+                            context.light_solvers.on_block_edited(context.world, context.blocks_loader, pos);
+                        }
+                        console.print(&output);
+                    }
+                    Err(error) => console.print(&format!("§c{}", error)),
+                }
+            }
+        }
+        Ok(None) => {}
+        Err(error) => console.print(&format!("§c{}", error)),
+    }
+}
+
+//Such blocks keep working without their hooks
+fn print_unknown_behaviors(behaviors: &BlockBehaviors, blocks_loader: &BlocksLoader) {
+    for (block_name, behavior_name) in behaviors.get_unknown(blocks_loader) {
+        println!("Block {} uses unknown behavior {}", block_name, behavior_name);
+    }
+}
 
-//TODO NEXT TIMING
 fn main() {
     let keyboard: Keyboard = Keyboard::new();
     let mouse: Mouse = Mouse::new();
     if let Ok(mut window) = Window::init(SCR_WIDTH, SCR_HEIGHT, TITLE, keyboard, mouse) {
-        if let Ok(shader_program_data) = get_shader_program(VERTEX_SHADER_SOURCE, FRAGMENT_SHADER_SOURCE) {
-            let shader_program: Rc<ShaderProgram> = Rc::new(shader_program_data);
-            if let Ok(blocks_loader) = get_blocks_loader(shader_program.clone()) {
-                let world_chunk_generator: LayerChunkGenerator = LayerChunkGenerator::from_bottom_layers(&[BEDROCK_BLOCK_ID, DIRT_BLOCK_ID, DIRT_BLOCK_ID, DIRT_BLOCK_ID, GRASS_BLOCK_ID]);
-                if let Ok(world) = World::new(&world_chunk_generator, &blocks_loader) {
-                    if let Ok(gui_renderer) = GuiRenderer2f::init_gui() {
-                        if let Ok(lines_renderer) = LinesRenderer::init_lines_renderer() {
-                            if let Some(bedrock) = blocks_loader.blocks_ids.get(&GRASS_BLOCK_ID) {
-                                if let Some(unknown) = blocks_loader.blocks_ids.get(&UNKNOWN_BLOCK_ID) {
-                                    world.load(&blocks_loader, String::from("world/world.data")).unwrap();
-                                    world.set_block(&[0, 5, 10], unknown.lid);
-                                    world.set_block(&[0, 6, 10], unknown.lid);
-                                    world.set_block(&[0, 5, 9], unknown.lid);
-                                } else {
-                                    return;
-                                }
-                            } else {
+        let shader_registry: ShaderRegistry = ShaderRegistry::new(Path::new(SHADERS_PATH));
+        if let Ok(shader_program) = get_blocks_shader_program(&shader_registry) {
+            let assets_reloader: AssetsReloader = AssetsReloader::new(Path::new(PACKS_LIST_PATH));
+            let blocks_loader_res = assets_reloader.load(shader_program.clone());
+            if let Err(error) = &blocks_loader_res {
+                println!("{}", error);
+            }
+            if let Ok(mut blocks_loader) = blocks_loader_res {
+                let mut game_blocks: GameBlocks = match GameBlocks::resolve(&blocks_loader) {
+                    Ok(game_blocks) => game_blocks,
+                    Err(error) => {
+                        println!("Failed to resolve game blocks: {}", error);
+                        return;
+                    }
+                };
+                let script_engine: ScriptEngine = match assets_reloader.open_packs().and_then(|packs| ScriptEngine::load(&packs)) {
+                    Ok(script_engine) => script_engine,
+                    Err(error) => {
+                        println!("Failed to load scripts: {}", error);
+                        return;
+                    }
+                };
+                let fonts_loader: FontsLoader = match assets_reloader.open_packs().and_then(|packs| FontsLoader::load(&packs)) {
+                    Ok(fonts_loader) => fonts_loader,
+                    Err(error) => {
+                        println!("Failed to load fonts: {}", error);
+                        return;
+                    }
+                };
+                let mut block_icons: BlockIcons = BlockIcons::load(&blocks_loader);
+                let mut gui_layouts: GuiLayouts = match assets_reloader.load_gui_layouts(&block_icons) {
+                    Ok(gui_layouts) => gui_layouts,
+                    Err(error) => {
+                        println!("Failed to load GUI layouts: {}", error);
+                        return;
+                    }
+                };
+                if let Ok(gui_renderer) = GuiRenderer2f::init_gui(&shader_registry) {
+                    if let Ok(lines_renderer) = LinesRenderer::init_lines_renderer(&shader_registry) {
+                        let mut settings: Settings = match Settings::load(Path::new(SETTINGS_PATH)) {
+                            Ok(settings) => settings,
+                            Err(error) => {
+                                println!("Settings not loaded, using the defaults: {}", error);
+                                Settings::new()
+                            }
+                        };
+                        let mut input_map: InputMap = match InputMap::load(Path::new(CONTROLS_PATH)) {
+                            Ok(input_map) => input_map,
+                            Err(error) => {
+                                println!("Controls not loaded, using the defaults: {}", error);
+                                InputMap::new()
+                            }
+                        };
+                        let mut selected_action: Option<InputAction> = None;
+                        let mut rebinding_action: Option<InputAction> = None;
+                        let worlds_list: WorldsList = WorldsList::new(Path::new(WORLDS_PATH));
+                        let mut world: Option<World> = None;
+                        let mut world_loading: Option<WorldLoading> = None;
+                        let mut world_name: String = String::new();
+                        let mut world_path: String = String::new();
+                        let mut player_data_path: String = String::new();
+                        let mut selected_world: Option<String> = None;
+                        let mut delete_confirmed: bool = false;
+                        let mut state_stack: StateStack = StateStack::new(GameState::MainMenu);
+                        let mut camera: Camera = Camera::new();
+                        let mut fov: f32 = settings.fov.to_radians();
+                        let z_near: f32 = 0.01;
+                        let z_far: f32 = 1024.0;
+                        let mut asp_rat: f32 = (800.0 / 600.0);
+                        let mut view_mat = Mat4f::new();
+                        let mut proj_mat = Mat4f::new();
+                        let mut iend_last: Vec3ub = [0, 0, 0];
+                        proj_mat.identity().perspective(fov, asp_rat, z_near, z_far);
+                        gui_renderer.set_asp_rat(asp_rat);
+                        lines_renderer.set_lines_width(1.0f32);
+                        let mut widget_renderer: WidgetRenderer = match WidgetRenderer::init_widget_renderer(&shader_registry, fonts_loader.default_font.clone(), &gui_layouts.atlas, TEXT_SCALE) {
+                            Ok(widget_renderer) => widget_renderer,
+                            Err(error) => {
+                                println!("Failed to initialize widget renderer: {}", error);
                                 return;
                             }
-                            let mut camera: Camera = Camera::new();
-                            let fov: f32 = (60.0f32).to_radians();
-                            let z_near: f32 = 0.01;
-                            let z_far: f32 = 1024.0;
-                            let mut asp_rat: f32 = (800.0 / 600.0);
-                            let mut view_mat = Mat4f::new();
-                            let mut proj_mat = Mat4f::new();
-                            let mut cur_lid: u16 = UNKNOWN_BLOCK_ID;
-                            let mut iend_last: Vec3ub = [0, 0, 0];
-                            proj_mat.identity().perspective(fov, asp_rat, z_near, z_far);
-                            gui_renderer.set_asp_rat(asp_rat);
-                            lines_renderer.set_lines_width(1.0f32);
-                            camera.set_position([1.0_f32, 7.0_f32, 1.0_f32]);
-                            let mut hitbox: HitBox = HitBox::new([1.0_f32, 7.0_f32, 1.0_f32], [0.25, 0.875, 0.25]);
-                            let physics_solver: PhysicsSolver = PhysicsSolver::new([0.0, -9.8 / 48.0, 0.0]);
-                            //rewrite to other init_light_solvers function
-                            let solver_r = LightSolver::new(R_CHANNEL);
-                            let solver_g = LightSolver::new(G_CHANNEL);
-                            let solver_b = LightSolver::new(B_CHANNEL);
-                            let solver_s = LightSolver::new(S_CHANNEL);
-
-                            for x_pos in 0x00_u8..=0xFF_u8 {
-                                for y_pos in 0x00_u8..=0xFF_u8 {
-                                    for z_pos in 0x00_u8..=0xFF_u8 {
-                                        let pos: Vec3ub = [x_pos, y_pos, z_pos];
-                                        let block_lid = world.get_block(&pos);
-                                        let mut light_r: u8 = 0;
-                                        let mut light_g: u8 = 0;
-                                        let mut light_b: u8 = 0;
-                                        if let Some(block) = blocks_loader.loaded_blocks.get(block_lid as usize) {
-                                            light_r = block.light_r;
-                                            light_g = block.light_g;
-                                            light_b = block.light_b;
-                                        } else {
-                                            world.set_block(&pos, UNKNOWN_BLOCK_ID);
-                                            if let Some(block) = blocks_loader.loaded_blocks.get(UNKNOWN_BLOCK_ID as usize) {
-                                                light_r = block.light_r;
-                                                light_g = block.light_g;
-                                                light_b = block.light_b;
-                                            } else {
-                                                println!("FATAL ERROR 0");
-                                                return;
+                        };
+                        widget_renderer.set_asp_rat(asp_rat);
+                        let mut widget_tree: WidgetTree = WidgetTree::new(asp_rat);
+                        widget_tree.set_source(BLOCKS_NAMES_SOURCE, &get_blocks_names(&blocks_loader));
+                        widget_tree.set_source(WORLDS_NAMES_SOURCE, &worlds_list.get_names().join("\n"));
+                        widget_tree.set_source(CONTROLS_BINDINGS_SOURCE, &get_bindings_lines(&input_map));
+                        build_layouts(&gui_layouts, &mut widget_tree);
+                        let mut debug_stats: DebugStats = DebugStats::new();
+                        let mut debug_visible: bool = false;
+                        //Player state is replaced when a world is loaded
+                        let mut hitbox: HitBox = HitBox::new(SPAWN_POS, PLAYER_HALF_SIZE);
+                        let mut player_controller: PlayerController = PlayerController::new();
+                        let mut inventory: Inventory = Inventory::new();
+                        let mut inventory_open: bool = false;
+                        let mut command_registry: CommandRegistry = CommandRegistry::new();
+                        if let Err(error) = register_core_commands(&mut command_registry) {
+                            println!("Failed to register commands: {}", error);
+                            return;
+                        }
+                        let mut console: Console = Console::new();
+                        let mut console_open: bool = false;
+                        let physics_solver: PhysicsSolver = PhysicsSolver::new([0.0, -9.8 / 48.0, 0.0]);
+                        let light_solvers: LightSolvers = LightSolvers::new();
+                        let mut behaviors: BlockBehaviors = BlockBehaviors::new();
+                        if let Err(error) = register_core_behaviors(&mut behaviors) {
+                            println!("Failed to register block behaviors: {}", error);
+                            return;
+                        }
+                        if let Err(error) = script_engine.register_behaviors(&mut behaviors) {
+                            println!("Failed to register script behaviors: {}", error);
+                            return;
+                        }
+                        print_unknown_behaviors(&behaviors, &blocks_loader);
+
+                        unsafe {
+                            gl::Enable(gl::BLEND);
+                            gl::Enable(gl::DEPTH_TEST);
+                            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+                            //TODO with gl::Enable(gl::CULL_FACE);
+                        }
+                        let mut last_top: GameState = state_stack.get_top();
+                        window.mouse.borrow_mut().set_cursor_state(&window, true);
+                        let mut tick_timer: TickTimer = TickTimer::new(TICK_RATE);
+                        let mut last_time: f32 = window.get_time() as f32;
+                        let mut last_reload_time: f32 = last_time;
+                        while !window.should_close() {
+                            let cur_time: f32 = window.get_time() as f32;
+                            let frame_time: f32 = cur_time - last_time;
+                            //Paused worlds keep their tick progress
+                            let ticks_cnt: u32 = tick_timer.advance(if state_stack.is_world_paused() { 0.0 } else { frame_time });
+                            last_time = cur_time;
+                            debug_stats.add_frame(frame_time);
+                            if (cur_time - last_reload_time) >= RELOAD_PERIOD {
+                                last_reload_time = cur_time;
+                                let mut reloaded: Vec<String> = Vec::new();
+                                if let Err(errors) = shader_registry.reload_changed(&mut reloaded) {
+                                    for error in errors {
+                                        println!("Failed to reload shader program: {}", error);
+                                    }
+                                }
+                                if reloaded.len() != 0 {
+                                    //Attribute locations are baked into vertex arrays
+                                    if let Some(world) = &world {
+                                        world.set_changed();
+                                    }
+                                    gui_renderer.is_changed.set(true);
+                                    widget_renderer.is_changed.set(true);
+                                    widget_renderer.text_renderer.is_changed.set(true);
+                                    lines_renderer.is_changed.set(true);
+                                    println!("Shader programs reloaded: {:?}", reloaded);
+                                }
+                                let mut gui_changed: bool = assets_reloader.is_gui_changed();
+                                //Worlds being loaded keep the old lids, so blocks are reloaded after the loading
+                                if world_loading.is_none() && assets_reloader.is_changed() {
+                                    match assets_reloader.reload(world.as_ref(), &blocks_loader) {
+                                        Ok(new_blocks_loader) => {
+                                            match GameBlocks::resolve(&new_blocks_loader) {
+                                                Ok(new_game_blocks) => {
+                                                    if let Some(world) = &world {
+                                                        AssetsReloader::apply(world, &light_solvers, &blocks_loader, &new_blocks_loader);
+                                                    }
+                                                    game_blocks = new_game_blocks;
+                                                    blocks_loader = new_blocks_loader;
+                                                    print_unknown_behaviors(&behaviors, &blocks_loader);
+                                                    widget_tree.set_source(BLOCKS_NAMES_SOURCE, &get_blocks_names(&blocks_loader));
+                                                    block_icons = BlockIcons::load(&blocks_loader);
+                                                    gui_changed = true;
+                                                    println!("Blocks data reloaded");
+                                                }
+                                                Err(error) => {
+                                                    println!("Failed to reload blocks data: {}", error);
+                                                }
                                             }
                                         }
-                                        if light_r != 0 { solver_r.add(&world, &pos, light_r); }
-                                        if light_g != 0 { solver_g.add(&world, &pos, light_g); }
-                                        if light_b != 0 { solver_b.add(&world, &pos, light_b); }
+                                        Err(error) => {
+                                            println!("Failed to reload blocks data: {}", error);
+                                        }
                                     }
                                 }
-                            }
-
-                            for x_pos in 0x00_u8..=0xFF_u8 {
-                                for z_pos in 0x00_u8..=0xFF_u8 {
-                                    for y_pos in (0x00_u8..=0xFF_u8).rev() {
-                                        let pos: Vec3ub = [x_pos, y_pos, z_pos];
-                                        let block_lid: u16 = world.get_block(&pos);
-                                        if blocks_loader.get_block(block_lid).mesh.is_cube() {
-                                            break;
+                                if gui_changed {
+                                    match assets_reloader.load_gui_layouts(&block_icons) {
+                                        Ok(new_gui_layouts) => {
+                                            widget_renderer.set_atlas(&new_gui_layouts.atlas);
+                                            gui_layouts = new_gui_layouts;
+                                            build_layouts(&gui_layouts, &mut widget_tree);
+                                            println!("GUI layouts reloaded");
+                                        }
+                                        Err(error) => {
+                                            println!("Failed to reload GUI layouts: {}", error);
                                         }
-                                        world.set_light_level(&pos, S_CHANNEL, 0x0F);
                                     }
                                 }
                             }
-
-                            for x_pos in 0x00_u8..=0xFF_u8 {
-                                for z_pos in 0x00_u8..=0xFF_u8 {
-                                    for y_pos in (0x00_u8..=0xFF_u8).rev() {
-                                        let pos: Vec3ub = [x_pos, y_pos, z_pos];
-                                        let block_lid: u16 = world.get_block(&pos);
-                                        if blocks_loader.get_block(block_lid).mesh.is_cube() {
-                                            break;
-                                        }
-
-                                        let mut flag: bool = false;
-                                        for neigh in NEIGHBORHOOD {
-                                            if let Some(neigh_pos) = LightSolver::get_neighbor_pos(&pos, &neigh) {
-                                                if world.get_light_level(&neigh_pos, S_CHANNEL) == 0 {
-                                                    flag = true;
-                                                    break;
-                                                }
+                            window.process_events();
+                            input_map.update(&window.keyboard, &window.mouse.borrow());
+                            window.swap_buffers();
+                            if asp_rat != window.asp_rat || fov != settings.fov.to_radians() {
+                                asp_rat = window.asp_rat;
+                                fov = settings.fov.to_radians();
+                                proj_mat.identity().perspective(fov, asp_rat, z_near, z_far);
+                                gui_renderer.set_asp_rat(asp_rat);
+                                widget_renderer.set_asp_rat(asp_rat);
+                                widget_tree.set_asp_rat(asp_rat);
+                            }
+                            //GUI gets the mouse only with the free cursor
+                            let gui_visible: bool = window.mouse.borrow().get_cursor_state();
+                            set_state_layouts(&mut widget_tree, &state_stack, inventory_open, console_open);
+                            let gui_input: GuiInput = GuiInput {
+                                cursor_pos: GuiInput::get_gui_pos(window.mouse.borrow().get_cursor_pos(), window.get_width(), window.get_height()),
+                                pressed: gui_visible && window.mouse.borrow().get_button_state(MouseButtonLeft),
+                                scroll: if gui_visible { window.mouse.borrow().get_scroll_delta()[1] as f32 } else { 0.0 },
+                                keys: window.keyboard.get_pressed_keys(),
+                                chars: window.keyboard.get_chars(),
+                            };
+                            let console_input: Option<WidgetId> = widget_tree.find(CONSOLE_INPUT_NAME);
+                            let mut submitted_line: Option<String> = None;
+                            let mut clicked: Vec<String> = Vec::new();
+                            for event in widget_tree.process_input(&gui_input) {
+                                match event {
+                                    GuiEvent::ItemSelected(id, item) => {
+                                        let widget_name: String = widget_tree.get(id).map_or(String::new(), |widget| widget.name.clone());
+                                        if widget_name == BLOCK_LIST_NAME {
+                                            inventory.pick(&blocks_loader.get_block(item as u16).name);
+                                        } else if widget_name == CONTROLS_LIST_NAME {
+                                            selected_action = INPUT_ACTIONS.get(item).map(|(action, _, _)| *action);
+                                        } else if widget_name == WORLD_LIST_NAME {
+                                            selected_world = worlds_list.get_names().get(item).cloned();
+                                            delete_confirmed = false;
+                                            if let Some(name) = &selected_world {
+                                                set_text_field_text(&mut widget_tree, WORLD_NAME_FIELD_NAME, name);
                                             }
                                         }
-                                        if flag {
-                                            solver_s.add_last(&world, &pos);
+                                    }
+                                    GuiEvent::Clicked(id) => {
+                                        if let Some(widget) = widget_tree.get(id) {
+                                            clicked.push(widget.name.clone());
                                         }
                                     }
+                                    GuiEvent::ValueChanged(id, value) => {
+                                        match widget_tree.get(id).map_or("", |widget| widget.name.as_str()) {
+                                            FOV_SLIDER_NAME => settings.fov = value,
+                                            SENSITIVITY_SLIDER_NAME => settings.mouse_sensitivity = value,
+                                            _ => {}
+                                        }
+                                    }
+                                    GuiEvent::TextSubmitted(id, line) => {
+                                        if console_open && Some(id) == console_input {
+                                            submitted_line = Some(line);
+                                        }
+                                    }
+                                    _ => {}
                                 }
                             }
-
-                            solver_r.solve(&world, &blocks_loader);
-                            solver_g.solve(&world, &blocks_loader);
-                            solver_b.solve(&world, &blocks_loader);
-                            solver_s.solve(&world, &blocks_loader);
-                            //
-
-                            unsafe {
-                                gl::Enable(gl::BLEND);
-                                gl::Enable(gl::DEPTH_TEST);
-                                gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
-                                //TODO with gl::Enable(gl::CULL_FACE);
+                            //Rebinding takes the first input pressed after the Rebind click, Escape cancels it
+                            let mut pause_pressed: bool = input_map.is_just_pressed(InputAction::Pause);
+                            if let Some(action) = rebinding_action.filter(|_| state_stack.get_top() == GameState::Controls) {
+                                if let Some(source) = input_map.get_just_pressed_source() {
+                                    rebinding_action = None;
+                                    pause_pressed = false;
+                                    if source == InputSource::Key(glfw::Key::Escape) {
+                                        widget_tree.set_source(CONTROLS_STATUS_SOURCE, "Rebinding cancelled");
+                                    } else {
+                                        match input_map.bind(action, source) {
+                                            Ok(()) => widget_tree.set_source(CONTROLS_STATUS_SOURCE, &format!("{} bound to {}", action.get_description(), source.get_name())),
+                                            Err(error) => widget_tree.set_source(CONTROLS_STATUS_SOURCE, &error.to_string()),
+                                        }
+                                    }
+                                }
                             }
-                            let mut bflag: bool = true;
-                            let mut dflag: bool = true;
-                            let mut tab_flag: bool = true;
-                            let mut delta: f32 = 0.0;
-                            let mut last_time: f32 = window.get_time() as f32;
-                            while !window.should_close() {
-                                let cur_time: f32 = window.get_time() as f32;
-                                delta = 1.0_f32.min((cur_time - last_time) * 16.0);
-                                last_time = cur_time;
-                                window.process_events();
-                                window.swap_buffers();
-                                if asp_rat != window.asp_rat {
-                                    asp_rat = window.asp_rat;
-                                    proj_mat.identity().perspective(fov, asp_rat, z_near, z_far);
-                                    gui_renderer.set_asp_rat(asp_rat);
+                            //Pause closes the top screen, the console is closed by Escape since its keys are typed text
+                            if console_open {
+                                if gui_input.keys.contains(&glfw::Key::Escape) {
+                                    console_open = false;
+                                    window.mouse.borrow_mut().set_cursor_state(&window, false);
                                 }
-                                camera.set_position([hitbox.pos[0], hitbox.pos[1] + 0.5, hitbox.pos[2]]);
-                                let mut steps_cnt: i32 = (delta * 1024.0) as i32;
-                                if steps_cnt <= 0 {steps_cnt = 1;};
-                                if steps_cnt > 100 {steps_cnt = 100;}
-                                let speed = 0.8f32;
-                                let jump = 1.25f32;
-
-                                camera.get_view_mat_to(&proj_mat, &mut view_mat);
-                                unsafe {
-                                    if let Err(_) = shader_program.set_uniform_mat4f("viewMat", &view_mat) {
-                                        println!("Failed to load view matrix");
+                            } else if pause_pressed {
+                                match state_stack.get_top() {
+                                    GameState::Playing => {
+                                        if inventory_open {
+                                            inventory_open = false;
+                                            window.mouse.borrow_mut().set_cursor_state(&window, false);
+                                        } else {
+                                            state_stack.push(GameState::Paused);
+                                        }
                                     }
-                                    if let Err(_) = lines_renderer.set_view_mat(&view_mat) {
-                                        println!("Failed to load view matrix");
+                                    GameState::Settings => {
+                                        clicked.push(String::from(SETTINGS_DONE_BUTTON_NAME));
                                     }
+                                    GameState::Controls => {
+                                        clicked.push(String::from(CONTROLS_DONE_BUTTON_NAME));
+                                    }
+                                    GameState::Paused | GameState::WorldList => {
+                                        state_stack.pop();
+                                    }
+                                    GameState::MainMenu | GameState::Loading => {}
                                 }
-                                unsafe {
-                                    gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-                                    gl::ClearColor(0.2, 0.3, 0.3, 1.0);
-                                }
-                                {
-                                    let mut move_pos_cam_dir: Vec3f = [0.0, 0.0, 0.0];
-                                    let mut move_rot_cam_vec: Vec3f = [0.0, 0.0, 0.0];
-
-                                    if window.keyboard.get_key_state(glfw::Key::Escape) {
-                                        window.close();
+                            }
+                            for button_name in &clicked {
+                                match button_name.as_str() {
+                                    "play_button" => {
+                                        widget_tree.set_source(WORLDS_NAMES_SOURCE, &worlds_list.get_names().join("\n"));
+                                        widget_tree.set_source(WORLDS_STATUS_SOURCE, "");
+                                        state_stack.push(GameState::WorldList);
                                     }
-
-
-                                    //TODO ROTSPEED MOVESPEED
-                                    if window.keyboard.get_key_state(glfw::Key::D) {
-                                        add_vec3f(&mut move_pos_cam_dir, camera.get_rdir());
+                                    "main_settings_button" | "pause_settings_button" => {
+                                        state_stack.push(GameState::Settings);
                                     }
-                                    if window.keyboard.get_key_state(glfw::Key::A) {
-                                        sub_vec3f(&mut move_pos_cam_dir, camera.get_rdir());
+                                    SETTINGS_DONE_BUTTON_NAME => {
+                                        if let Err(error) = settings.store(Path::new(SETTINGS_PATH)) {
+                                            println!("Failed to save the settings: {}", error);
+                                        }
+                                        state_stack.pop();
                                     }
-
-                                    if window.keyboard.get_key_state(glfw::Key::Space) && hitbox.grounded {
-                                        hitbox.vel[1] = jump;
-                                        //add_vec3f(&mut move_pos_cam_dir, camera.get_udir());
+                                    "controls_button" => {
+                                        widget_tree.set_source(CONTROLS_STATUS_SOURCE, "");
+                                        state_stack.push(GameState::Controls);
                                     }
-                                    /*if window.keyboard.get_key_state(glfw::Key::LeftShift) || window.keyboard.get_key_state(glfw::Key::RightShift) {
-                                        sub_vec3f(&mut move_pos_cam_dir, camera.get_udir());
-                                    }*/
-
-                                    if window.keyboard.get_key_state(glfw::Key::S) {
-                                        sub_vec3f(&mut move_pos_cam_dir, camera.get_fdir());
+                                    "controls_rebind_button" => {
+                                        if let Some(action) = selected_action {
+                                            rebinding_action = Some(action);
+                                            widget_tree.set_source(CONTROLS_STATUS_SOURCE, &format!("Press an input for {}, Escape cancels", action.get_description()));
+                                        } else {
+                                            widget_tree.set_source(CONTROLS_STATUS_SOURCE, "Select an action to rebind");
+                                        }
                                     }
-                                    if window.keyboard.get_key_state(glfw::Key::W) {
-                                        add_vec3f(&mut move_pos_cam_dir, camera.get_fdir());
+                                    "controls_reset_button" => {
+                                        if let Some(action) = selected_action {
+                                            match input_map.reset(action) {
+                                                Ok(()) => widget_tree.set_source(CONTROLS_STATUS_SOURCE, &format!("{} reset", action.get_description())),
+                                                Err(error) => widget_tree.set_source(CONTROLS_STATUS_SOURCE, &error.to_string()),
+                                            }
+                                        } else {
+                                            widget_tree.set_source(CONTROLS_STATUS_SOURCE, "Select an action to reset");
+                                        }
                                     }
-                                    if window.mouse.borrow().get_cursor_state() {
-                                        if window.keyboard.get_key_state(glfw::Key::Down) {
-                                            //let cur_rot_x: f32 = camera.get_rotation_x();
-                                            move_rot_cam_vec[0] += 0.5;
+                                    CONTROLS_DONE_BUTTON_NAME => {
+                                        rebinding_action = None;
+                                        if let Err(error) = input_map.store(Path::new(CONTROLS_PATH)) {
+                                            println!("Failed to save the controls: {}", error);
                                         }
-                                        if window.keyboard.get_key_state(glfw::Key::Up) {
-                                            //let cur_rot_x: f32 = camera.get_rotation_x();
-                                            move_rot_cam_vec[0] -= 0.5;
+                                        state_stack.pop();
+                                    }
+                                    "quit_button" => {
+                                        window.close();
+                                    }
+                                    "resume_button" => {
+                                        state_stack.pop();
+                                    }
+                                    "quit_to_menu_button" => {
+                                        if let Some(world) = &world {
+                                            if let Err(error) = world.store(&blocks_loader, world_path.clone()) {
+                                                println!("Failed to save the world: {}", error);
+                                            }
+                                            let player_data: PlayerData = PlayerData { pos: hitbox.pos, rot: camera.get_rotation(), mode: player_controller.mode, inventory };
+                                            if let Err(error) = player_data.store(player_data_path.clone()) {
+                                                println!("Failed to save the player data: {}", error);
+                                            }
+                                            inventory = player_data.inventory;
                                         }
-
-                                        if window.keyboard.get_key_state(glfw::Key::Right) {
-                                            move_rot_cam_vec[1] += 0.5;
+                                        world = None;
+                                        lines_renderer.clear();
+                                        state_stack.reset(GameState::MainMenu);
+                                    }
+                                    "world_play_button" => {
+                                        if let Some(name) = selected_world.clone().filter(|name| worlds_list.exists(name)) {
+                                            //Generator blocks may have changed since the last world
+                                            let chunk_generator: LayerChunkGenerator = LayerChunkGenerator::from_bottom_layers(&[game_blocks.bedrock, game_blocks.dirt, game_blocks.dirt, game_blocks.dirt, game_blocks.grass], game_blocks.air);
+                                            match WorldLoading::new(&chunk_generator, &blocks_loader, &worlds_list.get_world_path(&name)) {
+                                                Ok(loading) => {
+                                                    world_path = worlds_list.get_world_path(&name);
+                                                    player_data_path = worlds_list.get_player_data_path(&name);
+                                                    world_name = name;
+                                                    world_loading = Some(loading);
+                                                    state_stack.reset(GameState::Loading);
+                                                }
+                                                Err(error) => {
+                                                    widget_tree.set_source(WORLDS_STATUS_SOURCE, &format!("Failed to create world {}: {}", name, error));
+                                                }
+                                            }
+                                        } else {
+                                            widget_tree.set_source(WORLDS_STATUS_SOURCE, "Select a world to play");
                                         }
-                                        if window.keyboard.get_key_state(glfw::Key::Left) {
-                                            move_rot_cam_vec[1] -= 0.5;
+                                    }
+                                    "world_create_button" => {
+                                        let name: String = widget_tree.find(WORLD_NAME_FIELD_NAME).map_or(String::new(), |id| get_text_field_text(&widget_tree, id));
+                                        match worlds_list.create(&name) {
+                                            Ok(()) => {
+                                                widget_tree.set_source(WORLDS_STATUS_SOURCE, &format!("Created world {}", name));
+                                                selected_world = Some(name);
+                                            }
+                                            Err(error) => widget_tree.set_source(WORLDS_STATUS_SOURCE, &error.to_string()),
                                         }
-                                        if window.keyboard.get_key_state(glfw::Key::H) {
-                                            move_rot_cam_vec[2] += 0.5;
+                                    }
+                                    "world_rename_button" => {
+                                        let new_name: String = widget_tree.find(WORLD_NAME_FIELD_NAME).map_or(String::new(), |id| get_text_field_text(&widget_tree, id));
+                                        if let Some(name) = selected_world.clone() {
+                                            match worlds_list.rename(&name, &new_name) {
+                                                Ok(()) => {
+                                                    widget_tree.set_source(WORLDS_STATUS_SOURCE, &format!("Renamed world {} to {}", name, new_name));
+                                                    selected_world = Some(new_name);
+                                                }
+                                                Err(error) => widget_tree.set_source(WORLDS_STATUS_SOURCE, &error.to_string()),
+                                            }
+                                        } else {
+                                            widget_tree.set_source(WORLDS_STATUS_SOURCE, "Select a world to rename");
                                         }
-                                        if window.keyboard.get_key_state(glfw::Key::Y) {
-                                            move_rot_cam_vec[2] -= 0.5;
+                                    }
+                                    "world_delete_button" => {
+                                        //Deleting can't be undone, so it takes a second click
+                                        if let Some(name) = selected_world.clone() {
+                                            if !delete_confirmed {
+                                                delete_confirmed = true;
+                                                widget_tree.set_source(WORLDS_STATUS_SOURCE, &format!("Click Delete again to delete world {}", name));
+                                                continue;
+                                            }
+                                            match worlds_list.delete(&name) {
+                                                Ok(()) => {
+                                                    widget_tree.set_source(WORLDS_STATUS_SOURCE, &format!("Deleted world {}", name));
+                                                    selected_world = None;
+                                                }
+                                                Err(error) => widget_tree.set_source(WORLDS_STATUS_SOURCE, &error.to_string()),
+                                            }
+                                        } else {
+                                            widget_tree.set_source(WORLDS_STATUS_SOURCE, "Select a world to delete");
                                         }
-                                    } else {
-                                        let pos: Vec2d = window.mouse.borrow().get_cursor_delta_pos().clone();
-                                        let min_side = window.get_width().min(window.get_height());
-                                        //86 - sensitivity
-                                        move_rot_cam_vec[0] = ((pos[1] as f32) / (min_side as f32)) * 86.0f32;
-                                        move_rot_cam_vec[1] = ((pos[0] as f32) / (min_side as f32)) * 86.0f32;
                                     }
-
-                                    if window.keyboard.get_key_state(glfw::Key::Num1) {
-                                        cur_lid = UNKNOWN_BLOCK_ID;
+                                    "world_list_back_button" => {
+                                        state_stack.pop();
                                     }
-
-                                    if window.keyboard.get_key_state(glfw::Key::Num2) {
-                                        cur_lid = DIRT_BLOCK_ID;
+                                    _ => {}
+                                }
+                                if button_name != "world_delete_button" {
+                                    delete_confirmed = false;
+                                }
+                            }
+                            if !clicked.is_empty() {
+                                widget_tree.set_source(WORLDS_NAMES_SOURCE, &worlds_list.get_names().join("\n"));
+                            }
+                            if state_stack.get_top() == GameState::Controls {
+                                widget_tree.set_source(CONTROLS_BINDINGS_SOURCE, &get_bindings_lines(&input_map));
+                            }
+                            widget_tree.set_source(FOV_SOURCE, &settings.fov.to_string());
+                            widget_tree.set_source(MOUSE_SENSITIVITY_SOURCE, &settings.mouse_sensitivity.to_string());
+                            if let Some(mut loading) = world_loading.take() {
+                                match loading.step(&blocks_loader, &script_engine, &light_solvers) {
+                                    Err(error) => {
+                                        println!("Failed to load world {}: {}", world_name, error);
+                                        widget_tree.set_source(WORLDS_STATUS_SOURCE, &format!("Failed to load world {}: {}", world_name, error));
+                                        state_stack.reset(GameState::MainMenu);
+                                        state_stack.push(GameState::WorldList);
                                     }
-
-                                    if window.keyboard.get_key_state(glfw::Key::Num3) {
-                                        cur_lid = GRASS_BLOCK_ID;
+                                    Ok(()) if loading.is_done() => {
+                                        let new_world: World = loading.world;
+                                        camera = Camera::new();
+                                        hitbox = HitBox::new(SPAWN_POS, PLAYER_HALF_SIZE);
+                                        player_controller = PlayerController::new();
+                                        inventory = match PlayerData::load(player_data_path.clone()) {
+                                            Ok(player_data) => {
+                                                hitbox = HitBox::new(player_data.pos, PLAYER_HALF_SIZE);
+                                                camera.set_rotation(player_data.rot);
+                                                player_controller.set_mode(player_data.mode);
+                                                player_data.inventory
+                                            }
+                                            Err(error) => {
+                                                println!("Player data not loaded, starting as a new player: {}", error);
+                                                get_default_inventory(&blocks_loader, &game_blocks)
+                                            }
+                                        };
+                                        inventory_open = false;
+                                        console_open = false;
+                                        iend_last = [0, 0, 0];
+                                        lines_renderer.clear();
+                                        world = Some(new_world);
+                                        state_stack.replace(GameState::Playing);
                                     }
-
-                                    if window.keyboard.get_key_state(glfw::Key::Num4) {
-                                        cur_lid = BEDROCK_BLOCK_ID;
+                                    Ok(()) => {
+                                        widget_tree.set_source(LOADING_TEXT_SOURCE, &format!("{} {:.0}%", loading.get_description(), loading.get_progress() * 100.0));
+                                        world_loading = Some(loading);
+                                    }
+                                }
+                            }
+                            //Menus need the cursor, the game takes it back unless a GUI is open over the world
+                            if state_stack.get_top() != last_top {
+                                last_top = state_stack.get_top();
+                                window.mouse.borrow_mut().set_cursor_state(&window, last_top != GameState::Playing || inventory_open || console_open);
+                            }
+                            let playing: bool = state_stack.get_top() == GameState::Playing && !console_open;
+                            if let (Some(line), Some(world)) = (submitted_line, &world) {
+                                //Commands get the player as saved data and give it back
+                                let mut player_data: PlayerData = PlayerData { pos: hitbox.pos, rot: camera.get_rotation(), mode: player_controller.mode, inventory };
+                                let mut context: CommandContext = CommandContext {
+                                    world,
+                                    blocks_loader: &blocks_loader,
+                                    behaviors: &behaviors,
+                                    light_solvers: &light_solvers,
+                                    player: &mut player_data,
+                                    world_path: &world_path,
+                                    player_data_path: &player_data_path,
+                                };
+                                run_console_line(&mut console, &command_registry, &script_engine, &mut context, &line);
+                                if player_data.pos != hitbox.pos {
+                                    hitbox = HitBox::new(player_data.pos, PLAYER_HALF_SIZE);
+                                }
+                                inventory = player_data.inventory;
+                                console_open = false;
+                                window.mouse.borrow_mut().set_cursor_state(&window, false);
+                            } else if console_open {
+                                if let Some(console_input) = console_input {
+                                    for key in &gui_input.keys {
+                                        let line: String = get_text_field_text(&widget_tree, console_input);
+                                        let new_line: Option<String> = match key {
+                                            glfw::Key::Up => console.get_prev(&line),
+                                            glfw::Key::Down => console.get_next(),
+                                            glfw::Key::Tab => {
+                                                let candidates: Vec<String> = command_registry.complete(&line, &blocks_loader, &script_engine.get_commands_names());
+                                                console.complete(&line, &candidates)
+                                            }
+                                            _ => None,
+                                        };
+                                        if let (Some(new_line), Some(widget)) = (new_line, widget_tree.get_mut(console_input)) {
+                                            widget.set_text(&new_line);
+                                        }
                                     }
+                                    //Tab moves the focus in the tree, the console keeps it
+                                    if widget_tree.get_focused() != Some(console_input) {
+                                        widget_tree.set_focus(Some(console_input), &mut Vec::new());
+                                    }
+                                }
+                            }
+                            let gui_hovered: bool = gui_visible && widget_tree.get_widget_at(&gui_input.cursor_pos).is_some();
+                            unsafe {
+                                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                                gl::ClearColor(0.2, 0.3, 0.3, 1.0);
+                            }
+                            if let Some(world) = &world {
+                                {
+                                    let mut move_pos_cam_dir: Vec3f = [0.0, 0.0, 0.0];
+                                    let mut move_rot_cam_vec: Vec3f = [0.0, 0.0, 0.0];
 
-                                    if window.mouse.borrow().get_button_state(MouseButtonRight) {
-                                        if dflag || window.keyboard.get_key_state(glfw::Key::R) {
-                                            dflag = false;
-                                            let mut end: Vec3f = [0.0, 0.0, 0.0];
-                                            let mut norm: Vec3f = [0.0, 0.0, 0.0];
-                                            let mut iend: Vec3ub = [0, 0, 0];
-                                            world.ray_get(&camera.get_position(), &camera.get_dir(), 16.0, &mut end, &mut norm, &mut iend);
-                                            world.set_block(&iend, AIR_BLOCK_ID);
-                                            solver_r.remove(&world, &iend);
-                                            solver_g.remove(&world, &iend);
-                                            solver_b.remove(&world, &iend);
-                                            solver_r.solve(&world, &blocks_loader);
-                                            solver_g.solve(&world, &blocks_loader);
-                                            solver_b.solve(&world, &blocks_loader);
-                                            //TODO rename [0, 1, 0] to neighbor top
-                                            let mut flag: bool = false;
-                                            if let Some(neigh_pos) = LightSolver::get_neighbor_pos(&iend, &[0, 1, 0]) {
-                                                if world.get_light_level(&neigh_pos, S_CHANNEL) == 0x0F {
-                                                    flag = true;
-                                                }
-                                            } else {
-                                                flag = true;
+                                    //Keys typed into the console and menus over the world don't control the game
+                                    if playing {
+                                        //TODO ROTSPEED MOVESPEED
+                                        if input_map.is_pressed(InputAction::MoveRight) {
+                                            add_vec3f(&mut move_pos_cam_dir, camera.get_rdir());
+                                        }
+                                        if input_map.is_pressed(InputAction::MoveLeft) {
+                                            sub_vec3f(&mut move_pos_cam_dir, camera.get_rdir());
+                                        }
+
+
+                                        if input_map.is_pressed(InputAction::MoveBack) {
+                                            sub_vec3f(&mut move_pos_cam_dir, camera.get_fdir());
+                                        }
+                                        if input_map.is_pressed(InputAction::MoveForward) {
+                                            add_vec3f(&mut move_pos_cam_dir, camera.get_fdir());
+                                        }
+                                        if window.mouse.borrow().get_cursor_state() {
+                                            if input_map.is_pressed(InputAction::LookDown) {
+                                                //let cur_rot_x: f32 = camera.get_rotation_x();
+                                                move_rot_cam_vec[0] += 0.5;
                                             }
-                                            if flag {
-                                                for y_pos in (0x00..=iend[1]).rev() {
-                                                    let spos: Vec3ub = [iend[0], y_pos, iend[2]];
-                                                    let block_lid: u16 = world.get_block(&spos);
-                                                    if blocks_loader.get_block(block_lid).mesh.is_cube() {
-                                                        break;
-                                                    }
-                                                    solver_s.add(&world, &spos, 0x0F);
-                                                }
+                                            if input_map.is_pressed(InputAction::LookUp) {
+                                                //let cur_rot_x: f32 = camera.get_rotation_x();
+                                                move_rot_cam_vec[0] -= 0.5;
+                                            }
+
+                                            if input_map.is_pressed(InputAction::LookRight) {
+                                                move_rot_cam_vec[1] += 0.5;
+                                            }
+                                            if input_map.is_pressed(InputAction::LookLeft) {
+                                                move_rot_cam_vec[1] -= 0.5;
+                                            }
+                                            if input_map.is_pressed(InputAction::RollRight) {
+                                                move_rot_cam_vec[2] += 0.5;
+                                            }
+                                            if input_map.is_pressed(InputAction::RollLeft) {
+                                                move_rot_cam_vec[2] -= 0.5;
+                                            }
+                                        } else {
+                                            let pos: Vec2d = window.mouse.borrow().get_cursor_delta_pos().clone();
+                                            let min_side = window.get_width().min(window.get_height());
+                                            move_rot_cam_vec[0] = ((pos[1] as f32) / (min_side as f32)) * settings.mouse_sensitivity;
+                                            move_rot_cam_vec[1] = ((pos[0] as f32) / (min_side as f32)) * settings.mouse_sensitivity;
+                                        }
+
+                                        for (slot_ind, action) in HOTBAR_ACTIONS.iter().enumerate() {
+                                            if input_map.is_pressed(*action) {
+                                                inventory.select(slot_ind);
+                                            }
+                                        }
+                                        if !gui_visible {
+                                            //Scrolling down goes to the right like in most games
+                                            let scroll: f64 = window.mouse.borrow().get_scroll_delta()[1];
+                                            if scroll != 0.0 {
+                                                inventory.scroll(-(scroll.signum() as i32));
                                             }
-                                            for neigh in NEIGHBORHOOD {
-                                                if let Some(neigh_pos) = LightSolver::get_neighbor_pos(&iend, &neigh) {
-                                                    solver_r.add_last(&world, &neigh_pos);
-                                                    solver_g.add_last(&world, &neigh_pos);
-                                                    solver_b.add_last(&world, &neigh_pos);
-                                                    solver_s.add_last(&world, &neigh_pos);
+                                        }
+                                        if input_map.is_just_pressed(InputAction::Inventory) {
+                                            inventory_open = !inventory_open;
+                                            window.mouse.borrow_mut().set_cursor_state(&window, inventory_open);
+                                        }
+
+                                        if player_controller.can_interact() && !gui_hovered && input_map.is_pressed(InputAction::BreakBlock) {
+                                            if input_map.is_just_pressed(InputAction::BreakBlock) || input_map.is_pressed(InputAction::RepeatClick) {
+                                                if let Some(hit) = world.raycast(&blocks_loader, &camera.get_position(), &camera.get_dir(), 16.0, &RayFilter::SELECTABLE) {
+                                                    let iend: Vec3ub = [hit.block_pos[0] as u8, hit.block_pos[1] as u8, hit.block_pos[2] as u8];
+                                                    let changed: Vec<Vec3ub> = world.break_block(&blocks_loader, &behaviors, &iend);
+                                                    for pos in &changed {
+                                                        light_solvers.on_block_edited(&world, &blocks_loader, pos);
+                                                    }
                                                 }
                                             }
-                                            solver_r.solve(&world, &blocks_loader);
-                                            solver_g.solve(&world, &blocks_loader);
-                                            solver_b.solve(&world, &blocks_loader);
-                                            solver_s.solve(&world, &blocks_loader);
                                         }
-                                    } else {
-                                        dflag = true;
-                                    }
-
-                                    if window.mouse.borrow().get_button_state(MouseButtonLeft) {
-                                        if bflag || window.keyboard.get_key_state(glfw::Key::R) {
-                                            bflag = false;
-                                            let mut end: Vec3f = [0.0, 0.0, 0.0];
-                                            let mut norm: Vec3f = [0.0, 0.0, 0.0];
-                                            let mut iend: Vec3ub = [0, 0, 0];
-                                            if let Some(block) = world.ray_get(&camera.get_position(), &camera.get_dir(), 16.0, &mut end, &mut norm, &mut iend) {
-                                                if block != AIR_BLOCK_ID {
-                                                    let res: Vec3s = [(iend[0] as i16 + norm[0] as i16), (iend[1] as i16 + norm[1] as i16), (iend[2] as i16 + norm[2] as i16)];
-                                                    if res[0] >= 0x00 && res[0] <= 0xFF &&
+
+                                        if player_controller.can_interact() && !gui_hovered && input_map.is_pressed(InputAction::UseBlock) {
+                                            if input_map.is_just_pressed(InputAction::UseBlock) || input_map.is_pressed(InputAction::RepeatClick) {
+                                                if let Some(hit) = world.raycast(&blocks_loader, &camera.get_position(), &camera.get_dir(), 16.0, &RayFilter::SELECTABLE) {
+                                                    let res: Vec3i = [hit.block_pos[0] + hit.face[0], hit.block_pos[1] + hit.face[1], hit.block_pos[2] + hit.face[2]];
+                                                    let mut used_changed: Vec<Vec3ub> = Vec::new();
+                                                    if world.use_block(&blocks_loader, &behaviors, &[hit.block_pos[0] as u8, hit.block_pos[1] as u8, hit.block_pos[2] as u8], &mut used_changed) {
+                                                        for pos in &used_changed {
+                                                            light_solvers.on_block_edited(&world, &blocks_loader, pos);
+                                                        }
+                                                    } else if res[0] >= 0x00 && res[0] <= 0xFF &&
                                                         res[1] >= 0x00 && res[1] <= 0xFF &&
                                                         res[2] >= 0x00 && res[2] <= 0xFF {
                                                         let pos: Vec3ub = [res[0] as u8, res[1] as u8, res[2] as u8];
-                                                        if world.get_block(&pos) == AIR_BLOCK_ID && !(PhysicsSolver::is_block_inside(&[pos[0] as i32, pos[1] as i32, pos[2] as i32], &hitbox)) {
-                                                            let block = blocks_loader.get_block(cur_lid);
-                                                            world.set_block(&pos, block.lid);
-                                                            solver_r.remove(&world, &pos);
-                                                            solver_g.remove(&world, &pos);
-                                                            solver_b.remove(&world, &pos);
-                                                            solver_s.remove(&world, &pos);
-                                                            //TODO rename [0, -1, 0] to neighbor bottom
-
-                                                            //TODO REWRITE IT
-
-                                                            //PLACE WITH THE MOST WTF ERROR (IEND CONFUSED WITH POS)
-                                                            if let Some(neigh_pos) = LightSolver::get_neighbor_pos(&pos, &[0, -1, 0]) {
-                                                                for y_pos in (0x00..=neigh_pos[1]).rev() {
-                                                                    let spos: Vec3ub = [pos[0], y_pos, pos[2]];
-                                                                    solver_s.remove(&world, &spos);
-                                                                    if let Some(bottom_spos) = LightSolver::get_neighbor_pos(&spos, &[0, -1, 0]) {
-                                                                        let block_lid: u16 = world.get_block(&bottom_spos);
-                                                                        if blocks_loader.get_block(block_lid).mesh.is_cube() {
-                                                                            break;
-                                                                        }
-                                                                    } else if y_pos == 0 {
-                                                                        break;
-                                                                    }
-                                                                }
-                                                                solver_r.solve(&world, &blocks_loader);
-                                                                solver_g.solve(&world, &blocks_loader);
-                                                                solver_b.solve(&world, &blocks_loader);
-                                                                solver_s.solve(&world, &blocks_loader);
-                                                                if block.light_r != 0 {
-                                                                    solver_r.add(&world, &pos, block.light_r);
-                                                                    solver_r.solve(&world, &blocks_loader);
-                                                                }
-                                                                if block.light_g != 0 {
-                                                                    solver_g.add(&world, &pos, block.light_g);
-                                                                    solver_r.solve(&world, &blocks_loader);
-                                                                }
-                                                                if block.light_b != 0 {
-                                                                    solver_b.add(&world, &pos, block.light_b);
-                                                                    solver_r.solve(&world, &blocks_loader);
-                                                                }
+                                                        let replaced_lid: u16 = world.get_block(&pos);
+                                                        let selected_block: Option<Rc<Block>> = inventory.get_selected().and_then(|stack| blocks_loader.get_block_by_name(&stack.name));
+                                                        if let Some(block) = selected_block.filter(|_| (replaced_lid == game_blocks.air || blocks_loader.get_block(replaced_lid).fluid.is_some()) && !(PhysicsSolver::is_block_inside(&[pos[0] as i32, pos[1] as i32, pos[2] as i32], &hitbox))) {
+                                                            let changed: Vec<Vec3ub> = world.place_block(&blocks_loader, &behaviors, &pos, block.lid);
+                                                            for changed_pos in &changed {
+                                                                light_solvers.on_block_edited(&world, &blocks_loader, changed_pos);
                                                             }
                                                         }
                                                     }
                                                 }
                                             }
                                         }
-                                    } else {
-                                        bflag = true;
-                                    }
 
 
-                                    if window.keyboard.get_key_state(glfw::Key::F) {
-                                        if let Err(error) = world.store(&blocks_loader, String::from("world/world.data")) {
-                                            println!("Failed to save the world");
-                                        } else {
-                                            println!("World has been saved successfully");
+                                        if input_map.is_just_pressed(InputAction::SaveWorld) {
+                                            if let Err(error) = world.store(&blocks_loader, world_path.clone()) {
+                                                println!("Failed to save the world");
+                                            } else {
+                                                println!("World has been saved successfully");
+                                            }
+                                            let player_data: PlayerData = PlayerData { pos: hitbox.pos, rot: camera.get_rotation(), mode: player_controller.mode, inventory };
+                                            if let Err(error) = player_data.store(player_data_path.clone()) {
+                                                println!("Failed to save the player data: {}", error);
+                                            }
+                                            inventory = player_data.inventory;
                                         }
-                                    }
 
-                                    if window.keyboard.get_key_state(glfw::Key::G) {
-                                        if let Err(error) = world.load(&blocks_loader, String::from("world/world.data")) {
-                                            println!("Failed to load the world");
-                                        } else {
-                                            println!("World has been loaded successfully");
+                                        if input_map.is_just_pressed(InputAction::LoadWorld) {
+                                            if let Err(error) = world.load(&blocks_loader, world_path.clone()) {
+                                                println!("Failed to load the world");
+                                            } else {
+                                                println!("World has been loaded successfully");
+                                            }
+                                            match PlayerData::load(player_data_path.clone()) {
+                                                Ok(player_data) => {
+                                                    hitbox = HitBox::new(player_data.pos, PLAYER_HALF_SIZE);
+                                                    camera.set_rotation(player_data.rot);
+                                                    player_controller.set_mode(player_data.mode);
+                                                    inventory = player_data.inventory;
+                                                }
+                                                Err(error) => {
+                                                    println!("Failed to load the player data: {}", error);
+                                                }
+                                            }
                                         }
-                                    }
 
-                                    if window.keyboard.get_key_state(glfw::Key::Q) {
-                                        world.set_block(&[8, 8, 8], rand::thread_rng().gen_range(0..4) as u16);
-                                    }
+                                        if input_map.is_just_pressed(InputAction::RandomBlock) {
+                                            world.set_block(&[8, 8, 8], rand::thread_rng().gen_range(0..blocks_loader.loaded_blocks.len()) as u16);
+                                        }
 
-                                    if window.keyboard.get_key_state(glfw::Key::M) {
-                                        println!("{:?}, {:?}", camera.get_position(), camera.get_rotation());
-                                    }
+                                        if input_map.is_just_pressed(InputAction::PrintCamera) {
+                                            println!("{:?}, {:?}", camera.get_position(), camera.get_rotation());
+                                        }
 
-                                    if window.keyboard.get_key_state(glfw::Key::B) {
-                                        let mut end: Vec3f = [0.0, 0.0, 0.0];
-                                        let mut norm: Vec3f = [0.0, 0.0, 0.0];
-                                        let mut iend: Vec3ub = [0, 0, 0];
-                                        if let Some(block) = world.ray_get(&camera.get_position(), &camera.get_dir(), 16.0, &mut end, &mut norm, &mut iend) {
-                                            if block != AIR_BLOCK_ID {
-                                                println!("lid: {}, r: {}, g: {}, b: {}, s: {}", world.get_block(&iend), world.get_light_level(&iend, 0), world.get_light_level(&iend, 1), world.get_light_level(&iend, 2), world.get_light_level(&iend, 3));
+                                        if input_map.is_just_pressed(InputAction::PrintBlock) {
+                                            if let Some(hit) = world.raycast(&blocks_loader, &camera.get_position(), &camera.get_dir(), 16.0, &RayFilter::SELECTABLE) {
+                                                let iend: Vec3ub = [hit.block_pos[0] as u8, hit.block_pos[1] as u8, hit.block_pos[2] as u8];
+                                                let block = blocks_loader.get_block(hit.block);
+                                                println!("lid: {}, name: {}, pack: {}, r: {}, g: {}, b: {}, s: {}", block.lid, block.name, block.pack, world.get_light_level(&iend, 0), world.get_light_level(&iend, 1), world.get_light_level(&iend, 2), world.get_light_level(&iend, 3));
                                             }
                                         }
-                                    }
 
-                                    if window.keyboard.get_key_state(glfw::Key::L) {
-                                        let mut end: Vec3f = [0.0, 0.0, 0.0];
-                                        let mut norm: Vec3f = [0.0, 0.0, 0.0];
-                                        let mut iend: Vec3ub = [0, 0, 0];
-                                        if let Some(block) = world.ray_get(&camera.get_position(), &camera.get_dir(), 16.0, &mut end, &mut norm, &mut iend) {
-                                            solver_r.add(&world, &iend, 0x0F);
+                                        if input_map.is_just_pressed(InputAction::AddLight) {
+                                            if let Some(hit) = world.raycast(&blocks_loader, &camera.get_position(), &camera.get_dir(), 16.0, &RayFilter::SELECTABLE) {
+                                                light_solvers.r.add(&world, &[hit.block_pos[0] as u8, hit.block_pos[1] as u8, hit.block_pos[2] as u8], 0x0F);
+                                            }
                                         }
-                                    }
 
-                                    if window.keyboard.get_key_state(glfw::Key::Tab) {
-                                        if tab_flag {
-                                            tab_flag = false;
+                                        if input_map.is_just_pressed(InputAction::ToggleCursor) {
                                             window.mouse.borrow_mut().toggle_cursor(&window);
                                         }
-                                    } else {
-                                        tab_flag = true;
+                                        if input_map.is_just_pressed(InputAction::DebugInfo) {
+                                            debug_visible = !debug_visible;
+                                        }
+                                        if input_map.is_just_pressed(InputAction::SwitchMode) {
+                                            player_controller.next_mode();
+                                            println!("Player mode: {:?}", player_controller.mode);
+                                        }
+                                        if !inventory_open && (input_map.is_just_pressed(InputAction::Chat) || input_map.is_just_pressed(InputAction::Command)) {
+                                            console_open = true;
+                                            let line: String = if input_map.is_just_pressed(InputAction::Command) { String::from(COMMAND_PREFIX) } else { String::new() };
+                                            if let Some(console_input) = console_input {
+                                                if let Some(widget) = widget_tree.get_mut(console_input) {
+                                                    widget.set_text(&line);
+                                                }
+                                                widget_tree.set_focus(Some(console_input), &mut Vec::new());
+                                            }
+                                            window.mouse.borrow_mut().set_cursor_state(&window, true);
+                                        }
                                     }
+                                    move_pos_cam_dir[1] = 0.0;
                                     norm_vec3f(&mut move_pos_cam_dir);
-                                    hitbox.vel[0] = move_pos_cam_dir[0] * speed;
-                                    //hitbox.vel[1] = move_pos_cam_dir[1] * jump;
-                                    hitbox.vel[2] = move_pos_cam_dir[2] * speed;
-                                    physics_solver.step(&world, &mut hitbox, delta, (steps_cnt as u32));
+                                    let player_input: PlayerInput = PlayerInput {
+                                        move_dir: move_pos_cam_dir,
+                                        up: playing && input_map.is_pressed(InputAction::Jump),
+                                        down: playing && input_map.is_pressed(InputAction::Sneak),
+                                        sprint: playing && input_map.is_pressed(InputAction::Sprint),
+                                    };
+                                    let ticks_start_time: f64 = window.get_time();
+                                    for _ in 0..ticks_cnt {
+                                        player_controller.update(&player_input, &mut hitbox, tick_timer.get_tick_time() * PHYSICS_TIME_SCALE);
+                                        physics_solver.step(&WorldCollider::new(&world, &blocks_loader), &mut hitbox, tick_timer.get_tick_time() * PHYSICS_TIME_SCALE);
+                                        let mut changed: Vec<Vec3ub> = Vec::new();
+                                        world.collide_blocks(&blocks_loader, &behaviors, None, &mut hitbox, &mut changed);
+                                        changed.extend(world.step_entities(&blocks_loader, &behaviors, &physics_solver, tick_timer.get_tick_time() * PHYSICS_TIME_SCALE));
+                                        changed.extend(world.tick(&blocks_loader, &behaviors));
+                                        for pos in changed {
+                                            light_solvers.on_block_edited(&world, &blocks_loader, &pos);
+                                        }
+                                        light_solvers.solve(&world, &blocks_loader);
+                                    }
+                                    tick_timer.set_ticks_duration((window.get_time() - ticks_start_time) as f32);
                                     //camera.move_position(&move_pos_cam_dir, 0.05);
                                     camera.move_rotation(&move_rot_cam_vec);
                                 }
+                                //Interpolated between the last two ticks, so after this frame's ticks
+                                let hitbox_pos: Vec3f = hitbox.get_interpolated_pos(tick_timer.get_alpha());
+                                camera.set_position([hitbox_pos[0], hitbox_pos[1] + 0.5, hitbox_pos[2]]);
+                                camera.get_view_mat_to(&proj_mat, &mut view_mat);
+                                unsafe {
+                                    if let Err(_) = shader_program.set_uniform_mat4f("viewMat", &view_mat) {
+                                        println!("Failed to load view matrix");
+                                    }
+                                    if let Err(_) = lines_renderer.set_view_mat(&view_mat) {
+                                        println!("Failed to load view matrix");
+                                    }
+                                }
                                 {
-                                    let mut end: Vec3f = [0.0, 0.0, 0.0];
-                                    let mut norm: Vec3f = [0.0, 0.0, 0.0];
-                                    let mut iend: Vec3ub = [0, 0, 0];
-                                    if let Some(block) = world.ray_get(&camera.get_position(), &camera.get_dir(), 16.0, &mut end, &mut norm, &mut iend) {
-                                        if block != AIR_BLOCK_ID {
-                                            if !((iend[0] == iend_last[0]) && (iend[1] == iend_last[1]) && (iend[2] == iend_last[2])) {
-                                                iend_last = iend;
-                                                lines_renderer.clear();
-                                                lines_renderer.add_box(&iend);
-                                            }
-                                        } else {
+                                    if let Some(hit) = world.raycast(&blocks_loader, &camera.get_position(), &camera.get_dir(), 16.0, &RayFilter::SELECTABLE) {
+                                        let iend: Vec3ub = [hit.block_pos[0] as u8, hit.block_pos[1] as u8, hit.block_pos[2] as u8];
+                                        if !((iend[0] == iend_last[0]) && (iend[1] == iend_last[1]) && (iend[2] == iend_last[2])) {
+                                            iend_last = iend;
                                             lines_renderer.clear();
+                                            lines_renderer.add_box(&iend);
                                         }
+                                        debug_stats.target = Some(TargetStats {
+                                            pos: hit.block_pos,
+                                            name: blocks_loader.get_block(hit.block).name.clone(),
+                                            light: [world.get_light_level(&iend, R_CHANNEL), world.get_light_level(&iend, G_CHANNEL), world.get_light_level(&iend, B_CHANNEL), world.get_light_level(&iend, S_CHANNEL)],
+                                        });
                                     } else {
-                                        lines_renderer.clear()
+                                        lines_renderer.clear();
+                                        debug_stats.target = None;
                                     }
                                 }
-                                solver_r.solve(&world, &blocks_loader);
-                                solver_g.solve(&world, &blocks_loader);
-                                solver_b.solve(&world, &blocks_loader);
-                                solver_s.solve(&world, &blocks_loader);
+                                debug_stats.set_ticks(&tick_timer);
+                                debug_stats.set_player(camera.get_position(), camera.get_dir());
+                                debug_stats.set_world(&world, &light_solvers);
                                 if let Err(_) = world.render(&blocks_loader) {
                                     println!("Failed to render world");
                                 }
-                                world.draw(&blocks_loader);
+                                debug_stats.draw_calls = world.draw(&blocks_loader);
                                 if let Err(_) = gui_renderer.render() {
                                     println!("Failed to render GUI");
                                 }
@@ -538,18 +1048,34 @@ fn main() {
                                 }
                                 lines_renderer.draw();
                             }
+                            widget_tree.set_source(BLOCK_NAME_SOURCE, inventory.get_selected().map_or("", |stack| stack.name.as_str()));
+                            set_hotbar_sources(&mut widget_tree, &inventory);
+                            widget_tree.set_source(CONSOLE_OUTPUT_SOURCE, &console.get_output(CONSOLE_LINES));
+                            if debug_visible {
+                                widget_tree.set_source(DEBUG_LINES_SOURCE, &debug_stats.get_lines().join("\n"));
+                            } else {
+                                widget_tree.set_source(DEBUG_LINES_SOURCE, "");
+                            }
+                            if let Err(_) = widget_renderer.render(&mut widget_tree) {
+                                println!("Failed to render widgets");
+                            }
                             unsafe {
-                                gl::Disable(gl::BLEND);
                                 gl::Disable(gl::DEPTH_TEST);
                             }
-                        } else {
-                            println!("Failed to initialize lines renderer");
+                            widget_renderer.draw();
+                            unsafe {
+                                gl::Enable(gl::DEPTH_TEST);
+                            }
+                        }
+                        unsafe {
+                            gl::Disable(gl::BLEND);
+                            gl::Disable(gl::DEPTH_TEST);
                         }
                     } else {
-                        println!("Failed to initialize GUI");
+                        println!("Failed to initialize lines renderer");
                     }
                 } else {
-                    println!("Failed to create world");
+                    println!("Failed to initialize GUI");
                 }
             } else {
                 println!("Failed to load blocks data");
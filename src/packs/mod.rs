@@ -0,0 +1,4 @@
+pub mod pack;
+pub mod pack_stack;
+#[cfg(test)]
+pub mod test_packs;
\ No newline at end of file
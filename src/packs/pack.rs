@@ -0,0 +1,149 @@
+use std::cell::RefCell;
+use std::fs;
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+pub const PACK_MANIFEST_FILE: &str = "pack.json";
+pub const ZIP_PACK_EXTENSION: &str = "zip";
+
+#[derive(Error, Debug)]
+pub enum PackOpeningError {
+    #[error("Pack {0} is neither a directory nor a zip file")]
+    UnknownPackTypeError(String),
+    #[error("Pack {0} has no manifest")]
+    ManifestNotFoundError(String),
+    #[error("Invalid manifest of pack {0}: {1}")]
+    InvalidManifestError(String, String),
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PackManifest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+enum PackSource {
+    Directory(PathBuf),
+    Zip(RefCell<ZipArchive<File>>),
+}
+
+//Directory or zip file with pack.json in its root, files are requested by paths relative to the root
+pub struct Pack {
+    pub path: PathBuf,
+    pub manifest: PackManifest,
+    source: PackSource,
+}
+
+impl Pack {
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let source: PackSource = if path.is_dir() {
+            PackSource::Directory(path.to_path_buf())
+        } else if path.is_file() && path.extension().map_or(false, |extension| extension == ZIP_PACK_EXTENSION) {
+            PackSource::Zip(RefCell::new(ZipArchive::new(File::open(path)?)?))
+        } else {
+            return Err(Box::new(PackOpeningError::UnknownPackTypeError(path.display().to_string())));
+        };
+        let mut pack: Pack = Pack {
+            path: path.to_path_buf(),
+            manifest: PackManifest { name: String::new(), description: String::new() },
+            source,
+        };
+        if let Some(manifest_data) = pack.read_file(PACK_MANIFEST_FILE)? {
+            match serde_json::from_slice::<PackManifest>(&manifest_data) {
+                Ok(manifest) => {
+                    pack.manifest = manifest;
+                }
+                Err(error) => {
+                    return Err(Box::new(PackOpeningError::InvalidManifestError(path.display().to_string(), error.to_string())));
+                }
+            }
+        } else {
+            return Err(Box::new(PackOpeningError::ManifestNotFoundError(path.display().to_string())));
+        }
+        return Ok(pack);
+    }
+
+    //None if the pack does not provide the file
+    pub fn read_file(&self, file_name: &str) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        return match &self.source {
+            PackSource::Directory(dir_path) => {
+                match fs::read(dir_path.join(file_name)) {
+                    Ok(data) => Ok(Some(data)),
+                    Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+                    Err(error) => Err(Box::new(error)),
+                }
+            }
+            PackSource::Zip(archive) => {
+                match archive.borrow_mut().by_name(file_name) {
+                    Ok(mut zip_file) => {
+                        let mut data: Vec<u8> = Vec::new();
+                        zip_file.read_to_end(&mut data)?;
+                        Ok(Some(data))
+                    }
+                    Err(ZipError::FileNotFound) => Ok(None),
+                    Err(error) => Err(Box::new(error)),
+                }
+            }
+        }
+    }
+
+    //Zip packs are watched as a whole
+    pub fn get_watched_paths(&self, files_names: &[&str]) -> Vec<PathBuf> {
+        return match &self.source {
+            PackSource::Directory(dir_path) => {
+                let mut paths: Vec<PathBuf> = vec![dir_path.join(PACK_MANIFEST_FILE)];
+                for file_name in files_names {
+                    paths.push(dir_path.join(file_name));
+                }
+                paths
+            }
+            PackSource::Zip(_) => {
+                vec![self.path.clone()]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::packs::test_packs::{get_test_dir, write_dir_pack, write_zip_pack};
+    use super::*;
+
+    #[test]
+    fn zip_and_directory_packs_read_the_same_files() {
+        let dir_path: PathBuf = get_test_dir("pack_sources");
+        let files: [(&str, &[u8]); 2] = [("faces.json", b"[]"), ("scripts/test.rhai", b"let a = 1;")];
+        fs::create_dir_all(dir_path.join("dir_pack").join("scripts")).unwrap();
+        for pack_path in [write_dir_pack(&dir_path, "dir_pack", &files), write_zip_pack(&dir_path, "zip_pack", &files)] {
+            let pack: Pack = Pack::open(&pack_path).unwrap();
+            assert_eq!(pack.read_file("faces.json").unwrap(), Some(b"[]".to_vec()));
+            assert_eq!(pack.read_file("scripts/test.rhai").unwrap(), Some(b"let a = 1;".to_vec()));
+            assert_eq!(pack.read_file("meshes.json").unwrap(), None);
+        }
+        let dir_pack: Pack = Pack::open(&dir_path.join("dir_pack")).unwrap();
+        assert_eq!(dir_pack.manifest.name, "dir_pack");
+        assert_eq!(dir_pack.get_watched_paths(&["faces.json"]), vec![dir_path.join("dir_pack").join(PACK_MANIFEST_FILE), dir_path.join("dir_pack").join("faces.json")]);
+        let zip_pack: Pack = Pack::open(&dir_path.join("zip_pack.zip")).unwrap();
+        assert_eq!(zip_pack.manifest.name, "zip_pack");
+        assert_eq!(zip_pack.get_watched_paths(&["faces.json"]), vec![dir_path.join("zip_pack.zip")]);
+    }
+
+    #[test]
+    fn packs_without_valid_manifests_are_rejected() {
+        let dir_path: PathBuf = get_test_dir("pack_manifests");
+        fs::create_dir_all(dir_path.join("empty")).unwrap();
+        let empty_path: PathBuf = dir_path.join("empty");
+        assert_eq!(Pack::open(&empty_path).err().unwrap().to_string(), format!("Pack {} has no manifest", empty_path.display()));
+        fs::write(empty_path.join(PACK_MANIFEST_FILE), "{}").unwrap();
+        assert!(Pack::open(&empty_path).err().unwrap().to_string().starts_with(&format!("Invalid manifest of pack {}", empty_path.display())));
+        let text_path: PathBuf = dir_path.join("pack.txt");
+        fs::write(&text_path, "").unwrap();
+        assert_eq!(Pack::open(&text_path).err().unwrap().to_string(), format!("Pack {} is neither a directory nor a zip file", text_path.display()));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use image::RgbaImage;
+use thiserror::Error;
+use crate::packs::pack::Pack;
+
+#[derive(Error, Debug)]
+pub enum PackStackError {
+    #[error("Invalid packs list")]
+    InvalidPacksListError(),
+    #[error("Pack {0} is listed twice")]
+    DuplicatePackError(String),
+    #[error("No pack provides {0}")]
+    FileNotFoundError(String),
+    #[error("{1} of pack {0} is not a JSON array")]
+    DeserializationError(String, String),
+    #[error("Atlas {1} of pack {0} differs in size from the atlas of the packs below")]
+    AtlasSizeError(String, String),
+    #[error("Invalid definition in {1} of pack {0}: {2}")]
+    DefinitionError(String, String, String),
+}
+
+//Array elements of one definitions file of one pack
+pub struct PackDefinitions {
+    pub pack: String,
+    pub file_name: String,
+    pub values: Vec<serde_json::Value>,
+}
+
+impl PackDefinitions {
+    pub fn get_error(&self, error: Box<dyn std::error::Error>) -> Box<dyn std::error::Error> {
+        return Box::new(PackStackError::DefinitionError(self.pack.clone(), self.file_name.clone(), error.to_string()));
+    }
+}
+
+//Packs are ordered from the bottom to the top, upper packs override definitions of lower ones with the same names
+pub struct PackStack {
+    pub packs: Vec<Pack>,
+}
+
+impl PackStack {
+    //Packs list is a JSON array of packs paths
+    pub fn read_packs_list(packs_list_path: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        let packs_list_data = fs::read_to_string(packs_list_path)?;
+        let packs_paths: Vec<String> = match serde_json::from_str(&packs_list_data) {
+            Ok(packs_paths) => packs_paths,
+            Err(_) => {
+                return Err(Box::new(PackStackError::InvalidPacksListError()));
+            }
+        };
+        return Ok(packs_paths.iter().map(PathBuf::from).collect());
+    }
+
+    pub fn open(packs_paths: &[PathBuf]) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut packs: Vec<Pack> = Vec::new();
+        let mut packs_names: HashSet<String> = HashSet::new();
+        for pack_path in packs_paths {
+            let pack: Pack = Pack::open(pack_path)?;
+            if !packs_names.insert(pack.manifest.name.clone()) {
+                return Err(Box::new(PackStackError::DuplicatePackError(pack.manifest.name)));
+            }
+            packs.push(pack);
+        }
+        return Ok(Self { packs });
+    }
+
+    pub fn get_watched_paths(&self, files_names: &[&str]) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for pack in &self.packs {
+            paths.extend(pack.get_watched_paths(files_names));
+        }
+        return paths;
+    }
+
+    //Definitions of every pack providing file_name, from the bottom pack to the top one
+    pub fn read_definitions(&self, file_name: &str) -> Result<Vec<PackDefinitions>, Box<dyn std::error::Error>> {
+        let mut definitions: Vec<PackDefinitions> = Vec::new();
+        for pack in &self.packs {
+            if let Some(data) = pack.read_file(file_name)? {
+                let serialized: serde_json::Value = match serde_json::from_slice(&data) {
+                    Ok(serialized) => serialized,
+                    Err(error) => {
+                        return Err(Box::new(PackStackError::DefinitionError(pack.manifest.name.clone(), String::from(file_name), error.to_string())));
+                    }
+                };
+                if let serde_json::Value::Array(values) = serialized {
+                    definitions.push(PackDefinitions {
+                        pack: pack.manifest.name.clone(),
+                        file_name: String::from(file_name),
+                        values,
+                    });
+                } else {
+                    return Err(Box::new(PackStackError::DeserializationError(pack.manifest.name.clone(), String::from(file_name))));
+                }
+            }
+        }
+        return Ok(definitions);
+    }
+
+    //Atlases of all packs are layered, so a pack only has to fill the tiles it adds or overrides
+    pub fn load_atlas(&self, file_name: &str) -> Result<RgbaImage, Box<dyn std::error::Error>> {
+        let mut atlas: Option<RgbaImage> = None;
+        for pack in &self.packs {
+            if let Some(data) = pack.read_file(file_name)? {
+                let layer: RgbaImage = image::load_from_memory(&data)?.into_rgba8();
+                if let Some(atlas) = &mut atlas {
+                    if atlas.dimensions() != layer.dimensions() {
+                        return Err(Box::new(PackStackError::AtlasSizeError(pack.manifest.name.clone(), String::from(file_name))));
+                    }
+                    for (atlas_pixel, layer_pixel) in atlas.pixels_mut().zip(layer.pixels()) {
+                        if layer_pixel[3] != 0 {
+                            *atlas_pixel = *layer_pixel;
+                        }
+                    }
+                } else {
+                    atlas = Some(layer);
+                }
+            }
+        }
+        return if let Some(atlas) = atlas {
+            Ok(atlas)
+        } else {
+            Err(Box::new(PackStackError::FileNotFoundError(String::from(file_name))))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use image::{ImageOutputFormat, Rgba};
+    use crate::packs::test_packs::{get_test_dir, write_dir_pack, write_zip_pack};
+    use super::*;
+
+    fn get_png_data(image: &RgbaImage) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+        image.write_to(&mut Cursor::new(&mut data), ImageOutputFormat::Png).unwrap();
+        return data;
+    }
+
+    #[test]
+    fn definitions_are_read_from_the_bottom_pack_to_the_top() {
+        let dir_path: PathBuf = get_test_dir("stack_definitions");
+        let bottom_path: PathBuf = write_dir_pack(&dir_path, "bottom", &[("meshes.json", br#"[{"name": "a"}]"#)]);
+        let middle_path: PathBuf = write_dir_pack(&dir_path, "middle", &[]);
+        let top_path: PathBuf = write_zip_pack(&dir_path, "top", &[("meshes.json", br#"[{"name": "b"}, {"name": "c"}]"#)]);
+        let packs: PackStack = PackStack::open(&[bottom_path, middle_path, top_path]).unwrap();
+        let definitions: Vec<PackDefinitions> = packs.read_definitions("meshes.json").unwrap();
+        assert_eq!(definitions.iter().map(|pack_definitions| pack_definitions.pack.as_str()).collect::<Vec<&str>>(), vec!["bottom", "top"]);
+        assert_eq!(definitions[1].values.len(), 2);
+        assert_eq!(definitions[1].file_name, "meshes.json");
+        assert_eq!(packs.read_definitions("faces.json").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn definitions_must_be_json_arrays() {
+        let dir_path: PathBuf = get_test_dir("stack_arrays");
+        let pack_path: PathBuf = write_dir_pack(&dir_path, "test", &[("meshes.json", b"{}"), ("faces.json", b"[")]);
+        let packs: PackStack = PackStack::open(&[pack_path]).unwrap();
+        assert_eq!(packs.read_definitions("meshes.json").err().unwrap().to_string(), "meshes.json of pack test is not a JSON array");
+        assert!(packs.read_definitions("faces.json").err().unwrap().to_string().starts_with("Invalid definition in faces.json of pack test"));
+    }
+
+    #[test]
+    fn duplicate_pack_names_are_rejected() {
+        let dir_path: PathBuf = get_test_dir("stack_duplicates");
+        let dir_pack_path: PathBuf = write_dir_pack(&dir_path, "test", &[]);
+        let zip_pack_path: PathBuf = write_zip_pack(&dir_path, "test", &[]);
+        assert_eq!(PackStack::open(&[dir_pack_path, zip_pack_path]).err().unwrap().to_string(), "Pack test is listed twice");
+    }
+
+    #[test]
+    fn atlases_are_layered_by_opaque_pixels() {
+        let dir_path: PathBuf = get_test_dir("stack_atlases");
+        let red: Rgba<u8> = Rgba([0xFF, 0x00, 0x00, 0xFF]);
+        let blue: Rgba<u8> = Rgba([0x00, 0x00, 0xFF, 0xFF]);
+        let mut top_atlas: RgbaImage = RgbaImage::new(2, 2);
+        top_atlas.put_pixel(1, 0, blue);
+        let bottom_path: PathBuf = write_dir_pack(&dir_path, "bottom", &[("atlas.png", &get_png_data(&RgbaImage::from_pixel(2, 2, red)))]);
+        let top_path: PathBuf = write_zip_pack(&dir_path, "top", &[("atlas.png", &get_png_data(&top_atlas))]);
+        let atlas: RgbaImage = PackStack::open(&[bottom_path.clone(), top_path]).unwrap().load_atlas("atlas.png").unwrap();
+        assert_eq!(*atlas.get_pixel(0, 0), red);
+        assert_eq!(*atlas.get_pixel(1, 0), blue);
+        assert_eq!(*atlas.get_pixel(1, 1), red);
+        let small_path: PathBuf = write_dir_pack(&dir_path, "small", &[("atlas.png", &get_png_data(&RgbaImage::new(1, 1)))]);
+        let packs: PackStack = PackStack::open(&[bottom_path, small_path]).unwrap();
+        assert_eq!(packs.load_atlas("atlas.png").err().unwrap().to_string(), "Atlas atlas.png of pack small differs in size from the atlas of the packs below");
+        assert_eq!(packs.load_atlas("missing.png").err().unwrap().to_string(), "No pack provides missing.png");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,46 @@
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+use crate::packs::pack::PACK_MANIFEST_FILE;
+
+//Empty directory of the test under the system temp directory, every test must use its own name
+pub fn get_test_dir(test_name: &str) -> PathBuf {
+    let dir_path: PathBuf = std::env::temp_dir().join(format!("cubecode_tests_{}", std::process::id())).join(test_name);
+    if dir_path.exists() {
+        fs::remove_dir_all(&dir_path).unwrap();
+    }
+    fs::create_dir_all(&dir_path).unwrap();
+    return dir_path;
+}
+
+fn get_manifest(name: &str) -> String {
+    return format!("{{\"name\": \"{}\"}}", name);
+}
+
+//Directory pack with a manifest named name and the files as (path, data)
+pub fn write_dir_pack(dir_path: &Path, name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+    let pack_path: PathBuf = dir_path.join(name);
+    fs::create_dir_all(&pack_path).unwrap();
+    fs::write(pack_path.join(PACK_MANIFEST_FILE), get_manifest(name)).unwrap();
+    for (file_name, data) in files {
+        fs::write(pack_path.join(file_name), data).unwrap();
+    }
+    return pack_path;
+}
+
+pub fn write_zip_pack(dir_path: &Path, name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+    let pack_path: PathBuf = dir_path.join(format!("{}.zip", name));
+    let mut writer: ZipWriter<File> = ZipWriter::new(File::create(&pack_path).unwrap());
+    let options: FileOptions = FileOptions::default().compression_method(CompressionMethod::Stored);
+    writer.start_file(PACK_MANIFEST_FILE, options).unwrap();
+    writer.write_all(get_manifest(name).as_bytes()).unwrap();
+    for (file_name, data) in files {
+        writer.start_file(*file_name, options).unwrap();
+        writer.write_all(data).unwrap();
+    }
+    writer.finish().unwrap();
+    return pack_path;
+}
\ No newline at end of file
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use crate::render::types::Vec3f;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Aabb {
+    pub min: Vec3f,
+    pub max: Vec3f,
+}
+
+pub const FULL_BLOCK_AABB: Aabb = Aabb { min: [0.0, 0.0, 0.0], max: [1.0, 1.0, 1.0] };
+
+impl Aabb {
+    pub fn new(min: Vec3f, max: Vec3f) -> Self {
+        return Self { min, max };
+    }
+
+    pub fn from_center(center: &Vec3f, half_size: &Vec3f) -> Self {
+        return Self {
+            min: [center[0] - half_size[0], center[1] - half_size[1], center[2] - half_size[2]],
+            max: [center[0] + half_size[0], center[1] + half_size[1], center[2] + half_size[2]],
+        };
+    }
+
+    pub fn offset(&self, offset: &Vec3f) -> Self {
+        return Self {
+            min: [self.min[0] + offset[0], self.min[1] + offset[1], self.min[2] + offset[2]],
+            max: [self.max[0] + offset[0], self.max[1] + offset[1], self.max[2] + offset[2]],
+        };
+    }
+
+    //Touching boxes do not intersect
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        return self.min[0] < other.max[0] && self.max[0] > other.min[0] &&
+            self.min[1] < other.max[1] && self.max[1] > other.min[1] &&
+            self.min[2] < other.max[2] && self.max[2] > other.min[2];
+    }
+
+    //Slab method, returns the distance along dir and the axis of the entered side, axis is -1 if origin is inside
+    pub fn ray_intersect(&self, origin: &Vec3f, dir: &Vec3f) -> Option<(f32, i8)> {
+        let mut t_min: f32 = f32::NEG_INFINITY;
+        let mut t_max: f32 = f32::INFINITY;
+        let mut axis: i8 = -1;
+        for axis_ind in 0..3usize {
+            if dir[axis_ind] == 0.0 {
+                if origin[axis_ind] < self.min[axis_ind] || origin[axis_ind] > self.max[axis_ind] {
+                    return None;
+                }
+            } else {
+                let t_first: f32 = (self.min[axis_ind] - origin[axis_ind]) / dir[axis_ind];
+                let t_second: f32 = (self.max[axis_ind] - origin[axis_ind]) / dir[axis_ind];
+                let (t_near, t_far): (f32, f32) = if t_first < t_second { (t_first, t_second) } else { (t_second, t_first) };
+                if t_near > t_min {
+                    t_min = t_near;
+                    axis = axis_ind as i8;
+                }
+                if t_far < t_max {
+                    t_max = t_far;
+                }
+            }
+        }
+        return if t_min > t_max || t_max < 0.0 {
+            None
+        } else if t_min < 0.0 {
+            Some((0.0, -1))
+        } else {
+            Some((t_min, axis))
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,38 @@
+use crate::physics::aabb::Aabb;
+use crate::physics::fluid_solver::get_fluid_height;
+use crate::render::blocks_loader::{BlocksLoader, FluidData};
+use crate::render::types::Vec3i;
+use crate::world::World;
+
+//Source of voxel collision boxes, so solvers can run against synthetic grids too
+pub trait Collider {
+    //Appends world space boxes of the cell at pos
+    fn get_collision_boxes(&self, pos: &Vec3i, boxes: &mut Vec<Aabb>);
+
+    //Fluid of the cell at pos with its surface height in block space
+    fn get_fluid(&self, _pos: &Vec3i) -> Option<(FluidData, f32)> {
+        return None;
+    }
+}
+
+pub struct WorldCollider<'a> {
+    pub world: &'a World,
+    pub blocks_loader: &'a BlocksLoader,
+}
+
+impl<'a> WorldCollider<'a> {
+    pub fn new(world: &'a World, blocks_loader: &'a BlocksLoader) -> Self {
+        return Self { world, blocks_loader };
+    }
+}
+
+impl<'a> Collider for WorldCollider<'a> {
+    fn get_collision_boxes(&self, pos: &Vec3i, boxes: &mut Vec<Aabb>) {
+        self.world.get_collision_boxes(self.blocks_loader, pos, boxes);
+    }
+
+    fn get_fluid(&self, pos: &Vec3i) -> Option<(FluidData, f32)> {
+        let fluid: FluidData = self.blocks_loader.get_block(self.world.get_block_at(pos)?).fluid?;
+        return Some((fluid, get_fluid_height(self.world.get_state_at(pos)?)));
+    }
+}
\ No newline at end of file
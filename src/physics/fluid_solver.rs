@@ -0,0 +1,130 @@
+use crate::render::blocks_loader::{BlocksLoader, FluidData};
+use crate::render::light::light_solver::LightSolver;
+use crate::render::types::{Vec3b, Vec3ub};
+use crate::world::World;
+
+//Fluid block state: level is the distance from the source (0 is the source), falling fluids have a fluid above
+pub const FLUID_LEVEL_MASK: u8 = 0x07;
+pub const FLUID_FALLING: u8 = 0x08;
+pub const FLUID_SOURCE_HEIGHT: f32 = 0.875;
+
+const NEIGHBOR_TOP: Vec3b = [0, 1, 0];
+const NEIGHBOR_BOTTOM: Vec3b = [0, -1, 0];
+const HORIZONTAL_NEIGHBORHOOD: [Vec3b; 4] = [[0, 0, -1], [0, 0, 1], [-1, 0, 0], [1, 0, 0]];
+const NEIGHBORHOOD: [Vec3b; 7] = [[0, 0, 0], [0, 0, -1], [0, 0, 1], [0, -1, 0], [0, 1, 0], [-1, 0, 0], [1, 0, 0]];
+
+//Surface height of the fluid in block space
+pub fn get_fluid_height(state: u8) -> f32 {
+    return if (state & FLUID_FALLING) != 0 {
+        1.0
+    } else {
+        FLUID_SOURCE_HEIGHT * ((8 - (state & FLUID_LEVEL_MASK)) as f32) / 8.0
+    }
+}
+
+//Level the neighbors of the fluid continue from
+fn get_flow_level(state: u8) -> u8 {
+    return if (state & FLUID_FALLING) != 0 { 0 } else { state & FLUID_LEVEL_MASK };
+}
+
+//Fluid blocks are updated by scheduled ticks of the world
+pub struct FluidSolver;
+
+impl FluidSolver {
+    //Schedules the fluids at pos and around it, should be called after every block change
+    pub fn on_block_changed(world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub) {
+        for neigh in NEIGHBORHOOD {
+            if let Some(neigh_pos) = LightSolver::get_neighbor_pos(pos, &neigh) {
+                if let Some(fluid) = &blocks_loader.get_block(world.get_block(&neigh_pos)).fluid {
+                    world.ticks.schedule(&neigh_pos, fluid.tick_delay);
+                }
+            }
+        }
+    }
+
+    //State the flowing fluid at pos should have, None if nothing feeds it anymore
+    fn get_flowing_state(world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub, lid: u16, fluid: &FluidData) -> Option<u8> {
+        if let Some(top_pos) = LightSolver::get_neighbor_pos(pos, &NEIGHBOR_TOP) {
+            if world.get_block(&top_pos) == lid {
+                return Some(FLUID_FALLING);
+            }
+        }
+        let mut min_level: Option<u8> = None;
+        let mut sources_cnt: u8 = 0;
+        for neigh in HORIZONTAL_NEIGHBORHOOD {
+            if let Some(neigh_pos) = LightSolver::get_neighbor_pos(pos, &neigh) {
+                if world.get_block(&neigh_pos) == lid {
+                    let neigh_state: u8 = world.get_state(&neigh_pos);
+                    if neigh_state == 0 {
+                        sources_cnt += 1;
+                    }
+                    let neigh_level: u8 = get_flow_level(neigh_state);
+                    min_level = Some(min_level.map_or(neigh_level, |level| level.min(neigh_level)));
+                }
+            }
+        }
+        if fluid.regenerates && sources_cnt >= 2 {
+            if let Some(bottom_pos) = LightSolver::get_neighbor_pos(pos, &NEIGHBOR_BOTTOM) {
+                let bottom_lid: u16 = world.get_block(&bottom_pos);
+                if blocks_loader.get_block(bottom_lid).collision.len() != 0 || (bottom_lid == lid && world.get_state(&bottom_pos) == 0) {
+                    return Some(0);
+                }
+            }
+        }
+        return match min_level {
+            Some(level) if level < fluid.flow_distance => Some(level + 1),
+            _ => None,
+        }
+    }
+
+    fn set_fluid(world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub, lid: u16, state: u8, changed: &mut Vec<Vec3ub>) {
+        world.set_block_with_state(pos, lid, state);
+        changed.push(*pos);
+        Self::on_block_changed(world, blocks_loader, pos);
+    }
+
+    pub fn update(world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub, changed: &mut Vec<Vec3ub>) {
+        let lid: u16 = world.get_block(pos);
+        let fluid: FluidData = match blocks_loader.get_block(lid).fluid {
+            Some(fluid) => fluid,
+            None => return,
+        };
+        let mut state: u8 = world.get_state(pos);
+        if state != 0 {
+            match Self::get_flowing_state(world, blocks_loader, pos, lid, &fluid) {
+                None => {
+                    Self::set_fluid(world, blocks_loader, pos, blocks_loader.air_block.lid, 0, changed);
+                    return;
+                }
+                Some(new_state) => {
+                    if new_state != state {
+                        state = new_state;
+                        Self::set_fluid(world, blocks_loader, pos, lid, state, changed);
+                    }
+                }
+            }
+        }
+        //Falling first, fluids spread sideways only over something
+        if let Some(bottom_pos) = LightSolver::get_neighbor_pos(pos, &NEIGHBOR_BOTTOM) {
+            let bottom_lid: u16 = world.get_block(&bottom_pos);
+            if bottom_lid == blocks_loader.air_block.lid {
+                Self::set_fluid(world, blocks_loader, &bottom_pos, lid, FLUID_FALLING, changed);
+                return;
+            }
+            if bottom_lid == lid {
+                return;
+            }
+        }
+        let level: u8 = get_flow_level(state);
+        if level >= fluid.flow_distance {
+            return;
+        }
+        for neigh in HORIZONTAL_NEIGHBORHOOD {
+            if let Some(neigh_pos) = LightSolver::get_neighbor_pos(pos, &neigh) {
+                if world.get_block(&neigh_pos) == blocks_loader.air_block.lid {
+                    Self::set_fluid(world, blocks_loader, &neigh_pos, lid, level + 1, changed);
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -1,10 +1,20 @@
 use crate::render::types::Vec3f;
 
+const DEFAULT_FRICTION: f32 = 8.0;
+const DEFAULT_STEP_HEIGHT: f32 = 0.5;
+
+#[derive(Clone)]
 pub struct HitBox {
     pub pos: Vec3f,
+    pub prev_pos: Vec3f, //position before the last step
     pub vel: Vec3f,
     pub half_size: Vec3f,
+    pub friction: f32, //horizontal velocity decay per time unit while grounded
+    pub step_height: f32, //highest obstacle climbed without jumping while grounded
+    pub gravity_scale: f32,
+    pub collidable: bool, //moves through blocks if false
     pub grounded: bool,
+    pub submerged: f32, //part of the hitbox inside fluids
     pub shifting: bool,
 }
 
@@ -12,10 +22,24 @@ impl HitBox {
     pub fn new(position: Vec3f, half_size: Vec3f) -> Self {
         return Self {
             pos: position,
+            prev_pos: position,
             vel: [0.0, 0.0, 0.0],
             half_size,
+            friction: DEFAULT_FRICTION,
+            step_height: DEFAULT_STEP_HEIGHT,
+            gravity_scale: 1.0,
+            collidable: true,
             grounded: false,
+            submerged: 0.0,
             shifting: false,
         };
     }
+
+    pub fn get_interpolated_pos(&self, alpha: f32) -> Vec3f {
+        return [
+            self.prev_pos[0] + (self.pos[0] - self.prev_pos[0]) * alpha,
+            self.prev_pos[1] + (self.pos[1] - self.prev_pos[1]) * alpha,
+            self.prev_pos[2] + (self.pos[2] - self.prev_pos[2]) * alpha,
+        ];
+    }
 }
\ No newline at end of file
@@ -1,2 +1,6 @@
+pub mod aabb;
+pub mod collider;
+pub mod fluid_solver;
 pub mod hitbox;
-pub mod physics_solver;
\ No newline at end of file
+pub mod physics_solver;
+pub mod raycast;
\ No newline at end of file
@@ -1,9 +1,17 @@
-use std::collections::HashSet;
+use crate::physics::aabb::Aabb;
+use crate::physics::collider::Collider;
 use crate::physics::hitbox::HitBox;
-use crate::render::types::{len_vec3f, norm_vec3f, sub_vec3f, Vec3f, Vec3i};
-use crate::world::World;
+use crate::render::types::{Vec3f, Vec3i};
 
-const E: f32 = 0.015625; //ERROR
+const CONTACT_E: f32 = 0.00001; //boxes closer than this touch instead of overlapping
+const GROUND_PROBE: f32 = 0.015625; //depth of the ground check under shifting hitboxes
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SweepResult {
+    pub disp: Vec3f, //displacement passed without penetrating boxes
+    pub normal: Vec3i, //contact normal, axes that were not blocked are 0
+    pub time_of_impact: f32, //part of the requested displacement passed before the first contact, 1 without contacts
+}
 
 pub struct PhysicsSolver {
     gravity: Vec3f,
@@ -14,141 +22,176 @@ impl PhysicsSolver {
         return Self {gravity};
     }
 
-    pub fn step(&self, world: &World, hitbox: &mut HitBox, delta: f32, steps_cnt: u32) {
-        let mut ltmppos: Vec3f = [hitbox.pos[0], hitbox.pos[1], hitbox.pos[2]];
-        let step: f32 = delta / (steps_cnt as f32);
-        for ind in 0_u32..steps_cnt {
-            let mut pos: &mut Vec3f = &mut hitbox.pos;
-            let mut vel: &mut Vec3f = &mut hitbox.vel;
-            let hsz: &Vec3f = &hitbox.half_size;
-            vel[0] += self.gravity[0] * step;
-            vel[1] += self.gravity[1] * step;
-            vel[2] += self.gravity[2] * step;
-
-            let last_pos_x: f32 = pos[0];
-            let last_pos_z: f32 = pos[2];
-
-
-
-            //x
-            if vel[0] < 0.0 {
-                for y_pos in ((pos[1] - hsz[1] + E).floor() as i32)..=((pos[1] + hsz[1] - E).floor() as i32) {
-                    for z_pos in ((pos[2] - hsz[2] + E).floor() as i32)..=((pos[2] + hsz[2] - E).floor() as i32) {
-                        let x_pos: i32 = ((pos[0] - hsz[0] - E).floor() as i32);
-                        if world.is_obstacle(&[x_pos, y_pos, z_pos]) {
-                            vel[0] = 0.0;
-                            pos[0] = (x_pos as f32) + 1.0 + hsz[0] + E;
-                            break;
-                        }
-                    }
+    //Boxes of all cells overlapped by area
+    fn collect_boxes(collider: &dyn Collider, area: &Aabb, boxes: &mut Vec<Aabb>) {
+        boxes.clear();
+        for x_pos in (area.min[0].floor() as i32)..=(area.max[0].floor() as i32) {
+            for y_pos in (area.min[1].floor() as i32)..=(area.max[1].floor() as i32) {
+                for z_pos in (area.min[2].floor() as i32)..=(area.max[2].floor() as i32) {
+                    collider.get_collision_boxes(&[x_pos, y_pos, z_pos], boxes);
                 }
             }
-            if vel[0] > 0.0 {
-                for y_pos in ((pos[1] - hsz[1] + E).floor() as i32)..=((pos[1] + hsz[1] - E).floor() as i32) {
-                    for z_pos in ((pos[2] - hsz[2] + E).floor() as i32)..=((pos[2] + hsz[2] - E).floor() as i32) {
-                        let x_pos = ((pos[0] + hsz[0] + E).floor() as i32);
-                        if world.is_obstacle(&[x_pos, y_pos, z_pos]) {
-                            vel[0] = 0.0;
-                            pos[0] = ((x_pos as f32) - hsz[0]) - E;
-                            break;
-                        }
-                    }
-                }
+        }
+    }
+
+    //Part of disp the area can move along axis before touching one of boxes
+    fn clip_axis(area: &Aabb, boxes: &[Aabb], axis: usize, disp: f32) -> f32 {
+        let mut clipped: f32 = disp;
+        for collision_box in boxes {
+            let is_overlapped: bool = (0..3usize).filter(|other_axis| *other_axis != axis).all(|other_axis|
+                collision_box.max[other_axis] > area.min[other_axis] + CONTACT_E && collision_box.min[other_axis] < area.max[other_axis] - CONTACT_E);
+            if !is_overlapped {
+                continue;
             }
-            //
-
-            //z
-            if vel[2] < 0.0 {
-                for y_pos in ((pos[1] - hsz[1] + E).floor() as i32)..=((pos[1] + hsz[1] - E).floor() as i32) {
-                    for x_pos in ((pos[0] - hsz[0] + E).floor() as i32)..=((pos[0] + hsz[0] - E).floor() as i32) {
-                        let z_pos: i32 = ((pos[2] - hsz[2] - E).floor() as i32);
-                        if world.is_obstacle(&[x_pos, y_pos, z_pos]) {
-                            vel[2] = 0.0;
-                            pos[2] = (z_pos as f32) + 1.0 + hsz[2] + E;
-                            break;
-                        }
-                    }
-                }
+            if clipped > 0.0 && collision_box.min[axis] >= area.max[axis] - CONTACT_E {
+                clipped = clipped.min(collision_box.min[axis] - area.max[axis]);
+            } else if clipped < 0.0 && collision_box.max[axis] <= area.min[axis] + CONTACT_E {
+                clipped = clipped.max(collision_box.max[axis] - area.min[axis]);
             }
-            if vel[2] > 0.0 {
-                for y_pos in ((pos[1] - hsz[1] + E).floor() as i32)..=((pos[1] + hsz[1] - E).floor() as i32) {
-                    for x_pos in ((pos[0] - hsz[0] + E).floor() as i32)..=((pos[0] + hsz[0] - E).floor() as i32) {
-                        let z_pos = ((pos[2] + hsz[2] + E).floor() as i32);
-                        if world.is_obstacle(&[x_pos, y_pos, z_pos]) {
-                            vel[2] = 0.0;
-                            pos[2] = ((z_pos as f32) - hsz[2]) - E;
-                            break;
-                        }
-                    }
-                }
+        }
+        return clipped;
+    }
+
+    //Resolves y, then x, then z against every box of the swept region, so fast boxes can not tunnel
+    pub fn sweep(collider: &dyn Collider, area: &Aabb, disp: &Vec3f) -> SweepResult {
+        let mut swept_area: Aabb = *area;
+        for axis in 0..3usize {
+            if disp[axis] < 0.0 {
+                swept_area.min[axis] += disp[axis];
+            } else {
+                swept_area.max[axis] += disp[axis];
             }
-            //
+        }
+        let mut boxes: Vec<Aabb> = Vec::new();
+        Self::collect_boxes(collider, &swept_area, &mut boxes);
 
-            //y
-            hitbox.grounded = false;
-            if vel[1] < 0.0 {
-                'end:
-                for x_pos in ((pos[0] - hsz[0] + E).floor() as i32)..=((pos[0] + hsz[0] - E).floor() as i32) {
-                    for z_pos in ((pos[2] - hsz[2] + E).floor() as i32)..=((pos[2] + hsz[2] - E).floor() as i32) {
-                        let y_pos: i32 = ((pos[1] - hsz[1] - E).floor() as i32);
-                        if world.is_obstacle(&[x_pos, y_pos, z_pos]) {
-                            vel[1] = 0.0;
-                            pos[1] = (y_pos as f32) + 1.0 + hsz[1];
-                            let f: f32 = 8.0; //friction
-                            vel[0] *= 0.0_f32.max(1.0 - (step * f));
-                            vel[2] *= 0.0_f32.max(1.0 - (step * f));
-                            hitbox.grounded = true;
-                            break 'end;
-                        }
-                    }
-                }
+        let mut res: SweepResult = SweepResult { disp: [0.0, 0.0, 0.0], normal: [0, 0, 0], time_of_impact: 1.0 };
+        let mut cur_area: Aabb = *area;
+        for axis in [1usize, 0, 2] {
+            if disp[axis] == 0.0 {
+                continue;
             }
-            if vel[1] > 0.0 {
-                for x_pos in ((pos[0] - hsz[0] + E).floor() as i32)..=((pos[0] + hsz[0] - E).floor() as i32) {
-                    for z_pos in ((pos[2] - hsz[2] + E).floor() as i32)..=((pos[2] + hsz[2] - E).floor() as i32) {
-                        let y_pos = ((pos[1] + hsz[1] + E).floor() as i32);
-                        if world.is_obstacle(&[x_pos, y_pos, z_pos]) {
-                            vel[1] = 0.0;
-                            pos[1] = ((y_pos as f32) - hsz[1]) + E;
-                            break;
-                        }
-                    }
-                }
+            let clipped: f32 = Self::clip_axis(&cur_area, &boxes, axis, disp[axis]);
+            if clipped != disp[axis] {
+                res.normal[axis] = if disp[axis] > 0.0 { -1 } else { 1 };
+                res.time_of_impact = res.time_of_impact.min((clipped / disp[axis]).max(0.0));
             }
-            //
+            cur_area.min[axis] += clipped;
+            cur_area.max[axis] += clipped;
+            res.disp[axis] = clipped;
+        }
+        return res;
+    }
 
-            pos[0] += vel[0] * step;
-            pos[1] += vel[1] * step;
-            pos[2] += vel[2] * step;
+    //Moves the area up by step_height, then horizontally, then back down onto the obstacle
+    fn sweep_step_up(collider: &dyn Collider, area: &Aabb, disp: &Vec3f, step_height: f32) -> SweepResult {
+        let up_res: SweepResult = Self::sweep(collider, area, &[0.0, step_height, 0.0]);
+        let raised_area: Aabb = area.offset(&up_res.disp);
+        let horizontal_res: SweepResult = Self::sweep(collider, &raised_area, &[disp[0], 0.0, disp[2]]);
+        let moved_area: Aabb = raised_area.offset(&horizontal_res.disp);
+        let down_res: SweepResult = Self::sweep(collider, &moved_area, &[0.0, disp[1].min(0.0) - up_res.disp[1], 0.0]);
+        return SweepResult {
+            disp: [horizontal_res.disp[0], up_res.disp[1] + down_res.disp[1], horizontal_res.disp[2]],
+            normal: [horizontal_res.normal[0], down_res.normal[1], horizontal_res.normal[2]],
+            time_of_impact: horizontal_res.time_of_impact,
+        };
+    }
 
-            if hitbox.shifting && hitbox.grounded {
-                let y_pos: i32 = ((pos[1] - hsz[1]) - E).floor() as i32;
-                hitbox.grounded = false;
+    fn has_ground(collider: &dyn Collider, area: &Aabb) -> bool {
+        let probe: Aabb = Aabb::new([area.min[0], area.min[1] - GROUND_PROBE, area.min[2]], [area.max[0], area.min[1], area.max[2]]);
+        let mut boxes: Vec<Aabb> = Vec::new();
+        Self::collect_boxes(collider, &probe, &mut boxes);
+        return boxes.iter().any(|collision_box| collision_box.intersects(&probe));
+    }
 
-                for x_pos in ((last_pos_x - hsz[0] + E).floor() as i32)..=((last_pos_x + hsz[0] - E).floor() as i32) {
-                    for z_pos in ((pos[2] - hsz[2] + E).floor() as i32)..=((pos[2] + hsz[2] - E).floor() as i32) {
-                        if world.is_obstacle(&[x_pos, y_pos, z_pos]) {
-                            hitbox.grounded = true;
-                            break;
+    //Returns the submerged part of the area with buoyancy and drag of the fluids weighted by their parts
+    fn get_submersion(collider: &dyn Collider, area: &Aabb) -> (f32, f32, f32) {
+        let volume: f32 = (area.max[0] - area.min[0]) * (area.max[1] - area.min[1]) * (area.max[2] - area.min[2]);
+        if volume <= 0.0 {
+            return (0.0, 0.0, 0.0);
+        }
+        let mut submerged: f32 = 0.0;
+        let mut buoyancy: f32 = 0.0;
+        let mut drag: f32 = 0.0;
+        for x_pos in (area.min[0].floor() as i32)..=(area.max[0].floor() as i32) {
+            for y_pos in (area.min[1].floor() as i32)..=(area.max[1].floor() as i32) {
+                for z_pos in (area.min[2].floor() as i32)..=(area.max[2].floor() as i32) {
+                    if let Some((fluid, height)) = collider.get_fluid(&[x_pos, y_pos, z_pos]) {
+                        let fluid_box: Aabb = Aabb::new([x_pos as f32, y_pos as f32, z_pos as f32], [(x_pos + 1) as f32, (y_pos as f32) + height, (z_pos + 1) as f32]);
+                        let mut part: f32 = 1.0 / volume;
+                        for axis in 0..3usize {
+                            part *= (area.max[axis].min(fluid_box.max[axis]) - area.min[axis].max(fluid_box.min[axis])).max(0.0);
                         }
+                        submerged += part;
+                        buoyancy += part * fluid.buoyancy;
+                        drag += part * fluid.drag;
                     }
                 }
-                if !hitbox.grounded {pos[2] = last_pos_z;}
+            }
+        }
+        return (submerged, buoyancy, drag);
+    }
 
-                for x_pos in ((pos[0] - hsz[0] + E).floor() as i32)..=((pos[0] + hsz[0] - E).floor() as i32) {
-                    for z_pos in ((last_pos_z - hsz[2] + E).floor() as i32)..=((last_pos_z + hsz[2] - E).floor() as i32) {
-                        if world.is_obstacle(&[x_pos, y_pos, z_pos]) {
-                            hitbox.grounded = true;
-                            break;
-                        }
-                    }
-                }
-                if !hitbox.grounded {
-                    pos[0] = last_pos_x;
+    pub fn step(&self, collider: &dyn Collider, hitbox: &mut HitBox, delta: f32) -> SweepResult {
+        hitbox.prev_pos = hitbox.pos;
+        let (submerged, buoyancy, drag): (f32, f32, f32) = Self::get_submersion(collider, &Aabb::from_center(&hitbox.pos, &hitbox.half_size));
+        hitbox.submerged = submerged;
+        //buoyancy pushes against gravity
+        let gravity_scale: f32 = hitbox.gravity_scale * (1.0 - buoyancy);
+        hitbox.vel[0] += self.gravity[0] * gravity_scale * delta;
+        hitbox.vel[1] += self.gravity[1] * gravity_scale * delta;
+        hitbox.vel[2] += self.gravity[2] * gravity_scale * delta;
+        if drag > 0.0 {
+            let decay: f32 = (-drag * delta).exp();
+            for axis in 0..3usize {
+                hitbox.vel[axis] *= decay;
+            }
+        }
+
+        let mut disp: Vec3f = [hitbox.vel[0] * delta, hitbox.vel[1] * delta, hitbox.vel[2] * delta];
+        if !hitbox.collidable {
+            for axis in 0..3usize {
+                hitbox.pos[axis] += disp[axis];
+            }
+            hitbox.grounded = false;
+            return SweepResult { disp, normal: [0, 0, 0], time_of_impact: 1.0 };
+        }
+        let area: Aabb = Aabb::from_center(&hitbox.pos, &hitbox.half_size);
+        if hitbox.shifting && hitbox.grounded {
+            //x and z are checked apart, so the hitbox slides along edges
+            for axis in [0usize, 2] {
+                let mut offset: Vec3f = [0.0, 0.0, 0.0];
+                offset[axis] = disp[axis];
+                if disp[axis] != 0.0 && !Self::has_ground(collider, &area.offset(&offset)) {
+                    disp[axis] = 0.0;
                 }
-                hitbox.grounded = true;
             }
         }
+
+        let mut res: SweepResult = Self::sweep(collider, &area, &disp);
+        //shifting hitboxes hold their level like they hold edges, so they don't step up
+        if hitbox.grounded && !hitbox.shifting && hitbox.step_height > 0.0 && disp[1] <= 0.0 && (res.normal[0] != 0 || res.normal[2] != 0) {
+            let step_res: SweepResult = Self::sweep_step_up(collider, &area, &disp, hitbox.step_height);
+            //stepping is taken only if it lands on ground and gets further
+            let res_dist: f32 = res.disp[0] * res.disp[0] + res.disp[2] * res.disp[2];
+            let step_dist: f32 = step_res.disp[0] * step_res.disp[0] + step_res.disp[2] * step_res.disp[2];
+            if step_res.normal[1] == 1 && step_dist > res_dist {
+                res = step_res;
+            }
+        }
+        for axis in 0..3usize {
+            hitbox.pos[axis] += res.disp[axis];
+            if res.normal[axis] != 0 {
+                hitbox.vel[axis] = 0.0;
+            }
+        }
+        hitbox.grounded = res.normal[1] == 1;
+        if hitbox.grounded {
+            let decay: f32 = (-hitbox.friction * delta).exp();
+            hitbox.vel[0] *= decay;
+            hitbox.vel[2] *= decay;
+        }
+        return res;
     }
 
     //TODO CALLING VIA GETTERS AND SETTERS
@@ -159,4 +202,146 @@ impl PhysicsSolver {
                 pos[1] >= ((hpos[1] - hsz[1]).floor() as i32) && pos[1] <= ((hpos[1] + hsz[1]).floor() as i32) &&
                 pos[2] >= ((hpos[2] - hsz[2]).floor() as i32) && pos[2] <= ((hpos[2] + hsz[2]).floor() as i32);
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use super::*;
+
+    const E: f32 = 0.0001;
+
+    //Full cubes and bottom half slabs at the given cells
+    struct GridCollider {
+        cells: HashSet<Vec3i>,
+        slabs: HashSet<Vec3i>,
+    }
+
+    impl GridCollider {
+        fn new(cells: &[Vec3i]) -> Self {
+            return Self::with_slabs(cells, &[]);
+        }
+
+        fn with_slabs(cells: &[Vec3i], slabs: &[Vec3i]) -> Self {
+            return Self { cells: cells.iter().copied().collect(), slabs: slabs.iter().copied().collect() };
+        }
+    }
+
+    impl Collider for GridCollider {
+        fn get_collision_boxes(&self, pos: &Vec3i, boxes: &mut Vec<Aabb>) {
+            if self.cells.contains(pos) {
+                boxes.push(Aabb::new([pos[0] as f32, pos[1] as f32, pos[2] as f32], [(pos[0] + 1) as f32, (pos[1] + 1) as f32, (pos[2] + 1) as f32]));
+            } else if self.slabs.contains(pos) {
+                boxes.push(Aabb::new([pos[0] as f32, pos[1] as f32, pos[2] as f32], [(pos[0] + 1) as f32, (pos[1] as f32) + 0.5, (pos[2] + 1) as f32]));
+            }
+        }
+    }
+
+    const FLOOR: [Vec3i; 4] = [[0, 0, 0], [1, 0, 0], [2, 0, 0], [3, 0, 0]];
+
+    //Hitbox standing on the floor, walking towards the obstacle at x 2 with 1 block per step
+    fn walk_to_obstacle(collider: &GridCollider, grounded: bool, shifting: bool) -> HitBox {
+        let solver: PhysicsSolver = PhysicsSolver::new([0.0, 0.0, 0.0]);
+        let mut hitbox: HitBox = HitBox::new([1.5, 1.875, 0.5], [0.25, 0.875, 0.25]);
+        hitbox.grounded = grounded;
+        hitbox.shifting = shifting;
+        hitbox.vel = [4.0, 0.0, 0.0];
+        solver.step(collider, &mut hitbox, 0.25);
+        return hitbox;
+    }
+
+    fn assert_vec3f(actual: &Vec3f, expected: &Vec3f) {
+        for axis in 0..3usize {
+            assert!((actual[axis] - expected[axis]).abs() < E, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn sweep_slides_along_wall() {
+        let collider: GridCollider = GridCollider::new(&[[2, 0, 0], [2, 0, 1], [2, 0, 2]]);
+        let area: Aabb = Aabb::from_center(&[1.5, 0.5, 1.5], &[0.25, 0.25, 0.25]);
+        let res: SweepResult = PhysicsSolver::sweep(&collider, &area, &[1.0, 0.0, 0.5]);
+        assert_vec3f(&res.disp, &[0.25, 0.0, 0.5]);
+        assert_eq!(res.normal, [-1, 0, 0]);
+        assert!((res.time_of_impact - 0.25).abs() < E);
+    }
+
+    #[test]
+    fn sweep_stops_in_inside_corner() {
+        let collider: GridCollider = GridCollider::new(&[[2, 0, 0], [2, 0, 1], [2, 0, 2], [0, 0, 2], [1, 0, 2]]);
+        let area: Aabb = Aabb::from_center(&[1.5, 0.5, 1.5], &[0.25, 0.25, 0.25]);
+        let res: SweepResult = PhysicsSolver::sweep(&collider, &area, &[1.0, 0.0, 1.0]);
+        assert_vec3f(&res.disp, &[0.25, 0.0, 0.25]);
+        assert_eq!(res.normal, [-1, 0, -1]);
+    }
+
+    #[test]
+    fn step_hits_ceiling() {
+        let collider: GridCollider = GridCollider::new(&[[0, 3, 0]]);
+        let solver: PhysicsSolver = PhysicsSolver::new([0.0, 0.0, 0.0]);
+        let mut hitbox: HitBox = HitBox::new([0.5, 1.5, 0.5], [0.25, 0.875, 0.25]);
+        hitbox.vel = [0.0, 10.0, 0.0];
+        let res: SweepResult = solver.step(&collider, &mut hitbox, 1.0);
+        assert_eq!(res.normal, [0, -1, 0]);
+        assert_eq!(hitbox.vel[1], 0.0);
+        assert_vec3f(&hitbox.pos, &[0.5, 3.0 - 0.875, 0.5]);
+        assert!(!hitbox.grounded);
+    }
+
+    #[test]
+    fn step_does_not_tunnel_on_fast_fall() {
+        let collider: GridCollider = GridCollider::new(&[[0, 0, 0]]);
+        let solver: PhysicsSolver = PhysicsSolver::new([0.0, -1.0, 0.0]);
+        let mut hitbox: HitBox = HitBox::new([0.5, 20.0, 0.5], [0.25, 0.875, 0.25]);
+        hitbox.vel = [0.0, -50.0, 0.0];
+        let res: SweepResult = solver.step(&collider, &mut hitbox, 1.0);
+        assert_eq!(res.normal, [0, 1, 0]);
+        assert_vec3f(&hitbox.pos, &[0.5, 1.875, 0.5]);
+        assert!(hitbox.grounded);
+        assert_eq!(hitbox.vel[1], 0.0);
+    }
+
+    #[test]
+    fn step_is_deterministic() {
+        let collider: GridCollider = GridCollider::new(&[[0, 0, 0], [1, 0, 0], [1, 1, 0], [0, 2, 1]]);
+        let solver: PhysicsSolver = PhysicsSolver::new([0.0, -9.8 / 48.0, 0.0]);
+        let mut results: Vec<(Vec<SweepResult>, Vec3f)> = Vec::new();
+        for _ in 0..2 {
+            let mut hitbox: HitBox = HitBox::new([0.5, 3.0, 0.5], [0.25, 0.875, 0.25]);
+            hitbox.vel = [0.3, -2.0, 0.2];
+            let steps: Vec<SweepResult> = (0..32).map(|_| solver.step(&collider, &mut hitbox, 0.25)).collect();
+            results.push((steps, hitbox.pos));
+        }
+        assert_eq!(results[0], results[1]);
+    }
+
+    #[test]
+    fn step_climbs_half_slab() {
+        let collider: GridCollider = GridCollider::with_slabs(&FLOOR, &[[2, 1, 0]]);
+        let hitbox: HitBox = walk_to_obstacle(&collider, true, false);
+        assert_vec3f(&hitbox.pos, &[2.5, 2.375, 0.5]);
+        assert!(hitbox.grounded);
+    }
+
+    #[test]
+    fn step_refuses_ledge_above_step_height() {
+        let collider: GridCollider = GridCollider::new(&[FLOOR[0], FLOOR[1], FLOOR[2], FLOOR[3], [2, 1, 0]]);
+        let hitbox: HitBox = walk_to_obstacle(&collider, true, false);
+        assert_vec3f(&hitbox.pos, &[1.75, 1.875, 0.5]);
+        assert_eq!(hitbox.vel[0], 0.0);
+    }
+
+    #[test]
+    fn step_does_not_climb_while_airborne() {
+        let collider: GridCollider = GridCollider::with_slabs(&FLOOR, &[[2, 1, 0]]);
+        let hitbox: HitBox = walk_to_obstacle(&collider, false, false);
+        assert_vec3f(&hitbox.pos, &[1.75, 1.875, 0.5]);
+    }
+
+    #[test]
+    fn step_does_not_climb_while_shifting() {
+        let collider: GridCollider = GridCollider::with_slabs(&FLOOR, &[[2, 1, 0]]);
+        let hitbox: HitBox = walk_to_obstacle(&collider, true, true);
+        assert_vec3f(&hitbox.pos, &[1.75, 1.875, 0.5]);
+    }
 }
\ No newline at end of file
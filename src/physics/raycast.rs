@@ -0,0 +1,30 @@
+use crate::entity::entity::EntityId;
+use crate::render::types::{Vec3f, Vec3i};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RayBoxes {
+    Collision, //solid blocks only
+    Selection, //selectable blocks only
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RayFilter {
+    pub boxes: RayBoxes,
+    pub fluids: bool, //fluid blocks are hit by their whole cell
+    pub entities: bool,
+}
+
+impl RayFilter {
+    pub const SOLID: RayFilter = RayFilter { boxes: RayBoxes::Collision, fluids: false, entities: false };
+    pub const SELECTABLE: RayFilter = RayFilter { boxes: RayBoxes::Selection, fluids: false, entities: false };
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RayHit {
+    pub block_pos: Vec3i, //hit cell, the cell of the point for entity hits
+    pub face: Vec3i, //normal of the entered side, zero if the ray starts inside
+    pub point: Vec3f,
+    pub distance: f32,
+    pub block: u16, //lid at block_pos
+    pub entity: Option<EntityId>, //set if an entity is closer than any block
+}
\ No newline at end of file
@@ -0,0 +1,256 @@
+use thiserror::Error;
+
+pub const HOTBAR_SIZE: usize = 9;
+pub const INVENTORY_SIZE: usize = 36; //hotbar slots are the first ones
+pub const MAX_STACK_SIZE: u8 = 64;
+
+#[derive(Error, Debug)]
+pub enum InventoryLoadingError {
+    #[error("Invalid inventory data")]
+    InvalidInventoryDataError(),
+}
+
+//Items are blocks for now, stored by names since lids change between loads
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemStack {
+    pub name: String,
+    pub count: u8,
+}
+
+impl ItemStack {
+    pub fn new(name: &str, count: u8) -> Self {
+        return Self { name: String::from(name), count: count.clamp(1, MAX_STACK_SIZE) };
+    }
+}
+
+pub struct Inventory {
+    slots: Vec<Option<ItemStack>>,
+    selected: usize, //hotbar slot
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        return Self {
+            slots: vec![None; INVENTORY_SIZE],
+            selected: 0,
+        };
+    }
+
+    pub fn get_slot(&self, slot_ind: usize) -> Option<&ItemStack> {
+        return self.slots.get(slot_ind).and_then(|slot| slot.as_ref());
+    }
+
+    pub fn set_slot(&mut self, slot_ind: usize, stack: Option<ItemStack>) {
+        if let Some(slot) = self.slots.get_mut(slot_ind) {
+            *slot = stack;
+        }
+    }
+
+    pub fn get_selected_ind(&self) -> usize {
+        return self.selected;
+    }
+
+    pub fn get_selected(&self) -> Option<&ItemStack> {
+        return self.get_slot(self.selected);
+    }
+
+    pub fn select(&mut self, slot_ind: usize) {
+        if slot_ind < HOTBAR_SIZE {
+            self.selected = slot_ind;
+        }
+    }
+
+    //Positive steps go to the right, wraps around the hotbar
+    pub fn scroll(&mut self, steps: i32) {
+        self.selected = (self.selected as i32 + steps).rem_euclid(HOTBAR_SIZE as i32) as usize;
+    }
+
+    //Creative picking, selects the hotbar slot with the item or puts the item into the selected slot
+    pub fn pick(&mut self, name: &str) {
+        if let Some(slot_ind) = (0..HOTBAR_SIZE).find(|slot_ind| self.get_slot(*slot_ind).map_or(false, |stack| stack.name == name)) {
+            self.selected = slot_ind;
+        } else {
+            self.slots[self.selected] = Some(ItemStack::new(name, 1));
+        }
+    }
+
+    //Fills stacks of the same item first, then empty slots, returns the count which didn't fit
+    pub fn add(&mut self, stack: &ItemStack) -> u8 {
+        let mut left: u8 = stack.count;
+        for slot in self.slots.iter_mut().flatten() {
+            if left == 0 {
+                break;
+            }
+            if slot.name == stack.name && slot.count < MAX_STACK_SIZE {
+                let added: u8 = left.min(MAX_STACK_SIZE - slot.count);
+                slot.count += added;
+                left -= added;
+            }
+        }
+        for slot in self.slots.iter_mut() {
+            if left == 0 {
+                break;
+            }
+            if slot.is_none() {
+                let added: u8 = left.min(MAX_STACK_SIZE);
+                *slot = Some(ItemStack::new(&stack.name, added));
+                left -= added;
+            }
+        }
+        return left;
+    }
+
+    //Returns the removed part of the stack, empty slots are cleared
+    pub fn remove(&mut self, slot_ind: usize, count: u8) -> Option<ItemStack> {
+        let slot: &mut Option<ItemStack> = self.slots.get_mut(slot_ind)?;
+        let stack: &mut ItemStack = slot.as_mut()?;
+        let removed: u8 = count.min(stack.count);
+        if removed == 0 {
+            return None;
+        }
+        let removed_stack: ItemStack = ItemStack { name: stack.name.clone(), count: removed };
+        stack.count -= removed;
+        if stack.count == 0 {
+            *slot = None;
+        }
+        return Some(removed_stack);
+    }
+
+    //Selected slot, count of slots, then for every slot: name length (0 for empty slots), name bytes and count
+    pub fn store(&self, data: &mut Vec<u8>) {
+        data.push(self.selected as u8);
+        data.extend_from_slice(&(self.slots.len() as u16).to_be_bytes());
+        for slot in &self.slots {
+            match slot {
+                Some(stack) => {
+                    let name_data: &[u8] = stack.name.as_bytes();
+                    data.extend_from_slice(&(name_data.len() as u16).to_be_bytes());
+                    data.extend_from_slice(name_data);
+                    data.push(stack.count);
+                }
+                None => {
+                    data.extend_from_slice(&0u16.to_be_bytes());
+                }
+            }
+        }
+    }
+
+    fn read_data<'a>(data: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], InventoryLoadingError> {
+        return if let Some(read_data) = data.get(*pos..(*pos + len)) {
+            *pos += len;
+            Ok(read_data)
+        } else {
+            Err(InventoryLoadingError::InvalidInventoryDataError())
+        }
+    }
+
+    //Extra saved slots are dropped, missing ones stay empty
+    pub fn load(data: &[u8], pos: &mut usize) -> Result<Self, InventoryLoadingError> {
+        let mut inventory: Self = Self::new();
+        inventory.select(Self::read_data(data, pos, 1)?[0] as usize);
+        let cnt_data: &[u8] = Self::read_data(data, pos, 2)?;
+        let slots_cnt: u16 = ((cnt_data[0] as u16) << 8) | (cnt_data[1] as u16);
+        for slot_ind in 0..(slots_cnt as usize) {
+            let len_data: &[u8] = Self::read_data(data, pos, 2)?;
+            let name_len: usize = (((len_data[0] as u16) << 8) | (len_data[1] as u16)) as usize;
+            if name_len != 0 {
+                let name: String = String::from_utf8(Self::read_data(data, pos, name_len)?.to_vec()).map_err(|_| InventoryLoadingError::InvalidInventoryDataError())?;
+                let count: u8 = Self::read_data(data, pos, 1)?[0];
+                if count != 0 {
+                    inventory.set_slot(slot_ind, Some(ItemStack::new(&name, count)));
+                }
+            }
+        }
+        return Ok(inventory);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn store_and_load_round_trip() {
+        let mut inventory: Inventory = Inventory::new();
+        inventory.set_slot(0, Some(ItemStack::new("core:dirt", 12)));
+        inventory.set_slot(INVENTORY_SIZE - 1, Some(ItemStack::new("core:grass", MAX_STACK_SIZE)));
+        inventory.select(4);
+        let mut data: Vec<u8> = Vec::new();
+        inventory.store(&mut data);
+        let mut pos: usize = 0;
+        let loaded: Inventory = Inventory::load(&data, &mut pos).unwrap();
+        assert_eq!(pos, data.len());
+        assert_eq!(loaded.get_selected_ind(), 4);
+        for slot_ind in 0..INVENTORY_SIZE {
+            assert_eq!(loaded.get_slot(slot_ind), inventory.get_slot(slot_ind));
+        }
+    }
+
+    #[test]
+    fn load_rejects_truncated_data() {
+        let mut inventory: Inventory = Inventory::new();
+        inventory.set_slot(3, Some(ItemStack::new("core:dirt", 5)));
+        let mut data: Vec<u8> = Vec::new();
+        inventory.store(&mut data);
+        for len in [0, 1, 2, 10, data.len() - 1] {
+            let mut pos: usize = 0;
+            assert!(Inventory::load(&data[..len], &mut pos).is_err(), "{} bytes are loaded", len);
+        }
+    }
+
+    #[test]
+    fn add_fills_stacks_then_empty_slots_and_returns_overflow() {
+        let mut inventory: Inventory = Inventory::new();
+        inventory.set_slot(2, Some(ItemStack::new("core:dirt", 60)));
+        assert_eq!(inventory.add(&ItemStack::new("core:dirt", 10)), 0);
+        assert_eq!(inventory.get_slot(2), Some(&ItemStack::new("core:dirt", MAX_STACK_SIZE)));
+        assert_eq!(inventory.get_slot(0), Some(&ItemStack::new("core:dirt", 6)));
+        for slot_ind in 1..INVENTORY_SIZE {
+            if inventory.get_slot(slot_ind).is_none() {
+                inventory.set_slot(slot_ind, Some(ItemStack::new("core:grass", 1)));
+            }
+        }
+        assert_eq!(inventory.add(&ItemStack::new("core:dirt", MAX_STACK_SIZE)), 6);
+        assert_eq!(inventory.get_slot(0), Some(&ItemStack::new("core:dirt", MAX_STACK_SIZE)));
+        assert_eq!(inventory.add(&ItemStack::new("core:bedrock", 1)), 1);
+    }
+
+    #[test]
+    fn remove_splits_and_clears_stacks() {
+        let mut inventory: Inventory = Inventory::new();
+        inventory.set_slot(1, Some(ItemStack::new("core:dirt", 5)));
+        assert_eq!(inventory.remove(1, 2), Some(ItemStack::new("core:dirt", 2)));
+        assert_eq!(inventory.get_slot(1), Some(&ItemStack::new("core:dirt", 3)));
+        assert_eq!(inventory.remove(1, 10), Some(ItemStack::new("core:dirt", 3)));
+        assert_eq!(inventory.get_slot(1), None);
+        assert_eq!(inventory.remove(1, 1), None);
+        assert_eq!(inventory.remove(INVENTORY_SIZE, 1), None);
+    }
+
+    #[test]
+    fn scroll_wraps_around_the_hotbar() {
+        let mut inventory: Inventory = Inventory::new();
+        inventory.scroll(-1);
+        assert_eq!(inventory.get_selected_ind(), HOTBAR_SIZE - 1);
+        inventory.scroll(2);
+        assert_eq!(inventory.get_selected_ind(), 1);
+        inventory.scroll(-(HOTBAR_SIZE as i32) * 3);
+        assert_eq!(inventory.get_selected_ind(), 1);
+        inventory.select(HOTBAR_SIZE);
+        assert_eq!(inventory.get_selected_ind(), 1);
+    }
+
+    #[test]
+    fn pick_selects_hotbar_items_or_replaces_the_selected_slot() {
+        let mut inventory: Inventory = Inventory::new();
+        inventory.set_slot(5, Some(ItemStack::new("core:grass", 3)));
+        inventory.set_slot(HOTBAR_SIZE, Some(ItemStack::new("core:dirt", 3)));
+        inventory.pick("core:grass");
+        assert_eq!(inventory.get_selected_ind(), 5);
+        inventory.select(2);
+        inventory.pick("core:dirt");
+        assert_eq!(inventory.get_selected_ind(), 2);
+        assert_eq!(inventory.get_selected(), Some(&ItemStack::new("core:dirt", 1)));
+        assert_eq!(inventory.get_slot(HOTBAR_SIZE), Some(&ItemStack::new("core:dirt", 3)));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,3 @@
+pub mod player_controller;
+pub mod inventory;
+pub mod player_data;
\ No newline at end of file
@@ -0,0 +1,175 @@
+use crate::physics::hitbox::HitBox;
+use crate::render::types::Vec3f;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlayerMode {
+    Walk,
+    Fly,
+    Noclip, //flight through blocks
+    Spectator, //flight through blocks without interacting with the world
+}
+
+impl PlayerMode {
+    //Saved in player data
+    pub fn get_id(&self) -> u8 {
+        return match self {
+            PlayerMode::Walk => 0,
+            PlayerMode::Fly => 1,
+            PlayerMode::Noclip => 2,
+            PlayerMode::Spectator => 3,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        return match id {
+            0 => Some(PlayerMode::Walk),
+            1 => Some(PlayerMode::Fly),
+            2 => Some(PlayerMode::Noclip),
+            3 => Some(PlayerMode::Spectator),
+            _ => None,
+        }
+    }
+}
+
+//Wished movement of one tick, filled from the keyboard by the game
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlayerInput {
+    pub move_dir: Vec3f, //only x and z are used
+    pub up: bool,
+    pub down: bool,
+    pub sprint: bool,
+}
+
+//Velocities are in blocks per physics time unit, rates are per physics time unit
+pub struct PlayerController {
+    pub mode: PlayerMode,
+    pub walk_speed: f32,
+    pub sprint_multiplier: f32,
+    pub sneak_multiplier: f32,
+    pub fly_speed: f32,
+    pub ground_acceleration: f32, //also works as ground friction
+    pub air_acceleration: f32,
+    pub fly_acceleration: f32,
+    pub jump_impulse: f32,
+    pub swim_speed: f32, //upwards speed while up is held in fluids
+    pub gravity_scale: f32,
+    pub allow_flight: bool, //double tap of up toggles walking and flying
+    pub double_tap_time: f32,
+    time: f32,
+    last_up_time: Option<f32>,
+    last_up: bool,
+}
+
+impl PlayerController {
+    pub fn new() -> Self {
+        return Self {
+            mode: PlayerMode::Walk,
+            walk_speed: 0.8,
+            sprint_multiplier: 1.5,
+            sneak_multiplier: 0.4,
+            fly_speed: 1.6,
+            ground_acceleration: 12.0,
+            air_acceleration: 2.0,
+            fly_acceleration: 6.0,
+            jump_impulse: 1.25,
+            swim_speed: 0.6,
+            gravity_scale: 1.0,
+            allow_flight: true,
+            double_tap_time: 4.0,
+            time: 0.0,
+            last_up_time: None,
+            last_up: false,
+        };
+    }
+
+    pub fn set_mode(&mut self, mode: PlayerMode) {
+        self.mode = mode;
+        self.last_up_time = None;
+    }
+
+    pub fn next_mode(&mut self) {
+        self.set_mode(match self.mode {
+            PlayerMode::Walk => PlayerMode::Fly,
+            PlayerMode::Fly => PlayerMode::Noclip,
+            PlayerMode::Noclip => PlayerMode::Spectator,
+            PlayerMode::Spectator => PlayerMode::Walk,
+        });
+    }
+
+    pub fn can_interact(&self) -> bool {
+        return self.mode != PlayerMode::Spectator;
+    }
+
+    fn update_double_tap(&mut self, input: &PlayerInput) {
+        if input.up && !self.last_up {
+            if let Some(last_up_time) = self.last_up_time {
+                if self.allow_flight && (self.time - last_up_time) <= self.double_tap_time {
+                    match self.mode {
+                        PlayerMode::Walk => self.set_mode(PlayerMode::Fly),
+                        PlayerMode::Fly => self.set_mode(PlayerMode::Walk),
+                        _ => {}
+                    }
+                    self.last_up = input.up;
+                    return;
+                }
+            }
+            self.last_up_time = Some(self.time);
+        }
+        self.last_up = input.up;
+    }
+
+    //Moves velocity towards target, rate is how fast the difference decays
+    fn approach(vel: &mut f32, target: f32, rate: f32, delta: f32) {
+        *vel += (target - *vel) * (1.0 - (-rate * delta).exp());
+    }
+
+    //Sets up the hitbox for the mode and the velocity for the input, should be called before every physics step
+    pub fn update(&mut self, input: &PlayerInput, hitbox: &mut HitBox, delta: f32) {
+        self.time += delta;
+        self.update_double_tap(input);
+        if self.mode == PlayerMode::Fly && hitbox.grounded && input.down {
+            self.set_mode(PlayerMode::Walk);
+        }
+
+        let mut dir: Vec3f = [input.move_dir[0], 0.0, input.move_dir[2]];
+        let dir_len: f32 = (dir[0] * dir[0] + dir[2] * dir[2]).sqrt();
+        if dir_len > 1.0 {
+            dir[0] /= dir_len;
+            dir[2] /= dir_len;
+        }
+        //velocity is damped here, so the solver friction would slow it twice
+        hitbox.friction = 0.0;
+        match self.mode {
+            PlayerMode::Walk => {
+                hitbox.collidable = true;
+                hitbox.gravity_scale = self.gravity_scale;
+                hitbox.shifting = input.down;
+                let speed: f32 = if input.down {
+                    self.walk_speed * self.sneak_multiplier
+                } else if input.sprint {
+                    self.walk_speed * self.sprint_multiplier
+                } else {
+                    self.walk_speed
+                };
+                let rate: f32 = if hitbox.grounded { self.ground_acceleration } else { self.air_acceleration };
+                Self::approach(&mut hitbox.vel[0], dir[0] * speed, rate, delta);
+                Self::approach(&mut hitbox.vel[2], dir[2] * speed, rate, delta);
+                if input.up && hitbox.submerged > 0.0 {
+                    Self::approach(&mut hitbox.vel[1], self.swim_speed, self.air_acceleration, delta);
+                } else if input.up && hitbox.grounded {
+                    hitbox.vel[1] = self.jump_impulse;
+                }
+            }
+            PlayerMode::Fly | PlayerMode::Noclip | PlayerMode::Spectator => {
+                hitbox.collidable = self.mode == PlayerMode::Fly;
+                hitbox.gravity_scale = 0.0;
+                hitbox.shifting = false;
+                let speed: f32 = if input.sprint { self.fly_speed * self.sprint_multiplier } else { self.fly_speed };
+                let vertical: f32 = (if input.up { 1.0 } else { 0.0 }) - (if input.down { 1.0 } else { 0.0 });
+                Self::approach(&mut hitbox.vel[0], dir[0] * speed, self.fly_acceleration, delta);
+                Self::approach(&mut hitbox.vel[1], vertical * speed, self.fly_acceleration, delta);
+                Self::approach(&mut hitbox.vel[2], dir[2] * speed, self.fly_acceleration, delta);
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,82 @@
+use std::fs::File;
+use std::io::{Read, Write};
+use thiserror::Error;
+use crate::player::inventory::Inventory;
+use crate::player::player_controller::PlayerMode;
+use crate::render::types::Vec3f;
+
+const PLAYER_MAGIC: &[u8; 4] = b"CCPL";
+const PLAYER_VERSION: u16 = 1;
+
+#[derive(Error, Debug)]
+pub enum PlayerLoadingError {
+    #[error("Not a player data file")]
+    InvalidMagicError(),
+    #[error("Unsupported player data version {0}")]
+    UnsupportedVersionError(u16),
+    #[error("Invalid player data")]
+    InvalidPlayerDataError(),
+}
+
+//Everything about the player which outlives the session, kept apart from the world file
+pub struct PlayerData {
+    pub pos: Vec3f, //feet of the hitbox center
+    pub rot: Vec3f, //camera rotation
+    pub mode: PlayerMode,
+    pub inventory: Inventory,
+}
+
+impl PlayerData {
+    fn read_vec3f(data: &[u8], pos: &mut usize) -> Result<Vec3f, PlayerLoadingError> {
+        let mut vec: Vec3f = [0.0, 0.0, 0.0];
+        for value in vec.iter_mut() {
+            if let Some(value_data) = data.get(*pos..(*pos + 4)) {
+                *value = f32::from_be_bytes([value_data[0], value_data[1], value_data[2], value_data[3]]);
+                *pos += 4;
+            } else {
+                return Err(PlayerLoadingError::InvalidPlayerDataError());
+            }
+        }
+        return Ok(vec);
+    }
+
+    //Magic, version, position, rotation, mode id, then the inventory
+    pub fn store(&self, file_name: String) -> Result<(), Box<dyn std::error::Error>> {
+        let mut data: Vec<u8> = Vec::new();
+        data.extend_from_slice(PLAYER_MAGIC);
+        data.extend_from_slice(&PLAYER_VERSION.to_be_bytes());
+        for vec in [&self.pos, &self.rot] {
+            for value in vec {
+                data.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        data.push(self.mode.get_id());
+        self.inventory.store(&mut data);
+        let mut file: File = File::create(file_name)?;
+        file.write_all(data.as_slice())?;
+        return Ok(());
+    }
+
+    pub fn load(file_name: String) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut data: Vec<u8> = Vec::new();
+        File::open(file_name)?.read_to_end(&mut data)?;
+        if !data.starts_with(PLAYER_MAGIC) {
+            return Err(Box::new(PlayerLoadingError::InvalidMagicError()));
+        }
+        let mut pos: usize = PLAYER_MAGIC.len();
+        let version: u16 = match data.get(pos..(pos + 2)) {
+            Some(version_data) => ((version_data[0] as u16) << 8) | (version_data[1] as u16),
+            None => return Err(Box::new(PlayerLoadingError::InvalidPlayerDataError())),
+        };
+        pos += 2;
+        if version == 0 || version > PLAYER_VERSION {
+            return Err(Box::new(PlayerLoadingError::UnsupportedVersionError(version)));
+        }
+        let player_pos: Vec3f = Self::read_vec3f(&data, &mut pos)?;
+        let rot: Vec3f = Self::read_vec3f(&data, &mut pos)?;
+        let mode: PlayerMode = data.get(pos).and_then(|mode_id| PlayerMode::from_id(*mode_id)).ok_or(PlayerLoadingError::InvalidPlayerDataError())?;
+        pos += 1;
+        let inventory: Inventory = Inventory::load(&data, &mut pos)?;
+        return Ok(Self { pos: player_pos, rot, mode, inventory });
+    }
+}
\ No newline at end of file
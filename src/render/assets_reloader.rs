@@ -0,0 +1,183 @@
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use thiserror::Error;
+use crate::files_watcher::FilesWatcher;
+use crate::gui::layouts_loader::{GUI_ATLAS_FILE, GuiLayouts, LAYOUTS_FILE};
+use crate::packs::pack_stack::PackStack;
+use crate::render::blocks_loader::{Block, BLOCKS_FILE, BlocksLoader};
+use crate::render::faces_loader::{BLOCKS_ATLAS_FILE, FACES_FILE, FacesLoader};
+use crate::render::icons_baker::BlockIcons;
+use crate::render::light::light_solvers::LightSolvers;
+use crate::render::meshes_loader::{MESHES_FILE, MeshesLoader};
+use crate::render::shader_program::ShaderProgram;
+use crate::render::types::Vec3ub;
+use crate::world::World;
+
+#[derive(Error, Debug)]
+pub enum AssetsReloadingError {
+    #[error("Block {0} is used by the world but missing after reload")]
+    MissingBlockError(String),
+}
+
+pub struct AssetsReloader {
+    packs_list_path: PathBuf,
+    watcher: FilesWatcher,
+    gui_watcher: FilesWatcher, //GUI is reloaded apart from blocks, without touching the world
+}
+
+impl AssetsReloader {
+    pub fn new(packs_list_path: &Path) -> Self {
+        let watcher: FilesWatcher = FilesWatcher::new();
+        watcher.watch(packs_list_path);
+        let gui_watcher: FilesWatcher = FilesWatcher::new();
+        gui_watcher.watch(packs_list_path);
+        return Self { packs_list_path: packs_list_path.to_path_buf(), watcher, gui_watcher };
+    }
+
+    pub fn is_changed(&self) -> bool {
+        return self.watcher.is_changed();
+    }
+
+    pub fn is_gui_changed(&self) -> bool {
+        return self.gui_watcher.is_changed();
+    }
+
+    pub fn open_packs(&self) -> Result<PackStack, Box<dyn std::error::Error>> {
+        let packs_paths: Vec<PathBuf> = PackStack::read_packs_list(&self.packs_list_path)?;
+        return PackStack::open(&packs_paths);
+    }
+
+    //Packs list can change too, so watched files are collected again on every load
+    pub fn load(&self, shader_program: Rc<ShaderProgram>) -> Result<BlocksLoader, Box<dyn std::error::Error>> {
+        let packs: PackStack = self.open_packs()?;
+        self.watcher.clear();
+        self.watcher.watch(&self.packs_list_path);
+        for path in packs.get_watched_paths(&[BLOCKS_ATLAS_FILE, FACES_FILE, MESHES_FILE, BLOCKS_FILE]) {
+            self.watcher.watch(&path);
+        }
+        let faces_loader: FacesLoader = FacesLoader::load(&packs, shader_program)?;
+        let meshes_loader: MeshesLoader = MeshesLoader::load(&packs, faces_loader)?;
+        return BlocksLoader::load(&packs, meshes_loader);
+    }
+
+    //Icons come from the loaded blocks, so layouts are loaded again after blocks reloads too
+    pub fn load_gui_layouts(&self, block_icons: &BlockIcons) -> Result<GuiLayouts, Box<dyn std::error::Error>> {
+        let packs: PackStack = self.open_packs()?;
+        self.gui_watcher.clear();
+        self.gui_watcher.watch(&self.packs_list_path);
+        for path in packs.get_watched_paths(&[LAYOUTS_FILE, GUI_ATLAS_FILE]) {
+            self.gui_watcher.watch(&path);
+        }
+        return GuiLayouts::load(&packs, block_icons);
+    }
+
+    //Blocks placed in the running world must survive the reload, otherwise they would turn into unknown blocks
+    pub fn validate(world: Option<&World>, old_blocks_loader: &BlocksLoader, new_blocks_loader: &BlocksLoader) -> Result<(), Box<dyn std::error::Error>> {
+        let world: &World = match world {
+            Some(world) => world,
+            None => return Ok(()),
+        };
+        let used_lids: Vec<bool> = world.get_used_lids(old_blocks_loader.loaded_blocks.len());
+        for block in &old_blocks_loader.loaded_blocks {
+            if used_lids[block.lid as usize] && !new_blocks_loader.blocks_names.contains_key(&block.name) {
+                return Err(Box::new(AssetsReloadingError::MissingBlockError(block.name.clone())));
+            }
+        }
+        return Ok(());
+    }
+
+    pub fn reload(&self, world: Option<&World>, old_blocks_loader: &BlocksLoader) -> Result<BlocksLoader, Box<dyn std::error::Error>> {
+        let new_blocks_loader: BlocksLoader = self.load(old_blocks_loader.meshes_loader.faces_loader.get_shader_program()?.clone())?;
+        Self::validate(world, old_blocks_loader, &new_blocks_loader)?;
+        return Ok(new_blocks_loader);
+    }
+
+    //Must be called before old_blocks_loader is replaced with new_blocks_loader
+    pub fn apply(world: &World, light_solvers: &LightSolvers, old_blocks_loader: &BlocksLoader, new_blocks_loader: &BlocksLoader) {
+        let mut lids_map: Vec<u16> = Vec::new();
+        let mut emission_changed: Vec<bool> = vec![false; new_blocks_loader.loaded_blocks.len()];
+        for old_block in &old_blocks_loader.loaded_blocks {
+            let new_block: Rc<Block> = match new_blocks_loader.blocks_names.get(&old_block.name) {
+                Some(block) => block.clone(),
+                None => new_blocks_loader.unknown_block.clone(),
+            };
+            if old_block.light_r != new_block.light_r || old_block.light_g != new_block.light_g || old_block.light_b != new_block.light_b {
+                emission_changed[new_block.lid as usize] = true;
+            }
+            lids_map.push(new_block.lid);
+        }
+        let relit: Vec<Vec3ub> = world.remap_blocks(&lids_map, new_blocks_loader.unknown_block.lid, &emission_changed);
+        world.set_changed();
+        if relit.len() != 0 {
+            light_solvers.update_emissions(world, new_blocks_loader, &relit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread::{Builder, JoinHandle};
+    use crate::chunk::LayerChunkGenerator;
+    use crate::packs::test_packs::{get_test_dir, write_dir_pack};
+    use crate::render::light::light_map::R_CHANNEL;
+    use super::*;
+
+    const TEST_STACK_SIZE: usize = 64 * 1024 * 1024;
+
+    const OLD_BLOCKS: &str = r#"[
+        {"name": "test:spark", "mesh": "dirt_block", "light_r": 0, "light_g": 0, "light_b": 0},
+        {"name": "test:gone", "mesh": "dirt_block", "light_r": 0, "light_g": 0, "light_b": 0}
+    ]"#;
+    const NEW_BLOCKS: &str = r#"[
+        {"name": "test:new", "mesh": "dirt_block", "light_r": 0, "light_g": 0, "light_b": 0},
+        {"name": "test:spark", "mesh": "dirt_block", "light_r": 12, "light_g": 0, "light_b": 0}
+    ]"#;
+
+    //Core blocks with the test blocks of blocks_data on top
+    fn load_test_blocks(test_name: &str, blocks_data: &str) -> BlocksLoader {
+        let pack_path: PathBuf = write_dir_pack(&get_test_dir(test_name), "test", &[(BLOCKS_FILE, blocks_data.as_bytes())]);
+        return BlocksLoader::load_headless(&PackStack::open(&[PathBuf::from("assets"), pack_path]).unwrap()).unwrap();
+    }
+
+    //Chunks are generated on the stack, which is too big for the default test threads
+    fn run_test(test: fn(&World, &BlocksLoader)) {
+        let handle: JoinHandle<()> = Builder::new().stack_size(TEST_STACK_SIZE).spawn(move || {
+            let blocks_loader: BlocksLoader = load_test_blocks("reloader_old", OLD_BLOCKS);
+            let bedrock: u16 = blocks_loader.get_lid("core:bedrock").unwrap();
+            let world: World = World::new(&LayerChunkGenerator::from_bottom_layers(&[bedrock], blocks_loader.air_block.lid), &blocks_loader).unwrap();
+            test(&world, &blocks_loader);
+        }).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn validate_rejects_missing_used_blocks() {
+        run_test(|world: &World, old_blocks_loader: &BlocksLoader| {
+            let new_blocks_loader: BlocksLoader = load_test_blocks("reloader_validate", NEW_BLOCKS);
+            assert!(AssetsReloader::validate(None, old_blocks_loader, &new_blocks_loader).is_ok());
+            assert!(AssetsReloader::validate(Some(world), old_blocks_loader, &new_blocks_loader).is_ok());
+            world.set_block(&[8, 5, 8], old_blocks_loader.get_lid("test:gone").unwrap());
+            let error: Box<dyn std::error::Error> = AssetsReloader::validate(Some(world), old_blocks_loader, &new_blocks_loader).unwrap_err();
+            assert_eq!(error.to_string(), "Block test:gone is used by the world but missing after reload");
+        });
+    }
+
+    #[test]
+    fn apply_remaps_lids_and_relights_changed_emissions() {
+        run_test(|world: &World, old_blocks_loader: &BlocksLoader| {
+            let new_blocks_loader: BlocksLoader = load_test_blocks("reloader_apply", NEW_BLOCKS);
+            let dirt: u16 = old_blocks_loader.get_lid("core:dirt").unwrap();
+            world.set_block(&[8, 5, 8], old_blocks_loader.get_lid("test:spark").unwrap());
+            world.set_block(&[8, 5, 12], old_blocks_loader.get_lid("test:gone").unwrap());
+            world.set_block(&[8, 5, 4], dirt);
+            AssetsReloader::apply(world, &LightSolvers::new(), old_blocks_loader, &new_blocks_loader);
+            assert_eq!(world.get_block(&[8, 5, 8]), new_blocks_loader.get_lid("test:spark").unwrap());
+            assert_eq!(world.get_block(&[8, 5, 12]), new_blocks_loader.unknown_block.lid);
+            assert_eq!(world.get_block(&[8, 5, 4]), new_blocks_loader.get_lid("core:dirt").unwrap());
+            assert_eq!(world.get_block(&[8, 0, 8]), new_blocks_loader.get_lid("core:bedrock").unwrap());
+            assert_eq!(world.get_light_level(&[8, 5, 8], R_CHANNEL), 12);
+            assert_eq!(world.get_light_level(&[8, 6, 8], R_CHANNEL), 11);
+            assert_eq!(world.get_light_level(&[8, 5, 12], R_CHANNEL), new_blocks_loader.unknown_block.light_r);
+        });
+    }
+}
@@ -0,0 +1,470 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use serde::Deserialize;
+use crate::gui::layouts_loader::{GUI_ATLAS_FILE, GuiLayouts, LAYOUTS_FILE};
+use crate::packs::pack_stack::PackStack;
+use crate::render::blocks_loader::{AIR_BLOCK_NAME, BLOCKS_FILE, BlockData, is_namespaced_name, UNKNOWN_BLOCK_NAME};
+use crate::render::font::{Font, FontData, FONTS_FILE};
+use crate::render::faces_loader::{BLOCKS_ATLAS_FILE, Face, FACES_FILE};
+use crate::render::meshes_loader::{CubeMeshData, CustomMeshData, FluidMeshData, MESHES_FILE};
+use crate::render::types::{Vec2f, Vec3f};
+use crate::scripting::script_engine::{ScriptEngine, SCRIPTS_FILE};
+
+const AREA_E: f32 = 0.000001; //triangles with smaller doubled area are degenerate
+
+pub struct AssetsIssue {
+    pub pack: String,
+    pub file_name: String,
+    pub location: String,
+    pub message: String,
+}
+
+impl Display for AssetsIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return if self.location.is_empty() {
+            write!(f, "{}:{}: {}", self.pack, self.file_name, self.message)
+        } else {
+            write!(f, "{}:{}: {}: {}", self.pack, self.file_name, self.location, self.message)
+        }
+    }
+}
+
+//Location of the definition that won after packs were merged
+struct DefinitionPos {
+    pack: String,
+    location: String,
+}
+
+//Checks packs data without loading the atlas into GL, so it can run without a window
+pub struct AssetsValidator {
+    pub issues: Vec<AssetsIssue>,
+}
+
+fn get_location(ind: usize, value: &serde_json::Value) -> String {
+    return if let Some(name) = value.get("name").and_then(|name| name.as_str()) {
+        format!("#{} \"{}\"", ind, name)
+    } else {
+        format!("#{}", ind)
+    }
+}
+
+//Vertices with the same position share edges even if their texture coords differ
+type VertexKey = [u32; 3];
+
+fn get_vertex_key(pos: &Vec3f) -> VertexKey {
+    return [pos[0].to_bits(), pos[1].to_bits(), pos[2].to_bits()];
+}
+
+fn get_triangle_normal(a: &Vec3f, b: &Vec3f, c: &Vec3f) -> Vec3f {
+    let ab: Vec3f = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let ac: Vec3f = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    return [ab[1] * ac[2] - ab[2] * ac[1], ab[2] * ac[0] - ab[0] * ac[2], ab[0] * ac[1] - ab[1] * ac[0]];
+}
+
+impl AssetsValidator {
+    pub fn check(packs: &PackStack) -> Result<Vec<AssetsIssue>, Box<dyn std::error::Error>> {
+        let mut validator: AssetsValidator = AssetsValidator { issues: Vec::new() };
+        if let Err(error) = packs.load_atlas(BLOCKS_ATLAS_FILE) {
+            validator.add_issue("*", BLOCKS_ATLAS_FILE, String::new(), error.to_string());
+        }
+        let faces: HashMap<String, DefinitionPos> = validator.check_faces(packs)?;
+        let meshes: HashMap<String, (DefinitionPos, Vec<String>)> = validator.check_meshes(packs, &faces)?;
+        validator.check_blocks(packs, &meshes)?;
+        validator.check_scripts(packs)?;
+        validator.check_fonts(packs)?;
+        validator.check_layouts(packs)?;
+
+        let mut used_faces: HashSet<&String> = HashSet::new();
+        for (_mesh_pos, mesh_faces) in meshes.values() {
+            used_faces.extend(mesh_faces.iter());
+        }
+        let mut unused_faces: Vec<(&String, &DefinitionPos)> = faces.iter().filter(|(name, _face_pos)| !used_faces.contains(name)).collect();
+        unused_faces.sort_by(|(l_name, _), (r_name, _)| l_name.cmp(r_name));
+        for (_name, face_pos) in unused_faces {
+            validator.add_issue(&face_pos.pack, FACES_FILE, face_pos.location.clone(), String::from("face is not used by any mesh"));
+        }
+        return Ok(validator.issues);
+    }
+
+    fn add_issue(&mut self, pack: &str, file_name: &str, location: String, message: String) {
+        self.issues.push(AssetsIssue {
+            pack: String::from(pack),
+            file_name: String::from(file_name),
+            location,
+            message,
+        });
+    }
+
+    fn check_face(&mut self, pack: &str, location: &str, face: &Face) {
+        for (vertex_pos, vertex) in face.vertices.iter().enumerate() {
+            let tex: Vec2f = vertex.1; //vertices are packed
+            if tex.iter().any(|coord| *coord < 0.0 || *coord > 1.0) {
+                self.add_issue(pack, FACES_FILE, format!("{} vertex {}", location, vertex_pos), format!("tex {:?} is outside of the atlas", tex));
+            }
+        }
+        if (face.indices.len() % 3) != 0 {
+            self.add_issue(pack, FACES_FILE, String::from(location), format!("{} indices are not a multiple of 3", face.indices.len()));
+        }
+        let mut indices_valid: bool = true;
+        for (index_pos, index) in face.indices.iter().enumerate() {
+            if *index < 0 || (*index as usize) >= face.vertices.len() {
+                self.add_issue(pack, FACES_FILE, format!("{} index {}", location, index_pos), format!("index {} is outside of {} vertices", index, face.vertices.len()));
+                indices_valid = false;
+            }
+        }
+        if !indices_valid {
+            return;
+        }
+        //Triangles sharing an edge must go along it in opposite directions, so faces don't have to be planar, like crossed quads
+        let mut edges: HashMap<(VertexKey, VertexKey), usize> = HashMap::new();
+        for (triangle_pos, triangle) in face.indices.chunks_exact(3).enumerate() {
+            let (a, b, c): (Vec3f, Vec3f, Vec3f) = (face.vertices[triangle[0] as usize].0, face.vertices[triangle[1] as usize].0, face.vertices[triangle[2] as usize].0);
+            let normal: Vec3f = get_triangle_normal(&a, &b, &c);
+            if (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt() < AREA_E {
+                self.add_issue(pack, FACES_FILE, format!("{} triangle {}", location, triangle_pos), String::from("triangle is degenerate"));
+                continue;
+            }
+            let triangle_edges: [(VertexKey, VertexKey); 3] = [(get_vertex_key(&a), get_vertex_key(&b)), (get_vertex_key(&b), get_vertex_key(&c)), (get_vertex_key(&c), get_vertex_key(&a))];
+            if let Some(other_pos) = triangle_edges.iter().find_map(|edge| edges.get(edge)) {
+                self.add_issue(pack, FACES_FILE, format!("{} triangle {}", location, triangle_pos), format!("winding is inconsistent with triangle {} sharing an edge", other_pos));
+            }
+            for edge in triangle_edges {
+                edges.entry(edge).or_insert(triangle_pos);
+            }
+        }
+    }
+
+    fn check_faces(&mut self, packs: &PackStack) -> Result<HashMap<String, DefinitionPos>, Box<dyn std::error::Error>> {
+        let mut faces: HashMap<String, DefinitionPos> = HashMap::new();
+        for pack_definitions in packs.read_definitions(FACES_FILE)? {
+            let mut pack_faces: HashSet<String> = HashSet::new();
+            for (face_ind, face_value) in pack_definitions.values.iter().enumerate() {
+                let location: String = get_location(face_ind, face_value);
+                match Face::deserialize(face_value) {
+                    Ok(face) => {
+                        if !pack_faces.insert(face.name.clone()) {
+                            self.add_issue(&pack_definitions.pack, FACES_FILE, location.clone(), String::from("duplicate face name"));
+                        }
+                        self.check_face(&pack_definitions.pack, &location, &face);
+                        faces.insert(face.name, DefinitionPos { pack: pack_definitions.pack.clone(), location });
+                    }
+                    Err(error) => {
+                        self.add_issue(&pack_definitions.pack, FACES_FILE, location, format!("invalid face: {}", error));
+                    }
+                }
+            }
+        }
+        return Ok(faces);
+    }
+
+    fn get_mesh_faces(mesh_value: &serde_json::Value) -> Result<(String, Vec<String>), String> {
+        let name: &str = mesh_value.get("name").and_then(|name| name.as_str()).ok_or(String::from("mesh has no name"))?;
+        let mesh_type: &str = mesh_value.get("mesh_type").and_then(|mesh_type| mesh_type.as_str()).ok_or(String::from("mesh has no mesh_type"))?;
+        let mesh_data: &serde_json::Value = mesh_value.get("mesh").ok_or(String::from("mesh has no mesh data"))?;
+        return match mesh_type {
+            "cube" => {
+                let mesh: CubeMeshData = CubeMeshData::deserialize(mesh_data).map_err(|error| format!("invalid cube mesh: {}", error))?;
+                Ok((String::from(name), mesh.get_faces().iter().map(|face| (*face).clone()).collect()))
+            }
+            "fluid" => {
+                let mesh: FluidMeshData = FluidMeshData::deserialize(mesh_data).map_err(|error| format!("invalid fluid mesh: {}", error))?;
+                Ok((String::from(name), mesh.get_faces().iter().map(|face| (*face).clone()).collect()))
+            }
+            "custom" => {
+                let mesh: CustomMeshData = CustomMeshData::deserialize(mesh_data).map_err(|error| format!("invalid custom mesh: {}", error))?;
+                Ok((String::from(name), mesh.faces))
+            }
+            _ => {
+                Err(format!("unknown mesh_type {}", mesh_type))
+            }
+        }
+    }
+
+    fn check_meshes(&mut self, packs: &PackStack, faces: &HashMap<String, DefinitionPos>) -> Result<HashMap<String, (DefinitionPos, Vec<String>)>, Box<dyn std::error::Error>> {
+        let mut meshes: HashMap<String, (DefinitionPos, Vec<String>)> = HashMap::new();
+        for pack_definitions in packs.read_definitions(MESHES_FILE)? {
+            let mut pack_meshes: HashSet<String> = HashSet::new();
+            for (mesh_ind, mesh_value) in pack_definitions.values.iter().enumerate() {
+                let location: String = get_location(mesh_ind, mesh_value);
+                match Self::get_mesh_faces(mesh_value) {
+                    Ok((name, mesh_faces)) => {
+                        if !pack_meshes.insert(name.clone()) {
+                            self.add_issue(&pack_definitions.pack, MESHES_FILE, location.clone(), String::from("duplicate mesh name"));
+                        }
+                        for face in &mesh_faces {
+                            if !faces.contains_key(face) {
+                                self.add_issue(&pack_definitions.pack, MESHES_FILE, location.clone(), format!("unknown face {}", face));
+                            }
+                        }
+                        meshes.insert(name, (DefinitionPos { pack: pack_definitions.pack.clone(), location }, mesh_faces));
+                    }
+                    Err(error) => {
+                        self.add_issue(&pack_definitions.pack, MESHES_FILE, location, error);
+                    }
+                }
+            }
+        }
+        return Ok(meshes);
+    }
+
+    fn check_fonts(&mut self, packs: &PackStack) -> Result<(), Box<dyn std::error::Error>> {
+        for pack_definitions in packs.read_definitions(FONTS_FILE)? {
+            for (font_ind, font_value) in pack_definitions.values.iter().enumerate() {
+                let location: String = get_location(font_ind, font_value);
+                match FontData::deserialize(font_value) {
+                    Ok(font_data) => {
+                        if !is_namespaced_name(&font_data.name) {
+                            self.add_issue(&pack_definitions.pack, FONTS_FILE, location.clone(), String::from("font name is not namespaced like namespace:name"));
+                        }
+                        match packs.load_atlas(&font_data.atlas) {
+                            Ok(atlas) => {
+                                if let Err(error) = Font::from_data(font_data, atlas, &pack_definitions.pack) {
+                                    self.add_issue(&pack_definitions.pack, FONTS_FILE, location, error.to_string());
+                                }
+                            }
+                            Err(error) => {
+                                self.add_issue(&pack_definitions.pack, FONTS_FILE, location, error.to_string());
+                            }
+                        }
+                    }
+                    Err(error) => {
+                        self.add_issue(&pack_definitions.pack, FONTS_FILE, location, format!("invalid font: {}", error));
+                    }
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    //Textures are checked against the atlas, so a missing atlas only skips them
+    fn check_layouts(&mut self, packs: &PackStack) -> Result<(), Box<dyn std::error::Error>> {
+        let atlas_size: [u32; 2] = match packs.load_atlas(GUI_ATLAS_FILE) {
+            Ok(atlas) => [atlas.width(), atlas.height()],
+            Err(error) => {
+                self.add_issue("*", GUI_ATLAS_FILE, String::new(), error.to_string());
+                [u32::MAX, u32::MAX]
+            }
+        };
+        for pack_definitions in packs.read_definitions(LAYOUTS_FILE)? {
+            let mut pack_layouts: HashSet<String> = HashSet::new();
+            for (layout_ind, layout_value) in pack_definitions.values.iter().enumerate() {
+                let location: String = get_location(layout_ind, layout_value);
+                match GuiLayouts::load_layout(layout_value, &pack_definitions.pack, &atlas_size) {
+                    Ok(layout) => {
+                        if !pack_layouts.insert(layout.name) {
+                            self.add_issue(&pack_definitions.pack, LAYOUTS_FILE, location, String::from("duplicate layout name"));
+                        }
+                    }
+                    Err(error) => {
+                        self.add_issue(&pack_definitions.pack, LAYOUTS_FILE, location, error.to_string());
+                    }
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    fn check_scripts(&mut self, packs: &PackStack) -> Result<(), Box<dyn std::error::Error>> {
+        for pack_definitions in packs.read_definitions(SCRIPTS_FILE)? {
+            for (script_ind, script_value) in pack_definitions.values.iter().enumerate() {
+                let path: &str = match script_value.as_str() {
+                    Some(path) => path,
+                    None => {
+                        self.add_issue(&pack_definitions.pack, SCRIPTS_FILE, get_location(script_ind, script_value), String::from("script path must be a string"));
+                        continue;
+                    }
+                };
+                let mut source: Option<Vec<u8>> = None;
+                if let Some(pack) = packs.packs.iter().find(|pack| pack.manifest.name == pack_definitions.pack) {
+                    source = pack.read_file(path)?;
+                }
+                match source.map(String::from_utf8) {
+                    None => {
+                        self.add_issue(&pack_definitions.pack, SCRIPTS_FILE, String::from(path), String::from("script not found"));
+                    }
+                    Some(Err(_)) => {
+                        self.add_issue(&pack_definitions.pack, SCRIPTS_FILE, String::from(path), String::from("script is not valid UTF-8"));
+                    }
+                    Some(Ok(source)) => {
+                        if let Err(error) = ScriptEngine::check_script(&source) {
+                            self.add_issue(&pack_definitions.pack, SCRIPTS_FILE, String::from(path), error);
+                        }
+                    }
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    fn check_blocks(&mut self, packs: &PackStack, meshes: &HashMap<String, (DefinitionPos, Vec<String>)>) -> Result<(), Box<dyn std::error::Error>> {
+        let mut blocks: HashSet<String> = HashSet::new();
+        for pack_definitions in packs.read_definitions(BLOCKS_FILE)? {
+            let mut pack_blocks: HashSet<String> = HashSet::new();
+            for (block_ind, block_value) in pack_definitions.values.iter().enumerate() {
+                let location: String = get_location(block_ind, block_value);
+                match BlockData::deserialize(block_value) {
+                    Ok(block_data) => {
+                        if !is_namespaced_name(&block_data.name) {
+                            self.add_issue(&pack_definitions.pack, BLOCKS_FILE, location.clone(), String::from("block name is not namespaced like namespace:name"));
+                        }
+                        if !pack_blocks.insert(block_data.name.clone()) {
+                            self.add_issue(&pack_definitions.pack, BLOCKS_FILE, location.clone(), String::from("duplicate block name"));
+                        }
+                        if !meshes.contains_key(&block_data.mesh) {
+                            self.add_issue(&pack_definitions.pack, BLOCKS_FILE, location.clone(), format!("unknown mesh {}", block_data.mesh));
+                        }
+                        if block_data.light_r > 0x0F || block_data.light_g > 0x0F || block_data.light_b > 0x0F {
+                            self.add_issue(&pack_definitions.pack, BLOCKS_FILE, location.clone(), String::from("light levels must not exceed 15"));
+                        }
+                        for (box_pos, collision_box) in block_data.collision.iter().flatten().enumerate() {
+                            if (0..3).any(|axis| collision_box.min[axis] < 0.0 || collision_box.max[axis] > 1.0 || collision_box.min[axis] >= collision_box.max[axis]) {
+                                self.add_issue(&pack_definitions.pack, BLOCKS_FILE, format!("{} collision {}", location, box_pos), String::from("collision box must be non empty and inside the block"));
+                            }
+                        }
+                        if let Some(fluid) = &block_data.fluid {
+                            if fluid.flow_distance == 0 || fluid.flow_distance > 7 {
+                                self.add_issue(&pack_definitions.pack, BLOCKS_FILE, location.clone(), String::from("fluid flow_distance must be from 1 to 7"));
+                            }
+                            if fluid.tick_delay == 0 {
+                                self.add_issue(&pack_definitions.pack, BLOCKS_FILE, location.clone(), String::from("fluid tick_delay must not be 0"));
+                            }
+                            if block_data.collision.as_ref().map_or(true, |collision| collision.len() != 0) {
+                                self.add_issue(&pack_definitions.pack, BLOCKS_FILE, location.clone(), String::from("fluid blocks must have empty collision"));
+                            }
+                        }
+                        if let Some(behavior) = &block_data.behavior {
+                            if !is_namespaced_name(behavior) {
+                                self.add_issue(&pack_definitions.pack, BLOCKS_FILE, location.clone(), String::from("behavior name is not namespaced like namespace:name"));
+                            }
+                        }
+                        for (box_pos, selection_box) in block_data.selection.iter().flatten().enumerate() {
+                            if (0..3).any(|axis| selection_box.min[axis] < 0.0 || selection_box.max[axis] > 1.0 || selection_box.min[axis] >= selection_box.max[axis]) {
+                                self.add_issue(&pack_definitions.pack, BLOCKS_FILE, format!("{} selection {}", location, box_pos), String::from("selection box must be non empty and inside the block"));
+                            }
+                        }
+                        blocks.insert(block_data.name);
+                    }
+                    Err(error) => {
+                        self.add_issue(&pack_definitions.pack, BLOCKS_FILE, location, format!("invalid block: {}", error));
+                    }
+                }
+            }
+        }
+        for default_block in [AIR_BLOCK_NAME, UNKNOWN_BLOCK_NAME] {
+            if !blocks.contains(default_block) {
+                self.add_issue("*", BLOCKS_FILE, String::new(), format!("default block {} is not defined by any pack", default_block));
+            }
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use crate::packs::test_packs::{get_test_dir, write_dir_pack};
+    use super::*;
+
+    //Top face of a block split into two triangles with the given indices and the tex of the first vertex
+    fn get_face_json(name: &str, first_tex: Vec2f, indices: &[i32]) -> String {
+        return format!(r#"{{"name": "{}", "vertices": [
+            {{"pos": [0.0, 1.0, 0.0], "tex": [{:?}, {:?}]}}, {{"pos": [0.0, 1.0, 1.0], "tex": [0.0, 0.0]}},
+            {{"pos": [1.0, 1.0, 0.0], "tex": [0.0, 0.0]}}, {{"pos": [1.0, 1.0, 1.0], "tex": [0.0, 0.0]}}
+        ], "indices": {:?}}}"#, name, first_tex[0], first_tex[1], indices);
+    }
+
+    fn get_custom_mesh_json(name: &str, faces: &[&str]) -> String {
+        return format!(r#"{{"name": "{}", "mesh_type": "custom", "mesh": {{"faces": {:?}}}}}"#, name, faces);
+    }
+
+    //Issues of the test pack on top of the core pack as (file name, location, message)
+    fn check_test_pack(test_name: &str, faces: &[String], meshes: &[String], blocks: &str) -> Vec<(String, String, String)> {
+        let faces_data: String = format!("[{}]", faces.join(","));
+        let meshes_data: String = format!("[{}]", meshes.join(","));
+        let pack_path: PathBuf = write_dir_pack(&get_test_dir(test_name), "test", &[(FACES_FILE, faces_data.as_bytes()), (MESHES_FILE, meshes_data.as_bytes()), (BLOCKS_FILE, blocks.as_bytes())]);
+        let issues: Vec<AssetsIssue> = AssetsValidator::check(&PackStack::open(&[PathBuf::from("assets"), pack_path]).unwrap()).unwrap();
+        assert!(issues.iter().all(|issue| issue.pack == "test"), "core pack has issues");
+        return issues.into_iter().map(|issue| (issue.file_name, issue.location, issue.message)).collect();
+    }
+
+    fn get_issue(file_name: &str, location: &str, message: &str) -> (String, String, String) {
+        return (String::from(file_name), String::from(location), String::from(message));
+    }
+
+    #[test]
+    fn valid_pack_has_no_issues() {
+        let issues = check_test_pack("validator_valid", &[get_face_json("test_face", [0.0, 0.0], &[0, 1, 2, 2, 1, 3])], &[get_custom_mesh_json("test_mesh", &["test_face"])],
+            r#"[{"name": "test:block", "mesh": "test_mesh", "light_r": 0, "light_g": 0, "light_b": 0}]"#);
+        assert_eq!(issues, Vec::<(String, String, String)>::new());
+    }
+
+    #[test]
+    fn dangling_face_and_mesh_references_are_reported() {
+        let issues = check_test_pack("validator_dangling", &[get_face_json("test_face", [0.0, 0.0], &[0, 1, 2])], &[get_custom_mesh_json("test_mesh", &["test_face", "missing_face"])],
+            r#"[{"name": "test:block", "mesh": "missing_mesh", "light_r": 0, "light_g": 0, "light_b": 0}]"#);
+        assert_eq!(issues, vec![
+            get_issue(MESHES_FILE, "#0 \"test_mesh\"", "unknown face missing_face"),
+            get_issue(BLOCKS_FILE, "#0 \"test:block\"", "unknown mesh missing_mesh"),
+        ]);
+    }
+
+    #[test]
+    fn tex_outside_of_the_atlas_is_reported() {
+        let issues = check_test_pack("validator_tex", &[get_face_json("test_face", [1.5, -0.25], &[0, 1, 2])], &[get_custom_mesh_json("test_mesh", &["test_face"])], "[]");
+        assert_eq!(issues, vec![get_issue(FACES_FILE, "#0 \"test_face\" vertex 0", "tex [1.5, -0.25] is outside of the atlas")]);
+    }
+
+    #[test]
+    fn indices_out_of_range_are_reported() {
+        let issues = check_test_pack("validator_indices", &[get_face_json("test_face", [0.0, 0.0], &[0, 1, 4, 2])], &[get_custom_mesh_json("test_mesh", &["test_face"])], "[]");
+        assert_eq!(issues, vec![
+            get_issue(FACES_FILE, "#0 \"test_face\"", "4 indices are not a multiple of 3"),
+            get_issue(FACES_FILE, "#0 \"test_face\" index 2", "index 4 is outside of 4 vertices"),
+        ]);
+    }
+
+    #[test]
+    fn inconsistent_winding_of_adjacent_triangles_is_reported() {
+        let issues = check_test_pack("validator_winding", &[
+            get_face_json("test_face", [0.0, 0.0], &[0, 1, 2, 1, 2, 3]),
+            get_face_json("degenerate_face", [0.0, 0.0], &[0, 1, 2, 0, 0, 3]),
+        ], &[get_custom_mesh_json("test_mesh", &["test_face", "degenerate_face"])], "[]");
+        assert_eq!(issues, vec![
+            get_issue(FACES_FILE, "#0 \"test_face\" triangle 1", "winding is inconsistent with triangle 0 sharing an edge"),
+            get_issue(FACES_FILE, "#1 \"degenerate_face\" triangle 1", "triangle is degenerate"),
+        ]);
+    }
+
+    #[test]
+    fn crossed_quads_are_not_reported_as_inconsistent() {
+        let crossed_face: &str = r#"{"name": "test_face", "vertices": [
+            {"pos": [0.0, 0.0, 0.0], "tex": [0.0, 0.0]}, {"pos": [1.0, 0.0, 1.0], "tex": [0.0, 0.0]}, {"pos": [0.0, 1.0, 0.0], "tex": [0.0, 0.0]}, {"pos": [1.0, 1.0, 1.0], "tex": [0.0, 0.0]},
+            {"pos": [1.0, 0.0, 0.0], "tex": [0.0, 0.0]}, {"pos": [0.0, 0.0, 1.0], "tex": [0.0, 0.0]}, {"pos": [1.0, 1.0, 0.0], "tex": [0.0, 0.0]}, {"pos": [0.0, 1.0, 1.0], "tex": [0.0, 0.0]}
+        ], "indices": [0, 1, 2, 2, 1, 3, 5, 4, 7, 7, 4, 6]}"#;
+        let issues = check_test_pack("validator_crossed", &[String::from(crossed_face)], &[get_custom_mesh_json("test_mesh", &["test_face"])], "[]");
+        assert_eq!(issues, Vec::<(String, String, String)>::new());
+    }
+
+    #[test]
+    fn duplicate_names_are_reported() {
+        let issues = check_test_pack("validator_duplicates", &[
+            get_face_json("test_face", [0.0, 0.0], &[0, 1, 2]),
+            get_face_json("test_face", [0.0, 0.0], &[0, 1, 2]),
+        ], &[get_custom_mesh_json("test_mesh", &["test_face"]), get_custom_mesh_json("test_mesh", &["test_face"])],
+            r#"[{"name": "test:block", "mesh": "test_mesh", "light_r": 0, "light_g": 0, "light_b": 0}, {"name": "test:block", "mesh": "test_mesh", "light_r": 0, "light_g": 0, "light_b": 0}]"#);
+        assert_eq!(issues, vec![
+            get_issue(FACES_FILE, "#1 \"test_face\"", "duplicate face name"),
+            get_issue(MESHES_FILE, "#1 \"test_mesh\"", "duplicate mesh name"),
+            get_issue(BLOCKS_FILE, "#1 \"test:block\"", "duplicate block name"),
+        ]);
+    }
+
+    #[test]
+    fn unused_faces_are_reported() {
+        let issues = check_test_pack("validator_unused", &[
+            get_face_json("test_face", [0.0, 0.0], &[0, 1, 2]),
+            get_face_json("unused_face", [0.0, 0.0], &[0, 1, 2]),
+        ], &[get_custom_mesh_json("test_mesh", &["test_face"])], "[]");
+        assert_eq!(issues, vec![get_issue(FACES_FILE, "#1 \"unused_face\"", "face is not used by any mesh")]);
+    }
+}
\ No newline at end of file
@@ -1,12 +1,14 @@
 use std::cell::RefCell;
 use std::ops::Deref;
 use std::rc::Rc;
-use crate::render::blocks_loader::{Block, BlocksLoader, BlockUsingError, GRASS_BLOCK_ID, UNKNOWN_BLOCK_ID};
+use crate::render::blocks_loader::{Block, BlocksLoader, BlockUsingError};
 use crate::render::faces_loader::Face;
 use crate::render::light::light_map::{B_CHANNEL, G_CHANNEL, R_CHANNEL, S_CHANNEL};
 use crate::render::light::light_solver::LightSolver;
-use crate::render::meshes_loader::Mesh::{Cube, Custom};
-use crate::render::types::{Vec3b, Vec3s, Vec3ub, LightedTexVertex, RGBSColor};
+use crate::render::meshes_loader::FluidMesh;
+use crate::render::meshes_loader::Mesh::{Cube, Custom, Fluid};
+use crate::physics::fluid_solver::get_fluid_height;
+use crate::render::types::{Vec2f, Vec3b, Vec3f, Vec3s, Vec3ub, LightedTexVertex, RGBSColor};
 use crate::world::World;
 
 //TODO glEnable(CULL_FACE)
@@ -43,6 +45,105 @@ fn render_face(face: &Rc<Face>, vertices: &mut Vec<LightedTexVertex>, indices: &
     }
 }
 
+//Quad of corners (0, 0), (0, 1), (1, 0), (1, 1) in its plane
+fn render_quad(corners: &[Vec3f; 4], tex: &[Vec2f; 4], vertices: &mut Vec<LightedTexVertex>, indices: &mut Vec<i32>, pos: &Vec3ub, light: &RGBSColor) {
+    let ind_offset = (vertices.len() as i32);
+    for (corner, corner_tex) in corners.iter().zip(tex.iter()) {
+        vertices.push(LightedTexVertex([corner[0] + (pos[0] as f32), corner[1] + (pos[1] as f32), corner[2] + (pos[2] as f32)], *corner_tex, light.clone()));
+    }
+    for ind in [0, 1, 2, 2, 1, 3] {
+        indices.push(ind + ind_offset);
+    }
+}
+
+fn get_light(world: &World, pos: &Vec3ub) -> RGBSColor {
+    return [
+        (world.get_light_level(pos, R_CHANNEL) as f32) / 15.0f32,
+        (world.get_light_level(pos, G_CHANNEL) as f32) / 15.0f32,
+        (world.get_light_level(pos, B_CHANNEL) as f32) / 15.0f32,
+        (world.get_light_level(pos, S_CHANNEL) as f32) / 15.0f32,
+    ];
+}
+
+//Average surface height of the same fluid cells around the corner between x - 1..x and z - 1..z, full if fluid is above any of them
+fn get_fluid_corner_height(world: &World, block_lid: u16, global_pos: &Vec3ub, corner: &[i8; 2]) -> f32 {
+    let mut heights_sum: f32 = 0.0;
+    let mut heights_cnt: u32 = 0;
+    for x_offset in (corner[0] - 1)..=corner[0] {
+        for z_offset in (corner[1] - 1)..=corner[1] {
+            if let Some(cell_pos) = LightSolver::get_neighbor_pos(global_pos, &[x_offset, 0, z_offset]) {
+                if world.get_block(&cell_pos) == block_lid {
+                    if let Some(top_pos) = LightSolver::get_neighbor_pos(&cell_pos, &NEIGHBOR_TOP) {
+                        if world.get_block(&top_pos) == block_lid {
+                            return 1.0;
+                        }
+                    }
+                    heights_sum += get_fluid_height(world.get_state(&cell_pos));
+                    heights_cnt += 1;
+                }
+            }
+        }
+    }
+    return if heights_cnt != 0 { heights_sum / (heights_cnt as f32) } else { 0.0 };
+}
+
+//Sloped surface from the levels of the fluid, sides are drawn towards everything but cubes and the same fluid
+fn render_fluid(world: &World, fluid_mesh: &FluidMesh, block_lid: u16, vertices: &mut Vec<LightedTexVertex>, indices: &mut Vec<i32>, global_pos: &Vec3ub, pos: &Vec3ub, blocks_loader: &BlocksLoader) -> Result<(), Box<dyn std::error::Error>> {
+    let light: RGBSColor = get_light(world, global_pos);
+    let heights: [f32; 4] = [
+        get_fluid_corner_height(world, block_lid, global_pos, &[0, 0]),
+        get_fluid_corner_height(world, block_lid, global_pos, &[0, 1]),
+        get_fluid_corner_height(world, block_lid, global_pos, &[1, 0]),
+        get_fluid_corner_height(world, block_lid, global_pos, &[1, 1]),
+    ];
+    let (top_min, top_max) = fluid_mesh.top.get_tex_bounds();
+    let (side_min, side_max) = fluid_mesh.side.get_tex_bounds();
+    let is_hidden = |offset: &Vec3b| -> Result<bool, Box<dyn std::error::Error>> {
+        return if let Some(neigh_block_lid) = get_neighbor_block(world, global_pos, offset) {
+            if let Some(neigh_block) = blocks_loader.loaded_blocks.get(neigh_block_lid as usize) {
+                Ok(neigh_block_lid == block_lid || neigh_block.mesh.is_cube())
+            } else {
+                Err(Box::new(BlockUsingError::BlockNotFoundError()))
+            }
+        } else {
+            Ok(false)
+        }
+    };
+    if !is_hidden(&NEIGHBOR_TOP)? {
+        render_quad(&[[0.0, heights[0], 0.0], [0.0, heights[1], 1.0], [1.0, heights[2], 0.0], [1.0, heights[3], 1.0]],
+                    &[[top_min[0], top_max[1]], [top_min[0], top_min[1]], [top_max[0], top_max[1]], [top_max[0], top_min[1]]],
+                    vertices, indices, pos, &light);
+    }
+    if !is_hidden(&NEIGHBOR_BOTTOM)? {
+        render_quad(&[[0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [1.0, 0.0, 1.0]],
+                    &[[top_min[0], top_max[1]], [top_min[0], top_min[1]], [top_max[0], top_max[1]], [top_max[0], top_min[1]]],
+                    vertices, indices, pos, &light);
+    }
+    //sides by their bottom corners, heights indices follow the corners
+    let sides: [(Vec3b, [Vec3f; 2], [usize; 2]); 4] = [
+        (NEIGHBOR_FRONT, [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]], [0, 2]),
+        (NEIGHBOR_BACK, [[1.0, 0.0, 1.0], [0.0, 0.0, 1.0]], [3, 1]),
+        (NEIGHBOR_RIGHT, [[1.0, 0.0, 0.0], [1.0, 0.0, 1.0]], [2, 3]),
+        (NEIGHBOR_LEFT, [[0.0, 0.0, 1.0], [0.0, 0.0, 0.0]], [1, 0]),
+    ];
+    for (offset, side_corners, side_heights) in sides {
+        if !is_hidden(&offset)? {
+            let first_height: f32 = heights[side_heights[0]];
+            let second_height: f32 = heights[side_heights[1]];
+            let tex_height = |height: f32| get_side_tex_v(side_min[1], side_max[1], height);
+            render_quad(&[side_corners[0], [side_corners[0][0], first_height, side_corners[0][2]], side_corners[1], [side_corners[1][0], second_height, side_corners[1][2]]],
+                        &[[side_min[0], side_max[1]], [side_min[0], tex_height(first_height)], [side_max[0], side_max[1]], [side_max[0], tex_height(second_height)]],
+                        vertices, indices, pos, &light);
+        }
+    }
+    return Ok(());
+}
+
+//v of the texture at height, v grows down the tile
+fn get_side_tex_v(min_v: f32, max_v: f32, height: f32) -> f32 {
+    return max_v - (max_v - min_v) * height;
+}
+
 pub(crate) fn get_neighbor_light(world: &World, pos: &Vec3ub, offset: &Vec3b, channel: u8) -> u8 { //if not exists return 0
     return if let Some(neigh_pos) = LightSolver::get_neighbor_pos(&pos, &offset) {
         world.get_light_level(&neigh_pos, channel)
@@ -53,13 +154,7 @@ pub(crate) fn get_neighbor_light(world: &World, pos: &Vec3ub, offset: &Vec3b, ch
 
 pub(crate) fn render_block(world: &World, blocks_loader: &BlocksLoader, block_lid: u16, vertices: &mut Vec<LightedTexVertex>, indices: &mut Vec<i32>, subchunk_pos: &Vec3ub, pos: &Vec3ub) -> Result<(), Box<dyn std::error::Error>> {
     let block: &Rc<Block> = match blocks_loader.loaded_blocks.get(block_lid as usize) {
-        None => {
-            if let Some(block) = blocks_loader.blocks_ids.get(&UNKNOWN_BLOCK_ID) {
-                block
-            } else {
-                return Err(Box::new(BlockUsingError::BlockNotFoundError()));
-            }
-        }
+        None => {&blocks_loader.unknown_block}
         Some(block) => {block}
     };
     let global_pos: &Vec3ub = &[(pos[0] + (subchunk_pos[1] << 4)), (pos[1] + (subchunk_pos[0] << 4)), (pos[2] + (subchunk_pos[2] << 4))];
@@ -94,6 +189,9 @@ pub(crate) fn render_block(world: &World, blocks_loader: &BlocksLoader, block_li
                 }
             }
         }
+        Fluid(fluid_mesh) => {
+            render_fluid(world, fluid_mesh, block.lid, vertices, indices, global_pos, pos, blocks_loader)?;
+        }
         Custom(custom_mesh) => {
             if custom_mesh.faces.len() != 0 {
                 let mut rend = false;
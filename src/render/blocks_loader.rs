@@ -1,12 +1,10 @@
-//TODO Loading behavior
-//TODO struct BlockBehavior
-
-use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use crate::packs::pack_stack::PackStack;
+use crate::physics::aabb::{Aabb, FULL_BLOCK_AABB};
+use crate::render::faces_loader::FacesLoader;
 use crate::render::meshes_loader::{Mesh, MeshesLoader};
 
 
@@ -14,68 +12,97 @@ use crate::render::meshes_loader::{Mesh, MeshesLoader};
 pub enum BlocksLoadingError {
     #[error("Deserialization failed")]
     DeserializationError(),
-    #[error("Redefinition error")]
-    RedefinitionError(),
-    #[error("Default block not found error")]
-    DefaultBlockNotFoundError(),
-    #[error("Unknown mesh error")]
-    UnknownMeshError(),
+    #[error("Block {0} is defined twice")]
+    RedefinitionError(String),
+    #[error("Default block {0} not found")]
+    DefaultBlockNotFoundError(String),
+    #[error("Block {0} uses unknown mesh {1}")]
+    UnknownMeshError(String, String),
+    #[error("Block name {0} is not namespaced like namespace:name")]
+    InvalidNameError(String),
 }
 
 #[derive(Error, Debug)]
 pub enum BlockUsingError {
     #[error("Block not found")]
     BlockNotFoundError(),
+    #[error("Block {0} not found")]
+    BlockNameNotFoundError(String),
 }
 
 
-pub const AIR_BLOCK_ID: u16 = 0;
-pub const UNKNOWN_BLOCK_ID: u16 = 1;
-pub const DIRT_BLOCK_ID: u16 = 2;
-pub const GRASS_BLOCK_ID: u16 = 3;
-pub const BEDROCK_BLOCK_ID: u16 = 4;
+pub const BLOCKS_FILE: &str = "blocks.json";
 
-//TODO ARRAY OF BLOCKS WITHOUT DEFINING IDS IN JSON
+pub const AIR_BLOCK_NAME: &str = "core:air";
+pub const UNKNOWN_BLOCK_NAME: &str = "core:unknown";
 
-const DEFAULT_BLOCKS: &[(u16, &str)] = &[
-    (AIR_BLOCK_ID,                  "air"),
-    (UNKNOWN_BLOCK_ID,              "unknown"),
-    (DIRT_BLOCK_ID,                 "dirt"),
-    (GRASS_BLOCK_ID,                "grass"),
-    (BEDROCK_BLOCK_ID,              "bedrock"),
+const DEFAULT_BLOCKS: &[&str] = &[
+    AIR_BLOCK_NAME,
+    UNKNOWN_BLOCK_NAME,
 ];
 
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct FluidData {
+    pub flow_distance: u8, //how many blocks the fluid flows horizontally from a source, at most 7
+    pub tick_delay: u32, //ticks between flow updates
+    #[serde(default)]
+    pub regenerates: bool, //two neighboring sources over ground make a new source
+    #[serde(default)]
+    pub buoyancy: f32, //part of gravity pushing submerged hitboxes up
+    #[serde(default)]
+    pub drag: f32, //velocity decay per time unit of submerged hitboxes
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct BlockData {
-    pub id: u16,
     pub name: String,
     pub light_r: u8,
     pub light_g: u8,
     pub light_b: u8,
     pub mesh: String,
-    //TODO behavior
+    #[serde(default)]
+    pub collision: Option<Vec<Aabb>>, //full block if not set, block space boxes
+    #[serde(default)]
+    pub selection: Option<Vec<Aabb>>, //collision boxes if not set
+    #[serde(default)]
+    pub fluid: Option<FluidData>,
+    #[serde(default)]
+    pub behavior: Option<String>, //name of the registered behavior handling the block hooks
 }
 
 pub struct Block {
-    //TODO without lid
-    pub lid: u16,
-    pub id: u16,
+    pub lid: u16, //assigned by loading order, saves keep their own names registry
     pub name: String,
+    pub pack: String, //name of the pack the block came from
     //TODO MB rename to lum, lumen, illumination or something
     pub light_r: u8,
     pub light_g: u8,
     pub light_b: u8,
     pub mesh: Rc<Mesh>,
+    pub collision: Vec<Aabb>,
+    pub selection: Vec<Aabb>, //boxes hit by the player's ray, not selectable if empty
+    pub fluid: Option<FluidData>,
+    pub behavior: Option<String>,
 }
 
 pub struct BlocksLoader {
     pub loaded_blocks: Vec<Rc<Block>>,
     pub blocks_names: HashMap<String, Rc<Block>>,
-    pub blocks_ids: HashMap<u16, Rc<Block>>,
     pub meshes_loader: MeshesLoader,
+    pub air_block: Rc<Block>,
     pub unknown_block: Rc<Block>,
 }
 
+//namespace:name, both parts of lowercase letters, digits, '_', '-', '.' and '/'
+pub fn is_namespaced_name(name: &str) -> bool {
+    let is_valid_part = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-' || c == '.' || c == '/');
+    return if let Some((namespace, path)) = name.split_once(':') {
+        is_valid_part(namespace) && is_valid_part(path)
+    } else {
+        false
+    }
+}
+
 impl BlocksLoader {
 
     //TODO rewrite everywhere with this func
@@ -87,71 +114,83 @@ impl BlocksLoader {
         }
     }
 
-    pub fn load(blocks_path: &Path, meshes_loader: MeshesLoader) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn get_block_by_name(&self, name: &str) -> Option<Rc<Block>> {
+        return self.blocks_names.get(name).cloned();
+    }
+
+    pub fn get_lid(&self, name: &str) -> Result<u16, BlockUsingError> {
+        return if let Some(block) = self.blocks_names.get(name) {
+            Ok(block.lid)
+        } else {
+            Err(BlockUsingError::BlockNameNotFoundError(String::from(name)))
+        }
+    }
+
+    fn load_block(block_value: &serde_json::Value, meshes_loader: &MeshesLoader, lid: u16, pack: &str) -> Result<Block, Box<dyn std::error::Error>> {
+        let block_data: BlockData = BlockData::deserialize(block_value)?;
+        if !is_namespaced_name(&block_data.name) {
+            return Err(Box::new(BlocksLoadingError::InvalidNameError(block_data.name)));
+        }
+        let collision: Vec<Aabb> = block_data.collision.unwrap_or(vec![FULL_BLOCK_AABB]);
+        return if let Some(mesh) = meshes_loader.meshes.get(&block_data.mesh) {
+            Ok(Block {
+                lid,
+                name: block_data.name,
+                pack: String::from(pack),
+                light_r: block_data.light_r,
+                light_g: block_data.light_g,
+                light_b: block_data.light_b,
+                mesh: mesh.clone(),
+                collision: collision.clone(),
+                selection: block_data.selection.unwrap_or(collision),
+                fluid: block_data.fluid,
+                behavior: block_data.behavior,
+            })
+        } else {
+            Err(Box::new(BlocksLoadingError::UnknownMeshError(block_data.name, block_data.mesh)))
+        }
+    }
+
+    //Overridden blocks keep the place of the first definition, new blocks are appended in packs order
+    pub fn load(packs: &PackStack, meshes_loader: MeshesLoader) -> Result<Self, Box<dyn std::error::Error>> {
         let mut loaded_blocks: Vec<Rc<Block>> = Vec::new();
         let mut blocks_names: HashMap<String, Rc<Block>> = HashMap::new();
-        let mut blocks_ids: HashMap<u16, Rc<Block>> = HashMap::new();
-        let blocks_data = fs::read_to_string(blocks_path)?;
-        let serialized: serde_json::Value = serde_json::from_str(&blocks_data)?;
-        if let Some(blocks_values) = serialized.as_array() {
-            for block_value in blocks_values {
-                let block_data: BlockData = BlockData::deserialize(block_value)?;
-                if let Some(mesh) = meshes_loader.meshes.get(&block_data.mesh) {
-                    let block_name = block_data.name.clone();
-                    let block_ref: Rc<Block> = Rc::new( Block{
-                        lid: loaded_blocks.len() as u16,
-                        id: block_data.id,
-                        name: block_data.name,
-                        light_r: block_data.light_r,
-                        light_g: block_data.light_g,
-                        light_b: block_data.light_b,
-                        mesh: mesh.clone(),
-                    } );
-                    loaded_blocks.push(block_ref.clone());
-                    if let Some(_) = blocks_names.insert(block_name, block_ref.clone()) {
-                        return Err(Box::new(BlocksLoadingError::RedefinitionError()));
-                    }
-                    if let Some(_) = blocks_ids.insert(block_data.id, block_ref.clone()) {
-                        return Err(Box::new(BlocksLoadingError::RedefinitionError()));
-                    }
-                } else {
-                    return Err(Box::new(BlocksLoadingError::UnknownMeshError()));
-                }
-            }
-            for default_block in DEFAULT_BLOCKS {
-                if let Some(block) = blocks_names.get(default_block.1) {
-                    if block.id != default_block.0 {
-                        return Err(Box::new(BlocksLoadingError::DefaultBlockNotFoundError()));
-                    }
-                    if let Some(loaded_block) = loaded_blocks.get(block.lid as usize) {
-                        if !Rc::ptr_eq(loaded_block, block) {
-                            return Err(Box::new(BlocksLoadingError::DefaultBlockNotFoundError()));
-                        }
-                    }
-                } else {
-                    return Err(Box::new(BlocksLoadingError::DefaultBlockNotFoundError()));
+        for pack_definitions in packs.read_definitions(BLOCKS_FILE)? {
+            let mut pack_blocks: HashSet<String> = HashSet::new();
+            for block_value in &pack_definitions.values {
+                let mut block: Block = Self::load_block(block_value, &meshes_loader, loaded_blocks.len() as u16, &pack_definitions.pack).map_err(|error| pack_definitions.get_error(error))?;
+                if !pack_blocks.insert(block.name.clone()) {
+                    return Err(pack_definitions.get_error(Box::new(BlocksLoadingError::RedefinitionError(block.name))));
                 }
-                if let Some(block) = blocks_ids.get(&default_block.0) {
-                    if block.name != default_block.1 {
-                        return Err(Box::new(BlocksLoadingError::DefaultBlockNotFoundError()));
-                    }
-                    if let Some(loaded_block) = loaded_blocks.get(block.lid as usize) {
-                        if !Rc::ptr_eq(loaded_block, block) {
-                            return Err(Box::new(BlocksLoadingError::DefaultBlockNotFoundError()));
-                        }
-                    }
+                if let Some(overridden_block) = blocks_names.get(&block.name) {
+                    block.lid = overridden_block.lid;
+                    let block_ref: Rc<Block> = Rc::new(block);
+                    loaded_blocks[block_ref.lid as usize] = block_ref.clone();
+                    blocks_names.insert(block_ref.name.clone(), block_ref);
                 } else {
-                    return Err(Box::new(BlocksLoadingError::DefaultBlockNotFoundError()));
+                    let block_ref: Rc<Block> = Rc::new(block);
+                    loaded_blocks.push(block_ref.clone());
+                    blocks_names.insert(block_ref.name.clone(), block_ref);
                 }
             }
-        } else {
-            return Err(Box::new(BlocksLoadingError::DeserializationError()));
         }
-        return if let Some(block) = loaded_blocks.get(UNKNOWN_BLOCK_ID as usize) {
-            let unknown_block = block.clone();
-            Ok(Self { loaded_blocks, blocks_names, blocks_ids, meshes_loader, unknown_block })
+        for default_block in DEFAULT_BLOCKS {
+            if !blocks_names.contains_key(*default_block) {
+                return Err(Box::new(BlocksLoadingError::DefaultBlockNotFoundError(String::from(*default_block))));
+            }
+        }
+        return if let (Some(air_block), Some(unknown_block)) = (blocks_names.get(AIR_BLOCK_NAME), blocks_names.get(UNKNOWN_BLOCK_NAME)) {
+            let air_block = air_block.clone();
+            let unknown_block = unknown_block.clone();
+            Ok(Self { loaded_blocks, blocks_names, meshes_loader, air_block, unknown_block })
         } else {
-            Err(Box::new(BlocksLoadingError::DefaultBlockNotFoundError()))
+            Err(Box::new(BlocksLoadingError::DefaultBlockNotFoundError(String::from(UNKNOWN_BLOCK_NAME))))
         }
     }
+
+    //Blocks for code that only looks them up, like commands and tests
+    pub fn load_headless(packs: &PackStack) -> Result<Self, Box<dyn std::error::Error>> {
+        let meshes_loader: MeshesLoader = MeshesLoader::load(packs, FacesLoader::load_headless(packs)?)?;
+        return Self::load(packs, meshes_loader);
+    }
 }
\ No newline at end of file
@@ -1,11 +1,11 @@
-use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
+use image::RgbaImage;
 use crate::render::texture::Texture;
-use crate::render::types::{LightedTexVertex};
+use crate::render::types::{LightedTexVertex, Vec2f};
 use serde::{Serialize, Deserialize};
 use thiserror::Error;
+use crate::packs::pack_stack::PackStack;
 use crate::render::shader_program::ShaderProgram;
 
 //TODO TEX LOADER MUL TEX COORDS TEX NAME IN FACE AND LOCAL TEX COORDS IN JSON
@@ -15,61 +15,113 @@ use crate::render::shader_program::ShaderProgram;
 pub enum FacesLoadingError {
     #[error("Deserialization failed")]
     DeserializationError(),
-    #[error("Redefinition error")]
-    RedefinitionError(),
-    #[error("Wrong indices count error")]
-    WrongIndicesCountError(),
+    #[error("Face {0} is defined twice")]
+    RedefinitionError(String),
+    #[error("Face {0} has {1} indices, not a multiple of 3")]
+    WrongIndicesCountError(String, usize),
+    #[error("Faces were loaded without a GL context")]
+    HeadlessError(),
 }
 
 
+pub const FACES_FILE: &str = "faces.json";
+pub const BLOCKS_ATLAS_FILE: &str = "blocks_atlas.png";
+
 #[derive(Serialize, Deserialize)]
 pub(crate) struct Face {
     pub(crate) name: String,
     pub(crate) vertices: Vec<LightedTexVertex>,
     pub(crate) indices: Vec<i32>,
+    #[serde(skip)]
+    pub(crate) pack: String, //name of the pack the face came from
+}
+
+impl Face {
+    //Smallest and largest texture coords of the face
+    pub(crate) fn get_tex_bounds(&self) -> (Vec2f, Vec2f) {
+        let mut min: Vec2f = [f32::INFINITY, f32::INFINITY];
+        let mut max: Vec2f = [f32::NEG_INFINITY, f32::NEG_INFINITY];
+        for vertex in &self.vertices {
+            let tex: Vec2f = vertex.1; //vertices are packed
+            min = [min[0].min(tex[0]), min[1].min(tex[1])];
+            max = [max[0].max(tex[0]), max[1].max(tex[1])];
+        }
+        return if self.vertices.len() != 0 {
+            (min, max)
+        } else {
+            ([0.0, 0.0], [0.0, 0.0])
+        }
+    }
 }
 
 pub struct FacesLoader {
-    pub atlas: Texture,
+    pub atlas: Option<Texture>, //None when loaded headless
+    pub atlas_image: RgbaImage, //kept for baking block icons
     pub faces: HashMap<String, Rc<Face>>,
-    pub shader_program: Rc<ShaderProgram>,
+    pub shader_program: Option<Rc<ShaderProgram>>, //None when loaded headless
     //TODO SHADERS HASHMAP AND SHADER NAME IN JSON AND SHADERS CODE PATH IN JSON AND SHADERS CODES FILES .glsl
 }
 
 impl FacesLoader {
-    pub fn load(atlas_path: &Path, faces_path: &Path, shader_program: Rc<ShaderProgram>) -> Result<Self, Box<dyn std::error::Error>> {
-        unsafe {
-            let mut atlas = Texture::new();
-            atlas.load(atlas_path)?;
-            shader_program.set_uniform_i32("tex", 0)?;
-            let mut faces: HashMap<String, Rc<Face>> = HashMap::new();
-            let faces_data = fs::read_to_string(faces_path)?;
-            let serialized: serde_json::Value = serde_json::from_str(&faces_data)?;
-            if let Some(faces_values) = serialized.as_array() {
-                for face_value in faces_values {
-                    let face_data: Face = Face::deserialize(face_value)?;
-                    if (face_data.indices.len() % 3) == 0 {
-                        if let Some(_) = faces.insert(face_data.name.clone(), Rc::new( Face {
-                            name: face_data.name,
-                            vertices: face_data.vertices,
-                            indices: face_data.indices,
-                        } )) {
-                            return Err(Box::new(FacesLoadingError::RedefinitionError()));
-                        }
-                    } else {
-                        return Err(Box::new(FacesLoadingError::WrongIndicesCountError()));
-                    }
+    pub fn get_atlas(&self) -> Result<&Texture, FacesLoadingError> {
+        return self.atlas.as_ref().ok_or(FacesLoadingError::HeadlessError());
+    }
+
+    pub fn get_shader_program(&self) -> Result<&Rc<ShaderProgram>, FacesLoadingError> {
+        return self.shader_program.as_ref().ok_or(FacesLoadingError::HeadlessError());
+    }
+
+    fn load_face(face_value: &serde_json::Value, pack: &str) -> Result<Face, Box<dyn std::error::Error>> {
+        let mut face_data: Face = Face::deserialize(face_value)?;
+        return if (face_data.indices.len() % 3) == 0 {
+            face_data.pack = String::from(pack);
+            Ok(face_data)
+        } else {
+            Err(Box::new(FacesLoadingError::WrongIndicesCountError(face_data.name, face_data.indices.len())))
+        }
+    }
+
+    //No GL here
+    pub(crate) fn load_faces(packs: &PackStack) -> Result<HashMap<String, Rc<Face>>, Box<dyn std::error::Error>> {
+        let mut faces: HashMap<String, Rc<Face>> = HashMap::new();
+        for pack_definitions in packs.read_definitions(FACES_FILE)? {
+            let mut pack_faces: HashSet<String> = HashSet::new();
+            for face_value in &pack_definitions.values {
+                let face: Face = Self::load_face(face_value, &pack_definitions.pack).map_err(|error| pack_definitions.get_error(error))?;
+                if !pack_faces.insert(face.name.clone()) {
+                    return Err(pack_definitions.get_error(Box::new(FacesLoadingError::RedefinitionError(face.name))));
                 }
-            } else {
-                return Err(Box::new(FacesLoadingError::DeserializationError()));
+                faces.insert(face.name.clone(), Rc::new(face));
             }
+        }
+        return Ok(faces);
+    }
+
+    pub fn load(packs: &PackStack, shader_program: Rc<ShaderProgram>) -> Result<Self, Box<dyn std::error::Error>> {
+        let faces: HashMap<String, Rc<Face>> = Self::load_faces(packs)?;
+        let atlas_image: RgbaImage = packs.load_atlas(BLOCKS_ATLAS_FILE)?;
+        unsafe {
+            let atlas = Texture::new();
+            atlas.load_image(&atlas_image);
+            shader_program.set_uniform_i32("tex", 0)?;
             Ok(Self {
-                atlas,
+                atlas: Some(atlas),
+                atlas_image,
                 faces,
-                shader_program,
+                shader_program: Some(shader_program),
             })
         }
     }
-}
-
 
+    //Without a GL context, for commands and tests, nothing can be drawn with it
+    pub fn load_headless(packs: &PackStack) -> Result<Self, Box<dyn std::error::Error>> {
+        let faces: HashMap<String, Rc<Face>> = Self::load_faces(packs)?;
+        let atlas_image: RgbaImage = packs.load_atlas(BLOCKS_ATLAS_FILE)?;
+        return Ok(Self {
+            atlas: None,
+            atlas_image,
+            faces,
+            shader_program: None,
+        });
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::packs::pack_stack::PackStack;
+use crate::render::types::Vec2f;
+
+#[derive(Error, Debug)]
+pub enum FontsLoadingError {
+    #[error("Font {0} is defined twice")]
+    RedefinitionError(String),
+    #[error("Default font {0} not found")]
+    DefaultFontNotFoundError(String),
+    #[error("Glyph \"{1}\" of font {0} must be a single character")]
+    InvalidCharError(String, String),
+    #[error("Glyph \"{1}\" of font {0} is outside of the atlas")]
+    GlyphOutOfAtlasError(String, String),
+    #[error("Kerning pair \"{1}\" \"{2}\" of font {0} must be of single characters")]
+    InvalidKerningError(String, String, String),
+}
+
+pub const FONTS_FILE: &str = "fonts.json";
+pub const DEFAULT_FONT_NAME: &str = "core:default";
+const FALLBACK_CHAR: char = '?';
+
+//Atlas rect of the glyph in pixels, offset from the pen position at the top of the line
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GlyphData {
+    pub char: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub x_offset: i32,
+    #[serde(default)]
+    pub y_offset: i32,
+    pub advance: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct KerningData {
+    pub first: String,
+    pub second: String,
+    pub amount: i32, //added to the advance of the first char followed by the second one
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FontData {
+    pub name: String,
+    pub atlas: String,
+    pub line_height: i32,
+    pub glyphs: Vec<GlyphData>,
+    #[serde(default)]
+    pub kerning: Vec<KerningData>,
+}
+
+//Sizes are in font pixels
+pub struct Glyph {
+    pub tex_min: Vec2f,
+    pub tex_max: Vec2f,
+    pub size: Vec2f,
+    pub offset: Vec2f,
+    pub advance: f32,
+}
+
+pub struct Font {
+    pub name: String,
+    pub pack: String, //name of the pack the font came from
+    pub line_height: f32,
+    pub glyphs: HashMap<char, Glyph>,
+    pub kerning: HashMap<(char, char), f32>,
+    pub atlas: RgbaImage,
+}
+
+fn get_char(font_name: &str, char_str: &str) -> Result<char, FontsLoadingError> {
+    let mut chars = char_str.chars();
+    return match (chars.next(), chars.next()) {
+        (Some(char), None) => Ok(char),
+        _ => Err(FontsLoadingError::InvalidCharError(String::from(font_name), String::from(char_str))),
+    }
+}
+
+impl Font {
+    //No GL here, so fonts can be loaded and laid out without a window
+    pub fn from_data(font_data: FontData, atlas: RgbaImage, pack: &str) -> Result<Self, FontsLoadingError> {
+        let atlas_size: Vec2f = [atlas.width() as f32, atlas.height() as f32];
+        let mut glyphs: HashMap<char, Glyph> = HashMap::new();
+        for glyph_data in &font_data.glyphs {
+            let char: char = get_char(&font_data.name, &glyph_data.char)?;
+            if glyph_data.x + glyph_data.width > atlas.width() || glyph_data.y + glyph_data.height > atlas.height() {
+                return Err(FontsLoadingError::GlyphOutOfAtlasError(font_data.name, glyph_data.char.clone()));
+            }
+            glyphs.insert(char, Glyph {
+                tex_min: [(glyph_data.x as f32) / atlas_size[0], (glyph_data.y as f32) / atlas_size[1]],
+                tex_max: [((glyph_data.x + glyph_data.width) as f32) / atlas_size[0], ((glyph_data.y + glyph_data.height) as f32) / atlas_size[1]],
+                size: [glyph_data.width as f32, glyph_data.height as f32],
+                offset: [glyph_data.x_offset as f32, glyph_data.y_offset as f32],
+                advance: glyph_data.advance as f32,
+            });
+        }
+        let mut kerning: HashMap<(char, char), f32> = HashMap::new();
+        for kerning_data in &font_data.kerning {
+            match (get_char(&font_data.name, &kerning_data.first), get_char(&font_data.name, &kerning_data.second)) {
+                (Ok(first), Ok(second)) => {
+                    kerning.insert((first, second), kerning_data.amount as f32);
+                }
+                _ => {
+                    return Err(FontsLoadingError::InvalidKerningError(font_data.name, kerning_data.first.clone(), kerning_data.second.clone()));
+                }
+            }
+        }
+        return Ok(Self {
+            name: font_data.name,
+            pack: String::from(pack),
+            line_height: font_data.line_height as f32,
+            glyphs,
+            kerning,
+            atlas,
+        });
+    }
+
+    //Chars missing in the font are drawn as '?'
+    pub fn get_glyph(&self, char: char) -> Option<&Glyph> {
+        return self.glyphs.get(&char).or_else(|| self.glyphs.get(&FALLBACK_CHAR));
+    }
+
+    pub fn get_kerning(&self, first: char, second: char) -> f32 {
+        return self.kerning.get(&(first, second)).copied().unwrap_or(0.0);
+    }
+}
+
+pub struct FontsLoader {
+    pub fonts: HashMap<String, Rc<Font>>,
+    pub default_font: Rc<Font>,
+}
+
+impl FontsLoader {
+    pub fn load(packs: &PackStack) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut fonts: HashMap<String, Rc<Font>> = HashMap::new();
+        for pack_definitions in packs.read_definitions(FONTS_FILE)? {
+            let mut pack_fonts: HashSet<String> = HashSet::new();
+            for font_value in &pack_definitions.values {
+                let font_data: FontData = FontData::deserialize(font_value).map_err(|error| pack_definitions.get_error(Box::new(error)))?;
+                if !pack_fonts.insert(font_data.name.clone()) {
+                    return Err(pack_definitions.get_error(Box::new(FontsLoadingError::RedefinitionError(font_data.name))));
+                }
+                let atlas: RgbaImage = packs.load_atlas(&font_data.atlas)?;
+                let font: Font = Font::from_data(font_data, atlas, &pack_definitions.pack).map_err(|error| pack_definitions.get_error(Box::new(error)))?;
+                fonts.insert(font.name.clone(), Rc::new(font));
+            }
+        }
+        return if let Some(default_font) = fonts.get(DEFAULT_FONT_NAME) {
+            let default_font: Rc<Font> = default_font.clone();
+            Ok(Self { fonts, default_font })
+        } else {
+            Err(Box::new(FontsLoadingError::DefaultFontNotFoundError(String::from(DEFAULT_FONT_NAME))))
+        }
+    }
+}
\ No newline at end of file
@@ -3,34 +3,12 @@ use std::ptr;
 use std::rc::Rc;
 use crate::render::block_renderer;
 use crate::render::buffer::Buffer;
-use crate::render::shader::Shader;
 use crate::render::shader_program::ShaderProgram;
+use crate::render::shader_registry::ShaderRegistry;
 use crate::render::types::{RGBAVertex2f, ShaderError, LightedTexVertex, Vec2f, Vec3f, RGBAVertex3f, Mat4f};
 use crate::render::vertex_array::VertexArray;
 use crate::set_attribute;
 
-//temp
-//TODO: rewrite xpointer and others to json and load it TODO another .rs for GUI element/widget and draw it in pos by translation
-const XPOINTER_VERTICES: [RGBAVertex2f; 8] = [
-    RGBAVertex2f([0.001f32, 0.01f32], [1.0f32, 1.0f32, 1.0f32, 1.0f32]),
-    RGBAVertex2f([-0.001f32, 0.01f32], [1.0f32, 1.0f32, 1.0f32, 1.0f32]),
-
-
-    RGBAVertex2f([0.001f32, -0.01f32], [1.0f32, 1.0f32, 1.0f32, 1.0f32]),
-    RGBAVertex2f([-0.001f32, -0.01f32], [1.0f32, 1.0f32, 1.0f32, 1.0f32]),
-
-    RGBAVertex2f([0.01f32, 0.001f32], [1.0f32, 1.0f32, 1.0f32, 1.0f32]),
-    RGBAVertex2f([0.01f32, -0.001f32], [1.0f32, 1.0f32, 1.0f32, 1.0f32]),
-
-    RGBAVertex2f([-0.01f32, 0.001f32], [1.0f32, 1.0f32, 1.0f32, 1.0f32]),
-    RGBAVertex2f([-0.01f32, -0.001f32], [1.0f32, 1.0f32, 1.0f32, 1.0f32]),
-];
-
-const XPOINTER_INDICES: [i32; 12] = [
-    0, 1, 3,    2, 3, 0,
-    4, 5, 7,    6, 7, 4,
-];
-
 struct RGBAGuiElement2f {
     pos: Vec2f,
     vertices: Vec<RGBAVertex2f>,
@@ -45,68 +23,8 @@ struct RGBAGuiElement3f {
     renderer: Rc<GuiRenderer3f>,
 }
 
-pub const RGBA_VERTEX_GUI_SHADER_SOURCE_2F: &str = r#"
-#version 330
-
-in vec2 pos;
-in vec4 col;
-
-out vec4 out_color;
-
-void main() {
-    gl_Position = vec4(pos, 0.0, 1.0);
-    out_color = col;
-}
-"#;
-
-pub const RGBA_FRAGMENT_GUI_SHADER_SOURCE_2F: &str = r#"
-#version 330
-
-in vec4 out_color;
-out vec4 f_color;
-
-void main() {
-    f_color = out_color;
-}
-"#;
-
-pub const RGBA_VERTEX_GUI_SHADER_SOURCE_3F: &str = r#"
-#version 330
-
-in vec3 pos;
-in vec4 col;
-
-out vec4 out_col;
-
-uniform mat4 viewMat;
-
-void main() {
-    gl_Position = viewMat * vec4(pos, 1.0);
-    out_col = col;
-}
-"#;
-
-pub const RGBA_FRAGMENT_GUI_SHADER_SOURCE_3F: &str = r#"
-#version 330
-
-out vec4 FragColor;
-
-in vec4 out_col;
-
-void main() {
-    FragColor = out_col;
-}
-"#;
-
-//TODO rewrite get_shader_program to another place and use everywhere
-fn get_shader_program(vertex_src: &str , fragment_src: &str) -> Result<ShaderProgram, Box<dyn std::error::Error>> {
-    unsafe {
-        let vert_shader = Shader::new(vertex_src, gl::VERTEX_SHADER)?;
-        let frag_shader = Shader::new(fragment_src, gl::FRAGMENT_SHADER)?;
-        let shader_program = ShaderProgram::new(&[vert_shader, frag_shader])?;
-        return Ok(shader_program);
-    }
-}
+pub const GUI_SHADER_PROGRAM_2F: &str = "gui_2f";
+pub const GUI_SHADER_PROGRAM_3F: &str = "gui_3f";
 
 pub struct GuiRenderer2f {
     pub shader_program: Rc<ShaderProgram>,
@@ -120,10 +38,9 @@ pub struct GuiRenderer2f {
 }
 
 impl GuiRenderer2f {
-    pub fn init_gui() -> Result<Rc<Self>, Box<dyn std::error::Error>> {
-        return match get_shader_program(RGBA_VERTEX_GUI_SHADER_SOURCE_2F, RGBA_FRAGMENT_GUI_SHADER_SOURCE_2F) {
-            Ok(shader_program_data) => {
-                let shader_program: Rc<ShaderProgram> = Rc::from(shader_program_data);
+    pub fn init_gui(shader_registry: &ShaderRegistry) -> Result<Rc<Self>, Box<dyn std::error::Error>> {
+        return match shader_registry.load(GUI_SHADER_PROGRAM_2F, &[]) {
+            Ok(shader_program) => {
                 unsafe {
                     let res: Rc<Self> = Rc::from(Self {
                         shader_program,
@@ -135,13 +52,6 @@ impl GuiRenderer2f {
                         vert_array: RefCell::new(VertexArray::new()),
                         asp_rat: Cell::new(1.0f32),
                     });
-                    let xpointer: RGBAGuiElement2f = RGBAGuiElement2f {
-                        pos: [0.0f32, 0.0f32],
-                        vertices: Vec::from(XPOINTER_VERTICES),
-                        indices: Vec::from(XPOINTER_INDICES),
-                        renderer: res.clone(),
-                    };
-                    GuiRenderer2f::add_colorized_gui_element(res.clone(), xpointer);
                     Ok(res)
                 }
             }
@@ -239,10 +149,9 @@ pub struct GuiRenderer3f {
 }
 
 impl GuiRenderer3f {
-    pub fn init_gui() -> Result<Rc<Self>, Box<dyn std::error::Error>> {
-        return match get_shader_program(RGBA_VERTEX_GUI_SHADER_SOURCE_3F, RGBA_FRAGMENT_GUI_SHADER_SOURCE_3F) {
-            Ok(shader_program_data) => {
-                let shader_program: Rc<ShaderProgram> = Rc::from(shader_program_data);
+    pub fn init_gui(shader_registry: &ShaderRegistry) -> Result<Rc<Self>, Box<dyn std::error::Error>> {
+        return match shader_registry.load(GUI_SHADER_PROGRAM_3F, &[]) {
+            Ok(shader_program) => {
                 unsafe {
                     let res: Rc<Self> = Rc::from(Self {
                         shader_program,
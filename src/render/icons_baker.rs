@@ -0,0 +1,422 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+use image::{imageops, Rgba, RgbaImage};
+use thiserror::Error;
+use crate::render::blocks_loader::BlocksLoader;
+use crate::render::faces_loader::Face;
+use crate::render::meshes_loader::Mesh;
+use crate::render::types::{cross_vec3f, dot_vec3f, norm_vec3f, sub_vec3f, Vec2f, Vec3f};
+
+#[derive(Error, Debug)]
+pub enum IconsCacheError {
+    #[error("Not a block icons cache file")]
+    InvalidMagicError(),
+    #[error("Unsupported block icons cache version {0}")]
+    UnsupportedVersionError(u16),
+    #[error("Block icons cache is made for other blocks")]
+    OutdatedCacheError(),
+    #[error("Invalid block icons cache")]
+    InvalidCacheDataError(),
+}
+
+pub const ICON_SIZE: u32 = 32;
+pub const ICONS_CACHE_PATH: &str = "cache/block_icons.data";
+
+const ICONS_CACHE_MAGIC: &[u8; 4] = b"CCIC";
+const ICONS_CACHE_VERSION: u16 = 1;
+const FNV_OFFSET_BASIS: u64 = 0xCBF29CE484222325;
+const FNV_PRIME: u64 = 0x00000100000001B3;
+
+const SUPERSAMPLING: u32 = 4; //icons are rasterized this many times larger and scaled down for smooth edges
+const ICON_MARGIN: f32 = 1.0; //in icon pixels
+const FLUID_HEIGHT: f32 = 0.875; //fluid icons show a source
+
+//Isometric view of the top, front and right faces, VIEW_DIR points to the viewer
+const VIEW_RIGHT: Vec3f = [-0.70710677, 0.0, -0.70710677];
+const VIEW_UP: Vec3f = [-0.40824829, 0.81649658, 0.40824829];
+const VIEW_DIR: Vec3f = [0.57735027, 0.57735027, -0.57735027];
+const VIEW_HALF_HEIGHT: f32 = 0.81649658; //projected unit cube is taller than wide
+
+//Brightness of faces looking along each axis
+const X_SHADE: f32 = 0.8;
+const Y_SHADE: f32 = 1.0;
+const Z_SHADE: f32 = 0.6;
+
+//Triangle in block space, sampling is kept inside the tex bounds of its face so atlas neighbors don't bleed in
+struct IconTriangle {
+    vertices: [(Vec3f, Vec2f); 3],
+    tex_bounds: (Vec2f, Vec2f),
+}
+
+impl IconTriangle {
+    //Squared normal components weight the shades, so the side the triangle is seen from doesn't matter
+    fn get_shade(&self) -> f32 {
+        let mut edge_0: Vec3f = self.vertices[1].0;
+        sub_vec3f(&mut edge_0, &self.vertices[0].0);
+        let mut edge_1: Vec3f = self.vertices[2].0;
+        sub_vec3f(&mut edge_1, &self.vertices[0].0);
+        let mut normal: Vec3f = cross_vec3f(&edge_0, &edge_1);
+        norm_vec3f(&mut normal);
+        return (normal[0] * normal[0] * X_SHADE) + (normal[1] * normal[1] * Y_SHADE) + (normal[2] * normal[2] * Z_SHADE);
+    }
+}
+
+fn push_face_triangles(face: &Face, triangles: &mut Vec<IconTriangle>) {
+    let tex_bounds: (Vec2f, Vec2f) = face.get_tex_bounds();
+    for indices in face.indices.chunks_exact(3) {
+        let mut vertices: Vec<(Vec3f, Vec2f)> = Vec::new();
+        for ind in indices {
+            if let Some(vertex) = usize::try_from(*ind).ok().and_then(|ind| face.vertices.get(ind)) {
+                vertices.push((vertex.0, vertex.1));
+            }
+        }
+        if let [vertex_0, vertex_1, vertex_2] = vertices[..] {
+            triangles.push(IconTriangle { vertices: [vertex_0, vertex_1, vertex_2], tex_bounds });
+        }
+    }
+}
+
+//Corners go like the quads of faces, bottom left, top left, bottom right and top right of the texture
+fn push_quad(corners: [Vec3f; 4], tex_bounds: (Vec2f, Vec2f), triangles: &mut Vec<IconTriangle>) {
+    let (min, max) = tex_bounds;
+    let vertices: [(Vec3f, Vec2f); 4] = [(corners[0], [min[0], max[1]]), (corners[1], min), (corners[2], max), (corners[3], [max[0], min[1]])];
+    triangles.push(IconTriangle { vertices: [vertices[0], vertices[1], vertices[2]], tex_bounds });
+    triangles.push(IconTriangle { vertices: [vertices[2], vertices[1], vertices[3]], tex_bounds });
+}
+
+//Fluid meshes only give textures, so they are shown as a box of the source height
+fn get_mesh_triangles(mesh: &Mesh) -> Vec<IconTriangle> {
+    let mut triangles: Vec<IconTriangle> = Vec::new();
+    match mesh {
+        Mesh::Cube(cube_mesh) => {
+            for face in [&cube_mesh.top, &cube_mesh.bottom, &cube_mesh.front, &cube_mesh.back, &cube_mesh.right, &cube_mesh.left] {
+                push_face_triangles(face, &mut triangles);
+            }
+        }
+        Mesh::Custom(custom_mesh) => {
+            for face in &custom_mesh.faces {
+                push_face_triangles(face, &mut triangles);
+            }
+        }
+        Mesh::Fluid(fluid_mesh) => {
+            let height: f32 = FLUID_HEIGHT;
+            push_quad([[0.0, height, 0.0], [0.0, height, 1.0], [1.0, height, 0.0], [1.0, height, 1.0]], fluid_mesh.top.get_tex_bounds(), &mut triangles);
+            let (side_min, side_max) = fluid_mesh.side.get_tex_bounds();
+            let side_bounds: (Vec2f, Vec2f) = (side_min, [side_max[0], side_min[1] + (side_max[1] - side_min[1]) * height]);
+            let sides: [[Vec3f; 4]; 4] = [
+                [[0.0, 0.0, 0.0], [0.0, height, 0.0], [1.0, 0.0, 0.0], [1.0, height, 0.0]],
+                [[0.0, 0.0, 1.0], [0.0, height, 1.0], [1.0, 0.0, 1.0], [1.0, height, 1.0]],
+                [[1.0, 0.0, 0.0], [1.0, height, 0.0], [1.0, 0.0, 1.0], [1.0, height, 1.0]],
+                [[0.0, 0.0, 0.0], [0.0, height, 0.0], [0.0, 0.0, 1.0], [0.0, height, 1.0]],
+            ];
+            for corners in sides {
+                push_quad(corners, side_bounds, &mut triangles);
+            }
+        }
+    }
+    return triangles;
+}
+
+//Twice the signed area of the triangle of the edge and the point
+fn get_edge(start: &Vec3f, end: &Vec3f, point: &Vec2f) -> f32 {
+    return ((end[0] - start[0]) * (point[1] - start[1])) - ((end[1] - start[1]) * (point[0] - start[0]));
+}
+
+//Nearest texel, clamped to the texels covered by the tex bounds
+fn sample_texel(atlas: &RgbaImage, tex: &Vec2f, tex_bounds: &(Vec2f, Vec2f)) -> [u8; 4] {
+    if atlas.width() == 0 || atlas.height() == 0 {
+        return [0, 0, 0, 0];
+    }
+    let size: [f32; 2] = [atlas.width() as f32, atlas.height() as f32];
+    let mut texel: [u32; 2] = [0, 0];
+    for axis in 0..2 {
+        let min: f32 = (tex_bounds.0[axis] * size[axis]).floor().clamp(0.0, size[axis] - 1.0);
+        let max: f32 = ((tex_bounds.1[axis] * size[axis]).ceil() - 1.0).clamp(min, size[axis] - 1.0);
+        texel[axis] = (tex[axis] * size[axis]).floor().clamp(min, max) as u32;
+    }
+    return atlas.get_pixel(texel[0], texel[1]).0;
+}
+
+//Orthographic isometric rendering of the mesh faces from the blocks atlas, without GL
+pub fn bake_icon(mesh: &Mesh, blocks_atlas: &RgbaImage, size: u32) -> RgbaImage {
+    let samples_size: u32 = size * SUPERSAMPLING;
+    let scale: f32 = ((samples_size as f32) - ICON_MARGIN * 2.0 * (SUPERSAMPLING as f32)).max(0.0) / (VIEW_HALF_HEIGHT * 2.0);
+    let center: f32 = (samples_size as f32) * 0.5;
+    let mut colors: Vec<[f32; 4]> = vec![[0.0, 0.0, 0.0, 0.0]; (samples_size * samples_size) as usize];
+    let mut depths: Vec<f32> = vec![f32::NEG_INFINITY; (samples_size * samples_size) as usize];
+    for triangle in get_mesh_triangles(mesh) {
+        let shade: f32 = triangle.get_shade();
+        //Sample x, sample y going down and depth growing to the viewer
+        let points: [Vec3f; 3] = triangle.vertices.map(|(pos, _)| {
+            let pos: Vec3f = [pos[0] - 0.5, pos[1] - 0.5, pos[2] - 0.5];
+            [center + dot_vec3f(&pos, &VIEW_RIGHT) * scale, center - dot_vec3f(&pos, &VIEW_UP) * scale, dot_vec3f(&pos, &VIEW_DIR)]
+        });
+        let area: f32 = get_edge(&points[0], &points[1], &[points[2][0], points[2][1]]);
+        if area.abs() < f32::EPSILON {
+            continue;
+        }
+        let min_x: u32 = points.iter().map(|point| point[0]).fold(f32::INFINITY, f32::min).floor().max(0.0) as u32;
+        let max_x: u32 = points.iter().map(|point| point[0]).fold(f32::NEG_INFINITY, f32::max).ceil().min(samples_size as f32).max(0.0) as u32;
+        let min_y: u32 = points.iter().map(|point| point[1]).fold(f32::INFINITY, f32::min).floor().max(0.0) as u32;
+        let max_y: u32 = points.iter().map(|point| point[1]).fold(f32::NEG_INFINITY, f32::max).ceil().min(samples_size as f32).max(0.0) as u32;
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let sample: Vec2f = [(x as f32) + 0.5, (y as f32) + 0.5];
+                let weights: [f32; 3] = [get_edge(&points[1], &points[2], &sample) / area, get_edge(&points[2], &points[0], &sample) / area, get_edge(&points[0], &points[1], &sample) / area];
+                if weights.iter().any(|weight| *weight < 0.0) {
+                    continue;
+                }
+                let ind: usize = (y * samples_size + x) as usize;
+                let depth: f32 = (0..3).map(|vertex_ind| weights[vertex_ind] * points[vertex_ind][2]).sum();
+                if depth <= depths[ind] {
+                    continue;
+                }
+                let tex: Vec2f = [
+                    (0..3).map(|vertex_ind| weights[vertex_ind] * triangle.vertices[vertex_ind].1[0]).sum(),
+                    (0..3).map(|vertex_ind| weights[vertex_ind] * triangle.vertices[vertex_ind].1[1]).sum(),
+                ];
+                let texel: [u8; 4] = sample_texel(blocks_atlas, &tex, &triangle.tex_bounds);
+                if texel[3] == 0 {
+                    continue;
+                }
+                depths[ind] = depth;
+                colors[ind] = [(texel[0] as f32) * shade, (texel[1] as f32) * shade, (texel[2] as f32) * shade, texel[3] as f32];
+            }
+        }
+    }
+    //Box filter down to the icon size, colors are weighted by alpha
+    let mut icon: RgbaImage = RgbaImage::new(size, size);
+    let samples_cnt: f32 = (SUPERSAMPLING * SUPERSAMPLING) as f32;
+    for (x, y, pixel) in icon.enumerate_pixels_mut() {
+        let mut sum: [f32; 4] = [0.0, 0.0, 0.0, 0.0];
+        for sample_y in 0..SUPERSAMPLING {
+            for sample_x in 0..SUPERSAMPLING {
+                let color: [f32; 4] = colors[((y * SUPERSAMPLING + sample_y) * samples_size + x * SUPERSAMPLING + sample_x) as usize];
+                for channel in 0..3 {
+                    sum[channel] += color[channel] * color[3];
+                }
+                sum[3] += color[3];
+            }
+        }
+        if sum[3] > 0.0 {
+            *pixel = Rgba([
+                (sum[0] / sum[3]).round().min(255.0) as u8,
+                (sum[1] / sum[3]).round().min(255.0) as u8,
+                (sum[2] / sum[3]).round().min(255.0) as u8,
+                (sum[3] / samples_cnt).round().min(255.0) as u8,
+            ]);
+        }
+    }
+    return icon;
+}
+
+//64-bit FNV-1a, the same on every run and build unlike the std hashers, so cached keys stay valid
+struct IconsKeyHasher {
+    hash: u64,
+}
+
+impl IconsKeyHasher {
+    fn new() -> Self {
+        return Self { hash: FNV_OFFSET_BASIS };
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash ^= *byte as u64;
+            self.hash = self.hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_be_bytes());
+    }
+
+    //Length first, so consecutive strings can't run into each other
+    fn write_str(&mut self, value: &str) {
+        self.write_u32(value.len() as u32);
+        self.write(value.as_bytes());
+    }
+
+    fn finish(&self) -> u64 {
+        return self.hash;
+    }
+}
+
+//Changes with anything the icons are baked from
+fn get_icons_key(blocks_loader: &BlocksLoader) -> u64 {
+    let blocks_atlas: &RgbaImage = &blocks_loader.meshes_loader.faces_loader.atlas_image;
+    let mut hasher: IconsKeyHasher = IconsKeyHasher::new();
+    hasher.write_u32(ICON_SIZE);
+    hasher.write_u32(blocks_atlas.width());
+    hasher.write_u32(blocks_atlas.height());
+    hasher.write(blocks_atlas.as_raw());
+    for block in &blocks_loader.loaded_blocks {
+        hasher.write_str(&block.name);
+        for triangle in get_mesh_triangles(&block.mesh) {
+            let (tex_min, tex_max) = triangle.tex_bounds;
+            for (pos, tex) in triangle.vertices {
+                for value in pos.iter().chain(tex.iter()).chain(tex_min.iter()).chain(tex_max.iter()) {
+                    hasher.write_u32(value.to_bits());
+                }
+            }
+        }
+    }
+    return hasher.finish();
+}
+
+//Icons of all loaded blocks in rows of ICON_SIZE squares, in the loading order
+pub struct BlockIcons {
+    pub atlas: RgbaImage,
+    pub icons: HashMap<String, [u32; 4]>, //x, y, width and height in pixels of the atlas
+}
+
+impl BlockIcons {
+    fn get_columns_cnt(blocks_cnt: usize) -> u32 {
+        return ((blocks_cnt as f32).sqrt().ceil() as u32).max(1);
+    }
+
+    fn get_atlas_size(blocks_cnt: usize) -> (u32, u32) {
+        let columns_cnt: u32 = Self::get_columns_cnt(blocks_cnt);
+        let rows_cnt: u32 = ((blocks_cnt as u32 + columns_cnt - 1) / columns_cnt).max(1);
+        return (columns_cnt * ICON_SIZE, rows_cnt * ICON_SIZE);
+    }
+
+    fn get_icons(blocks_loader: &BlocksLoader) -> HashMap<String, [u32; 4]> {
+        let columns_cnt: u32 = Self::get_columns_cnt(blocks_loader.loaded_blocks.len());
+        let mut icons: HashMap<String, [u32; 4]> = HashMap::new();
+        for (block_ind, block) in blocks_loader.loaded_blocks.iter().enumerate() {
+            let block_ind: u32 = block_ind as u32;
+            icons.insert(block.name.clone(), [(block_ind % columns_cnt) * ICON_SIZE, (block_ind / columns_cnt) * ICON_SIZE, ICON_SIZE, ICON_SIZE]);
+        }
+        return icons;
+    }
+
+    pub fn bake(blocks_loader: &BlocksLoader) -> Self {
+        let blocks_atlas: &RgbaImage = &blocks_loader.meshes_loader.faces_loader.atlas_image;
+        let (width, height) = Self::get_atlas_size(blocks_loader.loaded_blocks.len());
+        let mut atlas: RgbaImage = RgbaImage::new(width, height);
+        let icons: HashMap<String, [u32; 4]> = Self::get_icons(blocks_loader);
+        for block in &blocks_loader.loaded_blocks {
+            if let Some([x, y, _, _]) = icons.get(&block.name) {
+                imageops::replace(&mut atlas, &bake_icon(&block.mesh, blocks_atlas, ICON_SIZE), *x as i64, *y as i64);
+            }
+        }
+        return Self { atlas, icons };
+    }
+
+    //Magic, version, key, atlas size, then the raw atlas pixels
+    fn store_cache(&self, file_name: &str, key: u64) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(dir) = Path::new(file_name).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut data: Vec<u8> = Vec::new();
+        data.extend_from_slice(ICONS_CACHE_MAGIC);
+        data.extend_from_slice(&ICONS_CACHE_VERSION.to_be_bytes());
+        data.extend_from_slice(&key.to_be_bytes());
+        data.extend_from_slice(&self.atlas.width().to_be_bytes());
+        data.extend_from_slice(&self.atlas.height().to_be_bytes());
+        data.extend_from_slice(self.atlas.as_raw());
+        let mut file: File = File::create(file_name)?;
+        file.write_all(data.as_slice())?;
+        return Ok(());
+    }
+
+    fn read_data<'a>(data: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], IconsCacheError> {
+        return if let Some(read_data) = data.get(*pos..(*pos + len)) {
+            *pos += len;
+            Ok(read_data)
+        } else {
+            Err(IconsCacheError::InvalidCacheDataError())
+        }
+    }
+
+    fn load_cache(file_name: &str, key: u64, blocks_loader: &BlocksLoader) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut data: Vec<u8> = Vec::new();
+        File::open(file_name)?.read_to_end(&mut data)?;
+        if !data.starts_with(ICONS_CACHE_MAGIC) {
+            return Err(Box::new(IconsCacheError::InvalidMagicError()));
+        }
+        let mut pos: usize = ICONS_CACHE_MAGIC.len();
+        let version_data: &[u8] = Self::read_data(&data, &mut pos, 2)?;
+        let version: u16 = u16::from_be_bytes([version_data[0], version_data[1]]);
+        if version == 0 || version > ICONS_CACHE_VERSION {
+            return Err(Box::new(IconsCacheError::UnsupportedVersionError(version)));
+        }
+        let mut key_data: [u8; 8] = [0; 8];
+        key_data.copy_from_slice(Self::read_data(&data, &mut pos, 8)?);
+        if u64::from_be_bytes(key_data) != key {
+            return Err(Box::new(IconsCacheError::OutdatedCacheError()));
+        }
+        let size_data: &[u8] = Self::read_data(&data, &mut pos, 8)?;
+        let width: u32 = u32::from_be_bytes([size_data[0], size_data[1], size_data[2], size_data[3]]);
+        let height: u32 = u32::from_be_bytes([size_data[4], size_data[5], size_data[6], size_data[7]]);
+        if (width, height) != Self::get_atlas_size(blocks_loader.loaded_blocks.len()) {
+            return Err(Box::new(IconsCacheError::InvalidCacheDataError()));
+        }
+        let pixels: Vec<u8> = Self::read_data(&data, &mut pos, (width * height * 4) as usize)?.to_vec();
+        let atlas: RgbaImage = RgbaImage::from_raw(width, height, pixels).ok_or(IconsCacheError::InvalidCacheDataError())?;
+        return Ok(Self { atlas, icons: Self::get_icons(blocks_loader) });
+    }
+
+    //Cached icons are used while the blocks and their atlas stay the same, otherwise they are baked and cached again
+    pub fn load(blocks_loader: &BlocksLoader) -> Self {
+        let key: u64 = get_icons_key(blocks_loader);
+        match Self::load_cache(ICONS_CACHE_PATH, key, blocks_loader) {
+            Ok(block_icons) => return block_icons,
+            Err(error) => println!("Baking block icons, cache not used: {}", error),
+        }
+        let block_icons: Self = Self::bake(blocks_loader);
+        if let Err(error) = block_icons.store_cache(ICONS_CACHE_PATH, key) {
+            println!("Failed to cache block icons: {}", error);
+        }
+        return block_icons;
+    }
+
+    //GUI atlas with the icons below it, so GUI textures keep their pixel positions
+    pub fn pack_into(&self, gui_atlas: &RgbaImage) -> (RgbaImage, HashMap<String, [Vec2f; 2]>) {
+        let width: u32 = gui_atlas.width().max(self.atlas.width());
+        let height: u32 = gui_atlas.height() + self.atlas.height();
+        let mut atlas: RgbaImage = RgbaImage::new(width, height);
+        imageops::replace(&mut atlas, gui_atlas, 0, 0);
+        imageops::replace(&mut atlas, &self.atlas, 0, gui_atlas.height() as i64);
+        let mut icons: HashMap<String, [Vec2f; 2]> = HashMap::new();
+        for (name, [x, y, icon_width, icon_height]) in &self.icons {
+            let y: u32 = y + gui_atlas.height();
+            let min: Vec2f = [(*x as f32) / (width as f32), (y as f32) / (height as f32)];
+            let max: Vec2f = [((x + icon_width) as f32) / (width as f32), ((y + icon_height) as f32) / (height as f32)];
+            icons.insert(name.clone(), [min, max]);
+        }
+        return (atlas, icons);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_hash(bytes: &[u8]) -> u64 {
+        let mut hasher: IconsKeyHasher = IconsKeyHasher::new();
+        hasher.write(bytes);
+        return hasher.finish();
+    }
+
+    #[test]
+    fn icons_key_hasher_is_fnv_1a() {
+        assert_eq!(get_hash(b""), 0xCBF29CE484222325);
+        assert_eq!(get_hash(b"a"), 0xAF63DC4C8601EC8C);
+        assert_eq!(get_hash(b"foobar"), 0x85944171F73967E8);
+    }
+
+    #[test]
+    fn icons_key_hasher_separates_strings() {
+        let mut first: IconsKeyHasher = IconsKeyHasher::new();
+        first.write_str("core:ab");
+        first.write_str("c");
+        let mut second: IconsKeyHasher = IconsKeyHasher::new();
+        second.write_str("core:a");
+        second.write_str("bc");
+        assert_ne!(first.finish(), second.finish());
+    }
+}
\ No newline at end of file
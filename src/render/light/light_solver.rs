@@ -2,7 +2,7 @@ use std::cell::{Cell, Ref, RefCell, RefMut};
 use std::collections::VecDeque;
 use std::sync::mpsc::channel;
 use crate::chunk::SubChunk;
-use crate::render::blocks_loader::{AIR_BLOCK_ID, BlocksLoader};
+use crate::render::blocks_loader::BlocksLoader;
 use crate::render::light::light_map::LightMap;
 use crate::render::types::{Vec3b, Vec3s, Vec3ub};
 use crate::world::World;
@@ -25,6 +25,7 @@ pub struct LightSolver {
     channel: Cell<u8>,
     add_queue: RefCell<VecDeque<LightEntry>>, //add queue
     rem_queue: RefCell<VecDeque<LightEntry>>, //remove queue
+    peak_queues_len: Cell<(usize, usize)>, //largest add and remove queues solved since the last take
 }
 impl LightSolver {
     pub fn new(channel: u8) -> Self {
@@ -32,9 +33,20 @@ impl LightSolver {
             channel: Cell::from(channel),
             add_queue: RefCell::from(VecDeque::new()),
             rem_queue: RefCell::from(VecDeque::new()),
+            peak_queues_len: Cell::new((0, 0)),
         };
     }
 
+    //Queues are empty after every solve, so their largest lengths are kept until taken
+    pub fn take_peak_queues_len(&self) -> (usize, usize) {
+        return self.peak_queues_len.replace((0, 0));
+    }
+
+    fn update_peak_queues_len(&self, add_len: usize, rem_len: usize) {
+        let (peak_add_len, peak_rem_len) = self.peak_queues_len.get();
+        self.peak_queues_len.set((peak_add_len.max(add_len), peak_rem_len.max(rem_len)));
+    }
+
     pub fn add(&self, world: &World, pos: &Vec3ub, emission: u8) {
         if emission <= 1 {return;} //If light does not spread
         let entry: LightEntry = LightEntry::new(pos, emission);
@@ -70,6 +82,7 @@ impl LightSolver {
         const NEIGHBORHOOD: [Vec3b; 6] = [[0, 0, -1], [0, 0, 1], [0, -1, 0], [0, 1, 0], [-1, 0, 0], [1, 0, 0]];
         let mut rem_queue: RefMut<VecDeque<LightEntry>> = self.rem_queue.borrow_mut();
         let mut add_queue: RefMut<VecDeque<LightEntry>> = self.add_queue.borrow_mut();
+        self.update_peak_queues_len(0, rem_queue.len());
         while !rem_queue.is_empty() {
             if let Some(entry) = rem_queue.pop_front() {
                 for neigh in NEIGHBORHOOD {
@@ -89,6 +102,8 @@ impl LightSolver {
             }
         }
 
+        //Removal queues more light to add again
+        self.update_peak_queues_len(add_queue.len(), 0);
         while !add_queue.is_empty() {
             if let Some(entry) = add_queue.pop_front() {
                 if entry.light_level <= 1 {continue;}
@@ -0,0 +1,165 @@
+use std::rc::Rc;
+use crate::render::blocks_loader::{Block, BlocksLoader};
+use crate::render::light::light_map::{B_CHANNEL, G_CHANNEL, R_CHANNEL, S_CHANNEL};
+use crate::render::light::light_solver::LightSolver;
+use crate::render::types::{Vec3b, Vec3ub};
+use crate::world::World;
+
+const NEIGHBORHOOD: [Vec3b; 6] = [[0, 0, -1], [0, 0, 1], [0, -1, 0], [0, 1, 0], [-1, 0, 0], [1, 0, 0]];
+
+pub struct LightSolvers {
+    pub r: LightSolver,
+    pub g: LightSolver,
+    pub b: LightSolver,
+    pub s: LightSolver,
+}
+
+impl LightSolvers {
+    pub fn new() -> Self {
+        return Self {
+            r: LightSolver::new(R_CHANNEL),
+            g: LightSolver::new(G_CHANNEL),
+            b: LightSolver::new(B_CHANNEL),
+            s: LightSolver::new(S_CHANNEL),
+        };
+    }
+
+    pub fn solve(&self, world: &World, blocks_loader: &BlocksLoader) {
+        self.r.solve(world, blocks_loader);
+        self.g.solve(world, blocks_loader);
+        self.b.solve(world, blocks_loader);
+        self.s.solve(world, blocks_loader);
+    }
+
+    //Lights the whole world from scratch
+    pub fn init(&self, world: &World, blocks_loader: &BlocksLoader) {
+        for x_pos in 0x00_u8..=0xFF_u8 {
+            for y_pos in 0x00_u8..=0xFF_u8 {
+                for z_pos in 0x00_u8..=0xFF_u8 {
+                    let pos: Vec3ub = [x_pos, y_pos, z_pos];
+                    let block_lid = world.get_block(&pos);
+                    let block: Rc<Block> = if let Some(block) = blocks_loader.loaded_blocks.get(block_lid as usize) {
+                        block.clone()
+                    } else {
+                        world.set_block(&pos, blocks_loader.unknown_block.lid);
+                        blocks_loader.unknown_block.clone()
+                    };
+                    if block.light_r != 0 { self.r.add(world, &pos, block.light_r); }
+                    if block.light_g != 0 { self.g.add(world, &pos, block.light_g); }
+                    if block.light_b != 0 { self.b.add(world, &pos, block.light_b); }
+                }
+            }
+        }
+
+        for x_pos in 0x00_u8..=0xFF_u8 {
+            for z_pos in 0x00_u8..=0xFF_u8 {
+                for y_pos in (0x00_u8..=0xFF_u8).rev() {
+                    let pos: Vec3ub = [x_pos, y_pos, z_pos];
+                    let block_lid: u16 = world.get_block(&pos);
+                    if blocks_loader.get_block(block_lid).mesh.is_cube() {
+                        break;
+                    }
+                    world.set_light_level(&pos, S_CHANNEL, 0x0F);
+                }
+            }
+        }
+
+        for x_pos in 0x00_u8..=0xFF_u8 {
+            for z_pos in 0x00_u8..=0xFF_u8 {
+                for y_pos in (0x00_u8..=0xFF_u8).rev() {
+                    let pos: Vec3ub = [x_pos, y_pos, z_pos];
+                    let block_lid: u16 = world.get_block(&pos);
+                    if blocks_loader.get_block(block_lid).mesh.is_cube() {
+                        break;
+                    }
+
+                    let mut flag: bool = false;
+                    for neigh in NEIGHBORHOOD {
+                        if let Some(neigh_pos) = LightSolver::get_neighbor_pos(&pos, &neigh) {
+                            if world.get_light_level(&neigh_pos, S_CHANNEL) == 0 {
+                                flag = true;
+                                break;
+                            }
+                        }
+                    }
+                    if flag {
+                        self.s.add_last(world, &pos);
+                    }
+                }
+            }
+        }
+
+        self.solve(world, blocks_loader);
+    }
+
+    //Relights around pos after its block was placed, broken or replaced in any other way
+    pub fn on_block_edited(&self, world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub) {
+        let block: Rc<Block> = blocks_loader.get_block(world.get_block(pos));
+        let is_opaque: bool = block.mesh.is_cube();
+        self.r.remove(world, pos);
+        self.g.remove(world, pos);
+        self.b.remove(world, pos);
+        if is_opaque {
+            self.s.remove(world, pos);
+            //Direct sky light is not dimmed going down, so the removal can't spread through the column by itself
+            let mut column_pos: Vec3ub = *pos;
+            while let Some(bottom_pos) = LightSolver::get_neighbor_pos(&column_pos, &[0, -1, 0]) {
+                if blocks_loader.get_block(world.get_block(&bottom_pos)).mesh.is_cube() {
+                    break;
+                }
+                self.s.remove(world, &bottom_pos);
+                column_pos = bottom_pos;
+            }
+        }
+        self.solve(world, blocks_loader);
+        if !is_opaque {
+            let has_sky: bool = match LightSolver::get_neighbor_pos(pos, &[0, 1, 0]) {
+                Some(top_pos) => world.get_light_level(&top_pos, S_CHANNEL) == 0x0F,
+                None => true,
+            };
+            if has_sky {
+                for y_pos in (0x00..=pos[1]).rev() {
+                    let column_pos: Vec3ub = [pos[0], y_pos, pos[2]];
+                    if blocks_loader.get_block(world.get_block(&column_pos)).mesh.is_cube() {
+                        break;
+                    }
+                    self.s.add(world, &column_pos, 0x0F);
+                }
+            }
+            //Light of the neighbors spreads into the freed cell
+            for neigh in NEIGHBORHOOD {
+                if let Some(neigh_pos) = LightSolver::get_neighbor_pos(pos, &neigh) {
+                    self.r.add_last(world, &neigh_pos);
+                    self.g.add_last(world, &neigh_pos);
+                    self.b.add_last(world, &neigh_pos);
+                    self.s.add_last(world, &neigh_pos);
+                }
+            }
+        }
+        if block.light_r != 0 { self.r.add(world, pos, block.light_r); }
+        if block.light_g != 0 { self.g.add(world, pos, block.light_g); }
+        if block.light_b != 0 { self.b.add(world, pos, block.light_b); }
+        self.solve(world, blocks_loader);
+    }
+
+    //Replaces the emitted light at every position with the emission of its current block, all positions are solved at once
+    pub fn update_emissions(&self, world: &World, blocks_loader: &BlocksLoader, positions: &[Vec3ub]) {
+        for pos in positions {
+            self.r.remove(world, pos);
+            self.g.remove(world, pos);
+            self.b.remove(world, pos);
+        }
+        self.r.solve(world, blocks_loader);
+        self.g.solve(world, blocks_loader);
+        self.b.solve(world, blocks_loader);
+        for pos in positions {
+            let block: Rc<Block> = blocks_loader.get_block(world.get_block(pos));
+            if block.light_r != 0 { self.r.add(world, pos, block.light_r); }
+            if block.light_g != 0 { self.g.add(world, pos, block.light_g); }
+            if block.light_b != 0 { self.b.add(world, pos, block.light_b); }
+        }
+        self.r.solve(world, blocks_loader);
+        self.g.solve(world, blocks_loader);
+        self.b.solve(world, blocks_loader);
+    }
+}
@@ -1,3 +1,5 @@
 pub mod light_map;
 
-pub mod light_solver;
\ No newline at end of file
+pub mod light_solver;
+
+pub mod light_solvers;
\ No newline at end of file
@@ -2,8 +2,8 @@ use std::cell::{Cell, RefCell, RefMut};
 use std::ptr;
 use std::rc::Rc;
 use crate::render::buffer::Buffer;
-use crate::render::shader::Shader;
 use crate::render::shader_program::ShaderProgram;
+use crate::render::shader_registry::ShaderRegistry;
 use crate::render::types::{RGBALine, Mat4f, RGBAVertex3f, Vec3f, Vec3ub};
 use crate::render::vertex_array::VertexArray;
 use crate::set_attribute;
@@ -21,33 +21,7 @@ pub struct LinesRenderer {
     pub vert_array: RefCell<VertexArray>,
 }
 
-pub const COLORIZED_VERTEX_LINES_SHADER_SOURCE: &str = r#"
-#version 330
-
-in vec3 pos;
-in vec4 col;
-
-out vec4 out_col;
-
-uniform mat4 viewMat;
-
-void main() {
-    gl_Position = viewMat * vec4(pos, 1.0);
-    out_col = col;
-}
-"#;
-
-pub const COLORIZED_FRAGMENT_LINES_SHADER_SOURCE: &str = r#"
-#version 330
-
-out vec4 FragColor;
-
-in vec4 out_col;
-
-void main() {
-    FragColor = out_col;
-}
-"#;
+pub const LINES_SHADER_PROGRAM: &str = "lines";
 
 //TODO MB REPLACE WITH BIT SHIFTING
 const CUBE_VERTICES_SHIFT: [[f32; 3]; 8] = [
@@ -78,21 +52,11 @@ const CUBE_LINES_INDICES: [i32; 24] = [
 
 impl LinesRenderer {
 
-    fn get_shader_program(vertex_src: &str , fragment_src: &str) -> Result<ShaderProgram, Box<dyn std::error::Error>> {
-        unsafe {
-            let vert_shader: Shader = Shader::new(vertex_src, gl::VERTEX_SHADER)?;
-            let frag_shader: Shader = Shader::new(fragment_src, gl::FRAGMENT_SHADER)?;
-            let shader_program: ShaderProgram = ShaderProgram::new(&[vert_shader, frag_shader])?;
-            return Ok(shader_program);
-        }
-    }
-
-    pub fn init_lines_renderer() -> Result<Self, Box<dyn std::error::Error>> {
-        let shader_program: ShaderProgram = Self::get_shader_program(COLORIZED_VERTEX_LINES_SHADER_SOURCE,
-                                                                     COLORIZED_FRAGMENT_LINES_SHADER_SOURCE)?;
+    pub fn init_lines_renderer(shader_registry: &ShaderRegistry) -> Result<Self, Box<dyn std::error::Error>> {
+        let shader_program: Rc<ShaderProgram> = shader_registry.load(LINES_SHADER_PROGRAM, &[])?;
         unsafe {
             return Ok(Self {
-                shader_program: Rc::from(shader_program),
+                shader_program,
                 vertices: RefCell::from(Vec::new()),
                 indices: RefCell::from(Vec::new()),
                 is_changed: Cell::new(false),
@@ -1,9 +1,8 @@
-use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use crate::packs::pack_stack::PackStack;
 use crate::render::faces_loader::{Face, FacesLoader};
 use crate::render::meshes_loader::Mesh::Cube;
 
@@ -11,18 +10,20 @@ use crate::render::meshes_loader::Mesh::Cube;
 pub enum MeshesLoadingError {
     #[error("Deserialization failed")]
     DeserializationError(),
-    #[error("Mesh type has unknown type")]
-    UnknownTypeError(),
-    #[error("Unknown face error")]
-    UnknownFaceError(),
-    #[error("Redefinition error")]
-    RedefinitionError(),
+    #[error("Mesh {0} has unknown type {1}")]
+    UnknownTypeError(String, String),
+    #[error("Mesh {0} uses unknown face {1}")]
+    UnknownFaceError(String, String),
+    #[error("Mesh {0} is defined twice")]
+    RedefinitionError(String),
 }
 
+pub const MESHES_FILE: &str = "meshes.json";
+
 //TODO MB CHECK BLOCK BORDER
 
 #[derive(Serialize, Deserialize)]
-struct CubeMeshData {
+pub(crate) struct CubeMeshData {
     pub(crate) top: String,
     pub(crate) bottom: String,
     pub(crate) front: String,
@@ -32,10 +33,29 @@ struct CubeMeshData {
 }
 
 #[derive(Serialize, Deserialize)]
-struct CustomMeshData {
+pub(crate) struct CustomMeshData {
     pub(crate) faces: Vec<String>,
 }
 
+//Geometry is built from fluid levels, faces only give textures
+#[derive(Serialize, Deserialize)]
+pub(crate) struct FluidMeshData {
+    pub(crate) top: String,
+    pub(crate) side: String,
+}
+
+impl CubeMeshData {
+    pub(crate) fn get_faces(&self) -> [&String; 6] {
+        return [&self.top, &self.bottom, &self.front, &self.back, &self.right, &self.left];
+    }
+}
+
+impl FluidMeshData {
+    pub(crate) fn get_faces(&self) -> [&String; 2] {
+        return [&self.top, &self.side];
+    }
+}
+
 //TODO MB MULTI THREAD ARC
 
 //ANALYZE FACES AND MB CREATE SHADERS RCS
@@ -54,9 +74,15 @@ pub(crate) struct CustomMesh {
     pub(crate) faces: Vec<Rc<Face>>,
 }
 
+pub(crate) struct FluidMesh {
+    pub(crate) top: Rc<Face>,
+    pub(crate) side: Rc<Face>,
+}
+
 pub enum Mesh {
     Cube(CubeMesh),
     Custom(CustomMesh),
+    Fluid(FluidMesh),
 }
 
 impl Mesh {
@@ -75,78 +101,78 @@ pub struct MeshesLoader {
 }
 
 impl MeshesLoader {
-    pub fn load(meshes_path: &Path, faces_loader: FacesLoader) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut meshes: HashMap<String, Rc<Mesh>> = HashMap::new();
-        let meshes_data = fs::read_to_string(meshes_path)?;
-        let serialized: serde_json::Value = serde_json::from_str(&meshes_data)?;
-        if let Some(meshes_values) = serialized.as_array() {
-            for mesh_value in meshes_values {
-                if let Some(name_value) = mesh_value.get("name") {
-                    if let Some(mesh_type_value) = mesh_value.get("mesh_type") {
-                        if let Some(name_str) = name_value.as_str() {
-                            if let Some(mesh_type_str) = mesh_type_value.as_str() {
-                                if let Some(mesh_value) = mesh_value.get("mesh") {
-                                    match mesh_type_str {
-                                        "cube" => {
-                                            let mesh: CubeMeshData = CubeMeshData::deserialize(mesh_value)?;
-                                            if let Some(top_face) = faces_loader.faces.get(&mesh.top) {
-                                                if let Some(bottom_face) = faces_loader.faces.get(&mesh.bottom) {
-                                                    if let Some(front_face) = faces_loader.faces.get(&mesh.front) {
-                                                        if let Some(back_face) = faces_loader.faces.get(&mesh.back) {
-                                                            if let Some(right_face) = faces_loader.faces.get(&mesh.right) {
-                                                                if let Some(left_face) = faces_loader.faces.get(&mesh.left) {
-                                                                    if let Some(_) = meshes.insert(String::from(name_str), Rc::new(Cube(CubeMesh {
-                                                                        top: top_face.clone(),
-                                                                        bottom: bottom_face.clone(),
-                                                                        front: front_face.clone(),
-                                                                        back: back_face.clone(),
-                                                                        right: right_face.clone(),
-                                                                        left: left_face.clone(),
-                                                                    }))) {
-                                                                        return Err(Box::new(MeshesLoadingError::RedefinitionError()));
-                                                                    }
-                                                                } else {
-                                                                    return Err(Box::new(MeshesLoadingError::UnknownFaceError()));
-                                                                }
-                                                            } else {
-                                                                return Err(Box::new(MeshesLoadingError::UnknownFaceError()));
-                                                            }
+    fn load_mesh(mesh_value: &serde_json::Value, faces: &HashMap<String, Rc<Face>>) -> Result<(String, Mesh), Box<dyn std::error::Error>> {
+        if let Some(name_value) = mesh_value.get("name") {
+            if let Some(mesh_type_value) = mesh_value.get("mesh_type") {
+                if let Some(name_str) = name_value.as_str() {
+                    if let Some(mesh_type_str) = mesh_type_value.as_str() {
+                        if let Some(mesh_value) = mesh_value.get("mesh") {
+                            match mesh_type_str {
+                                "cube" => {
+                                    let mesh: CubeMeshData = CubeMeshData::deserialize(mesh_value)?;
+                                    if let Some(top_face) = faces.get(&mesh.top) {
+                                        if let Some(bottom_face) = faces.get(&mesh.bottom) {
+                                            if let Some(front_face) = faces.get(&mesh.front) {
+                                                if let Some(back_face) = faces.get(&mesh.back) {
+                                                    if let Some(right_face) = faces.get(&mesh.right) {
+                                                        if let Some(left_face) = faces.get(&mesh.left) {
+                                                            return Ok((String::from(name_str), Cube(CubeMesh {
+                                                                top: top_face.clone(),
+                                                                bottom: bottom_face.clone(),
+                                                                front: front_face.clone(),
+                                                                back: back_face.clone(),
+                                                                right: right_face.clone(),
+                                                                left: left_face.clone(),
+                                                            })));
                                                         } else {
-                                                            return Err(Box::new(MeshesLoadingError::UnknownFaceError()));
+                                                            return Err(Box::new(MeshesLoadingError::UnknownFaceError(String::from(name_str), mesh.left.clone())));
                                                         }
                                                     } else {
-                                                        return Err(Box::new(MeshesLoadingError::UnknownFaceError()));
+                                                        return Err(Box::new(MeshesLoadingError::UnknownFaceError(String::from(name_str), mesh.right.clone())));
                                                     }
                                                 } else {
-                                                    return Err(Box::new(MeshesLoadingError::UnknownFaceError()));
+                                                    return Err(Box::new(MeshesLoadingError::UnknownFaceError(String::from(name_str), mesh.back.clone())));
                                                 }
                                             } else {
-                                                return Err(Box::new(MeshesLoadingError::UnknownFaceError()));
+                                                return Err(Box::new(MeshesLoadingError::UnknownFaceError(String::from(name_str), mesh.front.clone())));
                                             }
+                                        } else {
+                                            return Err(Box::new(MeshesLoadingError::UnknownFaceError(String::from(name_str), mesh.bottom.clone())));
                                         }
-                                        "custom" => {
-                                            let mesh: CustomMeshData = CustomMeshData::deserialize(mesh_value)?;
-                                            let mut faces: Vec<Rc<Face>> = Vec::new();
-                                            for face in &mesh.faces {
-                                                if let Some(face) = faces_loader.faces.get(face) {
-                                                    faces.push(face.clone());
-                                                } else {
-                                                    return Err(Box::new(MeshesLoadingError::UnknownFaceError()));
-                                                }
-                                            }
-                                            if let Some(_) = meshes.insert(String::from(name_str), Rc::new(Mesh::Custom(CustomMesh { faces }))) {
-                                                return Err(Box::new(MeshesLoadingError::RedefinitionError()));
-                                            }
+                                    } else {
+                                        return Err(Box::new(MeshesLoadingError::UnknownFaceError(String::from(name_str), mesh.top.clone())));
+                                    }
+                                }
+                                "custom" => {
+                                    let mesh: CustomMeshData = CustomMeshData::deserialize(mesh_value)?;
+                                    let mut mesh_faces: Vec<Rc<Face>> = Vec::new();
+                                    for face in &mesh.faces {
+                                        if let Some(face) = faces.get(face) {
+                                            mesh_faces.push(face.clone());
+                                        } else {
+                                            return Err(Box::new(MeshesLoadingError::UnknownFaceError(String::from(name_str), face.clone())));
                                         }
-                                        _ => {
-                                            return Err(Box::new(MeshesLoadingError::UnknownTypeError()));
+                                    }
+                                    return Ok((String::from(name_str), Mesh::Custom(CustomMesh { faces: mesh_faces })));
+                                }
+                                "fluid" => {
+                                    let mesh: FluidMeshData = FluidMeshData::deserialize(mesh_value)?;
+                                    if let Some(top_face) = faces.get(&mesh.top) {
+                                        if let Some(side_face) = faces.get(&mesh.side) {
+                                            return Ok((String::from(name_str), Mesh::Fluid(FluidMesh {
+                                                top: top_face.clone(),
+                                                side: side_face.clone(),
+                                            })));
+                                        } else {
+                                            return Err(Box::new(MeshesLoadingError::UnknownFaceError(String::from(name_str), mesh.side.clone())));
                                         }
+                                    } else {
+                                        return Err(Box::new(MeshesLoadingError::UnknownFaceError(String::from(name_str), mesh.top.clone())));
                                     }
-                                } else {
-                                    return Err(Box::new(MeshesLoadingError::DeserializationError()));
                                 }
-                            } else {
-                                return Err(Box::new(MeshesLoadingError::DeserializationError()));
+                                _ => {
+                                    return Err(Box::new(MeshesLoadingError::UnknownTypeError(String::from(name_str), String::from(mesh_type_str))));
+                                }
                             }
                         } else {
                             return Err(Box::new(MeshesLoadingError::DeserializationError()));
@@ -157,13 +183,93 @@ impl MeshesLoader {
                 } else {
                     return Err(Box::new(MeshesLoadingError::DeserializationError()));
                 }
+            } else {
+                return Err(Box::new(MeshesLoadingError::DeserializationError()));
             }
         } else {
             return Err(Box::new(MeshesLoadingError::DeserializationError()));
         }
+    }
+
+    //Faces are resolved after all packs faces are merged, so overridden faces are used by meshes of lower packs too
+    pub(crate) fn load_meshes(packs: &PackStack, faces: &HashMap<String, Rc<Face>>) -> Result<HashMap<String, Rc<Mesh>>, Box<dyn std::error::Error>> {
+        let mut meshes: HashMap<String, Rc<Mesh>> = HashMap::new();
+        for pack_definitions in packs.read_definitions(MESHES_FILE)? {
+            let mut pack_meshes: HashSet<String> = HashSet::new();
+            for mesh_value in &pack_definitions.values {
+                let (name, mesh) = Self::load_mesh(mesh_value, faces).map_err(|error| pack_definitions.get_error(error))?;
+                if !pack_meshes.insert(name.clone()) {
+                    return Err(pack_definitions.get_error(Box::new(MeshesLoadingError::RedefinitionError(name))));
+                }
+                meshes.insert(name, Rc::new(mesh));
+            }
+        }
+        return Ok(meshes);
+    }
+
+    pub fn load(packs: &PackStack, faces_loader: FacesLoader) -> Result<Self, Box<dyn std::error::Error>> {
+        let meshes: HashMap<String, Rc<Mesh>> = Self::load_meshes(packs, &faces_loader.faces)?;
         Ok(Self {
             meshes,
             faces_loader,
         })
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use crate::packs::test_packs::{get_test_dir, write_dir_pack};
+    use crate::render::faces_loader::FACES_FILE;
+    use super::*;
+
+    const OVERRIDE_FACES: &str = r#"[
+        {"name": "unknown_block_top", "vertices": [{"pos": [0.0, 1.0, 0.0], "tex": [0.0, 0.0]}, {"pos": [0.0, 1.0, 1.0], "tex": [0.0, 0.0]}, {"pos": [1.0, 1.0, 0.0], "tex": [0.0, 0.0]}], "indices": [0, 1, 2]}
+    ]"#;
+    const OVERRIDE_MESHES: &str = r#"[
+        {"name": "dirt_block", "mesh_type": "custom", "mesh": {"faces": ["unknown_block_top"]}}
+    ]"#;
+
+    fn open_test_packs(test_name: &str, files: &[(&str, &[u8])]) -> PackStack {
+        let pack_path: PathBuf = write_dir_pack(&get_test_dir(test_name), "test", files);
+        return PackStack::open(&[PathBuf::from("assets"), pack_path]).unwrap();
+    }
+
+    #[test]
+    fn upper_packs_override_meshes_and_faces() {
+        let packs: PackStack = open_test_packs("meshes_override", &[(FACES_FILE, OVERRIDE_FACES.as_bytes()), (MESHES_FILE, OVERRIDE_MESHES.as_bytes())]);
+        let faces: HashMap<String, Rc<Face>> = FacesLoader::load_faces(&packs).unwrap();
+        let meshes: HashMap<String, Rc<Mesh>> = MeshesLoader::load_meshes(&packs, &faces).unwrap();
+        match meshes.get("dirt_block").unwrap().as_ref() {
+            Mesh::Custom(mesh) => assert_eq!(mesh.faces.len(), 1),
+            _ => panic!("dirt_block is not overridden"),
+        }
+        //Meshes of the lower pack use the overridden face too
+        match meshes.get("unknown_block").unwrap().as_ref() {
+            Cube(mesh) => assert_eq!(mesh.top.vertices.len(), 3),
+            _ => panic!("unknown_block is not a cube"),
+        }
+        assert!(meshes.get("grass_block").unwrap().is_cube());
+    }
+
+    #[test]
+    fn meshes_are_not_defined_twice_by_one_pack() {
+        let packs: PackStack = open_test_packs("meshes_redefinition", &[(MESHES_FILE, br#"[
+            {"name": "test_mesh", "mesh_type": "custom", "mesh": {"faces": []}},
+            {"name": "test_mesh", "mesh_type": "custom", "mesh": {"faces": []}}
+        ]"#)]);
+        let faces: HashMap<String, Rc<Face>> = FacesLoader::load_faces(&packs).unwrap();
+        let error: Box<dyn std::error::Error> = MeshesLoader::load_meshes(&packs, &faces).err().unwrap();
+        assert_eq!(error.to_string(), "Invalid definition in meshes.json of pack test: Mesh test_mesh is defined twice");
+    }
+
+    #[test]
+    fn meshes_with_unknown_faces_are_rejected() {
+        let packs: PackStack = open_test_packs("meshes_unknown_face", &[(MESHES_FILE, br#"[
+            {"name": "test_mesh", "mesh_type": "fluid", "mesh": {"top": "unknown_block_top", "side": "missing_face"}}
+        ]"#)]);
+        let faces: HashMap<String, Rc<Face>> = FacesLoader::load_faces(&packs).unwrap();
+        let error: Box<dyn std::error::Error> = MeshesLoader::load_meshes(&packs, &faces).err().unwrap();
+        assert_eq!(error.to_string(), "Invalid definition in meshes.json of pack test: Mesh test_mesh uses unknown face missing_face");
+    }
 }
\ No newline at end of file
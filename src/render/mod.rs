@@ -4,12 +4,21 @@ pub mod buffer;
 pub mod vertex_array;
 pub mod shader;
 pub mod shader_program;
+pub mod shader_preprocessor;
+pub mod shader_registry;
 mod texture;
 pub mod faces_loader;
 pub mod meshes_loader;
 pub mod blocks_loader;
 pub mod block_renderer;
+pub mod assets_reloader;
+pub mod assets_validator;
 pub mod gui_renderer;
+pub mod font;
+pub mod text_layout;
+pub mod text_renderer;
+pub mod widget_renderer;
+pub mod icons_baker;
 pub mod light;
 
 pub mod lines_renderer;
\ No newline at end of file
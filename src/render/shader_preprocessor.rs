@@ -0,0 +1,190 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ShaderPreprocessingError {
+    #[error("Shader source {0} not found")]
+    SourceNotFoundError(String),
+    #[error("Invalid include directive in {0} at line {1}")]
+    InvalidIncludeError(String, usize),
+    #[error("Include cycle through {0}")]
+    IncludeCycleError(String),
+}
+
+pub struct PreprocessedShader {
+    pub source: String,
+    pub dependencies: Vec<String>, //names of all read sources, root included
+}
+
+//No GL here, sources are requested by name through read_source
+pub struct ShaderPreprocessor<'a> {
+    defines: &'a [(&'a str, &'a str)],
+    read_source: &'a mut dyn FnMut(&str) -> Option<String>,
+    includes_stack: Vec<String>,
+    dependencies: Vec<String>,
+}
+
+impl<'a> ShaderPreprocessor<'a> {
+    pub fn preprocess(name: &str, defines: &'a [(&'a str, &'a str)], read_source: &'a mut dyn FnMut(&str) -> Option<String>) -> Result<PreprocessedShader, ShaderPreprocessingError> {
+        let mut preprocessor: ShaderPreprocessor = ShaderPreprocessor {
+            defines,
+            read_source,
+            includes_stack: Vec::new(),
+            dependencies: Vec::new(),
+        };
+        let mut source: String = String::new();
+        preprocessor.expand(name, &mut source)?;
+        return Ok(PreprocessedShader {
+            source: preprocessor.inject_defines(&source),
+            dependencies: preprocessor.dependencies,
+        });
+    }
+
+    //#include "name" or #include <name>
+    fn parse_include(line: &str) -> Option<Option<String>> {
+        let trimmed: &str = line.trim();
+        if let Some(directive) = trimmed.strip_prefix('#') {
+            if let Some(arg) = directive.trim_start().strip_prefix("include") {
+                let arg: &str = arg.trim();
+                if arg.len() >= 2 && ((arg.starts_with('"') && arg.ends_with('"')) || (arg.starts_with('<') && arg.ends_with('>'))) {
+                    let include_name: &str = &arg[1..(arg.len() - 1)];
+                    if !include_name.is_empty() {
+                        return Some(Some(String::from(include_name)));
+                    }
+                }
+                return Some(None);
+            }
+        }
+        return None;
+    }
+
+    fn expand(&mut self, name: &str, out: &mut String) -> Result<(), ShaderPreprocessingError> {
+        if self.includes_stack.iter().any(|included| included == name) {
+            return Err(ShaderPreprocessingError::IncludeCycleError(String::from(name)));
+        }
+        let source: String = match (self.read_source)(name) {
+            Some(source) => source,
+            None => {
+                return Err(ShaderPreprocessingError::SourceNotFoundError(String::from(name)));
+            }
+        };
+        if !self.dependencies.iter().any(|dependency| dependency == name) {
+            self.dependencies.push(String::from(name));
+        }
+        self.includes_stack.push(String::from(name));
+        for (line_pos, line) in source.lines().enumerate() {
+            match Self::parse_include(line) {
+                Some(Some(include_name)) => {
+                    self.expand(&include_name, out)?;
+                }
+                Some(None) => {
+                    return Err(ShaderPreprocessingError::InvalidIncludeError(String::from(name), line_pos + 1));
+                }
+                None => {
+                    out.push_str(line);
+                    out.push('\n');
+                }
+            }
+        }
+        self.includes_stack.pop();
+        return Ok(());
+    }
+
+    //#version must stay the first directive, so defines go right after it
+    fn inject_defines(&self, source: &str) -> String {
+        if self.defines.is_empty() {
+            return String::from(source);
+        }
+        let mut defines_block: String = String::new();
+        for (define_name, define_value) in self.defines {
+            defines_block.push_str("#define ");
+            defines_block.push_str(define_name);
+            if !define_value.is_empty() {
+                defines_block.push(' ');
+                defines_block.push_str(define_value);
+            }
+            defines_block.push('\n');
+        }
+        let mut res: String = String::new();
+        let mut injected: bool = false;
+        for line in source.lines() {
+            res.push_str(line);
+            res.push('\n');
+            if !injected && line.trim_start().starts_with("#version") {
+                res.push_str(&defines_block);
+                injected = true;
+            }
+        }
+        if !injected {
+            res.insert_str(0, &defines_block);
+        }
+        return res;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    fn preprocess(name: &str, defines: &[(&str, &str)], sources: &[(&str, &str)]) -> Result<PreprocessedShader, ShaderPreprocessingError> {
+        let sources: HashMap<String, String> = sources.iter().map(|(source_name, source)| (String::from(*source_name), String::from(*source))).collect();
+        let mut read_source = |source_name: &str| sources.get(source_name).cloned();
+        return ShaderPreprocessor::preprocess(name, defines, &mut read_source);
+    }
+
+    #[test]
+    fn includes_are_expanded_in_place() {
+        let shader: PreprocessedShader = preprocess("main.glsl", &[], &[
+            ("main.glsl", "#version 330 core\n#include \"light.glsl\"\nvoid main() {}"),
+            ("light.glsl", "  #  include <common.glsl>\nfloat light;"),
+            ("common.glsl", "float common;"),
+        ]).unwrap();
+        assert_eq!(shader.source, "#version 330 core\nfloat common;\nfloat light;\nvoid main() {}\n");
+        assert_eq!(shader.dependencies, vec!["main.glsl", "light.glsl", "common.glsl"]);
+    }
+
+    #[test]
+    fn repeated_includes_are_listed_once() {
+        let shader: PreprocessedShader = preprocess("main.glsl", &[], &[
+            ("main.glsl", "#include \"common.glsl\"\n#include \"common.glsl\""),
+            ("common.glsl", "float common;"),
+        ]).unwrap();
+        assert_eq!(shader.source, "float common;\nfloat common;\n");
+        assert_eq!(shader.dependencies, vec!["main.glsl", "common.glsl"]);
+    }
+
+    #[test]
+    fn include_cycles_fail() {
+        let res = preprocess("a.glsl", &[], &[
+            ("a.glsl", "#include \"b.glsl\""),
+            ("b.glsl", "#include \"a.glsl\""),
+        ]);
+        assert!(matches!(res, Err(ShaderPreprocessingError::IncludeCycleError(name)) if name == "a.glsl"));
+        let res = preprocess("a.glsl", &[], &[("a.glsl", "#include \"a.glsl\"")]);
+        assert!(matches!(res, Err(ShaderPreprocessingError::IncludeCycleError(_))));
+    }
+
+    #[test]
+    fn broken_includes_fail() {
+        let res = preprocess("a.glsl", &[], &[("a.glsl", "float a;\n#include \"missing.glsl\"")]);
+        assert!(matches!(res, Err(ShaderPreprocessingError::SourceNotFoundError(name)) if name == "missing.glsl"));
+        let res = preprocess("a.glsl", &[], &[("a.glsl", "float a;\n#include missing.glsl")]);
+        assert!(matches!(res, Err(ShaderPreprocessingError::InvalidIncludeError(name, 2)) if name == "a.glsl"));
+        let res = preprocess("a.glsl", &[], &[("a.glsl", "#include \"\"")]);
+        assert!(matches!(res, Err(ShaderPreprocessingError::InvalidIncludeError(_, 1))));
+    }
+
+    #[test]
+    fn defines_go_after_version() {
+        let shader: PreprocessedShader = preprocess("main.glsl", &[("ALPHA_TEST", "1"), ("FOG", "")], &[
+            ("main.glsl", "// comment\n#version 330 core\nvoid main() {}"),
+        ]).unwrap();
+        assert_eq!(shader.source, "// comment\n#version 330 core\n#define ALPHA_TEST 1\n#define FOG\nvoid main() {}\n");
+    }
+
+    #[test]
+    fn defines_go_first_without_version() {
+        let shader: PreprocessedShader = preprocess("main.glsl", &[("ALPHA_TEST", "1")], &[("main.glsl", "void main() {}")]).unwrap();
+        assert_eq!(shader.source, "#define ALPHA_TEST 1\nvoid main() {}\n");
+    }
+}
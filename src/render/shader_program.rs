@@ -1,3 +1,5 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::ffi::{CString, NulError};
 use std::ptr;
 use gl::types::{GLfloat, GLint, GLuint};
@@ -5,36 +7,38 @@ use crate::render::shader::Shader;
 use crate::render::types::{Mat4f, ShaderError};
 
 pub struct ShaderProgram {
-    pub(crate) id: GLuint,
+    pub(crate) id: Cell<GLuint>,
+    uniforms_i32: RefCell<HashMap<String, i32>>, //kept to restore them after replace
 }
 
 impl ShaderProgram {
     pub fn get_id(&self) -> GLuint {
-        self.id
+        self.id.get()
     }
 
     pub unsafe fn new(shaders: &[Shader]) -> Result<Self, ShaderError> {
         let program = Self {
-            id: gl::CreateProgram(),
+            id: Cell::new(gl::CreateProgram()),
+            uniforms_i32: RefCell::new(HashMap::new()),
         };
 
         for shader in shaders {
-            gl::AttachShader(program.id, shader.id);
+            gl::AttachShader(program.id.get(), shader.id);
         }
 
-        gl::LinkProgram(program.id);
+        gl::LinkProgram(program.id.get());
 
         let mut success: GLint = 0;
-        gl::GetProgramiv(program.id, gl::LINK_STATUS, &mut success);
+        gl::GetProgramiv(program.id.get(), gl::LINK_STATUS, &mut success);
 
         if success == gl::TRUE as GLint {
             Ok(program)
         } else{
             let mut error_log_size: GLint = 512;
-            gl::GetProgramiv(program.id, gl::INFO_LOG_LENGTH, &mut error_log_size);
+            gl::GetProgramiv(program.id.get(), gl::INFO_LOG_LENGTH, &mut error_log_size);
             let mut error_log: Vec<u8> = Vec::with_capacity(error_log_size as usize);
             gl::GetProgramInfoLog(
-                program.id,
+                program.id.get(),
                 error_log_size,
                 &mut error_log_size,
                 error_log.as_mut_ptr() as *mut _,
@@ -45,26 +49,37 @@ impl ShaderProgram {
         }
     }
 
+    //Every Rc holder keeps using this program, old GL program is deleted with the dropped one
+    pub unsafe fn replace(&self, program: ShaderProgram) -> Result<(), ShaderError> {
+        self.id.swap(&program.id);
+        let uniforms_i32: Vec<(String, i32)> = self.uniforms_i32.borrow().iter().map(|(name, value)| (name.clone(), *value)).collect();
+        for (name, value) in uniforms_i32 {
+            self.set_uniform_i32(&name, value)?;
+        }
+        Ok(())
+    }
+
     pub unsafe fn apply(&self) {
-        gl::UseProgram(self.id);
+        gl::UseProgram(self.id.get());
     }
 
     pub unsafe fn get_attrib_location(&self, attrib: &str) -> Result<GLuint, NulError> {
         let attrib = CString::new(attrib)?;
-        Ok(gl::GetAttribLocation(self.id, attrib.as_ptr()) as GLuint)
+        Ok(gl::GetAttribLocation(self.id.get(), attrib.as_ptr()) as GLuint)
     }
 
     pub unsafe fn set_uniform_i32(&self, name: &str, value: i32) -> Result<(), ShaderError> {
         self.apply();
         let uniform = CString::new(name)?;
-        gl::Uniform1i(gl::GetUniformLocation(self.id, uniform.as_ptr()), value);
+        gl::Uniform1i(gl::GetUniformLocation(self.id.get(), uniform.as_ptr()), value);
+        self.uniforms_i32.borrow_mut().insert(String::from(name), value);
         Ok(())
     }
 
     pub unsafe fn set_uniform_mat4f(&self, name: &str, value: &Mat4f) -> Result<(), ShaderError> {
         self.apply();
         let uniform = CString::new(name)?;
-        gl::UniformMatrix4fv(gl::GetUniformLocation(self.id, uniform.as_ptr()), 1, gl::FALSE, ptr::addr_of!(value.matrix) as *const GLfloat);
+        gl::UniformMatrix4fv(gl::GetUniformLocation(self.id.get(), uniform.as_ptr()), 1, gl::FALSE, ptr::addr_of!(value.matrix) as *const GLfloat);
         Ok(())
     }
 }
@@ -72,7 +87,7 @@ impl ShaderProgram {
 impl Drop for ShaderProgram {
     fn drop(&mut self) {
         unsafe {
-            gl::DeleteProgram(self.id);
+            gl::DeleteProgram(self.id.get());
         }
     }
 }
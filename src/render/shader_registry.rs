@@ -0,0 +1,123 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use thiserror::Error;
+use crate::files_watcher::FilesWatcher;
+use crate::render::shader::Shader;
+use crate::render::shader_preprocessor::{PreprocessedShader, ShaderPreprocessor};
+use crate::render::shader_program::ShaderProgram;
+
+#[derive(Error, Debug)]
+pub enum ShaderRegistryError {
+    #[error("Failed to build shader program {0}: {1}")]
+    ProgramBuildError(String, String),
+}
+
+pub const VERTEX_SHADER_EXTENSION: &str = ".vert.glsl";
+pub const FRAGMENT_SHADER_EXTENSION: &str = ".frag.glsl";
+
+//Program name and its defines, the same program is built once for every set of defines
+type ShaderProgramKey = (String, Vec<(String, String)>);
+
+struct ShaderProgramEntry {
+    program: Rc<ShaderProgram>,
+    watcher: FilesWatcher,
+}
+
+//Program "name" is built from name.vert.glsl and name.frag.glsl in the shaders directory
+pub struct ShaderRegistry {
+    shaders_path: PathBuf,
+    programs: RefCell<HashMap<ShaderProgramKey, ShaderProgramEntry>>,
+}
+
+impl ShaderRegistry {
+    pub fn new(shaders_path: &Path) -> Self {
+        return Self {
+            shaders_path: shaders_path.to_path_buf(),
+            programs: RefCell::new(HashMap::new()),
+        };
+    }
+
+    fn preprocess(&self, name: &str, defines: &[(String, String)]) -> Result<PreprocessedShader, Box<dyn std::error::Error>> {
+        let defines: Vec<(&str, &str)> = defines.iter().map(|(define_name, define_value)| (define_name.as_str(), define_value.as_str())).collect();
+        let mut read_source = |source_name: &str| fs::read_to_string(self.shaders_path.join(source_name)).ok();
+        return Ok(ShaderPreprocessor::preprocess(name, &defines, &mut read_source)?);
+    }
+
+    fn compile(&self, name: &str, defines: &[(String, String)], watcher: &FilesWatcher) -> Result<ShaderProgram, Box<dyn std::error::Error>> {
+        return match self.compile_sources(name, defines, watcher) {
+            Ok(program) => Ok(program),
+            Err(error) => Err(Box::new(ShaderRegistryError::ProgramBuildError(String::from(name), error.to_string()))),
+        }
+    }
+
+    fn compile_sources(&self, name: &str, defines: &[(String, String)], watcher: &FilesWatcher) -> Result<ShaderProgram, Box<dyn std::error::Error>> {
+        let vertex_name: String = format!("{}{}", name, VERTEX_SHADER_EXTENSION);
+        let fragment_name: String = format!("{}{}", name, FRAGMENT_SHADER_EXTENSION);
+        //Root files are watched even if preprocessing fails, so fixing them triggers a reload
+        watcher.watch(&self.shaders_path.join(&vertex_name));
+        watcher.watch(&self.shaders_path.join(&fragment_name));
+        let vertex_shader: PreprocessedShader = self.preprocess(&vertex_name, defines)?;
+        let fragment_shader: PreprocessedShader = self.preprocess(&fragment_name, defines)?;
+        for dependency in vertex_shader.dependencies.iter().chain(fragment_shader.dependencies.iter()) {
+            watcher.watch(&self.shaders_path.join(dependency));
+        }
+        unsafe {
+            let vert_shader: Shader = Shader::new(&vertex_shader.source, gl::VERTEX_SHADER)?;
+            let frag_shader: Shader = Shader::new(&fragment_shader.source, gl::FRAGMENT_SHADER)?;
+            return Ok(ShaderProgram::new(&[vert_shader, frag_shader])?);
+        }
+    }
+
+    fn get_key(name: &str, defines: &[(&str, &str)]) -> ShaderProgramKey {
+        let defines: Vec<(String, String)> = defines.iter().map(|(define_name, define_value)| (String::from(*define_name), String::from(*define_value))).collect();
+        return (String::from(name), defines);
+    }
+
+    pub fn load(&self, name: &str, defines: &[(&str, &str)]) -> Result<Rc<ShaderProgram>, Box<dyn std::error::Error>> {
+        let key: ShaderProgramKey = Self::get_key(name, defines);
+        if let Some(entry) = self.programs.borrow().get(&key) {
+            return Ok(entry.program.clone());
+        }
+        let watcher: FilesWatcher = FilesWatcher::new();
+        let program: Rc<ShaderProgram> = Rc::new(self.compile(name, &key.1, &watcher)?);
+        self.programs.borrow_mut().insert(key, ShaderProgramEntry {
+            program: program.clone(),
+            watcher,
+        });
+        return Ok(program);
+    }
+
+    pub fn get(&self, name: &str, defines: &[(&str, &str)]) -> Option<Rc<ShaderProgram>> {
+        return self.programs.borrow().get(&Self::get_key(name, defines)).map(|entry| entry.program.clone());
+    }
+
+    //Failed programs keep their last working version
+    pub fn reload_changed(&self, reloaded: &mut Vec<String>) -> Result<(), Vec<Box<dyn std::error::Error>>> {
+        let mut errors: Vec<Box<dyn std::error::Error>> = Vec::new();
+        for ((name, defines), entry) in self.programs.borrow().iter() {
+            if entry.watcher.is_changed() {
+                match self.compile(name, defines, &entry.watcher) {
+                    Ok(program) => {
+                        unsafe {
+                            if let Err(error) = entry.program.replace(program) {
+                                errors.push(Box::new(error));
+                            }
+                        }
+                        reloaded.push(name.clone());
+                    }
+                    Err(error) => {
+                        errors.push(error);
+                    }
+                }
+            }
+        }
+        return if errors.len() == 0 {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
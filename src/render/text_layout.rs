@@ -0,0 +1,244 @@
+use crate::render::font::Font;
+use crate::render::types::{Vec2f, Vec4f};
+
+//'§' followed by a hex digit switches to one of TEXT_COLORS, '§r' resets to the color of the text
+pub const COLOR_CODE_CHAR: char = '§';
+pub const RESET_COLOR_CODE: char = 'r';
+
+pub const TEXT_COLORS: [Vec4f; 16] = [
+    [0.0, 0.0, 0.0, 1.0], //0 black
+    [0.0, 0.0, 0.667, 1.0], //1 dark blue
+    [0.0, 0.667, 0.0, 1.0], //2 dark green
+    [0.0, 0.667, 0.667, 1.0], //3 dark aqua
+    [0.667, 0.0, 0.0, 1.0], //4 dark red
+    [0.667, 0.0, 0.667, 1.0], //5 dark purple
+    [1.0, 0.667, 0.0, 1.0], //6 gold
+    [0.667, 0.667, 0.667, 1.0], //7 gray
+    [0.333, 0.333, 0.333, 1.0], //8 dark gray
+    [0.333, 0.333, 1.0, 1.0], //9 blue
+    [0.333, 1.0, 0.333, 1.0], //a green
+    [0.333, 1.0, 1.0, 1.0], //b aqua
+    [1.0, 0.333, 0.333, 1.0], //c red
+    [1.0, 0.333, 1.0, 1.0], //d light purple
+    [1.0, 1.0, 0.333, 1.0], //e yellow
+    [1.0, 1.0, 1.0, 1.0], //f white
+];
+
+//Font pixels, x to the right and y down from the top left corner of the text
+pub struct GlyphQuad {
+    pub pos: Vec2f,
+    pub size: Vec2f,
+    pub tex_min: Vec2f,
+    pub tex_max: Vec2f,
+    pub color: Vec4f,
+}
+
+pub struct TextLayout {
+    pub quads: Vec<GlyphQuad>,
+    pub size: Vec2f,
+    pub lines_cnt: usize,
+}
+
+//Chars with their colors, color codes removed
+fn get_colored_chars(text: &str, color: &Vec4f) -> Vec<(char, Vec4f)> {
+    let mut colored_chars: Vec<(char, Vec4f)> = Vec::new();
+    let mut cur_color: Vec4f = *color;
+    let mut chars = text.chars().peekable();
+    while let Some(char) = chars.next() {
+        if char == COLOR_CODE_CHAR {
+            if let Some(code) = chars.peek().copied() {
+                if let Some(color_ind) = code.to_digit(16) {
+                    cur_color = TEXT_COLORS[color_ind as usize];
+                    chars.next();
+                    continue;
+                }
+                if code.to_ascii_lowercase() == RESET_COLOR_CODE {
+                    cur_color = *color;
+                    chars.next();
+                    continue;
+                }
+            }
+        }
+        colored_chars.push((char, cur_color));
+    }
+    return colored_chars;
+}
+
+fn get_line_width(font: &Font, line: &[(char, Vec4f)]) -> f32 {
+    let mut width: f32 = 0.0;
+    let mut last_char: Option<char> = None;
+    for (char, _color) in line {
+        if let Some(last_char) = last_char {
+            width += font.get_kerning(last_char, *char);
+        }
+        width += font.get_glyph(*char).map_or(0.0, |glyph| glyph.advance);
+        last_char = Some(*char);
+    }
+    return width;
+}
+
+//Count of the first chars fitting into max_width, at least one
+fn get_fitting_len(font: &Font, line: &[(char, Vec4f)], max_width: f32) -> usize {
+    let mut len: usize = 1;
+    while len < line.len() && get_line_width(font, &line[..(len + 1)]) <= max_width {
+        len += 1;
+    }
+    return len;
+}
+
+//Lines wider than max_width are wrapped at the last space, words wider than it are split
+fn wrap_line(font: &Font, line: &[(char, Vec4f)], max_width: Option<f32>, lines: &mut Vec<Vec<(char, Vec4f)>>) {
+    let mut cur_line: Vec<(char, Vec4f)> = Vec::new();
+    for colored_char in line {
+        cur_line.push(*colored_char);
+        if let Some(max_width) = max_width {
+            //The carried over word may still be too wide, so the rest is checked again
+            while cur_line.len() > 1 && get_line_width(font, &cur_line) > max_width {
+                let split_pos: usize = match cur_line.iter().rposition(|(char, _color)| *char == ' ') {
+                    Some(space_pos) => space_pos,
+                    None => get_fitting_len(font, &cur_line, max_width),
+                };
+                let mut rest: Vec<(char, Vec4f)> = cur_line.split_off(split_pos);
+                //The space the line is wrapped at isn't shown
+                if rest.first().map_or(false, |(char, _color)| *char == ' ') {
+                    rest.remove(0);
+                }
+                lines.push(cur_line);
+                cur_line = rest;
+            }
+        }
+    }
+    lines.push(cur_line);
+}
+
+pub fn layout_text(font: &Font, text: &str, color: &Vec4f, max_width: Option<f32>) -> TextLayout {
+    let colored_chars: Vec<(char, Vec4f)> = get_colored_chars(text, color);
+    let mut lines: Vec<Vec<(char, Vec4f)>> = Vec::new();
+    for line in colored_chars.split(|(char, _color)| *char == '\n') {
+        wrap_line(font, line, max_width, &mut lines);
+    }
+    let mut quads: Vec<GlyphQuad> = Vec::new();
+    let mut width: f32 = 0.0;
+    for (line_ind, line) in lines.iter().enumerate() {
+        let line_y: f32 = (line_ind as f32) * font.line_height;
+        let mut pen_x: f32 = 0.0;
+        let mut last_char: Option<char> = None;
+        for (char, char_color) in line {
+            if let Some(last_char) = last_char {
+                pen_x += font.get_kerning(last_char, *char);
+            }
+            if let Some(glyph) = font.get_glyph(*char) {
+                if glyph.size[0] > 0.0 && glyph.size[1] > 0.0 {
+                    quads.push(GlyphQuad {
+                        pos: [pen_x + glyph.offset[0], line_y + glyph.offset[1]],
+                        size: glyph.size,
+                        tex_min: glyph.tex_min,
+                        tex_max: glyph.tex_max,
+                        color: *char_color,
+                    });
+                }
+                pen_x += glyph.advance;
+            }
+            last_char = Some(*char);
+        }
+        width = width.max(pen_x);
+    }
+    return TextLayout {
+        quads,
+        size: [width, (lines.len() as f32) * font.line_height],
+        lines_cnt: lines.len(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use image::RgbaImage;
+    use crate::render::font::{FontData, GlyphData, KerningData};
+    use super::*;
+
+    const WHITE: Vec4f = [1.0, 1.0, 1.0, 1.0];
+
+    //Chars advance by 10 pixels, 'i' and spaces by 4, spaces draw nothing and "av" is kerned by -3
+    fn get_test_font() -> Font {
+        let glyph = |char: char, width: u32, advance: i32| GlyphData { char: String::from(char), x: 0, y: 0, width, height: 10, x_offset: 1, y_offset: 2, advance };
+        let mut glyphs: Vec<GlyphData> = ('a'..='z').map(|char| if char == 'i' { glyph(char, 2, 4) } else { glyph(char, 8, 10) }).collect();
+        glyphs.push(glyph('?', 8, 10));
+        glyphs.push(glyph(' ', 0, 4));
+        let font_data: FontData = FontData {
+            name: String::from("test:font"),
+            atlas: String::new(),
+            line_height: 12,
+            glyphs,
+            kerning: vec![KerningData { first: String::from("a"), second: String::from("v"), amount: -3 }],
+        };
+        return Font::from_data(font_data, RgbaImage::new(16, 16), "test").unwrap();
+    }
+
+    fn get_quads_pos(text_layout: &TextLayout) -> Vec<Vec2f> {
+        return text_layout.quads.iter().map(|quad| quad.pos).collect();
+    }
+
+    #[test]
+    fn kerning_moves_the_next_char() {
+        let font: Font = get_test_font();
+        let text_layout: TextLayout = layout_text(&font, "avb", &WHITE, None);
+        assert_eq!(get_quads_pos(&text_layout), vec![[1.0, 2.0], [8.0, 2.0], [18.0, 2.0]]);
+        assert_eq!(text_layout.size, [27.0, 12.0]);
+        assert_eq!(layout_text(&font, "va", &WHITE, None).size, [20.0, 12.0]);
+    }
+
+    #[test]
+    fn color_codes_are_removed() {
+        let font: Font = get_test_font();
+        let color: Vec4f = [0.1, 0.2, 0.3, 0.4];
+        let text_layout: TextLayout = layout_text(&font, "a§cb§Rc§zd§", &color, None);
+        let colors: Vec<Vec4f> = text_layout.quads.iter().map(|quad| quad.color).collect();
+        //Unknown codes are shown as they are, '§' is missing in the font and falls back to '?'
+        assert_eq!(colors, vec![color, TEXT_COLORS[12], color, color, color, color, color]);
+        assert_eq!(text_layout.quads.len(), 7);
+        assert_eq!(text_layout.size, [70.0, 12.0]);
+        assert_eq!(layout_text(&font, "§4§e", &color, None).quads.len(), 0);
+    }
+
+    #[test]
+    fn lines_wrap_at_spaces() {
+        let font: Font = get_test_font();
+        let text_layout: TextLayout = layout_text(&font, "aa bb cc\ndd", &WHITE, Some(55.0));
+        assert_eq!(text_layout.lines_cnt, 3);
+        assert_eq!(text_layout.size, [44.0, 36.0]);
+        let lines_y: Vec<f32> = text_layout.quads.iter().map(|quad| quad.pos[1]).collect();
+        assert_eq!(lines_y, vec![2.0, 2.0, 2.0, 2.0, 14.0, 14.0, 26.0, 26.0]);
+        //The space the line is wrapped at starts no line
+        assert_eq!(text_layout.quads[4].pos[0], 1.0);
+        //Only the trailing space is too wide, so the line is wrapped at it
+        assert_eq!(layout_text(&font, "aa bb", &WHITE, Some(44.0)).lines_cnt, 1);
+        let text_layout: TextLayout = layout_text(&font, "aa bb c", &WHITE, Some(46.0));
+        assert_eq!(text_layout.lines_cnt, 2);
+        assert_eq!(text_layout.quads[4].pos, [1.0, 14.0]);
+    }
+
+    #[test]
+    fn long_words_are_split() {
+        let font: Font = get_test_font();
+        let text_layout: TextLayout = layout_text(&font, "abcdefgh", &WHITE, Some(35.0));
+        assert_eq!(text_layout.lines_cnt, 3);
+        assert_eq!(text_layout.size, [30.0, 36.0]);
+        assert_eq!(get_quads_pos(&text_layout)[3], [1.0, 14.0]);
+        //Chars wider than max_width get their own lines
+        assert_eq!(layout_text(&font, "abc", &WHITE, Some(5.0)).lines_cnt, 3);
+    }
+
+    #[test]
+    fn carried_over_words_are_split_too() {
+        let font: Font = get_test_font();
+        //"bcd" is carried over after the narrow "i " and is still too wide
+        let text_layout: TextLayout = layout_text(&font, "i bcd", &WHITE, Some(29.0));
+        assert_eq!(text_layout.lines_cnt, 3);
+        assert_eq!(text_layout.size[0], 20.0);
+        for text in [" bcde", "i bcdefghi j", "ab  cdefgh", "av avavav", "i i i abcdefgh"] {
+            for max_width in [15.0, 25.0, 29.0, 35.0, 45.0] {
+                assert!(layout_text(&font, text, &WHITE, Some(max_width)).size[0] <= max_width, "{} overflows {}", text, max_width);
+            }
+        }
+    }
+}
\ No newline at end of file
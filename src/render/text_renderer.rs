@@ -0,0 +1,157 @@
+use std::cell::{Cell, RefCell};
+use std::ptr;
+use std::rc::Rc;
+use crate::render::buffer::Buffer;
+use crate::render::font::Font;
+use crate::render::shader_program::ShaderProgram;
+use crate::render::shader_registry::ShaderRegistry;
+use crate::render::text_layout::{layout_text, TextLayout};
+use crate::render::texture::Texture;
+use crate::render::types::{TexRGBAVertex2f, Vec2f, Vec4f};
+use crate::render::vertex_array::VertexArray;
+use crate::set_attribute;
+
+pub const TEXT_SHADER_PROGRAM: &str = "gui_text";
+
+//Position of the top left corner and sizes are in GUI units, like gui elements
+pub struct TextElement {
+    pub pos: Vec2f,
+    pub text: String,
+    pub color: Vec4f,
+    pub scale: f32, //GUI units per font pixel
+    pub max_width: Option<f32>,
+}
+
+pub struct TextRenderer {
+    pub shader_program: Rc<ShaderProgram>,
+    pub font: Rc<Font>,
+    atlas: Texture,
+    pub texts: RefCell<Vec<TextElement>>,
+    pub is_changed: Cell<bool>,
+    pub vert_buf: RefCell<Buffer>,
+    pub ind_buf: RefCell<Buffer>,
+    pub ind_cnt: Cell<i32>,
+    pub vert_array: RefCell<VertexArray>,
+    asp_rat: Cell<f32>,
+}
+
+impl TextRenderer {
+    pub fn init_text_renderer(shader_registry: &ShaderRegistry, font: Rc<Font>) -> Result<Self, Box<dyn std::error::Error>> {
+        let shader_program: Rc<ShaderProgram> = shader_registry.load(TEXT_SHADER_PROGRAM, &[])?;
+        unsafe {
+            shader_program.set_uniform_i32("atlas", 0)?;
+            let atlas: Texture = Texture::new();
+            atlas.load_image(&font.atlas);
+            return Ok(Self {
+                shader_program,
+                font,
+                atlas,
+                texts: RefCell::new(Vec::new()),
+                is_changed: Cell::new(true),
+                vert_buf: RefCell::new(Buffer::new(gl::ARRAY_BUFFER)),
+                ind_buf: RefCell::new(Buffer::new(gl::ELEMENT_ARRAY_BUFFER)),
+                ind_cnt: Cell::new(0),
+                vert_array: RefCell::new(VertexArray::new()),
+                asp_rat: Cell::new(1.0f32),
+            });
+        }
+    }
+
+    //Returns the index of the text for set_text
+    pub fn add_text(&self, text_element: TextElement) -> usize {
+        let mut texts = self.texts.borrow_mut();
+        texts.push(text_element);
+        self.is_changed.set(true);
+        return texts.len() - 1;
+    }
+
+    pub fn set_text(&self, text_ind: usize, text: &str) {
+        if let Some(text_element) = self.texts.borrow_mut().get_mut(text_ind) {
+            if text_element.text != text {
+                text_element.text = String::from(text);
+                self.is_changed.set(true);
+            }
+        }
+    }
+
+    pub fn set_pos(&self, text_ind: usize, pos: &Vec2f) {
+        if let Some(text_element) = self.texts.borrow_mut().get_mut(text_ind) {
+            text_element.pos = *pos;
+            self.is_changed.set(true);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.texts.borrow_mut().clear();
+        self.is_changed.set(true);
+    }
+
+    pub fn set_asp_rat(&self, asp_rat: f32) {
+        self.asp_rat.replace(asp_rat);
+        self.is_changed.replace(true);
+    }
+
+    pub fn render(&self) -> Result<(), Vec<Box<dyn std::error::Error>>> {
+        let mut errors: Vec<Box<dyn std::error::Error>> = Vec::new();
+        if self.is_changed.get() {
+            let mut vertices: Vec<TexRGBAVertex2f> = Vec::new();
+            let mut indices: Vec<i32> = Vec::new();
+            for text_element in self.texts.borrow().iter() {
+                let layout: TextLayout = layout_text(&self.font, &text_element.text, &text_element.color, text_element.max_width.map(|max_width| max_width / text_element.scale));
+                for quad in &layout.quads {
+                    let first_ind: i32 = vertices.len() as i32;
+                    let min: Vec2f = [text_element.pos[0] + quad.pos[0] * text_element.scale, text_element.pos[1] - quad.pos[1] * text_element.scale];
+                    let max: Vec2f = [min[0] + quad.size[0] * text_element.scale, min[1] - quad.size[1] * text_element.scale];
+                    vertices.push(TexRGBAVertex2f([min[0], min[1] * self.asp_rat.get()], [quad.tex_min[0], quad.tex_min[1]], quad.color));
+                    vertices.push(TexRGBAVertex2f([max[0], min[1] * self.asp_rat.get()], [quad.tex_max[0], quad.tex_min[1]], quad.color));
+                    vertices.push(TexRGBAVertex2f([max[0], max[1] * self.asp_rat.get()], [quad.tex_max[0], quad.tex_max[1]], quad.color));
+                    vertices.push(TexRGBAVertex2f([min[0], max[1] * self.asp_rat.get()], [quad.tex_min[0], quad.tex_max[1]], quad.color));
+                    indices.extend_from_slice(&[first_ind, first_ind + 1, first_ind + 2, first_ind + 2, first_ind + 3, first_ind]);
+                }
+            }
+            unsafe {
+                self.vert_array.replace(VertexArray::new());
+                self.vert_array.borrow().bind();
+
+                self.vert_buf.replace(Buffer::new(gl::ARRAY_BUFFER));
+                self.vert_buf.borrow().set_data(vertices.as_slice(), gl::STATIC_DRAW);
+
+                match (self.shader_program.get_attrib_location("pos"), self.shader_program.get_attrib_location("tex"), self.shader_program.get_attrib_location("col")) {
+                    (Ok(pos_attrib), Ok(tex_attrib), Ok(col_attrib)) => {
+                        set_attribute!(self.vert_array.borrow(), pos_attrib, TexRGBAVertex2f::0);
+                        set_attribute!(self.vert_array.borrow(), tex_attrib, TexRGBAVertex2f::1);
+                        set_attribute!(self.vert_array.borrow(), col_attrib, TexRGBAVertex2f::2);
+
+                        self.ind_buf.replace(Buffer::new(gl::ELEMENT_ARRAY_BUFFER));
+                        self.ind_buf.borrow().set_data(indices.as_slice(), gl::STATIC_DRAW);
+                    }
+                    (pos_res, tex_res, col_res) => {
+                        for error in [pos_res.err(), tex_res.err(), col_res.err()].into_iter().flatten() {
+                            errors.push(Box::new(error));
+                        }
+                    }
+                }
+            }
+
+            self.ind_cnt.set(indices.len() as i32);
+            self.is_changed.set(false);
+        }
+
+        return if errors.len() == 0 {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn draw(&self) {
+        if self.ind_cnt.get() != 0 {
+            unsafe {
+                self.shader_program.apply();
+                self.atlas.activate(gl::TEXTURE0);
+                self.vert_array.borrow().bind();
+                gl::DrawElements(gl::TRIANGLES, self.ind_cnt.get(), gl::UNSIGNED_INT, ptr::null());
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -1,6 +1,6 @@
 use std::path::Path;
 use gl::types::{GLint, GLuint};
-use image::{EncodableLayout, ImageError};
+use image::{EncodableLayout, ImageError, RgbaImage};
 
 pub struct Texture {
     pub id: GLuint,
@@ -18,9 +18,13 @@ impl Texture {
     }
 
     pub unsafe fn load(&self, path: &Path) -> Result<(), ImageError> {
-        self.bind();
-
         let img = image::open(path)?.into_rgba8();
+        self.load_image(&img);
+        Ok(())
+    }
+
+    pub unsafe fn load_image(&self, img: &RgbaImage) {
+        self.bind();
 
         gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
 
@@ -47,8 +51,6 @@ impl Texture {
         gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, 4);
 
         gl::GenerateMipmap(gl::TEXTURE_2D);
-
-        Ok(())
     }
 
     pub unsafe fn activate(&self, unit: GLuint) {
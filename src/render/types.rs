@@ -73,6 +73,14 @@ pub fn norm_vec3f(x: &mut Vec3f) {
     }
 }
 
+pub fn dot_vec3f(x: &Vec3f, y: &Vec3f) -> f32 {
+    return (x[0] * y[0]) + (x[1] * y[1]) + (x[2] * y[2]);
+}
+
+pub fn cross_vec3f(x: &Vec3f, y: &Vec3f) -> Vec3f {
+    return [(x[1] * y[2]) - (x[2] * y[1]), (x[2] * y[0]) - (x[0] * y[2]), (x[0] * y[1]) - (x[1] * y[0])];
+}
+
 #[derive(Clone)]
 #[repr(C, packed)]
 pub struct RGBAVertex2f(pub Vec2f, pub RGBAColor);
@@ -259,6 +267,10 @@ impl<'de> Deserialize<'de> for RGBAVertex3f {
     }
 }
 
+#[derive(Clone)]
+#[repr(C, packed)]
+pub struct TexRGBAVertex2f(pub Vec2f, pub TexCoord, pub RGBAColor);
+
 #[derive(Clone)]
 #[repr(C, packed)]
 pub struct LightedTexVertex(pub Vec3f, pub TexCoord, pub RGBSColor);
@@ -0,0 +1,145 @@
+use std::cell::{Cell, RefCell};
+use std::ptr;
+use std::rc::Rc;
+use image::RgbaImage;
+use crate::gui::widget_tree::{GuiDrawList, WidgetTree};
+use crate::render::buffer::Buffer;
+use crate::render::font::Font;
+use crate::render::shader_program::ShaderProgram;
+use crate::render::shader_registry::ShaderRegistry;
+use crate::render::text_renderer::TextRenderer;
+use crate::render::texture::Texture;
+use crate::render::types::{TexRGBAVertex2f, Vec2f};
+use crate::render::vertex_array::VertexArray;
+use crate::set_attribute;
+
+pub const WIDGETS_SHADER_PROGRAM: &str = "gui_widgets";
+
+const NO_TEX: Vec2f = [-1.0, -1.0]; //plain color in the shader
+
+//Draws the quads of a widget tree, textured from the GUI atlas or plain, and its texts with an own text renderer
+pub struct WidgetRenderer {
+    pub shader_program: Rc<ShaderProgram>,
+    atlas: Texture,
+    pub text_renderer: TextRenderer,
+    pub is_changed: Cell<bool>,
+    pub vert_buf: RefCell<Buffer>,
+    pub ind_buf: RefCell<Buffer>,
+    pub ind_cnt: Cell<i32>,
+    pub vert_array: RefCell<VertexArray>,
+    text_scale: f32,
+    asp_rat: Cell<f32>,
+}
+
+impl WidgetRenderer {
+    pub fn init_widget_renderer(shader_registry: &ShaderRegistry, font: Rc<Font>, atlas_image: &RgbaImage, text_scale: f32) -> Result<Self, Box<dyn std::error::Error>> {
+        let shader_program: Rc<ShaderProgram> = shader_registry.load(WIDGETS_SHADER_PROGRAM, &[])?;
+        let text_renderer: TextRenderer = TextRenderer::init_text_renderer(shader_registry, font)?;
+        unsafe {
+            shader_program.set_uniform_i32("atlas", 0)?;
+            let atlas: Texture = Texture::new();
+            atlas.load_image(atlas_image);
+            return Ok(Self {
+                shader_program,
+                atlas,
+                text_renderer,
+                is_changed: Cell::new(true),
+                vert_buf: RefCell::new(Buffer::new(gl::ARRAY_BUFFER)),
+                ind_buf: RefCell::new(Buffer::new(gl::ELEMENT_ARRAY_BUFFER)),
+                ind_cnt: Cell::new(0),
+                vert_array: RefCell::new(VertexArray::new()),
+                text_scale,
+                asp_rat: Cell::new(1.0f32),
+            });
+        }
+    }
+
+    //GUI atlas of reloaded layouts
+    pub fn set_atlas(&mut self, atlas_image: &RgbaImage) {
+        unsafe {
+            let atlas: Texture = Texture::new();
+            atlas.load_image(atlas_image);
+            self.atlas = atlas;
+        }
+    }
+
+    pub fn set_asp_rat(&self, asp_rat: f32) {
+        self.asp_rat.replace(asp_rat);
+        self.text_renderer.set_asp_rat(asp_rat);
+        self.is_changed.replace(true);
+    }
+
+    //Rebuilds the buffers when the tree or the renderer is changed
+    pub fn render(&self, widget_tree: &mut WidgetTree) -> Result<(), Vec<Box<dyn std::error::Error>>> {
+        let mut errors: Vec<Box<dyn std::error::Error>> = Vec::new();
+        if widget_tree.take_changed() || self.is_changed.get() {
+            let draw_list: GuiDrawList = widget_tree.get_draw_list(&self.text_renderer.font, self.text_scale);
+            let mut vertices: Vec<TexRGBAVertex2f> = Vec::new();
+            let mut indices: Vec<i32> = Vec::new();
+            let asp_rat: f32 = self.asp_rat.get();
+            for quad in &draw_list.quads {
+                let first_ind: i32 = vertices.len() as i32;
+                let min: Vec2f = quad.rect.min;
+                let max: Vec2f = quad.rect.max;
+                //Atlas rows go down while GUI y goes up
+                let [tex_min, tex_max]: [Vec2f; 2] = quad.tex.unwrap_or([NO_TEX, NO_TEX]);
+                vertices.push(TexRGBAVertex2f([min[0], min[1] * asp_rat], [tex_min[0], tex_max[1]], quad.color));
+                vertices.push(TexRGBAVertex2f([max[0], min[1] * asp_rat], [tex_max[0], tex_max[1]], quad.color));
+                vertices.push(TexRGBAVertex2f([max[0], max[1] * asp_rat], [tex_max[0], tex_min[1]], quad.color));
+                vertices.push(TexRGBAVertex2f([min[0], max[1] * asp_rat], [tex_min[0], tex_min[1]], quad.color));
+                indices.extend_from_slice(&[first_ind, first_ind + 1, first_ind + 2, first_ind + 2, first_ind + 3, first_ind]);
+            }
+            self.text_renderer.clear();
+            for text_element in draw_list.texts {
+                self.text_renderer.add_text(text_element);
+            }
+            unsafe {
+                self.vert_array.replace(VertexArray::new());
+                self.vert_array.borrow().bind();
+
+                self.vert_buf.replace(Buffer::new(gl::ARRAY_BUFFER));
+                self.vert_buf.borrow().set_data(vertices.as_slice(), gl::STATIC_DRAW);
+
+                match (self.shader_program.get_attrib_location("pos"), self.shader_program.get_attrib_location("tex"), self.shader_program.get_attrib_location("col")) {
+                    (Ok(pos_attrib), Ok(tex_attrib), Ok(col_attrib)) => {
+                        set_attribute!(self.vert_array.borrow(), pos_attrib, TexRGBAVertex2f::0);
+                        set_attribute!(self.vert_array.borrow(), tex_attrib, TexRGBAVertex2f::1);
+                        set_attribute!(self.vert_array.borrow(), col_attrib, TexRGBAVertex2f::2);
+
+                        self.ind_buf.replace(Buffer::new(gl::ELEMENT_ARRAY_BUFFER));
+                        self.ind_buf.borrow().set_data(indices.as_slice(), gl::STATIC_DRAW);
+                    }
+                    (pos_res, tex_res, col_res) => {
+                        for error in [pos_res.err(), tex_res.err(), col_res.err()].into_iter().flatten() {
+                            errors.push(Box::new(error));
+                        }
+                    }
+                }
+            }
+
+            self.ind_cnt.set(indices.len() as i32);
+            self.is_changed.set(false);
+        }
+        if let Err(text_errors) = self.text_renderer.render() {
+            errors.extend(text_errors);
+        }
+
+        return if errors.len() == 0 {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn draw(&self) {
+        if self.ind_cnt.get() != 0 {
+            unsafe {
+                self.shader_program.apply();
+                self.atlas.activate(gl::TEXTURE0);
+                self.vert_array.borrow().bind();
+                gl::DrawElements(gl::TRIANGLES, self.ind_cnt.get(), gl::UNSIGNED_INT, ptr::null());
+            }
+        }
+        self.text_renderer.draw();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,2 @@
+pub mod script_behavior;
+pub mod script_engine;
\ No newline at end of file
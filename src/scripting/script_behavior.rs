@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use rhai::{Dynamic, FuncArgs, INT};
+use crate::behavior::block_behavior::{BlockBehavior, TickKind};
+use crate::entity::entity::EntityId;
+use crate::physics::hitbox::HitBox;
+use crate::render::blocks_loader::BlocksLoader;
+use crate::render::types::Vec3ub;
+use crate::scripting::script_engine::{ScriptFn, ScriptRuntime};
+use crate::world::World;
+
+pub const BEHAVIOR_HOOKS: &[&str] = &["on_place", "on_break", "on_use", "on_neighbor_changed", "on_entity_collide", "on_tick"];
+
+//Hooks get block coordinates, neighbor ones, the entity id (-1 for the player) or whether the tick is random
+pub struct ScriptBehavior {
+    name: String,
+    runtime: Rc<ScriptRuntime>,
+    hooks: HashMap<String, ScriptFn>,
+}
+
+impl ScriptBehavior {
+    pub fn new(name: &str, runtime: Rc<ScriptRuntime>, hooks: HashMap<String, ScriptFn>) -> Self {
+        return Self {
+            name: String::from(name),
+            runtime,
+            hooks,
+        };
+    }
+
+    //Failed hooks are reported and ignored, so a broken script does not stop the game
+    fn call_hook(&self, world: &World, blocks_loader: &BlocksLoader, hook: &str, args: impl FuncArgs, changed: &mut Vec<Vec3ub>) -> Option<Dynamic> {
+        return if let Some(script_fn) = self.hooks.get(hook) {
+            match self.runtime.call(world, blocks_loader, script_fn, args, changed) {
+                Ok(result) => Some(result),
+                Err(error) => {
+                    println!("Behavior {} failed in {}: {}", self.name, hook, error);
+                    None
+                }
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl BlockBehavior for ScriptBehavior {
+    fn on_place(&self, world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub, changed: &mut Vec<Vec3ub>) {
+        self.call_hook(world, blocks_loader, "on_place", (pos[0] as INT, pos[1] as INT, pos[2] as INT), changed);
+    }
+
+    fn on_break(&self, world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub, changed: &mut Vec<Vec3ub>) {
+        self.call_hook(world, blocks_loader, "on_break", (pos[0] as INT, pos[1] as INT, pos[2] as INT), changed);
+    }
+
+    fn on_use(&self, world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub, changed: &mut Vec<Vec3ub>) -> bool {
+        return self.call_hook(world, blocks_loader, "on_use", (pos[0] as INT, pos[1] as INT, pos[2] as INT), changed)
+            .and_then(|result| result.as_bool().ok())
+            .unwrap_or(false);
+    }
+
+    fn on_neighbor_changed(&self, world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub, neighbor_pos: &Vec3ub, changed: &mut Vec<Vec3ub>) {
+        self.call_hook(world, blocks_loader, "on_neighbor_changed", (pos[0] as INT, pos[1] as INT, pos[2] as INT, neighbor_pos[0] as INT, neighbor_pos[1] as INT, neighbor_pos[2] as INT), changed);
+    }
+
+    fn on_entity_collide(&self, world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub, entity: Option<EntityId>, _hitbox: &mut HitBox, changed: &mut Vec<Vec3ub>) {
+        self.call_hook(world, blocks_loader, "on_entity_collide", (pos[0] as INT, pos[1] as INT, pos[2] as INT, entity.map_or(-1, |id| id as INT)), changed);
+    }
+
+    fn on_tick(&self, world: &World, blocks_loader: &BlocksLoader, pos: &Vec3ub, kind: TickKind, changed: &mut Vec<Vec3ub>) {
+        self.call_hook(world, blocks_loader, "on_tick", (pos[0] as INT, pos[1] as INT, pos[2] as INT, kind == TickKind::Random), changed);
+    }
+}
\ No newline at end of file
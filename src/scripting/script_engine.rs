@@ -0,0 +1,373 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, FnPtr, FuncArgs, Map, AST, FLOAT, INT};
+use thiserror::Error;
+use crate::behavior::block_behavior::{BehaviorRegistrationError, BlockBehaviors};
+use crate::packs::pack_stack::PackStack;
+use crate::physics::hitbox::HitBox;
+use crate::render::blocks_loader::{is_namespaced_name, BlocksLoader};
+use crate::render::types::Vec3ub;
+use crate::scripting::script_behavior::{ScriptBehavior, BEHAVIOR_HOOKS};
+use crate::world::World;
+
+pub const SCRIPTS_FILE: &str = "scripts.json";
+
+const MAX_OPERATIONS: u64 = 1_000_000; //per call, runaway scripts are stopped after it
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_STRING_SIZE: usize = 4096;
+const MAX_ARRAY_SIZE: usize = 65536;
+const MAX_MAP_SIZE: usize = 1024;
+
+#[derive(Error, Debug)]
+pub enum ScriptLoadingError {
+    #[error("{1} of pack {0} must be an array of scripts paths")]
+    InvalidScriptsListError(String, String),
+    #[error("Script {1} of pack {0} not found")]
+    ScriptNotFoundError(String, String),
+    #[error("Script {1} of pack {0} failed: {2}")]
+    ScriptError(String, String, String),
+}
+
+#[derive(Error, Debug)]
+pub enum ScriptCallingError {
+    #[error("Unknown command {0}")]
+    UnknownCommandError(String),
+    #[error("Command {0} failed: {1}")]
+    CommandError(String, String),
+}
+
+//Set only while scripts are called, so the API can't reach the world outside of calls
+#[derive(Clone, Copy)]
+struct ScriptContext {
+    world: *const World,
+    blocks_loader: *const BlocksLoader,
+    changed: *mut Vec<Vec3ub>,
+}
+
+//Function of a script with the script it is defined in
+#[derive(Clone)]
+pub struct ScriptFn {
+    pub ast: Rc<AST>,
+    pub fn_ptr: FnPtr,
+}
+
+enum Registration {
+    Behavior(String, HashMap<String, FnPtr>),
+    Command(String, FnPtr),
+    Feature(String, FnPtr),
+}
+
+pub struct ScriptRuntime {
+    engine: Engine,
+    context: Rc<Cell<Option<ScriptContext>>>,
+    registrations: Rc<RefCell<Option<Vec<Registration>>>>, //Some only while a script is loaded
+}
+
+fn get_pos(x: INT, y: INT, z: INT) -> Option<Vec3ub> {
+    return if (0..=0xFF).contains(&x) && (0..=0xFF).contains(&y) && (0..=0xFF).contains(&z) {
+        Some([x as u8, y as u8, z as u8])
+    } else {
+        None
+    }
+}
+
+fn with_context<T>(context: &Cell<Option<ScriptContext>>, func: impl FnOnce(&World, &BlocksLoader, &mut Vec<Vec3ub>) -> Result<T, Box<EvalAltResult>>) -> Result<T, Box<EvalAltResult>> {
+    return if let Some(script_context) = context.get() {
+        //Pointers are valid during the call, see ScriptRuntime::call
+        unsafe {
+            func(&*script_context.world, &*script_context.blocks_loader, &mut *script_context.changed)
+        }
+    } else {
+        Err("the world is not available while scripts are loaded".into())
+    }
+}
+
+fn register(registrations: &RefCell<Option<Vec<Registration>>>, registration: Registration) -> Result<(), Box<EvalAltResult>> {
+    return if let Some(registrations) = registrations.borrow_mut().as_mut() {
+        registrations.push(registration);
+        Ok(())
+    } else {
+        Err("registration is only allowed while scripts are loaded".into())
+    }
+}
+
+impl ScriptRuntime {
+    fn new() -> Self {
+        let mut engine: Engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(MAX_CALL_LEVELS);
+        engine.set_max_string_size(MAX_STRING_SIZE);
+        engine.set_max_array_size(MAX_ARRAY_SIZE);
+        engine.set_max_map_size(MAX_MAP_SIZE);
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+        engine.on_print(|text| println!("[script] {}", text));
+        engine.on_debug(|text, _source, pos| println!("[script {}] {}", pos, text));
+        let context: Rc<Cell<Option<ScriptContext>>> = Rc::new(Cell::new(None));
+        let registrations: Rc<RefCell<Option<Vec<Registration>>>> = Rc::new(RefCell::new(None));
+        Self::register_world_api(&mut engine, &context);
+        Self::register_registration_api(&mut engine, &registrations);
+        return Self { engine, context, registrations };
+    }
+
+    //Blocks are passed by names, since lids differ between loads, positions outside the world read as ""
+    fn register_world_api(engine: &mut Engine, context: &Rc<Cell<Option<ScriptContext>>>) {
+        let get_block_context = context.clone();
+        engine.register_fn("get_block", move |x: INT, y: INT, z: INT| -> Result<String, Box<EvalAltResult>> {
+            return with_context(&get_block_context, |world, blocks_loader, _changed| {
+                Ok(get_pos(x, y, z).map_or(String::new(), |pos| blocks_loader.get_block(world.get_block(&pos)).name.clone()))
+            });
+        });
+        let set_block_context = context.clone();
+        engine.register_fn("set_block", move |x: INT, y: INT, z: INT, name: &str| -> Result<bool, Box<EvalAltResult>> {
+            return with_context(&set_block_context, |world, blocks_loader, changed| {
+                let lid: u16 = blocks_loader.get_lid(name).map_err(|error| error.to_string())?;
+                Ok(if let Some(pos) = get_pos(x, y, z) {
+                    world.set_block(&pos, lid);
+                    changed.push(pos);
+                    true
+                } else {
+                    false
+                })
+            });
+        });
+        let get_light_context = context.clone();
+        engine.register_fn("get_light", move |x: INT, y: INT, z: INT, channel: INT| -> Result<INT, Box<EvalAltResult>> {
+            return with_context(&get_light_context, |world, _blocks_loader, _changed| {
+                if !(0..4).contains(&channel) {
+                    return Err(format!("invalid light channel {}", channel).into());
+                }
+                Ok(get_pos(x, y, z).map_or(0, |pos| world.get_light_level(&pos, channel as u8) as INT))
+            });
+        });
+        let schedule_tick_context = context.clone();
+        engine.register_fn("schedule_tick", move |x: INT, y: INT, z: INT, delay: INT| -> Result<(), Box<EvalAltResult>> {
+            return with_context(&schedule_tick_context, |world, _blocks_loader, _changed| {
+                if let Some(pos) = get_pos(x, y, z) {
+                    world.ticks.schedule(&pos, delay.clamp(1, u32::MAX as INT) as u32);
+                }
+                Ok(())
+            });
+        });
+        let spawn_entity_context = context.clone();
+        engine.register_fn("spawn_entity", move |type_name: &str, x: FLOAT, y: FLOAT, z: FLOAT, half_x: FLOAT, half_y: FLOAT, half_z: FLOAT| -> Result<INT, Box<EvalAltResult>> {
+            return with_context(&spawn_entity_context, |world, _blocks_loader, _changed| {
+                let hitbox: HitBox = HitBox::new([x as f32, y as f32, z as f32], [half_x as f32, half_y as f32, half_z as f32]);
+                Ok(world.entities.spawn(type_name, hitbox).map_err(|error| error.to_string())? as INT)
+            });
+        });
+    }
+
+    //Behavior hooks are a map of hook names to functions
+    fn register_registration_api(engine: &mut Engine, registrations: &Rc<RefCell<Option<Vec<Registration>>>>) {
+        let behavior_registrations = registrations.clone();
+        engine.register_fn("register_behavior", move |name: &str, hooks: Map| -> Result<(), Box<EvalAltResult>> {
+            let mut hooks_fns: HashMap<String, FnPtr> = HashMap::new();
+            for (hook, hook_fn) in hooks {
+                if !BEHAVIOR_HOOKS.contains(&hook.as_str()) {
+                    return Err(format!("unknown behavior hook {}", hook).into());
+                }
+                if let Some(fn_ptr) = hook_fn.try_cast::<FnPtr>() {
+                    hooks_fns.insert(hook.to_string(), fn_ptr);
+                } else {
+                    return Err(format!("behavior hook {} is not a function", hook).into());
+                }
+            }
+            return register(&behavior_registrations, Registration::Behavior(String::from(name), hooks_fns));
+        });
+        let command_registrations = registrations.clone();
+        engine.register_fn("register_command", move |name: &str, handler: FnPtr| -> Result<(), Box<EvalAltResult>> {
+            return register(&command_registrations, Registration::Command(String::from(name), handler));
+        });
+        let feature_registrations = registrations.clone();
+        engine.register_fn("register_feature", move |name: &str, generator: FnPtr| -> Result<(), Box<EvalAltResult>> {
+            return register(&feature_registrations, Registration::Feature(String::from(name), generator));
+        });
+    }
+
+    pub fn call(&self, world: &World, blocks_loader: &BlocksLoader, script_fn: &ScriptFn, args: impl FuncArgs, changed: &mut Vec<Vec3ub>) -> Result<Dynamic, Box<EvalAltResult>> {
+        let last_context: Option<ScriptContext> = self.context.replace(Some(ScriptContext {
+            world,
+            blocks_loader,
+            changed,
+        }));
+        let result = script_fn.fn_ptr.call::<Dynamic>(&self.engine, &script_fn.ast, args);
+        self.context.set(last_context);
+        return result;
+    }
+}
+
+//Scripts of all packs, upper packs override behaviors, commands and features of lower ones with the same names
+pub struct ScriptEngine {
+    runtime: Rc<ScriptRuntime>,
+    behaviors: Vec<(String, HashMap<String, ScriptFn>)>,
+    commands: HashMap<String, ScriptFn>,
+    features: Vec<(String, ScriptFn)>, //run in registration order
+}
+
+impl ScriptEngine {
+    //Compiles without running, for checking packs
+    pub fn check_script(source: &str) -> Result<(), String> {
+        return ScriptRuntime::new().engine.compile(source).map(|_ast| ()).map_err(|error| error.to_string());
+    }
+
+    fn load_script(&mut self, pack: &str, path: &str, source: &str) -> Result<(), ScriptLoadingError> {
+        let get_error = |error: String| ScriptLoadingError::ScriptError(String::from(pack), String::from(path), error);
+        let ast: Rc<AST> = Rc::new(self.runtime.engine.compile(source).map_err(|error| get_error(error.to_string()))?);
+        self.runtime.registrations.replace(Some(Vec::new()));
+        let result = self.runtime.engine.run_ast(&ast);
+        let registrations: Vec<Registration> = self.runtime.registrations.replace(None).unwrap_or_default();
+        result.map_err(|error| get_error(error.to_string()))?;
+        for registration in registrations {
+            match registration {
+                Registration::Behavior(name, hooks) => {
+                    if !is_namespaced_name(&name) {
+                        return Err(get_error(format!("behavior name {} is not namespaced like namespace:name", name)));
+                    }
+                    let hooks: HashMap<String, ScriptFn> = hooks.into_iter().map(|(hook, fn_ptr)| (hook, ScriptFn { ast: ast.clone(), fn_ptr })).collect();
+                    self.behaviors.retain(|(behavior_name, _hooks)| *behavior_name != name);
+                    self.behaviors.push((name, hooks));
+                }
+                Registration::Command(name, fn_ptr) => {
+                    self.commands.insert(name, ScriptFn { ast: ast.clone(), fn_ptr });
+                }
+                Registration::Feature(name, fn_ptr) => {
+                    if !is_namespaced_name(&name) {
+                        return Err(get_error(format!("feature name {} is not namespaced like namespace:name", name)));
+                    }
+                    self.features.retain(|(feature_name, _generator)| *feature_name != name);
+                    self.features.push((name, ScriptFn { ast: ast.clone(), fn_ptr }));
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    //Every pack lists its scripts in scripts.json, they are run from the bottom pack to the top one
+    pub fn load(packs: &PackStack) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut script_engine: Self = Self {
+            runtime: Rc::new(ScriptRuntime::new()),
+            behaviors: Vec::new(),
+            commands: HashMap::new(),
+            features: Vec::new(),
+        };
+        for pack_definitions in packs.read_definitions(SCRIPTS_FILE)? {
+            for script_value in &pack_definitions.values {
+                let path: &str = script_value.as_str().ok_or(ScriptLoadingError::InvalidScriptsListError(pack_definitions.pack.clone(), String::from(SCRIPTS_FILE)))?;
+                let mut source: Option<Vec<u8>> = None;
+                if let Some(pack) = packs.packs.iter().find(|pack| pack.manifest.name == pack_definitions.pack) {
+                    source = pack.read_file(path)?;
+                }
+                if let Some(source) = source {
+                    script_engine.load_script(&pack_definitions.pack, path, &String::from_utf8(source)?)?;
+                } else {
+                    return Err(Box::new(ScriptLoadingError::ScriptNotFoundError(pack_definitions.pack.clone(), String::from(path))));
+                }
+            }
+        }
+        return Ok(script_engine);
+    }
+
+    pub fn register_behaviors(&self, behaviors: &mut BlockBehaviors) -> Result<(), BehaviorRegistrationError> {
+        for (name, hooks) in &self.behaviors {
+            behaviors.register(name, Rc::new(ScriptBehavior::new(name, self.runtime.clone(), hooks.clone())))?;
+        }
+        return Ok(());
+    }
+
+    pub fn get_commands_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.commands.keys().cloned().collect();
+        names.sort();
+        return names;
+    }
+
+    //Returns the output of the command and changed positions
+    pub fn run_command(&self, world: &World, blocks_loader: &BlocksLoader, name: &str, args: &[String]) -> Result<(String, Vec<Vec3ub>), ScriptCallingError> {
+        return if let Some(handler) = self.commands.get(name) {
+            let mut changed: Vec<Vec3ub> = Vec::new();
+            let args_array: Array = args.iter().map(|arg| Dynamic::from(arg.clone())).collect();
+            match self.runtime.call(world, blocks_loader, handler, (args_array,), &mut changed) {
+                Ok(output) => Ok((if output.is_unit() { String::new() } else { output.to_string() }, changed)),
+                Err(error) => Err(ScriptCallingError::CommandError(String::from(name), error.to_string())),
+            }
+        } else {
+            Err(ScriptCallingError::UnknownCommandError(String::from(name)))
+        }
+    }
+
+    //Features get the block coordinates of the chunk corner, failed ones are reported and skipped
+    pub fn generate_features(&self, world: &World, blocks_loader: &BlocksLoader, chunk_ind: usize) -> Vec<Vec3ub> {
+        let mut changed: Vec<Vec3ub> = Vec::new();
+        for (name, generator) in &self.features {
+            if let Err(error) = self.runtime.call(world, blocks_loader, generator, (((chunk_ind >> 4) << 4) as INT, ((chunk_ind & 0x0F) << 4) as INT), &mut changed) {
+                println!("Feature {} failed: {}", name, error);
+            }
+        }
+        return changed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::thread::{Builder, JoinHandle};
+    use crate::chunk::LayerChunkGenerator;
+    use super::*;
+
+    const TEST_STACK_SIZE: usize = 64 * 1024 * 1024;
+
+    fn get_test_engine(source: &str) -> ScriptEngine {
+        let mut script_engine: ScriptEngine = ScriptEngine {
+            runtime: Rc::new(ScriptRuntime::new()),
+            behaviors: Vec::new(),
+            commands: HashMap::new(),
+            features: Vec::new(),
+        };
+        script_engine.load_script("test", "test.rhai", source).unwrap();
+        return script_engine;
+    }
+
+    //Flat world of 4 dirt layers over bedrock, generated on a thread with a big enough stack for chunks
+    fn run_test(test: fn(&World, &BlocksLoader)) {
+        let handle: JoinHandle<()> = Builder::new().stack_size(TEST_STACK_SIZE).spawn(move || {
+            let packs: PackStack = PackStack::open(&[PathBuf::from("assets")]).unwrap();
+            let blocks_loader: BlocksLoader = BlocksLoader::load_headless(&packs).unwrap();
+            let bedrock: u16 = blocks_loader.get_lid("core:bedrock").unwrap();
+            let dirt: u16 = blocks_loader.get_lid("core:dirt").unwrap();
+            let world: World = World::new(&LayerChunkGenerator::from_bottom_layers(&[bedrock, dirt, dirt, dirt, dirt], blocks_loader.air_block.lid), &blocks_loader).unwrap();
+            test(&world, &blocks_loader);
+        }).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn max_operations_stop_runaway_scripts() {
+        run_test(|world: &World, blocks_loader: &BlocksLoader| {
+            let script_engine: ScriptEngine = get_test_engine("register_command(\"spin\", |args| { loop { } });");
+            assert!(matches!(script_engine.run_command(world, blocks_loader, "spin", &[]), Err(ScriptCallingError::CommandError(_, _))));
+        });
+    }
+
+    #[test]
+    fn collide_hooks_collect_changed_positions() {
+        run_test(|world: &World, blocks_loader: &BlocksLoader| {
+            let script_engine: ScriptEngine = get_test_engine("register_behavior(\"test:trap\", #{ on_entity_collide: |x, y, z, id| { set_block(x, y + 1, z, \"core:lava\"); } });");
+            let mut behaviors: BlockBehaviors = BlockBehaviors::new();
+            script_engine.register_behaviors(&mut behaviors).unwrap();
+            let mut hitbox: HitBox = HitBox::new([8.5, 5.5, 8.5], [0.25, 0.25, 0.25]);
+            let mut changed: Vec<Vec3ub> = Vec::new();
+            behaviors.get("test:trap").unwrap().on_entity_collide(world, blocks_loader, &[8, 4, 8], None, &mut hitbox, &mut changed);
+            assert_eq!(changed, vec![[8, 5, 8]]);
+            assert_eq!(world.get_block(&[8, 5, 8]), blocks_loader.get_lid("core:lava").unwrap());
+        });
+    }
+
+    #[test]
+    fn unknown_hooks_are_rejected() {
+        let mut script_engine: ScriptEngine = get_test_engine("");
+        assert!(script_engine.load_script("test", "test.rhai", "register_behavior(\"test:trap\", #{ on_fly: || 0 });").is_err());
+        assert!(script_engine.load_script("test", "test.rhai", "register_behavior(\"trap\", #{});").is_err());
+    }
+}
\ No newline at end of file
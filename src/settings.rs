@@ -0,0 +1,51 @@
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+pub const MIN_FOV: f32 = 30.0;
+pub const MAX_FOV: f32 = 110.0;
+pub const MIN_MOUSE_SENSITIVITY: f32 = 10.0;
+pub const MAX_MOUSE_SENSITIVITY: f32 = 300.0;
+
+//User settings kept apart from the worlds, missing values get defaults
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Settings {
+    #[serde(default = "get_default_fov")]
+    pub fov: f32, //vertical, in degrees
+    #[serde(default = "get_default_mouse_sensitivity")]
+    pub mouse_sensitivity: f32, //degrees of rotation per the shorter window side
+}
+
+fn get_default_fov() -> f32 {
+    return 60.0;
+}
+
+fn get_default_mouse_sensitivity() -> f32 {
+    return 86.0;
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        return Self {
+            fov: get_default_fov(),
+            mouse_sensitivity: get_default_mouse_sensitivity(),
+        };
+    }
+
+    //Values out of their ranges are clamped
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut data: Vec<u8> = Vec::new();
+        File::open(path)?.read_to_end(&mut data)?;
+        let mut settings: Self = serde_json::from_slice(&data)?;
+        settings.fov = settings.fov.clamp(MIN_FOV, MAX_FOV);
+        settings.mouse_sensitivity = settings.mouse_sensitivity.clamp(MIN_MOUSE_SENSITIVITY, MAX_MOUSE_SENSITIVITY);
+        return Ok(settings);
+    }
+
+    pub fn store(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let mut file: File = File::create(path)?;
+        file.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        return Ok(());
+    }
+}
\ No newline at end of file
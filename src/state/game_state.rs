@@ -0,0 +1,55 @@
+//Screens of the game, only the top one gets the input
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameState {
+    MainMenu,
+    WorldList,
+    Loading,
+    Playing,
+    Paused,
+    Settings,
+    Controls,
+}
+
+pub struct StateStack {
+    states: Vec<GameState>,
+}
+
+impl StateStack {
+    pub fn new(state: GameState) -> Self {
+        return Self {
+            states: vec![state],
+        };
+    }
+
+    pub fn get_top(&self) -> GameState {
+        return *self.states.last().unwrap_or(&GameState::MainMenu);
+    }
+
+    pub fn contains(&self, state: GameState) -> bool {
+        return self.states.contains(&state);
+    }
+
+    pub fn push(&mut self, state: GameState) {
+        self.states.push(state);
+    }
+
+    //The bottom state is never popped
+    pub fn pop(&mut self) -> Option<GameState> {
+        return if self.states.len() > 1 { self.states.pop() } else { None }
+    }
+
+    pub fn replace(&mut self, state: GameState) {
+        self.states.pop();
+        self.states.push(state);
+    }
+
+    pub fn reset(&mut self, state: GameState) {
+        self.states.clear();
+        self.states.push(state);
+    }
+
+    //The world only runs while nothing covers it
+    pub fn is_world_paused(&self) -> bool {
+        return self.get_top() != GameState::Playing;
+    }
+}
\ No newline at end of file
@@ -0,0 +1,3 @@
+pub mod game_state;
+pub mod worlds_list;
+pub mod world_loading;
\ No newline at end of file
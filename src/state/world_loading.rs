@@ -0,0 +1,83 @@
+use std::path::Path;
+use crate::chunk::ChunkGenerator;
+use crate::render::blocks_loader::BlocksLoader;
+use crate::render::light::light_solvers::LightSolvers;
+use crate::scripting::script_engine::ScriptEngine;
+use crate::world::World;
+
+const CHUNKS_CNT: usize = 16 * 16;
+const FEATURES_CHUNKS_PER_STEP: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LoadingStage {
+    Features(usize), //index of the next chunk getting script features
+    Reading,
+    Lighting,
+    Done,
+}
+
+//Builds the world over several frames, so the loading screen can show the progress
+pub struct WorldLoading {
+    pub world: World,
+    pub world_path: String,
+    stage: LoadingStage,
+}
+
+impl WorldLoading {
+    //Terrain is generated right away, saved worlds skip the features since the save replaces them
+    pub fn new<T: ChunkGenerator>(chunk_generator: &T, blocks_loader: &BlocksLoader, world_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let world: World = World::new(chunk_generator, blocks_loader)?;
+        let stage: LoadingStage = if Path::new(world_path).exists() { LoadingStage::Reading } else { LoadingStage::Features(0) };
+        return Ok(Self { world, world_path: String::from(world_path), stage });
+    }
+
+    pub fn get_stage(&self) -> LoadingStage {
+        return self.stage;
+    }
+
+    pub fn is_done(&self) -> bool {
+        return self.stage == LoadingStage::Done;
+    }
+
+    //Lighting takes about as long as the rest
+    pub fn get_progress(&self) -> f32 {
+        return match self.stage {
+            LoadingStage::Features(chunk_ind) => 0.5 * (chunk_ind as f32) / (CHUNKS_CNT as f32),
+            LoadingStage::Reading => 0.5,
+            LoadingStage::Lighting => 0.6,
+            LoadingStage::Done => 1.0,
+        }
+    }
+
+    pub fn get_description(&self) -> &'static str {
+        return match self.stage {
+            LoadingStage::Features(_) => "Generating features",
+            LoadingStage::Reading => "Reading the save",
+            LoadingStage::Lighting => "Lighting",
+            LoadingStage::Done => "Done",
+        }
+    }
+
+    //Does the work of one frame
+    pub fn step(&mut self, blocks_loader: &BlocksLoader, script_engine: &ScriptEngine, light_solvers: &LightSolvers) -> Result<(), Box<dyn std::error::Error>> {
+        match self.stage {
+            LoadingStage::Features(chunk_ind) => {
+                let end_ind: usize = (chunk_ind + FEATURES_CHUNKS_PER_STEP).min(CHUNKS_CNT);
+                for chunk_ind in chunk_ind..end_ind {
+                    script_engine.generate_features(&self.world, blocks_loader, chunk_ind);
+                }
+                self.stage = if end_ind == CHUNKS_CNT { LoadingStage::Lighting } else { LoadingStage::Features(end_ind) };
+            }
+            LoadingStage::Reading => {
+                self.world.load(blocks_loader, self.world_path.clone())?;
+                self.stage = LoadingStage::Lighting;
+            }
+            LoadingStage::Lighting => {
+                light_solvers.init(&self.world, blocks_loader);
+                self.stage = LoadingStage::Done;
+            }
+            LoadingStage::Done => {}
+        }
+        return Ok(());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+pub const WORLD_FILE: &str = "world.data";
+pub const PLAYER_DATA_FILE: &str = "player.data";
+
+const MAX_NAME_LEN: usize = 32;
+
+#[derive(Error, Debug)]
+pub enum WorldsListError {
+    #[error("World name {0} is not valid, names are up to 32 letters, digits, spaces, '-' and '_'")]
+    InvalidNameError(String),
+    #[error("World {0} already exists")]
+    ExistingWorldError(String),
+    #[error("World {0} does not exist")]
+    MissingWorldError(String),
+}
+
+//Every world is a directory named as the world with the world and player files
+pub struct WorldsList {
+    path: PathBuf,
+}
+
+impl WorldsList {
+    pub fn new(path: &Path) -> Self {
+        return Self { path: path.to_path_buf() };
+    }
+
+    //Names are used as directory names, so they are kept simple
+    pub fn is_valid_name(name: &str) -> bool {
+        return !name.is_empty() && name.chars().count() <= MAX_NAME_LEN && name.trim() == name &&
+            name.chars().all(|char| char.is_alphanumeric() || char == ' ' || char == '-' || char == '_');
+    }
+
+    //Sorted, empty if the worlds directory doesn't exist yet
+    pub fn get_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        if let Ok(entries) = fs::read_dir(&self.path) {
+            for entry in entries.flatten() {
+                if let (true, Some(name)) = (entry.path().is_dir(), entry.file_name().to_str()) {
+                    if Self::is_valid_name(name) {
+                        names.push(String::from(name));
+                    }
+                }
+            }
+        }
+        names.sort();
+        return names;
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        return Self::is_valid_name(name) && self.path.join(name).is_dir();
+    }
+
+    pub fn get_world_path(&self, name: &str) -> String {
+        return self.path.join(name).join(WORLD_FILE).to_string_lossy().into_owned();
+    }
+
+    pub fn get_player_data_path(&self, name: &str) -> String {
+        return self.path.join(name).join(PLAYER_DATA_FILE).to_string_lossy().into_owned();
+    }
+
+    //The world is generated when it is played for the first time
+    pub fn create(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if !Self::is_valid_name(name) {
+            return Err(Box::new(WorldsListError::InvalidNameError(String::from(name))));
+        }
+        if self.exists(name) {
+            return Err(Box::new(WorldsListError::ExistingWorldError(String::from(name))));
+        }
+        fs::create_dir_all(self.path.join(name))?;
+        return Ok(());
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.exists(name) {
+            return Err(Box::new(WorldsListError::MissingWorldError(String::from(name))));
+        }
+        fs::remove_dir_all(self.path.join(name))?;
+        return Ok(());
+    }
+
+    pub fn rename(&self, name: &str, new_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.exists(name) {
+            return Err(Box::new(WorldsListError::MissingWorldError(String::from(name))));
+        }
+        if !Self::is_valid_name(new_name) {
+            return Err(Box::new(WorldsListError::InvalidNameError(String::from(new_name))));
+        }
+        if self.exists(new_name) {
+            return Err(Box::new(WorldsListError::ExistingWorldError(String::from(new_name))));
+        }
+        fs::rename(self.path.join(name), self.path.join(new_name))?;
+        return Ok(());
+    }
+}
\ No newline at end of file
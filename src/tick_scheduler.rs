@@ -0,0 +1,231 @@
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use thiserror::Error;
+use crate::render::types::Vec3ub;
+
+const CHUNKS_CNT: usize = 16 * 16;
+const DEFAULT_RANDOM_TICKS: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum TicksLoadingError {
+    #[error("Invalid scheduled ticks data")]
+    InvalidTicksDataError(),
+}
+
+#[derive(Error, Debug)]
+pub enum TicksStoringError {
+    #[error("Too many scheduled ticks in chunk")]
+    TicksOverflowError(),
+}
+
+pub struct TickScheduler {
+    tick: Cell<u64>,
+    scheduled: RefCell<BTreeMap<u64, Vec<Vec3ub>>>, //positions by the tick of their update
+    pub random_ticks: Cell<u32>, //randomly ticked blocks per subchunk per tick
+    rng: RefCell<StdRng>,
+}
+
+impl TickScheduler {
+    pub fn new() -> Self {
+        return Self {
+            tick: Cell::new(0),
+            scheduled: RefCell::new(BTreeMap::new()),
+            random_ticks: Cell::new(DEFAULT_RANDOM_TICKS),
+            rng: RefCell::new(StdRng::from_entropy()),
+        };
+    }
+
+    pub fn get_tick(&self) -> u64 {
+        return self.tick.get();
+    }
+
+    //Scheduled ticks keep their remaining delays
+    pub fn set_tick(&self, tick: u64) {
+        let old_tick: u64 = self.tick.get();
+        let scheduled: BTreeMap<u64, Vec<Vec3ub>> = std::mem::take(&mut *self.scheduled.borrow_mut());
+        self.scheduled.replace(scheduled.into_iter().map(|(due_tick, positions)| (due_tick - old_tick + tick, positions)).collect());
+        self.tick.set(tick);
+    }
+
+    //The block at pos gets a scheduled tick after delay ticks, at least the next one
+    pub fn schedule(&self, pos: &Vec3ub, delay: u32) {
+        self.scheduled.borrow_mut().entry(self.tick.get() + (delay.max(1) as u64)).or_default().push(*pos);
+    }
+
+    pub fn clear(&self) {
+        self.scheduled.borrow_mut().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        return self.scheduled.borrow().values().map(|positions| positions.len()).sum();
+    }
+
+    //Advances the tick and returns the positions due, every position once
+    pub fn take_due(&self) -> Vec<Vec3ub> {
+        self.tick.set(self.tick.get() + 1);
+        let mut due: Vec<Vec3ub> = Vec::new();
+        let mut scheduled = self.scheduled.borrow_mut();
+        let later: BTreeMap<u64, Vec<Vec3ub>> = scheduled.split_off(&(self.tick.get() + 1));
+        for (_tick, positions) in std::mem::replace(&mut *scheduled, later) {
+            due.extend(positions);
+        }
+        due.sort();
+        due.dedup();
+        return due;
+    }
+
+    pub fn get_random_positions(&self) -> Vec<Vec3ub> {
+        let mut positions: Vec<Vec3ub> = Vec::new();
+        let mut rng = self.rng.borrow_mut();
+        for chunk_ind in 0..CHUNKS_CNT {
+            for subchunk_pos in 0..16u8 {
+                for _tick_pos in 0..self.random_ticks.get() {
+                    let block_pos: u16 = rng.gen();
+                    positions.push([
+                        (((chunk_ind >> 4) as u8) << 4) | ((block_pos & 0x0F) as u8),
+                        (subchunk_pos << 4) | (((block_pos >> 4) & 0x0F) as u8),
+                        (((chunk_ind & 0x0F) as u8) << 4) | (((block_pos >> 8) & 0x0F) as u8),
+                    ]);
+                }
+            }
+        }
+        return positions;
+    }
+
+    fn get_chunk_ind(pos: &Vec3ub) -> usize {
+        return (((pos[0] >> 4) as usize) << 4) | ((pos[2] >> 4) as usize);
+    }
+
+    //Count of ticks, then for every tick: position and remaining delay
+    pub fn store_chunk(&self, chunk_ind: usize, data: &mut Vec<u8>) -> Result<(), Box<dyn std::error::Error>> {
+        let mut chunk_ticks: Vec<(Vec3ub, u32)> = Vec::new();
+        for (tick, positions) in self.scheduled.borrow().iter() {
+            for pos in positions {
+                if Self::get_chunk_ind(pos) == chunk_ind {
+                    chunk_ticks.push((*pos, (tick - self.tick.get()).min(u32::MAX as u64) as u32));
+                }
+            }
+        }
+        if chunk_ticks.len() > (u16::MAX as usize) {
+            return Err(Box::new(TicksStoringError::TicksOverflowError()));
+        }
+        data.extend_from_slice(&(chunk_ticks.len() as u16).to_be_bytes());
+        for (pos, delay) in chunk_ticks {
+            data.extend_from_slice(&pos);
+            data.extend_from_slice(&delay.to_be_bytes());
+        }
+        return Ok(());
+    }
+
+    //Returns positions and delays, so the whole world can be parsed before scheduling
+    pub fn load_chunk(data: &[u8], pos: &mut usize) -> Result<Vec<(Vec3ub, u32)>, TicksLoadingError> {
+        let mut loaded: Vec<(Vec3ub, u32)> = Vec::new();
+        let cnt_data: &[u8] = data.get(*pos..(*pos + 2)).ok_or(TicksLoadingError::InvalidTicksDataError())?;
+        let ticks_cnt: u16 = ((cnt_data[0] as u16) << 8) | (cnt_data[1] as u16);
+        *pos += 2;
+        for _tick_pos in 0..ticks_cnt {
+            let tick_data: &[u8] = data.get(*pos..(*pos + 7)).ok_or(TicksLoadingError::InvalidTicksDataError())?;
+            loaded.push(([tick_data[0], tick_data[1], tick_data[2]], u32::from_be_bytes([tick_data[3], tick_data[4], tick_data[5], tick_data[6]])));
+            *pos += 7;
+        }
+        return Ok(loaded);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take_ticks(ticks: &TickScheduler, ticks_cnt: usize) -> Vec<Vec<Vec3ub>> {
+        return (0..ticks_cnt).map(|_| ticks.take_due()).collect();
+    }
+
+    #[test]
+    fn take_due_returns_each_position_once_in_order() {
+        let ticks: TickScheduler = TickScheduler::new();
+        ticks.schedule(&[5, 0, 0], 2);
+        ticks.schedule(&[1, 2, 3], 2);
+        ticks.schedule(&[5, 0, 0], 2);
+        ticks.schedule(&[0, 9, 0], 0);
+        ticks.schedule(&[0, 9, 0], 1);
+        ticks.schedule(&[7, 7, 7], 3);
+        assert_eq!(ticks.len(), 6);
+        assert_eq!(take_ticks(&ticks, 4), vec![vec![[0, 9, 0]], vec![[1, 2, 3], [5, 0, 0]], vec![[7, 7, 7]], vec![]]);
+        assert_eq!(ticks.get_tick(), 4);
+        assert_eq!(ticks.len(), 0);
+    }
+
+    #[test]
+    fn overdue_positions_are_taken_with_the_next_tick() {
+        let ticks: TickScheduler = TickScheduler::new();
+        ticks.schedule(&[1, 1, 1], 1);
+        ticks.schedule(&[2, 2, 2], 2);
+        //Entries which are already due are not left behind
+        ticks.scheduled.borrow_mut().insert(0, vec![[3, 3, 3]]);
+        assert_eq!(ticks.take_due(), vec![[1, 1, 1], [3, 3, 3]]);
+        assert_eq!(ticks.take_due(), vec![[2, 2, 2]]);
+    }
+
+    #[test]
+    fn set_tick_keeps_the_delays() {
+        let ticks: TickScheduler = TickScheduler::new();
+        ticks.take_due();
+        ticks.schedule(&[1, 1, 1], 3);
+        ticks.schedule(&[2, 2, 2], 1);
+        ticks.set_tick(1000);
+        assert_eq!(ticks.get_tick(), 1000);
+        assert_eq!(take_ticks(&ticks, 3), vec![vec![[2, 2, 2]], vec![], vec![[1, 1, 1]]]);
+        ticks.schedule(&[3, 3, 3], 5);
+        ticks.set_tick(10);
+        assert_eq!(take_ticks(&ticks, 5), vec![vec![], vec![], vec![], vec![], vec![[3, 3, 3]]]);
+    }
+
+    #[test]
+    fn chunks_store_and_load_their_ticks() {
+        let ticks: TickScheduler = TickScheduler::new();
+        ticks.set_tick(500);
+        ticks.schedule(&[0x12, 0x40, 0x35], 7);
+        ticks.schedule(&[0x1F, 0x00, 0x30], 1);
+        ticks.schedule(&[0x22, 0x40, 0x35], 2); //another chunk
+        let chunk_ind: usize = TickScheduler::get_chunk_ind(&[0x12, 0x40, 0x35]);
+        assert_eq!(chunk_ind, 0x13);
+        let mut data: Vec<u8> = vec![0xAA];
+        ticks.store_chunk(chunk_ind, &mut data).unwrap();
+        ticks.store_chunk(0x00, &mut data).unwrap();
+        assert_eq!(data.len(), 1 + 2 + 2 * 7 + 2);
+        let mut pos: usize = 1;
+        let loaded: Vec<(Vec3ub, u32)> = TickScheduler::load_chunk(&data, &mut pos).unwrap();
+        assert_eq!(loaded, vec![([0x1F, 0x00, 0x30], 1), ([0x12, 0x40, 0x35], 7)]);
+        assert_eq!(TickScheduler::load_chunk(&data, &mut pos).unwrap(), vec![]);
+        assert_eq!(pos, data.len());
+        //Loaded delays are scheduled from the tick of the loading world
+        let loaded_ticks: TickScheduler = TickScheduler::new();
+        for (tick_pos, delay) in &loaded {
+            loaded_ticks.schedule(tick_pos, *delay);
+        }
+        assert_eq!(take_ticks(&loaded_ticks, 7)[6], vec![[0x12, 0x40, 0x35]]);
+    }
+
+    #[test]
+    fn truncated_ticks_data_fails() {
+        let mut pos: usize = 0;
+        assert!(TickScheduler::load_chunk(&[0x00], &mut pos).is_err());
+        let mut pos: usize = 0;
+        assert!(TickScheduler::load_chunk(&[0x00, 0x01, 0x01, 0x02, 0x03, 0x00, 0x00, 0x00], &mut pos).is_err());
+    }
+
+    #[test]
+    fn random_positions_cover_every_subchunk() {
+        let ticks: TickScheduler = TickScheduler::new();
+        ticks.random_ticks.set(2);
+        let positions: Vec<Vec3ub> = ticks.get_random_positions();
+        assert_eq!(positions.len(), CHUNKS_CNT * 16 * 2);
+        for (ind, pos) in positions.iter().enumerate() {
+            let subchunk_ind: usize = ind / 2;
+            assert_eq!(TickScheduler::get_chunk_ind(pos), subchunk_ind / 16);
+            assert_eq!((pos[1] >> 4) as usize, subchunk_ind % 16);
+        }
+    }
+}
\ No newline at end of file
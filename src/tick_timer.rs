@@ -0,0 +1,97 @@
+const MAX_FRAME_TICKS: u32 = 8; //slow frames drop the rest of time instead of spiraling
+
+pub struct TickTimer {
+    tick_time: f32,
+    accumulator: f32,
+    ticks_count: u64,
+    ticks_duration: f32,
+}
+
+impl TickTimer {
+    pub fn new(tick_rate: f32) -> Self {
+        return Self {
+            tick_time: 1.0 / tick_rate,
+            accumulator: 0.0,
+            ticks_count: 0,
+            ticks_duration: 0.0,
+        };
+    }
+
+    //Returns count of ticks to run for the passed frame time
+    pub fn advance(&mut self, frame_time: f32) -> u32 {
+        self.accumulator += frame_time.max(0.0);
+        let mut ticks_cnt: u32 = (self.accumulator / self.tick_time) as u32;
+        if ticks_cnt > MAX_FRAME_TICKS {
+            ticks_cnt = MAX_FRAME_TICKS;
+            self.accumulator = self.tick_time * (MAX_FRAME_TICKS as f32);
+        }
+        self.accumulator -= self.tick_time * (ticks_cnt as f32);
+        self.ticks_count += ticks_cnt as u64;
+        return ticks_cnt;
+    }
+
+    //Part of the next tick already passed, used to interpolate rendered positions
+    pub fn get_alpha(&self) -> f32 {
+        return (self.accumulator / self.tick_time).clamp(0.0, 1.0);
+    }
+
+    pub fn get_tick_time(&self) -> f32 {
+        return self.tick_time;
+    }
+
+    pub fn get_ticks_count(&self) -> u64 {
+        return self.ticks_count;
+    }
+
+    //Real time spent on the ticks of the last frame
+    pub fn get_ticks_duration(&self) -> f32 {
+        return self.ticks_duration;
+    }
+
+    pub fn set_ticks_duration(&mut self, ticks_duration: f32) {
+        self.ticks_duration = ticks_duration;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advance_runs_whole_ticks_and_keeps_the_rest() {
+        let mut tick_timer: TickTimer = TickTimer::new(8.0);
+        assert_eq!(tick_timer.advance(0.25), 2);
+        assert_eq!(tick_timer.advance(0.0625), 0);
+        assert_eq!(tick_timer.get_alpha(), 0.5);
+        assert_eq!(tick_timer.advance(0.0625), 1);
+        assert_eq!(tick_timer.get_alpha(), 0.0);
+        assert_eq!(tick_timer.get_ticks_count(), 3);
+    }
+
+    #[test]
+    fn advance_ignores_negative_frame_time() {
+        let mut tick_timer: TickTimer = TickTimer::new(8.0);
+        assert_eq!(tick_timer.advance(0.0625), 0);
+        assert_eq!(tick_timer.advance(-1.0), 0);
+        assert_eq!(tick_timer.get_alpha(), 0.5);
+    }
+
+    #[test]
+    fn slow_frames_are_clamped_and_drop_excess_time() {
+        let mut tick_timer: TickTimer = TickTimer::new(8.0);
+        assert_eq!(tick_timer.advance(10.0), MAX_FRAME_TICKS);
+        assert_eq!(tick_timer.get_alpha(), 0.0);
+        assert_eq!(tick_timer.advance(0.0625), 0);
+        assert_eq!(tick_timer.get_ticks_count(), MAX_FRAME_TICKS as u64);
+    }
+
+    #[test]
+    fn alpha_stays_in_unit_range() {
+        let mut tick_timer: TickTimer = TickTimer::new(20.0);
+        for frame_pos in 0..1000 {
+            tick_timer.advance(((frame_pos * 7919) % 113) as f32 * 0.001);
+            let alpha: f32 = tick_timer.get_alpha();
+            assert!((0.0..=1.0).contains(&alpha), "alpha {} at frame {}", alpha, frame_pos);
+        }
+    }
+}
\ No newline at end of file
@@ -37,6 +37,8 @@ impl Window {
             window.set_key_polling(true);
             window.set_cursor_pos_polling(true);
             window.set_mouse_button_polling(true);
+            window.set_scroll_polling(true);
+            window.set_char_polling(true);
             window.set_framebuffer_size_polling(true);
             gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
             Ok(Self {
@@ -66,6 +68,7 @@ impl Window {
     fn default_events_processor(&mut self) {
         self.glfw.poll_events();
         self.mouse.borrow_mut().poll_events();
+        self.keyboard.poll_events();
         for (_, event) in glfw::flush_messages(&self.events) {
             match event {
                 WindowEvent::FramebufferSize(width, height) => {
@@ -83,6 +86,12 @@ impl Window {
                 WindowEvent::MouseButton(button, action, modifiers) => {
                     self.mouse.borrow_mut().button_callback(button, action, modifiers);
                 }
+                WindowEvent::Scroll(xoffset, yoffset) => {
+                    self.mouse.borrow_mut().scroll_callback(xoffset, yoffset);
+                }
+                WindowEvent::Char(char) => {
+                    self.keyboard.char_callback(char);
+                }
                 _ => {}
             }
         }
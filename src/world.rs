@@ -1,12 +1,33 @@
 use std::fs::File;
 use std::io::{Read, Write};
-use std::ops::{Deref, DerefMut};
 use thiserror::Error;
-use crate::chunk::{Chunk, ChunkGenerator};
-use crate::render::blocks_loader::{AIR_BLOCK_ID, BlocksLoader};
-use crate::render::types::{Vec3f, Vec3i, Vec3s, Vec3ub};
+use crate::behavior::block_behavior::{BlockBehaviors, TickKind};
+use crate::chunk::{Chunk, ChunkGenerator, CHUNK_STATES_SIZE};
+use crate::entity::entity::EntityId;
+use crate::entity::entity_store::EntityStore;
+use crate::physics::aabb::{Aabb, FULL_BLOCK_AABB};
+use crate::physics::collider::WorldCollider;
+use crate::physics::fluid_solver::get_fluid_height;
+use crate::physics::hitbox::HitBox;
+use crate::physics::physics_solver::PhysicsSolver;
+use crate::physics::raycast::{RayBoxes, RayFilter, RayHit};
+use crate::render::blocks_loader::BlocksLoader;
+use crate::render::light::light_map::LightMap;
+use crate::render::light::light_solver::LightSolver;
+use crate::render::types::{Vec3b, Vec3f, Vec3i, Vec3s, Vec3ub};
+use crate::tick_scheduler::TickScheduler;
 
 const CHUNK_SIZE: usize = 16 * 16 * 16 * 16 * 2;
+const WORLD_MAGIC: &[u8; 4] = b"CCWD";
+const WORLD_VERSION: u16 = 4;
+const LEGACY_WORLD_VERSION: u16 = 0; //headerless files
+const ENTITIES_WORLD_VERSION: u16 = 2; //first version with entities saved after every chunk
+const STATES_WORLD_VERSION: u16 = 3; //first version with block states saved after every chunk blocks
+const SCHEDULED_TICKS_WORLD_VERSION: u16 = 4; //first version with scheduled ticks saved after every chunk entities
+const NEIGHBORHOOD: [Vec3b; 6] = [[0, 0, -1], [0, 0, 1], [0, -1, 0], [0, 1, 0], [-1, 0, 0], [1, 0, 0]];
+
+//Saves without a header were stored with the ids hardcoded before blocks got namespaced names
+const LEGACY_BLOCKS_NAMES: &[&str] = &["core:air", "core:unknown", "core:dirt", "core:grass", "core:bedrock"];
 
 #[derive(Error, Debug)]
 pub enum WorldCreationError {
@@ -18,10 +39,22 @@ pub enum WorldCreationError {
 pub enum WorldLoadingError {
     #[error("Invalid chunk size")]
     InvalidChunkSizeError(),
+    #[error("Unsupported world version {0}")]
+    UnsupportedVersionError(u16),
+    #[error("Invalid blocks registry")]
+    InvalidRegistryError(),
+}
+
+#[derive(Error, Debug)]
+pub enum WorldStoringError {
+    #[error("Too many blocks for the registry")]
+    RegistryOverflowError(),
 }
 
 pub struct World {
     pub chunks: [[Box<Chunk>; 16]; 16],
+    pub entities: EntityStore,
+    pub ticks: TickScheduler,
 }
 
 impl World {
@@ -41,7 +74,7 @@ impl World {
         }
         let chunks_res: Result<[[Box<Chunk>; 16]; 16], Vec<[Box<Chunk>; 16]>> = chunks_plane.try_into();
         return if let Ok(chunks) = chunks_res {
-            Ok(Self { chunks })
+            Ok(Self { chunks, entities: EntityStore::new(), ticks: TickScheduler::new() })
         } else {
             Err(Box::new(WorldCreationError::VectorSizingError()))
         }
@@ -53,21 +86,62 @@ impl World {
         self.chunks[subchunk_pos[0] as usize][subchunk_pos[2] as usize].subchunks[subchunk_pos[1] as usize].data.borrow()[block_pos[1] as usize][block_pos[2] as usize][block_pos[0] as usize]
     }
 
-    pub fn is_obstacle(&self, pos: &Vec3i) -> bool {
+    pub fn is_obstacle(&self, blocks_loader: &BlocksLoader, pos: &Vec3i) -> bool {
         return if pos[0] >= 0x00 && pos[0] <= 0xFF &&
             pos[1] >= 0x00 && pos[1] <= 0xFF &&
             pos[2] >= 0x00 && pos[2] <= 0xFF {
             let res: Vec3ub = [pos[0] as u8, pos[1] as u8, pos[2] as u8];
-            self.get_block(&res) != AIR_BLOCK_ID
+            blocks_loader.get_block(self.get_block(&res)).collision.len() != 0
         } else {
             true
         }
     }
 
+    //World space boxes, out of the world everything is solid
+    pub fn get_collision_boxes(&self, blocks_loader: &BlocksLoader, pos: &Vec3i, boxes: &mut Vec<Aabb>) {
+        let offset: Vec3f = [pos[0] as f32, pos[1] as f32, pos[2] as f32];
+        if pos[0] >= 0x00 && pos[0] <= 0xFF &&
+            pos[1] >= 0x00 && pos[1] <= 0xFF &&
+            pos[2] >= 0x00 && pos[2] <= 0xFF {
+            let res: Vec3ub = [pos[0] as u8, pos[1] as u8, pos[2] as u8];
+            for collision_box in &blocks_loader.get_block(self.get_block(&res)).collision {
+                boxes.push(collision_box.offset(&offset));
+            }
+        } else {
+            boxes.push(FULL_BLOCK_AABB.offset(&offset));
+        }
+    }
+
+    //Resets the block state
     pub fn set_block(&self, pos: &Vec3ub, block_lid: u16) {
+        self.set_block_with_state(pos, block_lid, 0);
+    }
+
+    pub fn set_block_with_state(&self, pos: &Vec3ub, block_lid: u16, state: u8) {
         let subchunk_pos: Vec3ub = [pos[0] >> 4, pos[1] >> 4, pos[2] >> 4];
         let block_pos: Vec3ub = [pos[0] & 0x0F, pos[1] & 0x0F, pos[2] & 0x0F];
         self.chunks[subchunk_pos[0] as usize][subchunk_pos[2] as usize].subchunks[subchunk_pos[1] as usize].data.borrow_mut()[block_pos[1] as usize][block_pos[2] as usize][block_pos[0] as usize] = block_lid;
+        self.chunks[subchunk_pos[0] as usize][subchunk_pos[2] as usize].subchunks[subchunk_pos[1] as usize].states.borrow_mut()[block_pos[1] as usize][block_pos[2] as usize][block_pos[0] as usize] = state;
+        self.set_block_changed(pos);
+    }
+
+    pub fn get_state(&self, pos: &Vec3ub) -> u8 {
+        let subchunk_pos: Vec3ub = [pos[0] >> 4, pos[1] >> 4, pos[2] >> 4];
+        let block_pos: Vec3ub = [pos[0] & 0x0F, pos[1] & 0x0F, pos[2] & 0x0F];
+        return self.chunks[subchunk_pos[0] as usize][subchunk_pos[2] as usize].subchunks[subchunk_pos[1] as usize].states.borrow()[block_pos[1] as usize][block_pos[2] as usize][block_pos[0] as usize];
+    }
+
+    pub fn set_state(&self, pos: &Vec3ub, state: u8) {
+        let subchunk_pos: Vec3ub = [pos[0] >> 4, pos[1] >> 4, pos[2] >> 4];
+        let block_pos: Vec3ub = [pos[0] & 0x0F, pos[1] & 0x0F, pos[2] & 0x0F];
+        self.chunks[subchunk_pos[0] as usize][subchunk_pos[2] as usize].subchunks[subchunk_pos[1] as usize].states.borrow_mut()[block_pos[1] as usize][block_pos[2] as usize][block_pos[0] as usize] = state;
+        self.set_block_changed(pos);
+    }
+
+    //Marks the subchunk of pos and the neighboring subchunks touching it for rerendering
+    fn set_block_changed(&self, pos: &Vec3ub) {
+        let subchunk_pos: Vec3ub = [pos[0] >> 4, pos[1] >> 4, pos[2] >> 4];
+        let block_pos: Vec3ub = [pos[0] & 0x0F, pos[1] & 0x0F, pos[2] & 0x0F];
         self.chunks[subchunk_pos[0] as usize][subchunk_pos[2] as usize].subchunks[subchunk_pos[1] as usize].is_changed.set(true);
 
         if block_pos[0] == 0x0F && subchunk_pos[0] < 0x0F {self.chunks[(subchunk_pos[0] + 1) as usize][subchunk_pos[2] as usize].subchunks[subchunk_pos[1] as usize].is_changed.set(true);}
@@ -79,7 +153,24 @@ impl World {
         if block_pos[2] == 0x00 && subchunk_pos[2] > 0x00 {self.chunks[subchunk_pos[0] as usize][(subchunk_pos[2] - 1) as usize].subchunks[subchunk_pos[1] as usize].is_changed.set(true);}
     }
 
-    pub fn ray_get(&self, pos: &Vec3f, dir: &Vec3f, max_dist: f32, end: &mut Vec3f, norm: &mut Vec3f, iend: &mut Vec3ub) -> Option<u16> {
+    pub fn get_block_at(&self, pos: &Vec3i) -> Option<u16> {
+        return if (0..3).all(|axis| pos[axis] >= 0x00 && pos[axis] <= 0xFF) {
+            Some(self.get_block(&[pos[0] as u8, pos[1] as u8, pos[2] as u8]))
+        } else {
+            None
+        }
+    }
+
+    pub fn get_state_at(&self, pos: &Vec3i) -> Option<u8> {
+        return if (0..3).all(|axis| pos[axis] >= 0x00 && pos[axis] <= 0xFF) {
+            Some(self.get_state(&[pos[0] as u8, pos[1] as u8, pos[2] as u8]))
+        } else {
+            None
+        }
+    }
+
+    //Nearest hit along dir (normalized) within max_dist, cells outside the world are passed through
+    pub fn raycast(&self, blocks_loader: &BlocksLoader, pos: &Vec3f, dir: &Vec3f, max_dist: f32, filter: &RayFilter) -> Option<RayHit> {
         let mut pdist: f32 = 0.0; //passed dist
 
         let mut ipos: Vec3i = [(pos[0].floor() as i32), (pos[1].floor() as i32), (pos[2].floor() as i32)];
@@ -107,75 +198,90 @@ impl World {
         let mut tymax: f32 = {if tdy < f32::INFINITY { tdy * ydist } else { f32::INFINITY }};
         let mut tzmax: f32 = {if tdz < f32::INFINITY { tdz * zdist } else { f32::INFINITY }};
 
-        let mut stepind: i8 = -1;
-
+        let mut block_hit: Option<RayHit> = None;
+        let mut boxes: Vec<Aabb> = Vec::new();
         while pdist <= max_dist {
-            if ipos[0] <= 0xFF && ipos[0] >= 0x00 &&
-                ipos[1] <= 0xFF && ipos[1] >= 0x00 &&
-                ipos[2] <= 0xFF && ipos[2] >= 0x00 {
-                let block = self.get_block(&[(ipos[0] as u8), (ipos[1] as u8), (ipos[2] as u8)]);
-                if block != AIR_BLOCK_ID { //TODO REWRITE for not cube blocks
-                    end[0] = pos[0] + pdist * dir[0];
-                    end[1] = pos[1] + pdist * dir[1];
-                    end[2] = pos[2] + pdist * dir[2];
-
-                    iend[0] = ipos[0] as u8;
-                    iend[1] = ipos[1] as u8;
-                    iend[2] = ipos[2] as u8;
-
-                    norm[0] = 0.0f32;
-                    norm[1] = 0.0f32;
-                    norm[2] = 0.0f32;
-                    match stepind {
-                        0 => norm[0] = -stepx,
-                        1 => norm[1] = -stepy,
-                        2 => norm[2] = -stepz,
-                        _ => {}
+            if let Some(block) = self.get_block_at(&ipos) {
+                let block_data = blocks_loader.get_block(block);
+                boxes.clear();
+                match filter.boxes {
+                    RayBoxes::Collision => boxes.extend_from_slice(&block_data.collision),
+                    RayBoxes::Selection => boxes.extend_from_slice(&block_data.selection),
+                }
+                if filter.fluids && block_data.fluid.is_some() {
+                    if let Some(state) = self.get_state_at(&ipos) {
+                        boxes.push(Aabb::new([0.0, 0.0, 0.0], [1.0, get_fluid_height(state), 1.0]));
                     }
-                    return Some(block);
                 }
-                if txmax < tymax {
-                    if txmax < tzmax {
-                        ipos[0] += stepx as i32;
-                        pdist = txmax;
-                        txmax += tdx;
-                        stepind = 0;
-                    } else {
-                        ipos[2] += stepz as i32;
-                        pdist = tzmax;
-                        tzmax += tdz;
-                        stepind = 2;
+                //Nearest box of the cell, boxes can be hit farther than the cell entry
+                let cell_offset: Vec3f = [ipos[0] as f32, ipos[1] as f32, ipos[2] as f32];
+                let mut box_hit: Option<(f32, i8)> = None;
+                for block_box in &boxes {
+                    if let Some((box_dist, box_axis)) = block_box.offset(&cell_offset).ray_intersect(pos, dir) {
+                        if box_dist <= max_dist && box_hit.map_or(true, |(hit_dist, _)| box_dist < hit_dist) {
+                            box_hit = Some((box_dist, box_axis));
+                        }
                     }
+                }
+                if let Some((box_dist, box_axis)) = box_hit {
+                    block_hit = Some(RayHit {
+                        block_pos: ipos,
+                        face: Self::get_ray_face(dir, box_axis),
+                        point: [pos[0] + box_dist * dir[0], pos[1] + box_dist * dir[1], pos[2] + box_dist * dir[2]],
+                        distance: box_dist,
+                        block,
+                        entity: None,
+                    });
+                    break;
+                }
+            }
+            if txmax < tymax {
+                if txmax < tzmax {
+                    ipos[0] += stepx as i32;
+                    pdist = txmax;
+                    txmax += tdx;
                 } else {
-                    if tymax < tzmax {
-                        ipos[1] += stepy as i32;
-                        pdist = tymax;
-                        tymax += tdy;
-                        stepind = 1;
-                    } else {
-                        ipos[2] += stepz as i32;
-                        pdist = tzmax;
-                        tzmax += tdz;
-                        stepind = 2;
-                    }
+                    ipos[2] += stepz as i32;
+                    pdist = tzmax;
+                    tzmax += tdz;
                 }
             } else {
-                break;
+                if tymax < tzmax {
+                    ipos[1] += stepy as i32;
+                    pdist = tymax;
+                    tymax += tdy;
+                } else {
+                    ipos[2] += stepz as i32;
+                    pdist = tzmax;
+                    tzmax += tdz;
+                }
             }
         }
 
-        iend[0] = ipos[0] as u8;
-        iend[1] = ipos[1] as u8;
-        iend[2] = ipos[2] as u8;
-
-        end[0] = pos[0] + (pdist * dir[0]);
-        end[1] = pos[1] + (pdist * dir[1]);
-        end[2] = pos[2] + (pdist * dir[2]);
+        if filter.entities {
+            let entity_max_dist: f32 = block_hit.map_or(max_dist, |hit| hit.distance);
+            if let Some((entity, entity_dist, entity_axis)) = self.entities.ray_intersect(pos, dir, entity_max_dist) {
+                let point: Vec3f = [pos[0] + entity_dist * dir[0], pos[1] + entity_dist * dir[1], pos[2] + entity_dist * dir[2]];
+                let block_pos: Vec3i = [point[0].floor() as i32, point[1].floor() as i32, point[2].floor() as i32];
+                return Some(RayHit {
+                    block_pos,
+                    face: Self::get_ray_face(dir, entity_axis),
+                    point,
+                    distance: entity_dist,
+                    block: self.get_block_at(&block_pos).unwrap_or(blocks_loader.air_block.lid),
+                    entity: Some(entity),
+                });
+            }
+        }
+        return block_hit;
+    }
 
-        norm[0] = 0.0f32;
-        norm[1] = 0.0f32;
-        norm[2] = 0.0f32;
-        return None;
+    fn get_ray_face(dir: &Vec3f, axis: i8) -> Vec3i {
+        let mut face: Vec3i = [0, 0, 0];
+        if axis >= 0 && axis < 3 {
+            face[axis as usize] = if dir[axis as usize] > 0.0 { -1 } else { 1 };
+        }
+        return face;
     }
 
     pub fn get_light_level(&self, pos: &Vec3ub, channel: u8) -> u8 {
@@ -191,6 +297,71 @@ impl World {
         self.chunks[subchunk_pos[0] as usize][subchunk_pos[2] as usize].subchunks[subchunk_pos[1] as usize].is_changed.set(true);
     }
 
+    //lids_map[old_lid] = new_lid, lids out of the map become unknown_lid, returns positions of the new lids which are marked
+    pub fn remap_blocks(&self, lids_map: &[u16], unknown_lid: u16, marked_lids: &[bool]) -> Vec<Vec3ub> {
+        let mut marked: Vec<Vec3ub> = Vec::new();
+        for line_pos in 0..16u8 {
+            for chunk_pos in 0..16u8 {
+                for (subchunk_pos, subchunk) in self.chunks[line_pos as usize][chunk_pos as usize].subchunks.iter().enumerate() {
+                    for (y_pos, plane_data) in subchunk.data.borrow_mut().iter_mut().enumerate() {
+                        for (z_pos, line_data) in plane_data.iter_mut().enumerate() {
+                            for (x_pos, block_data) in line_data.iter_mut().enumerate() {
+                                *block_data = *lids_map.get(*block_data as usize).unwrap_or(&unknown_lid);
+                                if marked_lids.get(*block_data as usize).copied().unwrap_or(false) {
+                                    marked.push([(line_pos << 4) | x_pos as u8, ((subchunk_pos as u8) << 4) | y_pos as u8, (chunk_pos << 4) | z_pos as u8]);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        return marked;
+    }
+
+    //Whether each lid below lids_cnt is placed somewhere in the world
+    pub fn get_used_lids(&self, lids_cnt: usize) -> Vec<bool> {
+        let mut used_lids: Vec<bool> = vec![false; lids_cnt];
+        for line_pos in 0..16u8 {
+            for chunk_pos in 0..16u8 {
+                for subchunk in &self.chunks[line_pos as usize][chunk_pos as usize].subchunks {
+                    for plane_data in subchunk.data.borrow().iter() {
+                        for line_data in plane_data.iter() {
+                            for block_data in line_data.iter() {
+                                if let Some(used) = used_lids.get_mut(*block_data as usize) {
+                                    *used = true;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        return used_lids;
+    }
+
+    //Before lighting the whole world again, LightSolvers::init only adds light
+    pub fn clear_light(&self) {
+        for line_pos in 0..16u8 {
+            for chunk_pos in 0..16u8 {
+                for subchunk in &self.chunks[line_pos as usize][chunk_pos as usize].subchunks {
+                    subchunk.light_map.replace(LightMap::new());
+                }
+            }
+        }
+        self.set_changed();
+    }
+
+    pub fn set_changed(&self) {
+        for line_pos in 0..16u8 {
+            for chunk_pos in 0..16u8 {
+                for subchunk in &self.chunks[line_pos as usize][chunk_pos as usize].subchunks {
+                    subchunk.is_changed.set(true);
+                }
+            }
+        }
+    }
+
     pub fn render(&self, blocks_loader: &BlocksLoader) -> Result<(), Vec<Box<dyn std::error::Error>>> {
         let mut errors: Vec<Box<dyn std::error::Error>> = Vec::new();
         for line_pos in 0..16u8 {
@@ -212,30 +383,207 @@ impl World {
         }
     }
 
-    pub fn draw(&self, blocks_loader: &BlocksLoader) {
+    //Returns the count of draw calls
+    pub fn draw(&self, blocks_loader: &BlocksLoader) -> usize {
+        let (atlas, shader_program) = match (blocks_loader.meshes_loader.faces_loader.get_atlas(), blocks_loader.meshes_loader.faces_loader.get_shader_program()) {
+            (Ok(atlas), Ok(shader_program)) => (atlas, shader_program),
+            _ => return 0, //headless blocks can't be drawn
+        };
         unsafe {
-            blocks_loader.meshes_loader.faces_loader.atlas.activate(gl::TEXTURE0);
+            atlas.activate(gl::TEXTURE0);
         }
+        let mut draw_calls: usize = 0;
         for line_pos in 0..16u8 {
             for chunk_pos in 0..16u8 {
-                self.chunks[line_pos as usize][chunk_pos as usize].draw(&blocks_loader.meshes_loader.faces_loader.shader_program, &[(line_pos << 4), 0, (chunk_pos << 4)]);
+                draw_calls += self.chunks[line_pos as usize][chunk_pos as usize].draw(shader_program, &[(line_pos << 4), 0, (chunk_pos << 4)]);
+            }
+        }
+        return draw_calls;
+    }
+
+    //Subchunks waiting to be rendered again
+    pub fn get_changed_subchunks_cnt(&self) -> usize {
+        return self.chunks.iter().flatten().map(|chunk| chunk.get_changed_subchunks_cnt()).sum();
+    }
+
+    //TODO all chunks are loaded until the world is streamed
+    pub fn get_loaded_chunks_cnt(&self) -> usize {
+        return self.chunks.iter().map(|chunk_line| chunk_line.len()).sum();
+    }
+
+    //Blocks overlapping hitboxes get their collide hooks called after stepping, returns changed positions
+    pub fn step_entities(&self, blocks_loader: &BlocksLoader, behaviors: &BlockBehaviors, physics_solver: &PhysicsSolver, delta: f32) -> Vec<Vec3ub> {
+        let mut changed: Vec<Vec3ub> = Vec::new();
+        self.entities.step(physics_solver, &WorldCollider::new(self, blocks_loader), delta);
+        for id in self.entities.get_entities_in(&Aabb::new([0.0, 0.0, 0.0], [256.0, 256.0, 256.0])) {
+            let entity_hitbox: Option<HitBox> = self.entities.get_entity(id).map(|entity| entity.hitbox.clone());
+            if let Some(mut hitbox) = entity_hitbox {
+                self.collide_blocks(blocks_loader, behaviors, Some(id), &mut hitbox, &mut changed);
+                if let Some(mut entity) = self.entities.get_entity_mut(id) {
+                    entity.hitbox = hitbox;
+                }
+            }
+        }
+        return changed;
+    }
+
+    pub fn collide_blocks(&self, blocks_loader: &BlocksLoader, behaviors: &BlockBehaviors, entity: Option<EntityId>, hitbox: &mut HitBox, changed: &mut Vec<Vec3ub>) {
+        let area: Aabb = Aabb::from_center(&hitbox.pos, &hitbox.half_size);
+        for x_pos in (area.min[0].floor() as i32)..=(area.max[0].floor() as i32) {
+            for y_pos in (area.min[1].floor() as i32)..=(area.max[1].floor() as i32) {
+                for z_pos in (area.min[2].floor() as i32)..=(area.max[2].floor() as i32) {
+                    if let Some(block_lid) = self.get_block_at(&[x_pos, y_pos, z_pos]) {
+                        if let Some(behavior) = behaviors.get_block_behavior(&blocks_loader.get_block(block_lid)) {
+                            behavior.on_entity_collide(self, blocks_loader, &[x_pos as u8, y_pos as u8, z_pos as u8], entity, hitbox, changed);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    //Runs the due scheduled ticks and then the random ones, returns changed positions
+    pub fn tick(&self, blocks_loader: &BlocksLoader, behaviors: &BlockBehaviors) -> Vec<Vec3ub> {
+        let mut changed: Vec<Vec3ub> = Vec::new();
+        for pos in self.ticks.take_due() {
+            if let Some(behavior) = behaviors.get_block_behavior(&blocks_loader.get_block(self.get_block(&pos))) {
+                behavior.on_tick(self, blocks_loader, &pos, TickKind::Scheduled, &mut changed);
+            }
+        }
+        for pos in self.ticks.get_random_positions() {
+            if let Some(behavior) = behaviors.get_block_behavior(&blocks_loader.get_block(self.get_block(&pos))) {
+                behavior.on_tick(self, blocks_loader, &pos, TickKind::Random, &mut changed);
+            }
+        }
+        return changed;
+    }
+
+    fn notify_neighbors(&self, blocks_loader: &BlocksLoader, behaviors: &BlockBehaviors, pos: &Vec3ub, changed: &mut Vec<Vec3ub>) {
+        for neigh in NEIGHBORHOOD {
+            if let Some(neigh_pos) = LightSolver::get_neighbor_pos(pos, &neigh) {
+                if let Some(behavior) = behaviors.get_block_behavior(&blocks_loader.get_block(self.get_block(&neigh_pos))) {
+                    behavior.on_neighbor_changed(self, blocks_loader, &neigh_pos, pos, changed);
+                }
             }
         }
     }
 
+    //Edits by players and scripts, unlike set_block these call the behaviors hooks
+    pub fn place_block(&self, blocks_loader: &BlocksLoader, behaviors: &BlockBehaviors, pos: &Vec3ub, block_lid: u16) -> Vec<Vec3ub> {
+        let mut changed: Vec<Vec3ub> = vec![*pos];
+        self.set_block(pos, block_lid);
+        if let Some(behavior) = behaviors.get_block_behavior(&blocks_loader.get_block(block_lid)) {
+            behavior.on_place(self, blocks_loader, pos, &mut changed);
+        }
+        self.notify_neighbors(blocks_loader, behaviors, pos, &mut changed);
+        return changed;
+    }
+
+    pub fn break_block(&self, blocks_loader: &BlocksLoader, behaviors: &BlockBehaviors, pos: &Vec3ub) -> Vec<Vec3ub> {
+        let mut changed: Vec<Vec3ub> = vec![*pos];
+        if let Some(behavior) = behaviors.get_block_behavior(&blocks_loader.get_block(self.get_block(pos))) {
+            behavior.on_break(self, blocks_loader, pos, &mut changed);
+        }
+        self.set_block(pos, blocks_loader.air_block.lid);
+        self.notify_neighbors(blocks_loader, behaviors, pos, &mut changed);
+        return changed;
+    }
+
+    //True if the block handled the use
+    pub fn use_block(&self, blocks_loader: &BlocksLoader, behaviors: &BlockBehaviors, pos: &Vec3ub, changed: &mut Vec<Vec3ub>) -> bool {
+        return if let Some(behavior) = behaviors.get_block_behavior(&blocks_loader.get_block(self.get_block(pos))) {
+            behavior.on_use(self, blocks_loader, pos, changed)
+        } else {
+            false
+        }
+    }
+
+    //Header: magic, version, registry of the saved blocks names (saved id = position), then chunks of saved ids each followed by its block states, entities and scheduled ticks
+    fn store_header(blocks_loader: &BlocksLoader) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut header_data: Vec<u8> = Vec::new();
+        header_data.extend_from_slice(WORLD_MAGIC);
+        header_data.extend_from_slice(&WORLD_VERSION.to_be_bytes());
+        if blocks_loader.loaded_blocks.len() > (u16::MAX as usize) {
+            return Err(Box::new(WorldStoringError::RegistryOverflowError()));
+        }
+        header_data.extend_from_slice(&(blocks_loader.loaded_blocks.len() as u16).to_be_bytes());
+        for block in &blocks_loader.loaded_blocks {
+            let name_data: &[u8] = block.name.as_bytes();
+            if name_data.len() > (u16::MAX as usize) {
+                return Err(Box::new(WorldStoringError::RegistryOverflowError()));
+            }
+            header_data.extend_from_slice(&(name_data.len() as u16).to_be_bytes());
+            header_data.extend_from_slice(name_data);
+        }
+        return Ok(header_data);
+    }
+
+    fn read_u16(data: &[u8], pos: &mut usize) -> Result<u16, WorldLoadingError> {
+        return if let (Some(b_data), Some(l_data)) = (data.get(*pos), data.get(*pos + 1)) {
+            *pos += 2;
+            Ok(((*b_data as u16) << 8) | (*l_data as u16))
+        } else {
+            Err(WorldLoadingError::InvalidRegistryError())
+        }
+    }
+
+    //Returns the version, lids_map[saved_id] = lid and the position of the chunks data
+    fn load_header(blocks_loader: &BlocksLoader, data: &[u8]) -> Result<(u16, Vec<u16>, usize), Box<dyn std::error::Error>> {
+        let mut saved_names: Vec<String> = Vec::new();
+        let mut pos: usize = 0;
+        let mut version: u16 = LEGACY_WORLD_VERSION;
+        if data.starts_with(WORLD_MAGIC) {
+            pos += WORLD_MAGIC.len();
+            version = Self::read_u16(data, &mut pos)?;
+            if version == LEGACY_WORLD_VERSION || version > WORLD_VERSION {
+                return Err(Box::new(WorldLoadingError::UnsupportedVersionError(version)));
+            }
+            let names_cnt: u16 = Self::read_u16(data, &mut pos)?;
+            for _name_pos in 0..names_cnt {
+                let name_len: usize = Self::read_u16(data, &mut pos)? as usize;
+                if let Some(name_data) = data.get(pos..(pos + name_len)) {
+                    saved_names.push(String::from_utf8(name_data.to_vec())?);
+                    pos += name_len;
+                } else {
+                    return Err(Box::new(WorldLoadingError::InvalidRegistryError()));
+                }
+            }
+        } else {
+            for name in LEGACY_BLOCKS_NAMES {
+                saved_names.push(String::from(*name));
+            }
+        }
+        //Blocks missing from the loaded assets become unknown instead of failing the whole world
+        let mut lids_map: Vec<u16> = Vec::new();
+        for name in &saved_names {
+            match blocks_loader.get_block_by_name(name) {
+                Some(block) => lids_map.push(block.lid),
+                None => {
+                    println!("Block {} of the saved world is not loaded", name);
+                    lids_map.push(blocks_loader.unknown_block.lid);
+                }
+            }
+        }
+        return Ok((version, lids_map, pos));
+    }
+
     pub fn store(&self, blocks_loader: &BlocksLoader, file_name: String) -> Result<(), Box<dyn std::error::Error>> {
         return match File::create(file_name) {
             Err(error) => {
                 Err(Box::new(error))
             }
             Ok(mut file) => {
+                file.write_all(Self::store_header(blocks_loader)?.as_slice())?;
                 for line_pos in 0..16u8 {
                     for chunk_pos in 0..16u8 {
                         match self.chunks[line_pos as usize][chunk_pos as usize].store(blocks_loader) {
                             Err(error) => {
                                 return Err(error);
                             }
-                            Ok(chunk_data) => {
+                            Ok(mut chunk_data) => {
+                                chunk_data.extend(self.chunks[line_pos as usize][chunk_pos as usize].store_states());
+                                self.entities.store_chunk(((line_pos as usize) << 4) | (chunk_pos as usize), &mut chunk_data)?;
+                                self.ticks.store_chunk(((line_pos as usize) << 4) | (chunk_pos as usize), &mut chunk_data)?;
                                 if let Err(error) = file.write_all(chunk_data.as_slice()) {
                                     return Err(Box::new(error));
                                 }
@@ -254,29 +602,51 @@ impl World {
                 return Err(Box::new(error));
             }
             Ok(mut file) => {
-                for line_pos in 0..16u8 {
-                    for chunk_pos in 0..16u8 {
-                        let mut data: Box<[u8; CHUNK_SIZE]> = Box::new([0u8; CHUNK_SIZE]);
-                        match file.read(data.deref_mut()) {
-                            Err(error) => {
-                                return Err(Box::new(error));
-                            }
-                            Ok(size) => {
-                                if size == CHUNK_SIZE {
-                                    match Chunk::load(blocks_loader, data.deref_mut()) {
-                                        Err(error) => {
-                                            return Err(error);
-                                        }
-                                        Ok(chunk) => {
-                                            self.chunks[line_pos as usize][chunk_pos as usize].set_data(chunk);
-                                        }
-                                    }
-                                } else {
-                                    return Err(Box::new(WorldLoadingError::InvalidChunkSizeError()));
-                                }
-                            }
+                let mut data: Vec<u8> = Vec::new();
+                file.read_to_end(&mut data)?;
+                let (version, lids_map, mut pos) = Self::load_header(blocks_loader, &data)?;
+                //All chunks are loaded before any of them is replaced
+                let mut chunks: Vec<Chunk> = Vec::new();
+                let mut entities: Vec<(String, HitBox)> = Vec::new();
+                let mut scheduled_ticks: Vec<(Vec3ub, u32)> = Vec::new();
+                for _chunk_ind in 0..(16 * 16) {
+                    let chunk: Chunk = if let Some(chunk_data) = data.get(pos..(pos + CHUNK_SIZE)) {
+                        pos += CHUNK_SIZE;
+                        Chunk::load(blocks_loader, chunk_data, &lids_map)?
+                    } else {
+                        return Err(Box::new(WorldLoadingError::InvalidChunkSizeError()));
+                    };
+                    if version >= STATES_WORLD_VERSION {
+                        if let Some(states_data) = data.get(pos..(pos + CHUNK_STATES_SIZE)) {
+                            chunk.load_states(states_data)?;
+                            pos += CHUNK_STATES_SIZE;
+                        } else {
+                            return Err(Box::new(WorldLoadingError::InvalidChunkSizeError()));
                         }
                     }
+                    chunks.push(chunk);
+                    if version >= ENTITIES_WORLD_VERSION {
+                        entities.extend(EntityStore::load_chunk(&data, &mut pos)?);
+                    }
+                    if version >= SCHEDULED_TICKS_WORLD_VERSION {
+                        scheduled_ticks.extend(TickScheduler::load_chunk(&data, &mut pos)?);
+                    }
+                }
+                if pos != data.len() {
+                    return Err(Box::new(WorldLoadingError::InvalidChunkSizeError()));
+                }
+                for (chunk_ind, chunk) in chunks.into_iter().enumerate() {
+                    self.chunks[chunk_ind >> 4][chunk_ind & 0x0F].set_data(chunk);
+                }
+                self.entities.clear();
+                for (type_name, hitbox) in entities {
+                    if let Err(error) = self.entities.spawn(&type_name, hitbox) {
+                        println!("Failed to load entity: {}", error);
+                    }
+                }
+                self.ticks.clear();
+                for (tick_pos, delay) in scheduled_ticks {
+                    self.ticks.schedule(&tick_pos, delay);
                 }
                 return Ok(());
             }